@@ -2,16 +2,23 @@ import Transport from '@ledgerhq/hw-transport'
 
 export const CLA = 0x80
 export const CHUNK_SIZE = 250
+export const PKEY_CHECKSUM_LEN = 4
 export const APP_KEY = 'XTZ'
 
 export const INS = {
   GET_VERSION: 0x10,
   GET_ADDR: 0x11,
   SIGN: 0x12,
+  GET_SUPPORTED_OPERATIONS: 0x14,
+  GET_APP_CONFIG: 0x15,
+  GET_SUPPORTED_INSTRUCTIONS: 0x16,
   AUTHORIZE_BAKING: 0xa1,
   DEAUTHORIZE_BAKING: 0xac,
   QUERY_AUTH_KEY_WITH_CURVE: 0xad,
   BAKER_SIGN: 0xaf,
+  QUERY_REJECTED_HWM: 0xa8,
+  EXPORT_BAKING_STATE: 0xa9,
+  IMPORT_BAKING_STATE: 0xaa,
 }
 
 export const LEGACY_INS = {
@@ -65,6 +72,12 @@ export enum Curve {
   Ed25519 = P2_CURVE.ED25519,
 }
 
+export enum BakingExpiry {
+  Never = 0,
+  Signatures = 1,
+  Level = 2,
+}
+
 export enum LedgerError {
   U2FUnknown = 1,
   U2FBadRequest = 2,
@@ -83,11 +96,13 @@ export enum LedgerError {
   ConditionsNotSatisfied = 0x6985,
   TransactionRejected = 0x6986,
   BadKeyHandle = 0x6a80,
+  ConsensusNotAllowed = 0x6a81,
   InvalidP1P2 = 0x6b00,
   InstructionNotSupported = 0x6d00,
   AppDoesNotSeemToBeOpen = 0x6e00,
   UnknownError = 0x6f00,
   SignVerifyError = 0x6f01,
+  ParseError = 0x9405,
 }
 
 export const ERROR_DESCRIPTION = {
@@ -108,11 +123,13 @@ export const ERROR_DESCRIPTION = {
   [LedgerError.ConditionsNotSatisfied]: 'Conditions not satisfied',
   [LedgerError.TransactionRejected]: 'Transaction rejected',
   [LedgerError.BadKeyHandle]: 'Bad key handle',
+  [LedgerError.ConsensusNotAllowed]: 'Consensus operations are not allowed',
   [LedgerError.InvalidP1P2]: 'Invalid P1/P2',
   [LedgerError.InstructionNotSupported]: 'Instruction not supported',
   [LedgerError.AppDoesNotSeemToBeOpen]: 'App does not seem to be open',
   [LedgerError.UnknownError]: 'Unknown error',
   [LedgerError.SignVerifyError]: 'Sign/verify error',
+  [LedgerError.ParseError]: 'Parse error',
 }
 
 export function errorCodeToString(statusCode: LedgerError) {
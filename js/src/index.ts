@@ -18,17 +18,23 @@ import Transport from '@ledgerhq/hw-transport'
 import { serializePath, sha256x2 } from './helper'
 import {
   ResponseAddress,
+  ResponseAppConfig,
   ResponseAppInfo,
+  ResponseBakingState,
   ResponseBase,
   ResponseHMAC,
   ResponseLegacyGit,
   ResponseLegacyHWM,
   ResponseLegacyVersion,
   ResponseQueryAuthKey,
+  ResponseRejectedHWM,
   ResponseSign,
+  ResponseSupportedInstructions,
+  ResponseSupportedOperations,
   ResponseVersion,
 } from './types'
 import {
+  BakingExpiry,
   CHUNK_SIZE,
   CLA,
   Curve,
@@ -39,12 +45,13 @@ import {
   LEGACY_INS,
   P1_VALUES,
   PAYLOAD_TYPE,
+  PKEY_CHECKSUM_LEN,
   processErrorResponse,
 } from './common'
 
 import { blake2b } from 'hash-wasm'
 
-export { LedgerError, Curve }
+export { BakingExpiry, LedgerError, Curve }
 export * from './types'
 
 function processGetAddrResponse(response: Buffer) {
@@ -60,7 +67,17 @@ function processGetAddrResponse(response: Buffer) {
   //"advance" buffer
   partialResponse = partialResponse.slice(1 + PKLEN)
 
-  const address = Buffer.from(partialResponse.slice(0, -2)).toString()
+  const address = Buffer.from(partialResponse.slice(0, -2 - PKEY_CHECKSUM_LEN)).toString()
+
+  const checksum = partialResponse.slice(-2 - PKEY_CHECKSUM_LEN, -2)
+  if (!sha256x2(publicKey).slice(0, PKEY_CHECKSUM_LEN).equals(checksum)) {
+    return {
+      publicKey,
+      address,
+      returnCode: LedgerError.DataIsInvalid,
+      errorMessage: 'Public key checksum mismatch',
+    }
+  }
 
   return {
     publicKey,
@@ -151,14 +168,71 @@ export default class TezosApp {
     return chunks
   }
 
-  async signGetChunks(path: string, message: Buffer) {
-    return TezosApp.prepareChunks(message, serializePath(path))
+  async signGetChunks(path: string, message: Buffer, origin?: string) {
+    let first = serializePath(path)
+    if (origin !== undefined) {
+      //the origin label is sent after the path, prefixed by its length
+      const label = Buffer.from(origin, 'ascii')
+      first = Buffer.concat([first, Buffer.from([label.length]), label])
+    }
+
+    return TezosApp.prepareChunks(message, first)
   }
 
   async getVersion(): Promise<ResponseVersion> {
     return getVersion(this.transport).catch(err => processErrorResponse(err))
   }
 
+  async getSupportedOperations(): Promise<ResponseSupportedOperations> {
+    return this.transport.send(CLA, INS.GET_SUPPORTED_OPERATIONS, 0, 0).then(response => {
+      const errorCodeData = response.slice(-2)
+      const returnCode = errorCodeData[0] * 256 + errorCodeData[1]
+
+      const n = response[0]
+      const tags = Array.from(response.slice(1, 1 + n))
+
+      return {
+        returnCode,
+        errorMessage: errorCodeToString(returnCode),
+        tags,
+      }
+    }, processErrorResponse)
+  }
+
+  async getSupportedInstructions(): Promise<ResponseSupportedInstructions> {
+    return this.transport.send(CLA, INS.GET_SUPPORTED_INSTRUCTIONS, 0, 0).then(response => {
+      const errorCodeData = response.slice(-2)
+      const returnCode = errorCodeData[0] * 256 + errorCodeData[1]
+
+      const n = response[0]
+      const instructions = Array.from(response.slice(1, 1 + n))
+
+      return {
+        returnCode,
+        errorMessage: errorCodeToString(returnCode),
+        instructions,
+      }
+    }, processErrorResponse)
+  }
+
+  async getAppConfig(): Promise<ResponseAppConfig> {
+    return this.transport.send(CLA, INS.GET_APP_CONFIG, 0, 0).then(response => {
+      const errorCodeData = response.slice(-2)
+      const returnCode = errorCodeData[0] * 256 + errorCodeData[1]
+
+      const flags = response[0]
+
+      return {
+        returnCode,
+        errorMessage: errorCodeToString(returnCode),
+        expertMode: (flags & 0x01) !== 0,
+        blindSigning: (flags & 0x02) !== 0,
+        baking: (flags & 0x04) !== 0,
+        signatureCounter: response.readBigUInt64BE(1),
+      }
+    }, processErrorResponse)
+  }
+
   async getAppInfo(): Promise<ResponseAppInfo> {
     return this.transport.send(0xb0, 0x01, 0, 0).then(response => {
       const errorCodeData = response.slice(-2)
@@ -222,10 +296,17 @@ export default class TezosApp {
       .then(processGetAddrResponse, processErrorResponse)
   }
 
-  async authorizeBaking(path: string, curve: Curve): Promise<ResponseAddress> {
-    const serializedPath = serializePath(path)
+  async authorizeBaking(path: string, curve: Curve, expiry?: BakingExpiry, limit = 0): Promise<ResponseAddress> {
+    let payload = serializePath(path)
+    if (expiry !== undefined) {
+      const expiryBuf = Buffer.alloc(5)
+      expiryBuf.writeUInt8(expiry, 0)
+      expiryBuf.writeUInt32BE(limit, 1)
+      payload = Buffer.concat([payload, expiryBuf])
+    }
+
     return this.transport
-      .send(CLA, INS.AUTHORIZE_BAKING, 0x01, curve, serializedPath, [LedgerError.NoErrors])
+      .send(CLA, INS.AUTHORIZE_BAKING, 0x01, curve, payload, [LedgerError.NoErrors])
       .then(processAuthorizeBakingResponse, processErrorResponse)
   }
 
@@ -243,6 +324,45 @@ export default class TezosApp {
       .then(processQueryAuthKeyWithCurve, processErrorResponse)
   }
 
+  async queryRejectedHWM(): Promise<ResponseRejectedHWM> {
+    return this.transport.send(CLA, INS.QUERY_REJECTED_HWM, 0, 0).then(response => {
+      const errorCodeData = response.slice(-2)
+      const returnCode = errorCodeData[0] * 256 + errorCodeData[1]
+
+      const result: ResponseRejectedHWM = {
+        returnCode,
+        errorMessage: errorCodeToString(returnCode),
+      }
+
+      if (response.length > 2) {
+        result.level = response.readUInt32BE(0)
+        result.round = response.readUInt32BE(4)
+        result.kind = response[8]
+      }
+
+      return result
+    }, processErrorResponse)
+  }
+
+  async exportBakingState(): Promise<ResponseBakingState> {
+    return this.transport.send(CLA, INS.EXPORT_BAKING_STATE, 0, 0).then(response => {
+      const errorCodeData = response.slice(-2)
+      const returnCode = errorCodeData[0] * 256 + errorCodeData[1]
+
+      return {
+        returnCode,
+        errorMessage: errorCodeToString(returnCode),
+        state: response.slice(0, response.length - 2),
+      }
+    }, processErrorResponse)
+  }
+
+  async importBakingState(state: Buffer): Promise<ResponseAddress> {
+    return this.transport
+      .send(CLA, INS.IMPORT_BAKING_STATE, 0x01, 0x00, state, [LedgerError.NoErrors])
+      .then(processAuthorizeBakingResponse, processErrorResponse)
+  }
+
   async signSendChunk(
     chunkIdx: number,
     chunkNum: number,
@@ -276,6 +396,7 @@ export default class TezosApp {
         LedgerError.DataIsInvalid,
         LedgerError.BadKeyHandle,
         LedgerError.SignVerifyError,
+        LedgerError.ParseError,
       ])
       .then((response: Buffer) => {
         const errorCodeData = response.slice(-2)
@@ -377,11 +498,11 @@ export default class TezosApp {
     }, processErrorResponse)
   }
 
-  async signOperation(path: string, curve: Curve, message: Buffer) {
+  async signOperation(path: string, curve: Curve, message: Buffer, origin?: string) {
     //prepend 0x03 to signal an operation as the message
     message = Buffer.concat([Buffer.from([3]), message])
 
-    return this.signGetChunks(path, message).then(chunks => {
+    return this.signGetChunks(path, message, origin).then(chunks => {
       return this.signSendChunk(1, chunks.length, chunks[0], false, curve, INS.SIGN).then(async response => {
         let result = {
           returnCode: response.returnCode,
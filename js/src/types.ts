@@ -13,6 +13,21 @@ export interface ResponseQueryAuthKey extends ResponseBase {
   curve: number
 }
 
+export interface ResponseSupportedOperations extends ResponseBase {
+  tags: number[]
+}
+
+export interface ResponseSupportedInstructions extends ResponseBase {
+  instructions: number[]
+}
+
+export interface ResponseAppConfig extends ResponseBase {
+  expertMode: boolean
+  blindSigning: boolean
+  baking: boolean
+  signatureCounter: bigint
+}
+
 export interface ResponseVersion extends ResponseBase {
   testMode: boolean
   major: number
@@ -57,6 +72,18 @@ export interface ResponseLegacyHWM extends ResponseBase {
   chain_id?: number
 }
 
+export interface ResponseRejectedHWM extends ResponseBase {
+  //undefined if nothing was rejected yet
+  level?: number
+  round?: number
+  kind?: number
+}
+
+export interface ResponseBakingState extends ResponseBase {
+  //snapshot followed by its MAC, to be given as is to `importBakingState`
+  state: Buffer
+}
+
 export interface ResponseHMAC extends ResponseBase {
   hmac: Buffer
 }
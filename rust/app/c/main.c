@@ -42,6 +42,7 @@ unsigned char io_event(unsigned char channel) {
                         UX_REDISPLAY();
                     }
             });
+            rs_ticker();
 #ifdef BAKING
 			//even tho we should use this only for prompting the user
 			//for security purposes,
@@ -125,6 +126,7 @@ int main(void) {
             {
                 if (!app_init_done) {
                     io_app_init();
+                    rs_app_init();
                     view_idle_show(0, NULL);
                     app_init_done = 1;
                     check_canary();
@@ -0,0 +1,107 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Generates the test vectors of the keys derived by the mock
+//!
+//! The mock derives its keys from the mnemonic of the emulator, so the public keys
+//! and addresses are the same the zemu tests retrieve from the device.
+//!
+//! For each path and curve the public key, address and a few signatures are written as JSON,
+//! to be checked by `tests/vectors.rs` and by the zemu tests:
+//!
+//! `make mock_vectors`, or `cargo run --example test_vectors --features wallet > tests/vectors.json`
+use bolos::crypto::bip32::BIP32Path;
+use rslib::{
+    constants::BIP32_MAX_LENGTH,
+    crypto::{signature_base58, Curve, SIGNATURE_BASE58_LEN},
+};
+use serde_json::{json, Value};
+
+/// Paths to derive, hardened
+const PATHS: &[&[u32]] = &[&[44, 1729], &[44, 1729, 0, 0], &[44, 1729, 1, 0]];
+
+const CURVES: &[Curve] = &[
+    Curve::Ed25519,
+    Curve::Secp256K1,
+    Curve::Secp256R1,
+    Curve::Bip32Ed25519,
+];
+
+/// Signed as is, like the digest of an operation
+const MESSAGES: &[[u8; 32]] = &[[0; 32], [0xAB; 32]];
+
+fn path_str(path: &[u32]) -> String {
+    path.iter()
+        .fold(String::from("m"), |acc, n| format!("{}/{}'", acc, n))
+}
+
+fn vector(path: &[u32], curve: Curve) -> Value {
+    let bip32 = BIP32Path::<BIP32_MAX_LENGTH>::new(path.iter().map(|n| 0x8000_0000 + n))
+        .expect("invalid path");
+
+    let mut pk = curve
+        .to_secret(&bip32)
+        .and_then(|sk| sk.into_public())
+        .unwrap_or_else(|_| panic!("couldn't derive key at {}", path_str(path)));
+    pk.compress().expect("couldn't compress key");
+
+    let mut hash = [0; 20];
+    pk.hash(&mut hash).expect("couldn't hash key");
+    let address = bs58::encode([curve.to_hash_prefix(), &hash[..]].concat())
+        .with_check()
+        .into_string();
+
+    let signatures = MESSAGES
+        .iter()
+        .map(|msg| {
+            let mut sig = [0; 100];
+            let len = curve
+                .to_secret(&bip32)
+                .expect("couldn't derive key")
+                .sign(&msg[..], &mut sig)
+                .unwrap_or_else(|_| panic!("couldn't sign with {}", path_str(path)));
+
+            let mut base58 = [0; SIGNATURE_BASE58_LEN];
+            let base58_len =
+                signature_base58(curve, &sig[..len], &mut base58).expect("invalid signature");
+
+            json!({
+                "message": hex::encode(msg),
+                "signature": hex::encode(&sig[..len]),
+                "signature_base58": std::str::from_utf8(&base58[..base58_len]).unwrap(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "path": path_str(path),
+        "curve": u8::from(curve),
+        "public_key": hex::encode(pk.as_ref()),
+        "address": address,
+        "signatures": signatures,
+    })
+}
+
+fn main() {
+    let vectors = PATHS
+        .iter()
+        .flat_map(|path| CURVES.iter().map(move |&curve| vector(path, curve)))
+        .collect::<Vec<_>>();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&vectors).expect("couldn't serialize vectors")
+    );
+}
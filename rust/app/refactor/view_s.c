@@ -33,6 +33,7 @@ void rs_h_expert_update();
 void rs_h_review_button_left();
 void rs_h_review_button_right();
 void rs_h_review_button_both();
+void rs_h_review_button_skip();
 
 bool rs_h_paging_can_decrease(void);
 bool rs_h_paging_can_increase(void);
@@ -90,6 +91,9 @@ static unsigned int view_error_button(unsigned int button_mask, unsigned int but
     return 0;
 }
 
+// set when a long press skipped a section, so the release doesn't also move forward
+static bool review_skipped = false;
+
 //Referenced by crapoline_ux_display_view_review macro call
 static unsigned int view_review_button(unsigned int button_mask, unsigned int button_mask_counter) {
     UNUSED(button_mask_counter);
@@ -102,9 +106,21 @@ static unsigned int view_review_button(unsigned int button_mask, unsigned int bu
             rs_h_review_button_left();
             break;
 
+        case BUTTON_EVT_FAST | BUTTON_RIGHT:
+            // Hold right to skip to the next operation, only once per press
+            if (!review_skipped) {
+                review_skipped = true;
+                rs_h_review_button_skip();
+            }
+            break;
+
         case BUTTON_EVT_RELEASED | BUTTON_RIGHT:
             // Press right to progress to the next element
-            rs_h_review_button_right();
+            if (review_skipped) {
+                review_skipped = false;
+            } else {
+                rs_h_review_button_right();
+            }
             break;
     }
     return 0;
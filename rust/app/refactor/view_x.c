@@ -35,6 +35,7 @@ void rs_h_expert_update();
 void rs_h_review_loop_start();
 void rs_h_review_loop_inside();
 void rs_h_review_loop_end();
+void rs_h_review_button_skip();
 
 void rs_h_approve(unsigned int);
 void rs_h_reject(unsigned int);
@@ -75,7 +76,8 @@ UX_FLOW(
 
 UX_FLOW_DEF_NOCB(ux_review_flow_1_review_title, pbb, { &C_icon_app, "Please", "review",});
 UX_STEP_INIT(ux_review_flow_2_start_step, NULL, NULL, { rs_h_review_loop_start(); });
-UX_STEP_NOCB_INIT(ux_review_flow_2_step, bnnn_paging, { rs_h_review_loop_inside(); }, { .title = (const char *) BACKEND_LAZY.key, .text = (const char *) BACKEND_LAZY.message, });
+// Press both buttons to skip to the next operation
+UX_STEP_CB_INIT(ux_review_flow_2_step, bnnn_paging, { rs_h_review_loop_inside(); }, rs_h_review_button_skip(), { .title = (const char *) BACKEND_LAZY.key, .text = (const char *) BACKEND_LAZY.message, });
 UX_STEP_INIT(ux_review_flow_2_end_step, NULL, NULL, { rs_h_review_loop_end(); });
 UX_STEP_VALID(ux_review_flow_3_step, pb, rs_h_approve(0), { &C_icon_validate_14, APPROVE_LABEL });
 UX_STEP_VALID(ux_review_flow_4_step, pb, rs_h_reject(0), { &C_icon_crossmark, REJECT_LABEL });
@@ -151,4 +153,12 @@ void crapoline_ux_flow_relayout() {
     CUR_FLOW.index--;
     ux_flow_relayout();
 }
+
+void crapoline_ux_flow_refresh() {
+    ux_flow_relayout();
+}
+
+void crapoline_ux_show_review_approve() {
+    ux_flow_init(0, ux_review_flow, &ux_review_flow_3_step);
+}
 #endif
@@ -0,0 +1,131 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! CLA and instructions understood by the app
+//!
+//! Everything is declared once in the table below, which generates
+//! the constants used by the dispatchers and the list of instructions
+//! reported by `INS_GET_SUPPORTED_INSTRUCTIONS`
+
+macro_rules! apdu_spec {
+    (
+        CLA = $cla:literal;
+        $($(#[$attr:meta])* $ins:ident = $value:literal;)*
+    ) => {
+        pub const CLA: u8 = $cla;
+
+        $(
+            $(#[$attr])*
+            pub const $ins: u8 = $value;
+        )*
+
+        /// Instructions available in this build, in declaration order
+        pub const SUPPORTED_INSTRUCTIONS: &[u8] = &[
+            $($(#[$attr])* $ins,)*
+        ];
+    };
+}
+
+apdu_spec! {
+    CLA = 0x80;
+
+    //common legacy instructions
+    INS_LEGACY_GET_VERSION = 0x00;
+    INS_LEGACY_GET_PUBLIC_KEY = 0x02;
+    INS_LEGACY_PROMPT_PUBLIC_KEY = 0x03;
+    INS_LEGACY_SIGN = 0x04;
+    INS_LEGACY_GIT = 0x09;
+    INS_LEGACY_SIGN_WITH_HASH = 0x0F;
+
+    //common new instructions
+    INS_GET_VERSION = 0x10;
+    INS_GET_ADDRESS = 0x11;
+    INS_SIGN = 0x12;
+    INS_GET_ALL_ADDRESSES = 0x13;
+    INS_GET_SUPPORTED_OPERATIONS = 0x14;
+    INS_GET_APP_CONFIG = 0x15;
+    INS_GET_SUPPORTED_INSTRUCTIONS = 0x16;
+    INS_SET_FEE_THRESHOLD = 0x17;
+
+    //wallet-only instructions
+    #[cfg(feature = "wallet")]
+    INS_LEGACY_SIGN_UNSAFE = 0x05;
+
+    //baking-only legacy instructions
+    #[cfg(feature = "baking")]
+    INS_LEGACY_AUTHORIZE_BAKING = 0x01;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_RESET = 0x06;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_QUERY_AUTH_KEY = 0x07;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_QUERY_MAIN_HWM = 0x08;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_SETUP = 0x0A;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_QUERY_ALL_HWM = 0x0B;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_DEAUTHORIZE = 0x0C;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_QUERY_AUTH_KEY_WITH_CURVE = 0x0D;
+    #[cfg(feature = "baking")]
+    INS_LEGACY_HMAC = 0x0E;
+
+    //baking-only new instructions
+    #[cfg(feature = "baking")]
+    INS_AUTHORIZE_BAKING = 0xA1;
+    #[cfg(feature = "baking")]
+    INS_QUERY_AUTH_KEY = 0xA7;
+    #[cfg(feature = "baking")]
+    INS_QUERY_REJECTED_HWM = 0xA8;
+    #[cfg(feature = "baking")]
+    INS_EXPORT_BAKING_STATE = 0xA9;
+    #[cfg(feature = "baking")]
+    INS_IMPORT_BAKING_STATE = 0xAA;
+    #[cfg(feature = "baking")]
+    INS_DEAUTHORIZE_BAKING = 0xAC;
+    #[cfg(feature = "baking")]
+    INS_QUERY_AUTH_KEY_WITH_CURVE = 0xAD;
+    #[cfg(feature = "baking")]
+    INS_BAKER_SIGN = 0xAF;
+
+    //development instructions
+    #[cfg(feature = "dev")]
+    INS_DEV_HASH = 0xF0;
+    #[cfg(feature = "dev")]
+    INS_DEV_EXCEPT = 0xF1;
+    #[cfg(feature = "dev")]
+    INS_DEV_ECHO_UI = 0xF2;
+    #[cfg(feature = "dev")]
+    INS_BLIND_SIGN = 0xF3;
+    #[cfg(feature = "dev")]
+    INS_DEV_SIGN_METRICS = 0xF4;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_instructions() {
+        for (i, ins) in SUPPORTED_INSTRUCTIONS.iter().enumerate() {
+            assert!(
+                !SUPPORTED_INSTRUCTIONS[i + 1..].contains(ins),
+                "instruction {:#04x} declared twice",
+                ins
+            );
+        }
+    }
+}
@@ -13,79 +13,7 @@
 *  See the License for the specific language governing permissions and
 *  limitations under the License.
 ********************************************************************************/
-// Based on ISO7816
-#[repr(u16)]
-#[derive(PartialEq, Eq)]
-#[cfg_attr(test, derive(Debug))]
-pub enum ApduError {
-    ExecutionError = 0x6400,
-    WrongLength = 0x6700,
-    ApduCodeEmptyBuffer = 0x6982,
-    OutputBufferTooSmall = 0x6983,
-    DataInvalid = 0x6984,
-    ApduCodeConditionsNotSatisfied = 0x6985,
-    CommandNotAllowed = 0x6986,
-    BadKeyExample = 0x6A80,
-    InvalidP1P2 = 0x6B00,
-    InsNotSupported = 0x6D00,
-    ClaNotSupported = 0x6E00,
-    Unknown = 0x6F00,
-    SignVerifyError = 0x6F01,
-    Success = 0x9000,
-    Busy = 0x9001,
-}
-
-#[cfg_attr(test, derive(Debug))]
-pub enum ConvertApduError {
-    Length { expected: usize, found: usize },
-    Unknown(u16),
-}
-
-impl std::convert::TryFrom<&[u8]> for ApduError {
-    type Error = ConvertApduError;
-
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if value.len() > 2 {
-            return Err(Self::Error::Length {
-                expected: 2,
-                found: value.len(),
-            });
-        }
-
-        let value = {
-            let mut array = [0; 2];
-            array.copy_from_slice(value);
-            u16::from_be_bytes(array)
-        };
-
-        Self::try_from(value)
-    }
-}
-
-impl std::convert::TryFrom<u16> for ApduError {
-    type Error = ConvertApduError;
-
-    fn try_from(value: u16) -> Result<Self, Self::Error> {
-        match value {
-            0x6400 => Ok(Self::ExecutionError),
-            0x6700 => Ok(Self::WrongLength),
-            0x6982 => Ok(Self::ApduCodeEmptyBuffer),
-            0x6983 => Ok(Self::OutputBufferTooSmall),
-            0x6984 => Ok(Self::DataInvalid),
-            0x6985 => Ok(Self::ApduCodeConditionsNotSatisfied),
-            0x6986 => Ok(Self::CommandNotAllowed),
-            0x6A80 => Ok(Self::BadKeyExample),
-            0x6B00 => Ok(Self::InvalidP1P2),
-            0x6D00 => Ok(Self::InsNotSupported),
-            0x6E00 => Ok(Self::ClaNotSupported),
-            0x6F00 => Ok(Self::Unknown),
-            0x6F01 => Ok(Self::SignVerifyError),
-            0x9000 => Ok(Self::Success),
-            0x9001 => Ok(Self::Busy),
-            err => Err(Self::Error::Unknown(err)),
-        }
-    }
-}
+pub use bolos::apdu::{ApduError, ConvertApduError};
 
 pub const APDU_INDEX_CLA: usize = 0;
 pub const APDU_INDEX_INS: usize = 1;
@@ -100,20 +28,92 @@ pub const BIP32_MAX_LENGTH: usize = 10;
 pub const EDWARDS_SIGN_BUFFER_MIN_LENGTH: usize = 64;
 pub const SECP256_SIGN_BUFFER_MIN_LENGTH: usize = 100;
 
+/// Entrypoints that can only be signed in expert mode
+/// and that are shown with an additional warning to confirm
+///
+/// `approve` is only dangerous when the allowance is unlimited,
+/// see `Parameters::is_unlimited_approve`
+pub const DANGEROUS_ENTRYPOINTS: &[&[u8]] = &[
+    b"set_administrator",
+    b"set_admin",
+    b"transfer_ownership",
+    b"update_operators",
+];
+
+/// FA1.2 token contract with known decimals
+pub struct KnownToken {
+    /// Hash of the KT1 address
+    pub contract: &'static [u8; 20],
+    pub ticker: &'static [u8],
+    pub decimals: u8,
+}
+
+/// Token contracts whose `transfer` and `approve` amounts
+/// are also shown scaled by the token's decimals
+pub const KNOWN_TOKENS: &[KnownToken] = &[
+    //KT1PWx2mnDueood7fEmfbBDKx1D9BAnnXitn
+    KnownToken {
+        contract: &[
+            0xa3, 0xd0, 0xf5, 0x8d, 0x89, 0x64, 0xbd, 0x1b, 0x37, 0xfb, 0x0a, 0x0c, 0x19, 0x7b,
+            0x38, 0xcf, 0x46, 0x60, 0x8d, 0x49,
+        ],
+        ticker: b"tzBTC",
+        decimals: 8,
+    },
+    //KT1K9gCRgaLRFKTErYt1wVxA3Frb9FjasjTV
+    KnownToken {
+        contract: &[
+            0x73, 0xea, 0x80, 0xf0, 0x5b, 0xe7, 0x78, 0xbe, 0x22, 0x7c, 0xd8, 0xe9, 0x86, 0xf7,
+            0x14, 0xf2, 0x79, 0xbd, 0xe0, 0x42,
+        ],
+        ticker: b"kUSD",
+        decimals: 18,
+    },
+    //KT1LN4LPSqTMS7Sd2CJw4bbDGRkMv2t68Fy9
+    KnownToken {
+        contract: &[
+            0x81, 0x3a, 0x19, 0xaa, 0x1c, 0xb9, 0x6f, 0xe5, 0xb0, 0x39, 0xc0, 0xbf, 0xc1, 0x63,
+            0x3e, 0x61, 0xda, 0xf0, 0xae, 0x3f,
+        ],
+        ticker: b"USDtz",
+        decimals: 6,
+    },
+    //KT1SjXiUX63QvdNMcM2m492f7kuf8JxXRLp4
+    KnownToken {
+        contract: &[
+            0xc7, 0x1a, 0x72, 0x4e, 0x60, 0x08, 0x8d, 0x39, 0x8e, 0x77, 0x24, 0x73, 0x9f, 0xaa,
+            0x74, 0x88, 0xdf, 0xab, 0x46, 0xb1,
+        ],
+        ticker: b"ctez",
+        decimals: 6,
+    },
+];
+
 ///[source](https://github.com/ecadlabs/taquito/blob/dfb78acc673d48f8498998653a569f0374fb75e3/packages/taquito-utils/src/constants.ts#L40-L78)
 pub mod tzprefix {
     pub const TZ1: &[u8] = &[6, 161, 159];
     pub const TZ2: &[u8] = &[6, 161, 161];
     pub const TZ3: &[u8] = &[6, 161, 164];
+    pub const TZ4: &[u8] = &[6, 161, 166];
 
     pub const KT1: &[u8] = &[2, 90, 121];
 
     pub const EDPK: &[u8] = &[13, 15, 37, 217];
     pub const SPPK: &[u8] = &[3, 254, 226, 86];
     pub const P2PK: &[u8] = &[3, 178, 139, 127];
+    pub const BLPK: &[u8] = &[6, 149, 135, 204];
+
+    pub const EDSIG: &[u8] = &[9, 245, 205, 134, 18];
+    pub const SPSIG1: &[u8] = &[13, 115, 101, 19, 63];
+    pub const P2SIG: &[u8] = &[54, 240, 44, 52];
+    pub const BLSIG: &[u8] = &[40, 171, 64, 207];
 
     pub const B: &[u8] = &[1, 52];
     pub const P: &[u8] = &[2, 170];
 
     pub const NET: &[u8] = &[87, 82, 0];
+
+    pub const SR1: &[u8] = &[6, 124, 117];
+    pub const SRC1: &[u8] = &[17, 165, 134, 138];
+    pub const SRS1: &[u8] = &[17, 165, 235, 240];
 }
@@ -17,7 +17,7 @@ use core::{mem::MaybeUninit, ptr::addr_of_mut};
 use std::convert::TryFrom;
 
 use crate::{
-    constants::{EDWARDS_SIGN_BUFFER_MIN_LENGTH, SECP256_SIGN_BUFFER_MIN_LENGTH},
+    constants::{ApduError, EDWARDS_SIGN_BUFFER_MIN_LENGTH, SECP256_SIGN_BUFFER_MIN_LENGTH},
     sys,
     utils::ApduPanic,
 };
@@ -52,16 +52,13 @@ impl PublicKey {
                 hasher.update(&bytes[1..len])?;
             }
             Curve::Secp256K1 | Curve::Secp256R1 => {
-                let bytes = self.0.as_ref();
-
-                //calculate a new first byte
-                let first = 0x02 + (bytes[64] & 0x01);
-                hasher.update(&[first])?;
+                //the key is always uncompressed for secp curves
+                let compressed = self.compressed_secp().apdu_unwrap();
 
-                //we already hashed the first byte
-                // so hash from the second to the 33rd (ignore the rest)
-                hasher.update(&bytes[1..33])?;
+                hasher.update(&compressed[..])?;
             }
+            //keys are never derived on this curve
+            Curve::Bls12_381 => return Err((ApduError::DataInvalid as u16).into()),
         }
 
         hasher.finalize_into(out)
@@ -74,6 +71,42 @@ impl PublicKey {
 
         self.0.curve().try_into().apdu_unwrap()
     }
+
+    /// Retrieve the compressed point for secp256k1 and secp256r1 keys,
+    /// as used in the sppk and p2pk encodings
+    pub fn compressed_secp(&self) -> Option<[u8; 33]> {
+        self.0.compressed_secp()
+    }
+
+    /// Encodes the key as base58 (edpk, sppk, p2pk) on the provided `out` buffer,
+    /// the same way octez-client prints it
+    ///
+    /// Ed25519 keys need to be compressed first
+    ///
+    /// returns the number of bytes written
+    pub fn base58(&self, out: &mut [u8; PUBLIC_KEY_BASE58_LEN]) -> Result<usize, Error> {
+        self.with_encoded(|bytes| public_key_base58((self.curve(), bytes), out))
+    }
+
+    /// Returns true if `(curve, bytes)`, as found in an operation, is this key
+    ///
+    /// Ed25519 keys need to be compressed first
+    pub fn matches(&self, (curve, bytes): (Curve, &[u8])) -> bool {
+        //both Ed25519 derivations produce the same kind of key
+        curve.to_prefix() == self.curve().to_prefix() && self.with_encoded(|own| own == bytes)
+    }
+
+    /// Calls `f` with the key as encoded in operations
+    fn with_encoded<T>(&self, f: impl FnOnce(&[u8]) -> T) -> T {
+        match self.compressed_secp() {
+            Some(compressed) => f(&compressed[..]),
+            None => {
+                //only the 32 bytes of the point, without the tag in front
+                let bytes = self.as_ref();
+                f(&bytes[bytes.len().saturating_sub(32)..])
+            }
+        }
+    }
 }
 
 impl AsRef<[u8]> for PublicKey {
@@ -89,6 +122,9 @@ pub enum Curve {
     Secp256K1,
     Secp256R1,
     Bip32Ed25519,
+    /// Only found in parsed operations (tz4 addresses),
+    /// keys can't be derived on this curve
+    Bls12_381,
 }
 
 impl TryFrom<u8> for Curve {
@@ -112,16 +148,20 @@ impl From<Curve> for u8 {
             Curve::Secp256K1 => 1,
             Curve::Secp256R1 => 2,
             Curve::Bip32Ed25519 => 3,
+            Curve::Bls12_381 => 4,
         }
     }
 }
 
-impl From<Curve> for sys::crypto::Curve {
-    fn from(from: Curve) -> Self {
+impl TryFrom<Curve> for sys::crypto::Curve {
+    type Error = ();
+
+    fn try_from(from: Curve) -> Result<Self, Self::Error> {
         match from {
-            Curve::Ed25519 | Curve::Bip32Ed25519 => Self::Ed25519,
-            Curve::Secp256K1 => Self::Secp256K1,
-            Curve::Secp256R1 => Self::Secp256R1,
+            Curve::Ed25519 | Curve::Bip32Ed25519 => Ok(Self::Ed25519),
+            Curve::Secp256K1 => Ok(Self::Secp256K1),
+            Curve::Secp256R1 => Ok(Self::Secp256R1),
+            Curve::Bls12_381 => Err(()),
         }
     }
 }
@@ -151,18 +191,20 @@ pub enum SignError {
 }
 
 impl<const B: usize> SecretKey<B> {
-    pub fn new(curve: Curve, path: BIP32Path<B>) -> Self {
+    /// Fails if keys can't be derived on `curve`
+    pub fn new(curve: Curve, path: BIP32Path<B>) -> Result<Self, Error> {
         use sys::crypto::Mode;
 
         let mode = match curve {
             Curve::Ed25519 => Mode::Ed25519Slip10,
             _ => Mode::BIP32,
         };
-        Self(sys::crypto::ecfp256::SecretKey::new(
-            mode,
-            curve.into(),
-            path,
-        ))
+        let curve = sys::crypto::Curve::try_from(curve)
+            .map_err(|_| Error::from(ApduError::DataInvalid as u16))?;
+
+        Ok(Self(sys::crypto::ecfp256::SecretKey::new(
+            mode, curve, path,
+        )))
     }
 
     pub fn into_public(self) -> Result<PublicKey, Error> {
@@ -186,25 +228,56 @@ impl<const B: usize> SecretKey<B> {
         self.0.curve().try_into().apdu_unwrap()
     }
 
+    /// Derives the private key once, to sign multiple times with it
+    #[inline(never)]
+    pub fn derive(&self) -> Result<DerivedSecretKey, Error> {
+        self.0.derive().map(DerivedSecretKey)
+    }
+
     pub fn sign(&self, data: &[u8], out: &mut [u8]) -> Result<usize, SignError> {
-        match self.curve() {
-            Curve::Ed25519 | Curve::Bip32Ed25519 if out.len() < EDWARDS_SIGN_BUFFER_MIN_LENGTH => {
-                Err(SignError::BufferTooSmall)
-            }
-            Curve::Secp256K1 | Curve::Secp256R1 if out.len() < SECP256_SIGN_BUFFER_MIN_LENGTH => {
-                Err(SignError::BufferTooSmall)
-            }
+        check_sign_buffer(self.curve(), out)?;
 
-            Curve::Ed25519 | Curve::Bip32Ed25519 | Curve::Secp256K1 | Curve::Secp256R1 => self
-                .0
-                .sign::<Sha256>(data, out) //pass Sha256 for the signature nonce hasher
-                .map_err(SignError::Sys),
+        self.0
+            .sign::<Sha256>(data, out) //pass Sha256 for the signature nonce hasher
+            .map_err(SignError::Sys)
+    }
+}
+
+/// Private key already derived from the seed
+///
+/// Signing with it skips the derivation done by [`SecretKey::sign`]
+pub struct DerivedSecretKey(sys::crypto::ecfp256::DerivedSecretKey);
+
+impl DerivedSecretKey {
+    pub fn curve(&self) -> Curve {
+        use std::convert::TryInto;
+        //see `SecretKey::curve`
+        self.0.curve().try_into().apdu_unwrap()
+    }
+
+    pub fn sign(&self, data: &[u8], out: &mut [u8]) -> Result<usize, SignError> {
+        check_sign_buffer(self.curve(), out)?;
+
+        self.0
+            .sign::<Sha256>(data, out) //pass Sha256 for the signature nonce hasher
+            .map_err(SignError::Sys)
+    }
+}
+
+fn check_sign_buffer(curve: Curve, out: &[u8]) -> Result<(), SignError> {
+    match curve {
+        Curve::Ed25519 | Curve::Bip32Ed25519 if out.len() < EDWARDS_SIGN_BUFFER_MIN_LENGTH => {
+            Err(SignError::BufferTooSmall)
+        }
+        Curve::Secp256K1 | Curve::Secp256R1 if out.len() < SECP256_SIGN_BUFFER_MIN_LENGTH => {
+            Err(SignError::BufferTooSmall)
         }
+        _ => Ok(()),
     }
 }
 
 impl Curve {
-    pub fn to_secret<const B: usize>(self, path: &BIP32Path<B>) -> SecretKey<B> {
+    pub fn to_secret<const B: usize>(self, path: &BIP32Path<B>) -> Result<SecretKey<B>, Error> {
         SecretKey::new(self, *path)
     }
 
@@ -215,6 +288,7 @@ impl Curve {
             Curve::Ed25519 | Curve::Bip32Ed25519 => tzprefix::TZ1,
             Curve::Secp256K1 => tzprefix::TZ2,
             Curve::Secp256R1 => tzprefix::TZ3,
+            Curve::Bls12_381 => tzprefix::TZ4,
         })
         .into_inner()
     }
@@ -226,7 +300,259 @@ impl Curve {
             Curve::Ed25519 | Curve::Bip32Ed25519 => tzprefix::EDPK,
             Curve::Secp256K1 => tzprefix::SPPK,
             Curve::Secp256R1 => tzprefix::P2PK,
+            Curve::Bls12_381 => tzprefix::BLPK,
         })
         .into_inner()
     }
+
+    pub fn to_sig_prefix(self) -> &'static [u8] {
+        use crate::constants::tzprefix;
+
+        sys::PIC::new(match self {
+            Curve::Ed25519 | Curve::Bip32Ed25519 => tzprefix::EDSIG,
+            Curve::Secp256K1 => tzprefix::SPSIG1,
+            Curve::Secp256R1 => tzprefix::P2SIG,
+            Curve::Bls12_381 => tzprefix::BLSIG,
+        })
+        .into_inner()
+    }
+}
+
+pub const PUBLIC_KEY_BASE58_LEN: usize = 56;
+
+/// Encodes a public key as base58 (edpk, sppk, p2pk) on the provided `out` buffer
+///
+/// returns the number of bytes written
+pub fn public_key_base58(
+    (crv, bytes): (Curve, &[u8]),
+    out: &mut [u8; PUBLIC_KEY_BASE58_LEN],
+) -> Result<usize, Error> {
+    let prefix = crv.to_prefix();
+
+    let mut checksum = [0; 4];
+    crate::handlers::sha256x2(&[prefix, bytes], &mut checksum)?;
+
+    let (len, input) = {
+        //initialize with max len
+        let mut array = [0; 4 + 33 + 4];
+        array[..4].copy_from_slice(prefix);
+        array[4..4 + bytes.len()].copy_from_slice(bytes);
+        array[4 + bytes.len()..4 + bytes.len() + 4].copy_from_slice(&checksum[..]);
+        (4 + bytes.len() + 4, array)
+    };
+
+    let len = bs58::encode(&input[..len])
+        .into(&mut out[..])
+        .expect("encoded in base58 is not of the right length");
+    Ok(len)
+}
+
+pub const SIGNATURE_BASE58_LEN: usize = 100;
+
+/// Converts a signature produced by [`SecretKey::sign`] to the
+/// 64 bytes representation used by Tezos
+///
+/// ECDSA signatures are DER encoded (with the parity bit in the first byte),
+/// so `r` and `s` are extracted and padded to 32 bytes each
+fn signature_to_raw(sig: &[u8]) -> Option<[u8; 64]> {
+    let mut out = [0; 64];
+
+    if sig.len() == 64 {
+        out.copy_from_slice(sig);
+        return Some(out);
+    }
+
+    //DER sequence tag, ignoring the parity bit
+    if sig.len() < 2 || sig[0] & 0xFE != 0x30 {
+        return None;
+    }
+
+    let mut rem = &sig[2..];
+    for half in out.chunks_exact_mut(32) {
+        //each integer is: tag (0x02), length, big endian bytes
+        let (len, bytes) = match rem {
+            [0x02, len, bytes @ ..] if bytes.len() >= *len as usize => (*len as usize, bytes),
+            _ => return None,
+        };
+        let (int, rest) = bytes.split_at(len);
+        rem = rest;
+
+        //strip the leading zero added to keep the integer positive
+        let int = match int {
+            [0, int @ ..] if int.len() >= 32 => int,
+            int => int,
+        };
+        if int.len() > 32 {
+            return None;
+        }
+
+        half[32 - int.len()..].copy_from_slice(int);
+    }
+
+    Some(out)
+}
+
+/// Encodes a signature as base58 (edsig, spsig1, p2sig) on the provided `out` buffer
+///
+/// returns the number of bytes written
+#[inline(never)]
+pub fn signature_base58(
+    curve: Curve,
+    sig: &[u8],
+    out: &mut [u8; SIGNATURE_BASE58_LEN],
+) -> Result<usize, Error> {
+    let prefix = curve.to_sig_prefix();
+    let sig = signature_to_raw(sig).ok_or(ApduError::DataInvalid as u16)?;
+
+    let mut checksum = [0; 4];
+    crate::handlers::sha256x2(&[prefix, &sig[..]], &mut checksum)?;
+
+    let (len, input) = {
+        //initialize with max len
+        let mut array = [0; 5 + 64 + 4];
+        array[..prefix.len()].copy_from_slice(prefix);
+        array[prefix.len()..prefix.len() + 64].copy_from_slice(&sig[..]);
+        array[prefix.len() + 64..prefix.len() + 64 + 4].copy_from_slice(&checksum[..]);
+        (prefix.len() + 64 + 4, array)
+    };
+
+    let len = bs58::encode(&input[..len])
+        .into(&mut out[..])
+        .expect("encoded in base58 is not of the right length");
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        public_key_base58, signature_base58, signature_to_raw, Curve, PUBLIC_KEY_BASE58_LEN,
+        SIGNATURE_BASE58_LEN,
+    };
+
+    #[test]
+    fn secp_compression() {
+        use crate::sys::crypto::bip32::BIP32Path;
+
+        let path = BIP32Path::<5>::new([0x8000_002c, 0x8000_06c1, 0x8000_0000, 0x8000_0000])
+            .expect("invalid bip32 path");
+
+        for crv in [Curve::Secp256K1, Curve::Secp256R1] {
+            let pk = crv
+                .to_secret(&path)
+                .and_then(|sk| sk.into_public())
+                .expect("couldn't derive key");
+            let uncompressed = pk.as_ref();
+
+            let compressed = pk.compressed_secp().expect("secp key should be compressed");
+            assert_eq!(compressed[0], 0x02 + (uncompressed[64] & 1));
+            assert_eq!(&compressed[1..], &uncompressed[1..33]);
+        }
+
+        let pk = Curve::Ed25519
+            .to_secret(&path)
+            .and_then(|sk| sk.into_public())
+            .expect("couldn't derive key");
+        assert!(pk.compressed_secp().is_none());
+    }
+
+    #[test]
+    fn underivable_curve() {
+        use crate::sys::crypto::bip32::BIP32Path;
+
+        let path = BIP32Path::<5>::new([0x8000_002c, 0x8000_06c1, 0x8000_0000, 0x8000_0000])
+            .expect("invalid bip32 path");
+
+        assert!(Curve::Bls12_381.to_secret(&path).is_err());
+    }
+
+    #[test]
+    fn public_key_prefixes() {
+        let mut base58 = [0; PUBLIC_KEY_BASE58_LEN];
+
+        let len = public_key_base58((Curve::Bip32Ed25519, &[0x00; 32]), &mut base58)
+            .expect("couldn't encode Secp256K1 to base58");
+        assert_eq!(len, 54);
+        assert!(base58[..len].starts_with(b"edpk"));
+
+        let len = public_key_base58((Curve::Bip32Ed25519, &[0xff; 32]), &mut base58)
+            .expect("couldn't encode Secp256K1 to base58");
+        assert_eq!(len, 54);
+
+        let len = public_key_base58((Curve::Secp256K1, &[0; 33]), &mut base58)
+            .expect("couldn't encode Secp256K1 to base58");
+        assert_eq!(len, 55);
+
+        let len = public_key_base58((Curve::Secp256K1, &[0xff; 33]), &mut base58)
+            .expect("couldn't encode Secp256K1 to base58");
+        assert_eq!(len, 55);
+        assert!(base58[..len].starts_with(b"sppk"));
+
+        let len = public_key_base58((Curve::Secp256R1, &[0; 33]), &mut base58)
+            .expect("couldn't encode Secp256K1 to base58");
+        assert_eq!(len, 55);
+
+        let len = public_key_base58((Curve::Secp256R1, &[0xff; 33]), &mut base58)
+            .expect("couldn't encode Secp256K1 to base58");
+        assert_eq!(len, 55);
+        assert!(base58[..len].starts_with(b"p2pk"));
+    }
+
+    #[test]
+    fn derived_public_key_base58() {
+        use crate::sys::crypto::bip32::BIP32Path;
+
+        let path = BIP32Path::<5>::new([0x8000_002c, 0x8000_06c1, 0x8000_0000, 0x8000_0000])
+            .expect("invalid bip32 path");
+
+        for (crv, prefix, expected_len) in [
+            (Curve::Ed25519, &b"edpk"[..], 54),
+            (Curve::Secp256K1, &b"sppk"[..], 55),
+            (Curve::Secp256R1, &b"p2pk"[..], 55),
+        ] {
+            let mut pk = crv
+                .to_secret(&path)
+                .and_then(|sk| sk.into_public())
+                .expect("couldn't derive key");
+            pk.compress().expect("couldn't compress key");
+
+            let mut out = [0; PUBLIC_KEY_BASE58_LEN];
+            let len = pk.base58(&mut out).expect("couldn't encode key");
+            assert_eq!(len, expected_len);
+            assert!(out[..len].starts_with(prefix));
+        }
+    }
+
+    #[test]
+    fn der_to_raw() {
+        let mut der = std::vec![0x31, 0x45, 0x02, 0x21, 0x00];
+        der.extend_from_slice(&[0x80; 32]);
+        der.extend_from_slice(&[0x02, 0x20]);
+        der.extend_from_slice(&[0x11; 32]);
+
+        let raw = signature_to_raw(&der).expect("couldn't convert DER signature");
+        assert_eq!(&raw[..32], &[0x80; 32][..]);
+        assert_eq!(&raw[32..], &[0x11; 32][..]);
+
+        //short integers are padded
+        let der = [0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02];
+        let raw = signature_to_raw(&der).expect("couldn't convert DER signature");
+        assert_eq!(raw[31], 1);
+        assert_eq!(raw[63], 2);
+        assert!(raw[..31].iter().chain(&raw[32..63]).all(|&b| b == 0));
+    }
+
+    #[test]
+    fn signature_prefixes() {
+        let mut out = [0; SIGNATURE_BASE58_LEN];
+
+        let len = signature_base58(Curve::Ed25519, &[0; 64], &mut out).unwrap();
+        assert!(out[..len].starts_with(b"edsig"));
+        assert_eq!(len, 99);
+
+        let len = signature_base58(Curve::Secp256K1, &[0xff; 64], &mut out).unwrap();
+        assert!(out[..len].starts_with(b"spsig1"));
+
+        let len = signature_base58(Curve::Secp256R1, &[0xff; 64], &mut out).unwrap();
+        assert!(out[..len].starts_with(b"p2sig"));
+    }
 }
@@ -16,88 +16,22 @@
 
 use core::hint::unreachable_unchecked;
 
-use cfg_if::cfg_if;
-
 use crate::constants::ApduError;
 use crate::constants::ApduError::{ClaNotSupported, CommandNotAllowed};
 
-use crate::handlers::public_key::GetAddress;
+use crate::handlers::app_config::{GetAppConfig, SetFeeThreshold};
+use crate::handlers::public_key::{GetAddress, GetAllAddresses};
 use crate::handlers::signing::Sign;
+use crate::handlers::supported_operations::{GetSupportedInstructions, GetSupportedOperations};
 use crate::handlers::version::GetVersion;
 
 use crate::handlers::legacy::public_key::{LegacyGetPublic, LegacyPromptAddress};
 use crate::handlers::legacy::signing::{LegacySign, LegacySignWithHash};
 use crate::handlers::legacy::version::{LegacyGetVersion, LegacyGit};
 
-use crate::utils::{ApduBufferRead, ApduPanic};
-
-pub const CLA: u8 = 0x80;
-
-cfg_if! {
-    if #[cfg(feature = "baking")] {
-        //baking-only legacy instructions
-        pub const INS_LEGACY_AUTHORIZE_BAKING: u8 = 0x1;
-        pub const INS_LEGACY_RESET: u8 = 0x6;
-        pub const INS_LEGACY_QUERY_AUTH_KEY: u8 = 0x7;
-        pub const INS_LEGACY_QUERY_MAIN_HWM: u8 = 0x8;
-        pub const INS_LEGACY_SETUP: u8 = 0xA;
-        pub const INS_LEGACY_QUERY_ALL_HWM: u8 = 0xB;
-        pub const INS_LEGACY_DEAUTHORIZE: u8 = 0xC;
-        pub const INS_LEGACY_QUERY_AUTH_KEY_WITH_CURVE: u8 = 0xD;
-        pub const INS_LEGACY_HMAC: u8 = 0xE;
-
-        pub const INS_AUTHORIZE_BAKING: u8 = 0xA1;
-        pub const INS_DEAUTHORIZE_BAKING: u8 = 0xAC;
-        pub const INS_QUERY_AUTH_KEY: u8 = 0xA7;
-        pub const INS_QUERY_AUTH_KEY_WITH_CURVE: u8 = 0xAD;
-        pub const INS_BAKER_SIGN: u8 = 0xAF;
-
-        //baking-only legacy imports
-        use crate::handlers::legacy::hwm::{LegacyResetHWM, LegacyQueryMainHWM,
-                                           LegacyQueryAllHWM};
-        use crate::handlers::legacy::baking::{LegacyAuthorize, LegacyDeAuthorize,
-                                              LegacyQueryAuthKey, LegacyQueryAuthKeyWithCurve,
-                                              LegacySetup};
-        use crate::handlers::legacy::hmac::LegacyHMAC;
-
-        //baking-only new instructions
-        use crate::handlers::baking::{AuthorizeBaking, DeAuthorizeBaking, QueryAuthKey,
-                                      QueryAuthKeyWithCurve, Baking};
-    } else if #[cfg(feature = "wallet")] {
-        //wallet-only legacy instructions
-        pub const INS_LEGACY_SIGN_UNSAFE: u8 = 0x5;
-
-        //wallet-only legacy imports
-        use crate::handlers::legacy::signing::LegacySignUnsafe;
-
-        //wallet-only new instructions
-    }
-}
+use crate::utils::{ApduBufferRead, ApduBufferWrite};
 
-//common legacy instructions
-pub const INS_LEGACY_GET_VERSION: u8 = 0x0;
-pub const INS_LEGACY_GET_PUBLIC_KEY: u8 = 0x2;
-pub const INS_LEGACY_PROMPT_PUBLIC_KEY: u8 = 0x3;
-pub const INS_LEGACY_SIGN: u8 = 0x4;
-pub const INS_LEGACY_GIT: u8 = 0x9;
-pub const INS_LEGACY_SIGN_WITH_HASH: u8 = 0xF;
-
-//common new instructions
-pub const INS_GET_VERSION: u8 = 0x10;
-pub const INS_GET_ADDRESS: u8 = 0x11;
-pub const INS_SIGN: u8 = 0x12;
-
-//dev-only
-cfg_if! {
-    if #[cfg(feature = "dev")] {
-        use crate::handlers::dev::{Except, Sha256, Echo, BlindSign, Debug};
-
-        pub const INS_DEV_HASH: u8 = 0xF0;
-        pub const INS_DEV_EXCEPT: u8 = 0xF1;
-        pub const INS_DEV_ECHO_UI: u8 = 0xF2;
-        pub const INS_BLIND_SIGN: u8 = 0xF3;
-    }
-}
+pub use crate::apdu_spec::*;
 
 pub trait ApduHandler {
     fn handle<'apdu>(
@@ -111,7 +45,7 @@ pub trait ApduHandler {
 pub fn apdu_dispatch<'apdu>(
     flags: &mut u32,
     tx: &mut u32,
-    apdu_buffer: ApduBufferRead<'apdu>,
+    mut apdu_buffer: ApduBufferRead<'apdu>,
 ) -> Result<(), ApduError> {
     crate::sys::zemu_log_stack("apdu_dispatch\x00");
     *flags = 0;
@@ -123,53 +57,14 @@ pub fn apdu_dispatch<'apdu>(
 
     let ins = apdu_buffer.ins();
 
-    // Reference for legacy API https://github.com/obsidiansystems/ledger-app-tezos/blob/58797b2f9606c5a30dd1ccc9e5b9962e45e10356/src/main.c#L16-L31
+    //conditions not depending on the payload are checked here for all the handlers
+    crate::policy::Requirements::of(ins).check(apdu_buffer.p1())?;
 
-    //dev-only instructions
-    cfg_if! {
-        if #[cfg(feature = "dev")] {
-            match ins {
-                INS_DEV_HASH => return Sha256::handle(flags, tx, apdu_buffer),
-                INS_DEV_EXCEPT => return Except::handle(flags, tx, apdu_buffer),
-                INS_DEV_ECHO_UI => return Echo::handle(flags, tx, apdu_buffer),
-                INS_BLIND_SIGN => return BlindSign::handle(flags, tx, apdu_buffer),
-                _ => {},
-            }
-        }
-    }
+    // Reference for legacy API https://github.com/obsidiansystems/ledger-app-tezos/blob/58797b2f9606c5a30dd1ccc9e5b9962e45e10356/src/main.c#L16-L31
 
-    //these are exclusive
-    cfg_if! {
-        if #[cfg(feature = "baking")] {
-            //baking-only instructions
-            match ins {
-                INS_LEGACY_RESET => return LegacyResetHWM::handle(flags, tx, apdu_buffer),
-                INS_LEGACY_QUERY_MAIN_HWM => return LegacyQueryMainHWM::handle(flags, tx, apdu_buffer),
-                INS_LEGACY_QUERY_ALL_HWM => return LegacyQueryAllHWM::handle(flags, tx, apdu_buffer),
-
-                INS_AUTHORIZE_BAKING => return AuthorizeBaking::handle(flags, tx, apdu_buffer),
-                INS_DEAUTHORIZE_BAKING => return DeAuthorizeBaking::handle(flags, tx, apdu_buffer),
-                INS_QUERY_AUTH_KEY => return QueryAuthKey::handle(flags, tx, apdu_buffer),
-                INS_QUERY_AUTH_KEY_WITH_CURVE => return QueryAuthKeyWithCurve::handle(flags, tx, apdu_buffer),
-                INS_BAKER_SIGN => return Baking::handle(flags, tx, apdu_buffer),
-
-                INS_LEGACY_AUTHORIZE_BAKING => return LegacyAuthorize::handle(flags, tx, apdu_buffer),
-                INS_LEGACY_DEAUTHORIZE => return LegacyDeAuthorize::handle(flags, tx, apdu_buffer),
-                INS_LEGACY_QUERY_AUTH_KEY => return LegacyQueryAuthKey::handle(flags, tx, apdu_buffer),
-                INS_LEGACY_QUERY_AUTH_KEY_WITH_CURVE => return LegacyQueryAuthKeyWithCurve::handle(flags, tx, apdu_buffer),
-
-                INS_LEGACY_SETUP => return LegacySetup::handle(flags, tx, apdu_buffer),
-                INS_LEGACY_HMAC => return LegacyHMAC::handle(flags, tx, apdu_buffer),
-                _ => {}
-            }
-        } else if #[cfg(feature = "wallet")] {
-            //wallet-only instructions
-            #[allow(clippy::single_match)]
-            match ins {
-                INS_LEGACY_SIGN_UNSAFE => return LegacySignUnsafe::handle(flags, tx, apdu_buffer),
-                _ => {}
-            }
-        }
+    //dev, wallet-only or baking-only instructions
+    if let Some(res) = crate::handlers::dispatch_features(ins, flags, tx, apdu_buffer.reborrow()) {
+        return res;
     }
 
     //common instructions
@@ -179,6 +74,7 @@ pub fn apdu_dispatch<'apdu>(
         INS_LEGACY_GET_PUBLIC_KEY => LegacyGetPublic::handle(flags, tx, apdu_buffer),
         INS_LEGACY_PROMPT_PUBLIC_KEY => LegacyPromptAddress::handle(flags, tx, apdu_buffer),
         INS_GET_ADDRESS => GetAddress::handle(flags, tx, apdu_buffer),
+        INS_GET_ALL_ADDRESSES => GetAllAddresses::handle(flags, tx, apdu_buffer),
 
         INS_LEGACY_GIT => LegacyGit::handle(flags, tx, apdu_buffer),
 
@@ -187,9 +83,13 @@ pub fn apdu_dispatch<'apdu>(
         INS_SIGN => Sign::handle(flags, tx, apdu_buffer),
 
         INS_GET_VERSION => GetVersion::handle(flags, tx, apdu_buffer),
+        INS_GET_SUPPORTED_OPERATIONS => GetSupportedOperations::handle(flags, tx, apdu_buffer),
+        INS_GET_APP_CONFIG => GetAppConfig::handle(flags, tx, apdu_buffer),
+        INS_GET_SUPPORTED_INSTRUCTIONS => GetSupportedInstructions::handle(flags, tx, apdu_buffer),
+        INS_SET_FEE_THRESHOLD => SetFeeThreshold::handle(flags, tx, apdu_buffer),
 
         #[cfg(feature = "dev")]
-        _ => Debug::handle(flags, tx, apdu_buffer),
+        _ => crate::handlers::dev::Debug::handle(flags, tx, apdu_buffer),
         #[allow(unreachable_patterns)] //not unrechable for all feature configurations
         _ => Err(CommandNotAllowed),
     }
@@ -198,6 +98,12 @@ pub fn apdu_dispatch<'apdu>(
 pub fn handle_apdu(flags: &mut u32, tx: &mut u32, rx: u32, apdu_buffer: &mut [u8]) {
     crate::sys::zemu_log_stack("handle_apdu\x00");
 
+    //a new command means the host gave up on the review still on screen (if any),
+    // so reject it before processing the new one
+    if crate::sys::cancel_pending_review(rx) {
+        crate::sys::zemu_log("handle_apdu: cancelled pending review\n\x00");
+    }
+
     //construct reader
     let status_word = match ApduBufferRead::new(apdu_buffer, rx) {
         Ok(reader) => match apdu_dispatch(flags, tx, reader)
@@ -210,42 +116,42 @@ pub fn handle_apdu(flags: &mut u32, tx: &mut u32, rx: u32, apdu_buffer: &mut [u8
         Err(_) => ApduError::WrongLength as u16,
     };
 
-    let txu = *tx as usize;
-    apdu_buffer
-        .get_mut(txu..txu + 2)
-        .apdu_unwrap()
-        .copy_from_slice(status_word.to_be_bytes().as_ref());
-
-    *tx += 2;
+    *tx = ApduBufferWrite::new(apdu_buffer).close(*tx as usize, status_word) as u32;
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::assert_error_code;
-    use crate::constants::ApduError::WrongLength;
-    use crate::dispatcher::handle_apdu;
-    use std::convert::TryInto;
+    use super::handle_apdu;
+    use crate::constants::ApduError::{ClaNotSupported, WrongLength};
+    use crate::utils::ApduFixture;
 
     #[test]
     fn apdu_too_short() {
-        let flags = &mut 0u32;
-        let tx = &mut 0u32;
-        let rx = 0u32;
-        let buffer = &mut [0u8; 260];
-
-        handle_apdu(flags, tx, rx, buffer);
-        assert_eq!(*tx, 2u32);
-        assert_error_code!(*tx, buffer, WrongLength);
+        let mut fixture = ApduFixture::new();
+        let response = fixture.send_raw(&[]);
+
+        assert!(response.assert_code(WrongLength).is_empty());
     }
 
     #[test]
     fn apdu_invalid_cla() {
-        let flags = &mut 0u32;
-        let tx = &mut 0u32;
-        let rx = 5u32;
-        let buffer = &mut [0u8; 260];
+        let mut fixture = ApduFixture::new();
+        let response = fixture.send_raw(&[0; 5]);
+
+        assert!(response.assert_code(ClaNotSupported).is_empty());
+    }
+
+    #[test]
+    fn apdu_response_zeroized() {
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0xAB; 260];
+        //invalid CLA, with a full payload
+        buffer[..5].copy_from_slice(&[0, 0, 0, 0, 0xFF]);
+
+        handle_apdu(&mut flags, &mut tx, 5 + 0xFF, &mut buffer);
 
-        handle_apdu(flags, tx, rx, buffer);
-        assert_eq!(*tx, 2u32);
+        assert_eq!(tx, 2);
+        assert!(buffer[2..].iter().all(|&b| b == 0));
     }
 }
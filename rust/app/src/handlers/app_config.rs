@@ -0,0 +1,277 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use bolos::pic_str;
+use zemu_sys::{Show, ViewError, Viewable};
+
+use crate::{
+    constants::ApduError as Error,
+    dispatcher::ApduHandler,
+    handlers::handle_ui_message,
+    parser::Zarith,
+    sys::{flash_slot::Wear, new_flash_slot, pic::PIC, Settings},
+    utils::{ApduBufferRead, ApduPanic},
+};
+
+const N_PAGES: usize = 8;
+
+type WearLeveller = Wear<'static, N_PAGES>;
+
+#[bolos::lazy_static]
+static mut COUNTER: WearLeveller = new_flash_slot!(N_PAGES).apdu_expect("NVM might be corrupted");
+
+/// Number of signatures produced by the app, persisted in NVM
+///
+/// The counter only ever goes up, so external tooling can compare it
+/// with the signatures it expects to have been requested
+pub struct SignatureCounter;
+
+impl SignatureCounter {
+    pub const LEN: usize = 8;
+
+    /// Returns the number of signatures produced so far
+    pub fn get() -> u64 {
+        match unsafe { COUNTER.read() } {
+            Ok(data) => u64::from_be_bytes(*arrayref::array_ref!(data, 0, SignatureCounter::LEN)),
+            //never written
+            Err(_) => 0,
+        }
+    }
+
+    /// Increments the counter, to be called before a signature is returned
+    ///
+    /// If the counter can't be persisted then the signature shouldn't be returned
+    pub fn increment() -> Result<(), Error> {
+        let next = Self::get().checked_add(1).ok_or(Error::ExecutionError)?;
+
+        let mut data = [0; 52];
+        data[..Self::LEN].copy_from_slice(&next.to_be_bytes()[..]);
+
+        unsafe { COUNTER.write(data) }.map_err(|_| Error::ExecutionError)
+    }
+}
+
+/// Reports the app settings and the signature counter
+pub struct GetAppConfig;
+
+impl GetAppConfig {
+    pub const FLAG_EXPERT: u8 = 1 << 0;
+    pub const FLAG_BLIND_SIGN: u8 = 1 << 1;
+    pub const FLAG_BAKING: u8 = 1 << 2;
+
+    fn flags() -> u8 {
+        let mut flags = 0;
+        if Settings::expert() {
+            flags |= Self::FLAG_EXPERT;
+        }
+        if Settings::blind_sign() {
+            flags |= Self::FLAG_BLIND_SIGN;
+        }
+        if cfg!(feature = "baking") {
+            flags |= Self::FLAG_BAKING;
+        }
+
+        flags
+    }
+}
+
+impl ApduHandler for GetAppConfig {
+    #[inline(never)]
+    fn handle<'apdu>(
+        _: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        *tx = 0;
+
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let out = buffer.write();
+        out[0] = Self::flags();
+        out[1..1 + SignatureCounter::LEN].copy_from_slice(&SignatureCounter::get().to_be_bytes());
+        *tx = 1 + SignatureCounter::LEN as u32;
+
+        Ok(())
+    }
+}
+
+/// Sets the thresholds above which the fees of an operation are flagged
+///
+/// Payload: total threshold and per-byte threshold, both as big endian u64 mutez
+pub struct SetFeeThreshold;
+
+impl SetFeeThreshold {
+    pub const PAYLOAD_LEN: usize = 2 * 8;
+}
+
+impl ApduHandler for SetFeeThreshold {
+    #[inline(never)]
+    fn handle<'apdu>(
+        flags: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        *tx = 0;
+
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let payload = buffer.payload().map_err(|_| Error::DataInvalid)?;
+        if payload.len() != Self::PAYLOAD_LEN {
+            return Err(Error::WrongLength);
+        }
+
+        let total = u64::from_be_bytes(*arrayref::array_ref!(payload, 0, 8));
+        let per_byte = u64::from_be_bytes(*arrayref::array_ref!(payload, 8, 8));
+
+        //the host could otherwise silence the warning, so the user confirms the change
+        let ui = FeeThresholdUI { total, per_byte };
+
+        unsafe { ui.show(flags) }.map_err(|_| Error::ExecutionError)
+    }
+}
+
+struct FeeThresholdUI {
+    total: u64,
+    per_byte: u64,
+}
+
+impl Viewable for FeeThresholdUI {
+    fn num_items(&mut self) -> Result<u8, ViewError> {
+        Ok(3)
+    }
+
+    fn render_item(
+        &mut self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use lexical_core::{write as itoa, Number};
+
+        match item_n {
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"Set Fee Threshold")[..], message, page)
+            }
+            1 => {
+                let title_content = pic_str!(b"Total Fee");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                if self.total == 0 {
+                    return handle_ui_message(&pic_str!(b"Disabled")[..], message, page);
+                }
+
+                let mut buf = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let total = Zarith::format_mutez(self.total, &mut buf).ok_or(ViewError::Unknown)?;
+                handle_ui_message(total, message, page)
+            }
+            2 => {
+                let title_content = pic_str!(b"Fee per Byte");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                if self.per_byte == 0 {
+                    return handle_ui_message(&pic_str!(b"Disabled")[..], message, page);
+                }
+
+                let unit = pic_str!(b" mutez"!);
+                let mut mex = [0; u64::FORMATTED_SIZE_DECIMAL + 6];
+                let len = itoa(self.per_byte, &mut mex[..]).len();
+                mex[len..len + unit.len()].copy_from_slice(unit);
+
+                handle_ui_message(&mex[..len + unit.len()], message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+
+    fn accept(&mut self, _: &mut [u8]) -> (usize, u16) {
+        Settings::set_fee_threshold(self.total);
+        Settings::set_fee_per_byte_threshold(self.per_byte);
+
+        (0, Error::Success as _)
+    }
+
+    fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
+        (0, Error::CommandNotAllowed as _)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dispatcher::INS_GET_APP_CONFIG, utils::ApduFixture};
+
+    #[test]
+    fn apdu_get_app_config() {
+        let before = SignatureCounter::get();
+        SignatureCounter::increment().unwrap();
+
+        let mut fixture = ApduFixture::new();
+        let response = fixture.send(INS_GET_APP_CONFIG, 0, 0, &[]).assert_success();
+
+        assert_eq!(response.len(), 1 + SignatureCounter::LEN);
+        assert_eq!(
+            response[0] & GetAppConfig::FLAG_BAKING != 0,
+            cfg!(feature = "baking")
+        );
+
+        //other tests might be signing at the same time
+        let counter = u64::from_be_bytes(*arrayref::array_ref!(response, 1, 8));
+        assert!(counter > before);
+    }
+
+    #[test]
+    #[serial_test::serial(ui)]
+    fn apdu_set_fee_threshold() {
+        use crate::{dispatcher::INS_SET_FEE_THRESHOLD, sys::get_out};
+
+        let mut payload = [0; SetFeeThreshold::PAYLOAD_LEN];
+        payload[..8].copy_from_slice(&1_000_000u64.to_be_bytes());
+        payload[8..].copy_from_slice(&2u64.to_be_bytes());
+
+        let mut fixture = ApduFixture::new();
+        fixture
+            .send(INS_SET_FEE_THRESHOLD, 0, 0, &payload[..8])
+            .assert_code(Error::WrongLength);
+
+        //the mock accepts the change right away
+        fixture
+            .send(INS_SET_FEE_THRESHOLD, 0, 0, &payload)
+            .assert_success();
+        let (_, out) = get_out().expect("UI mock used");
+        assert_eq!(&out[..2], &(Error::Success as u16).to_be_bytes()[..]);
+        assert_eq!(Settings::fee_threshold(), 1_000_000);
+        assert_eq!(Settings::fee_per_byte_threshold(), 2);
+
+        fixture
+            .send(
+                INS_SET_FEE_THRESHOLD,
+                0,
+                0,
+                &[0; SetFeeThreshold::PAYLOAD_LEN],
+            )
+            .assert_success();
+        get_out().expect("UI mock used");
+        assert_eq!(Settings::fee_threshold(), 0);
+        assert_eq!(Settings::fee_per_byte_threshold(), 0);
+    }
+}
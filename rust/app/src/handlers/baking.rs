@@ -25,19 +25,25 @@ use bolos::{
 
 use crate::{
     constants::{ApduError as Error, BIP32_MAX_LENGTH},
-    crypto::Curve,
+    crypto::{Curve, DerivedSecretKey},
     dispatcher::ApduHandler,
-    handlers::{hwm::HWM, signing::Sign},
+    handlers::{app_config::SignatureCounter, signing::Sign},
     parser::{
         baking::{BlockData, EndorsementData, EndorsementType, Fitness, TenderbakeEndorsement},
-        operations::{Delegation, Reveal},
+        operations::{
+            Delegation, DrainDelegate, Reveal, SeedNonceRevelation, SetDepositsLimit,
+            UpdateConsensusKey,
+        },
         DisplayableItem, Preemble,
     },
     sys::{flash_slot::Wear, new_flash_slot},
-    utils::{ApduBufferRead, ApduPanic, Uploader},
+    utils::{read_u32_be, ApduBufferRead, ApduPanic, Uploader},
 };
 
-const N_PAGES_BAKINGPATH: usize = 1;
+use hwm::HWM;
+
+//the slot is also written when an authorization with an expiry is used
+const N_PAGES_BAKINGPATH: usize = 8;
 
 type WearLeveller = Wear<'static, N_PAGES_BAKINGPATH>;
 
@@ -45,6 +51,10 @@ type WearLeveller = Wear<'static, N_PAGES_BAKINGPATH>;
 static mut BAKINGPATH: WearLeveller =
     new_flash_slot!(N_PAGES_BAKINGPATH).apdu_expect("NVM might be corrupted");
 
+/// Authorized baking key, derived when the authorization changes
+/// (or on boot) so signing doesn't need to derive it every time
+static mut BAKING_KEY: Option<(Curve, BIP32Path<BIP32_MAX_LENGTH>, DerivedSecretKey)> = None;
+
 #[derive(PartialEq, Clone)]
 #[cfg_attr(test, derive(Debug))]
 /// Utility struct to store and read BIP32Path and Curve from NVM slots
@@ -59,14 +69,23 @@ static mut BAKINGPATH: WearLeveller =
 ///
 /// [3..3+i*4] = `BIP32Path`;
 /// byte representation of (`BIP32Path`)[sys::crypto::bip32::BIP32Path]
+///
+/// [43..48] = `Expiry`; byte representation of [`Expiry`]
 struct Bip32PathAndCurve {
     curve: Curve,
     path: BIP32Path<BIP32_MAX_LENGTH>,
+    expiry: Expiry,
 }
 
 impl Bip32PathAndCurve {
-    pub fn new(curve: Curve, path: BIP32Path<BIP32_MAX_LENGTH>) -> Self {
-        Self { curve, path }
+    const EXPIRY_OFFSET: usize = 3 + 4 * BIP32_MAX_LENGTH;
+
+    pub fn with_expiry(curve: Curve, path: BIP32Path<BIP32_MAX_LENGTH>, expiry: Expiry) -> Self {
+        Self {
+            curve,
+            path,
+            expiry,
+        }
     }
 
     /// Attempt to read a Bip32PathAndCurve from some bytes
@@ -90,7 +109,14 @@ impl Bip32PathAndCurve {
             BIP32Path::<BIP32_MAX_LENGTH>::read(&from[2..2 + 1 + 4 * components_length as usize])
                 .map_err(|_| Error::DataInvalid)?;
 
-        Ok(Some(Self { curve, path }))
+        //records written without an expiry have this zeroed, which is `Expiry::Never`
+        let expiry = Expiry::read(&from[Self::EXPIRY_OFFSET..Self::EXPIRY_OFFSET + Expiry::LEN])?;
+
+        Ok(Some(Self {
+            curve,
+            path,
+            expiry,
+        }))
     }
 
     ///Used to set a slot as empty when writing to NVM
@@ -117,10 +143,72 @@ impl From<Bip32PathAndCurve> for [u8; 52] {
             .zip(components)
             .for_each(|(chunk, comp)| chunk.copy_from_slice(&comp.to_be_bytes()[..]));
 
+        let offset = Bip32PathAndCurve::EXPIRY_OFFSET;
+        from.expiry
+            .write(arrayref::array_mut_ref!(out, offset, Expiry::LEN));
+
         out
     }
 }
 
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(test, derive(Debug))]
+/// Limit of a baking authorization, after which blocks and endorsements
+/// are not signed anymore until the key is authorized again
+///
+/// # Codec
+///
+/// [0] = 0x00 | 0x01 | 0x02; never, signatures or level
+///
+/// [1..5] = the limit, big endian
+pub enum Expiry {
+    Never,
+    /// Number of signatures left
+    Signatures(u32),
+    /// Last level that can be signed
+    Level(u32),
+}
+
+impl Expiry {
+    pub const LEN: usize = 1 + 4;
+
+    pub fn read(input: &[u8]) -> Result<Self, Error> {
+        let kind = *input.first().ok_or(Error::WrongLength)?;
+        let limit = read_u32_be(input, 1).map_err(|_| Error::WrongLength)?;
+
+        match kind {
+            0 => Ok(Self::Never),
+            1 => Ok(Self::Signatures(limit)),
+            2 => Ok(Self::Level(limit)),
+            _ => Err(Error::DataInvalid),
+        }
+    }
+
+    pub fn write(self, out: &mut [u8; Self::LEN]) {
+        let (kind, limit) = match self {
+            Self::Never => (0, 0),
+            Self::Signatures(n) => (1, n),
+            Self::Level(level) => (2, level),
+        };
+
+        out[0] = kind;
+        out[1..].copy_from_slice(&limit.to_be_bytes()[..]);
+    }
+
+    /// Checks if a block or endorsement at `level` can be signed
+    ///
+    /// Returns the expiry to store once signed
+    pub fn consume(self, level: u32) -> Result<Self, Error> {
+        match self {
+            Self::Never => Ok(self),
+            Self::Signatures(0) => Err(Error::ApduCodeConditionsNotSatisfied),
+            Self::Signatures(n) => Ok(Self::Signatures(n - 1)),
+            Self::Level(last) if level > last => Err(Error::ApduCodeConditionsNotSatisfied),
+            Self::Level(_) => Ok(self),
+        }
+    }
+}
+
 pub struct Baking;
 
 impl Baking {
@@ -134,26 +222,93 @@ impl Baking {
 
     #[inline(never)]
     fn check_with_stored(curve: Curve, path: &BIP32Path<BIP32_MAX_LENGTH>) -> Result<bool, Error> {
-        //Check if the current baking path in NVM is initialized
-        let current_path =
-            unsafe { BAKINGPATH.read() }.map_err(|_| Error::ApduCodeConditionsNotSatisfied)?;
-        //path seems to be initialized so we can return it
-        //check if it is a good path
-        let nvm_bip = Bip32PathAndCurve::try_from_bytes(current_path)?
-            .ok_or(Error::ApduCodeConditionsNotSatisfied)?;
+        //the dispatcher made sure a key is authorized
+        let current_path = unsafe { BAKINGPATH.read() }.map_err(|_| Error::ExecutionError)?;
+        let nvm_bip =
+            Bip32PathAndCurve::try_from_bytes(current_path)?.ok_or(Error::ExecutionError)?;
 
         Ok(nvm_bip.path == *path && nvm_bip.curve == curve)
     }
 
     /// Will store a curve and path in NVM memory
     pub fn store_baking_key(curve: Curve, path: BIP32Path<BIP32_MAX_LENGTH>) -> Result<(), Error> {
-        let path_and_curve = Bip32PathAndCurve::new(curve, path);
+        Self::store_baking_key_with_expiry(curve, path, Expiry::Never)
+    }
+
+    /// Will store a curve and path in NVM memory,
+    /// usable for signing until `expiry`
+    pub fn store_baking_key_with_expiry(
+        curve: Curve,
+        path: BIP32Path<BIP32_MAX_LENGTH>,
+        expiry: Expiry,
+    ) -> Result<(), Error> {
+        let path_and_curve = Bip32PathAndCurve::with_expiry(curve, path, expiry);
+
+        unsafe { BAKINGPATH.write(path_and_curve.into()) }.map_err(|_| Error::ExecutionError)?;
+
+        Self::derive_baking_key(curve, path).map(|_| ())
+    }
+
+    /// Derives the baking key stored in NVM, if any, ahead of the first signature
+    pub fn load_baking_key() -> Result<(), Error> {
+        match Self::read_baking_key()? {
+            Some((curve, path)) => Self::derive_baking_key(curve, path).map(|_| ()),
+            None => {
+                unsafe { BAKING_KEY.take() };
+                Ok(())
+            }
+        }
+    }
+
+    /// Retrieves the derived key for the given curve and path,
+    /// deriving it (and replacing the previous one) if it's not the current one
+    #[inline(never)]
+    fn derive_baking_key(
+        curve: Curve,
+        path: BIP32Path<BIP32_MAX_LENGTH>,
+    ) -> Result<&'static DerivedSecretKey, Error> {
+        let cached = unsafe { &mut *core::ptr::addr_of_mut!(BAKING_KEY) };
+
+        let current = matches!(cached, Some((crv, p, _)) if *crv == curve && *p == path);
+        if !current {
+            //drop (and zeroize) the previous key first
+            cached.take();
+
+            let key = curve
+                .to_secret(&path)
+                .and_then(|sk| sk.derive())
+                .map_err(|_| Error::ExecutionError)?;
+
+            cached.replace((curve, path, key));
+        }
 
-        unsafe { BAKINGPATH.write(path_and_curve.into()) }.map_err(|_| Error::ExecutionError)
+        cached
+            .as_ref()
+            .map(|(_, _, key)| key)
+            .ok_or(Error::ExecutionError)
+    }
+
+    /// Checks the expiry of the stored authorization before signing
+    /// a block or endorsement at `level`, and updates it
+    #[inline(never)]
+    fn consume_authorization(level: u32) -> Result<(), Error> {
+        let current = unsafe { BAKINGPATH.read() }.map_err(|_| Error::ExecutionError)?;
+        let mut stored =
+            Bip32PathAndCurve::try_from_bytes(current)?.ok_or(Error::ExecutionError)?;
+
+        let expiry = stored.expiry.consume(level)?;
+        if expiry != stored.expiry {
+            stored.expiry = expiry;
+            unsafe { BAKINGPATH.write(stored.into()) }.map_err(|_| Error::ExecutionError)?;
+        }
+
+        Ok(())
     }
 
     /// Will remove the stored baking key
     pub fn remove_baking_key() -> Result<(), Error> {
+        unsafe { BAKING_KEY.take() };
+
         unsafe { BAKINGPATH.write(Bip32PathAndCurve::empty()) }.map_err(|_| Error::ExecutionError)
     }
 
@@ -167,25 +322,33 @@ impl Baking {
         Ok(path_and_curve.map(|both| (both.curve, both.path)))
     }
 
+    /// Retrieves the expiry of the stored authorization, if any
+    pub fn read_baking_expiry() -> Result<Option<Expiry>, Error> {
+        let current =
+            unsafe { BAKINGPATH.read() }.map_err(|_| Error::ApduCodeConditionsNotSatisfied)?;
+
+        let path_and_curve = Bip32PathAndCurve::try_from_bytes(current)?;
+
+        Ok(path_and_curve.map(|both| both.expiry))
+    }
+
     #[inline(never)]
     fn sign(digest: &[u8; 32]) -> Result<(usize, [u8; 100]), Error> {
         let current_path = unsafe { BAKINGPATH.read() }.map_err(|_| Error::ExecutionError)?;
 
-        //path seems to be initialized so we can return it
-        //check if it is a good path
-        let bip32_nvm = match Bip32PathAndCurve::try_from_bytes(current_path) {
-            Ok(Some(bip)) => bip,
-            Ok(None) => return Err(Error::ApduCodeConditionsNotSatisfied as _),
-            Err(e) => return Err(e),
-        };
+        let bip32_nvm =
+            Bip32PathAndCurve::try_from_bytes(current_path)?.ok_or(Error::ExecutionError)?;
 
-        let secret = bip32_nvm.curve.to_secret(&bip32_nvm.path);
+        let secret = Self::derive_baking_key(bip32_nvm.curve, bip32_nvm.path)?;
 
         let mut sig = [0; 100];
-        secret
+        let sz = secret
             .sign(digest, &mut sig[..])
-            .map_err(|_| Error::ExecutionError)
-            .map(|sz| (sz, sig))
+            .map_err(|_| Error::ExecutionError)?;
+
+        SignatureCounter::increment()?;
+
+        Ok((sz, sig))
     }
 
     #[inline(never)]
@@ -198,8 +361,13 @@ impl Baking {
     ) -> Result<usize, Error> {
         let hw = HWM::read().map_err(|_| Error::ExecutionError)?;
 
-        let (_, endorsement) =
-            EndorsementData::from_bytes(input).map_err(|_| Error::DataInvalid)?;
+        let (rem, endorsement) =
+            EndorsementData::from_bytes(input, preemble).map_err(|_| Error::ParseError)?;
+
+        //nothing else should be signed along with the endorsement
+        if !rem.is_empty() {
+            return Err(Error::ParseError);
+        }
 
         //parsed endorsement should match preemble
         match (preemble, &endorsement) {
@@ -219,12 +387,15 @@ impl Baking {
             )
             | (Preemble::Endorsement, EndorsementData::Emmy(_)) => {
                 if !endorsement.validate_with_watermark(&hw) {
-                    return Err(Error::DataInvalid);
+                    //only for diagnostics, the request is refused regardless
+                    HWM::write_rejected(endorsement.derive_watermark());
+                    return Err(Error::WrongValues);
                 }
             }
-            _ => return Err(Error::DataInvalid),
+            _ => return Err(Error::ParseError),
         }
 
+        Self::consume_authorization(endorsement.level())?;
         HWM::write(endorsement.derive_watermark()).map_err(|_| Error::ExecutionError)?;
 
         let (sz, sig) = Self::sign(&digest)?;
@@ -254,19 +425,22 @@ impl Baking {
     ) -> Result<usize, Error> {
         let hw = HWM::read().map_err(|_| Error::ExecutionError)?;
 
-        let (_, blockdata) = BlockData::from_bytes(input).map_err(|_| Error::DataInvalid)?;
+        let (_, blockdata) = BlockData::from_bytes(input).map_err(|_| Error::ParseError)?;
 
         //preemble should back block fitness
         match (preemble, &blockdata.fitness) {
             (Preemble::Block, Fitness::Emmy(_))
             | (Preemble::TenderbakeBlock, Fitness::Tenderbake(_)) => {
                 if !blockdata.validate_with_watermark(&hw) {
-                    return Err(Error::DataInvalid);
+                    //only for diagnostics, the request is refused regardless
+                    HWM::write_rejected(blockdata.derive_watermark());
+                    return Err(Error::WrongValues);
                 }
             }
-            _ => return Err(Error::DataInvalid),
+            _ => return Err(Error::ParseError),
         }
 
+        Self::consume_authorization(blockdata.level)?;
         HWM::write(blockdata.derive_watermark()).map_err(|_| Error::ExecutionError)?;
 
         let (sz, sig) = Self::sign(&digest)?;
@@ -297,12 +471,23 @@ impl Baking {
         use crate::parser::operations::{Operation, OperationType};
 
         let mut op = core::mem::MaybeUninit::uninit();
-        let mut operation = Operation::new(input).map_err(|_| Error::DataInvalid)?;
+        let mut operation = Operation::new(input).map_err(|_| Error::ParseError)?;
         operation
             .mut_ops()
             .parse_next_into(&mut op)
-            .map_err(|_| Error::DataInvalid)?
-            .ok_or(Error::DataInvalid)?;
+            .map_err(|_| Error::ParseError)?
+            .ok_or(Error::ParseError)?;
+
+        //the whole group is signed but only the first operation is reviewed,
+        // so nothing can follow it
+        if operation
+            .mut_ops()
+            .parse_next()
+            .map_err(|_| Error::ParseError)?
+            .is_some()
+        {
+            return Err(Error::CommandNotAllowed);
+        }
 
         let (data, branch) = match unsafe { op.assume_init() } {
             OperationType::Delegation(deleg) => {
@@ -315,6 +500,22 @@ impl Baking {
                 //what checks do we need here?
                 Ok((BakingTransactionType::Reveal(reveal), operation.branch()))
             }
+            OperationType::SeedNonceRevelation(snr) => Ok((
+                BakingTransactionType::SeedNonceRevelation(snr),
+                operation.branch(),
+            )),
+            OperationType::SetDepositsLimit(limit) => Ok((
+                BakingTransactionType::SetDepositsLimit(limit),
+                operation.branch(),
+            )),
+            OperationType::UpdateConsensusKey(key) => Ok((
+                BakingTransactionType::UpdateConsensusKey(key),
+                operation.branch(),
+            )),
+            OperationType::DrainDelegate(drain) => Ok((
+                BakingTransactionType::DrainDelegate(drain),
+                operation.branch(),
+            )),
             _ => Err(Error::CommandNotAllowed),
         }?;
 
@@ -350,7 +551,7 @@ impl Baking {
         let mut digest = [0; Sign::SIGN_HASH_SIZE];
         Self::blake2b_digest_into(cdata, &mut digest)?;
 
-        let (rem, preemble) = Preemble::from_bytes(cdata).map_err(|_| Error::DataInvalid)?;
+        let (rem, preemble) = Preemble::from_bytes(cdata).map_err(|_| Error::ParseError)?;
 
         //endorses and bakes are automatically signed without any review
         match preemble {
@@ -371,6 +572,10 @@ impl Baking {
 enum BakingTransactionType<'b> {
     Delegation(Delegation<'b>),
     Reveal(Reveal<'b>),
+    SeedNonceRevelation(SeedNonceRevelation<'b>),
+    SetDepositsLimit(SetDepositsLimit<'b>),
+    UpdateConsensusKey(UpdateConsensusKey<'b>),
+    DrainDelegate(DrainDelegate<'b>),
 }
 
 struct BakingSignUI {
@@ -385,6 +590,10 @@ impl Viewable for BakingSignUI {
         let n = match self.data {
             BakingTransactionType::Delegation(data) => data.num_items(),
             BakingTransactionType::Reveal(data) => data.num_items(),
+            BakingTransactionType::SeedNonceRevelation(data) => data.num_items(),
+            BakingTransactionType::SetDepositsLimit(data) => data.num_items(),
+            BakingTransactionType::UpdateConsensusKey(data) => data.num_items(),
+            BakingTransactionType::DrainDelegate(data) => data.num_items(),
         } + 1;
 
         Ok(n as u8)
@@ -419,6 +628,18 @@ impl Viewable for BakingSignUI {
                 BakingTransactionType::Reveal(data) => {
                     data.render_item(item_n - 1, title, message, page)
                 }
+                BakingTransactionType::SeedNonceRevelation(data) => {
+                    data.render_item(item_n - 1, title, message, page)
+                }
+                BakingTransactionType::SetDepositsLimit(data) => {
+                    data.render_item(item_n - 1, title, message, page)
+                }
+                BakingTransactionType::UpdateConsensusKey(data) => {
+                    data.render_item(item_n - 1, title, message, page)
+                }
+                BakingTransactionType::DrainDelegate(data) => {
+                    data.render_item(item_n - 1, title, message, page)
+                }
             }
         }
     }
@@ -450,6 +671,14 @@ impl Viewable for BakingSignUI {
     }
 }
 
+pub mod hwm;
+
+pub mod legacy;
+use legacy::{
+    LegacyAuthorize, LegacyDeAuthorize, LegacyHMAC, LegacyQueryAllHWM, LegacyQueryAuthKey,
+    LegacyQueryAuthKeyWithCurve, LegacyQueryMainHWM, LegacyResetHWM, LegacySetup,
+};
+
 mod authorization;
 pub use authorization::{AuthorizeBaking, DeAuthorizeBaking};
 
@@ -459,6 +688,53 @@ pub use queryauth::{QueryAuthKey, QueryAuthKeyWithCurve};
 mod hmac;
 pub use hmac::HMAC;
 
+mod diagnostics;
+pub use diagnostics::QueryRejectedHWM;
+
+mod migration;
+pub use migration::{ExportBakingState, ImportBakingState};
+
+/// Handles the baking-only instructions
+///
+/// Returns `None` if `ins` is not one of them
+#[inline(never)]
+pub fn dispatch<'apdu>(
+    ins: u8,
+    flags: &mut u32,
+    tx: &mut u32,
+    buffer: ApduBufferRead<'apdu>,
+) -> Option<Result<(), Error>> {
+    use crate::apdu_spec::*;
+
+    let res = match ins {
+        INS_LEGACY_RESET => LegacyResetHWM::handle(flags, tx, buffer),
+        INS_LEGACY_QUERY_MAIN_HWM => LegacyQueryMainHWM::handle(flags, tx, buffer),
+        INS_LEGACY_QUERY_ALL_HWM => LegacyQueryAllHWM::handle(flags, tx, buffer),
+
+        INS_AUTHORIZE_BAKING => AuthorizeBaking::handle(flags, tx, buffer),
+        INS_DEAUTHORIZE_BAKING => DeAuthorizeBaking::handle(flags, tx, buffer),
+        INS_QUERY_AUTH_KEY => QueryAuthKey::handle(flags, tx, buffer),
+        INS_QUERY_AUTH_KEY_WITH_CURVE => QueryAuthKeyWithCurve::handle(flags, tx, buffer),
+        INS_BAKER_SIGN => Baking::handle(flags, tx, buffer),
+        INS_QUERY_REJECTED_HWM => QueryRejectedHWM::handle(flags, tx, buffer),
+        INS_EXPORT_BAKING_STATE => ExportBakingState::handle(flags, tx, buffer),
+        INS_IMPORT_BAKING_STATE => ImportBakingState::handle(flags, tx, buffer),
+
+        INS_LEGACY_AUTHORIZE_BAKING => LegacyAuthorize::handle(flags, tx, buffer),
+        INS_LEGACY_DEAUTHORIZE => LegacyDeAuthorize::handle(flags, tx, buffer),
+        INS_LEGACY_QUERY_AUTH_KEY => LegacyQueryAuthKey::handle(flags, tx, buffer),
+        INS_LEGACY_QUERY_AUTH_KEY_WITH_CURVE => {
+            LegacyQueryAuthKeyWithCurve::handle(flags, tx, buffer)
+        }
+
+        INS_LEGACY_SETUP => LegacySetup::handle(flags, tx, buffer),
+        INS_LEGACY_HMAC => LegacyHMAC::handle(flags, tx, buffer),
+        _ => return None,
+    };
+
+    Some(res)
+}
+
 impl ApduHandler for Baking {
     #[inline(never)]
     fn handle<'apdu>(
@@ -485,10 +761,17 @@ impl ApduHandler for Baking {
 
 #[cfg(test)]
 mod tests {
-    use crate::{crypto, utils::MaybeNullTerminatedToString};
+    use crate::{
+        assert_error_code, crypto,
+        dispatcher::{handle_apdu, CLA, INS_BAKER_SIGN},
+        handlers::{baking::hwm::ChainID, ZPacketType},
+        utils::{ApduFixture, MaybeNullTerminatedToString},
+    };
     use bolos::crypto::bip32::BIP32Path;
 
     use arrayref::array_ref;
+    use serial_test::serial;
+    use std::convert::TryInto;
     use zuit::{MockDriver, Page};
 
     use super::*;
@@ -500,7 +783,7 @@ mod tests {
 
         let path =
             BIP32Path::<BIP32_MAX_LENGTH>::new(pathdata.iter().map(|n| 0x8000_0000 + n)).unwrap();
-        let path_and_curve = Bip32PathAndCurve::new(curve, path);
+        let path_and_curve = Bip32PathAndCurve::with_expiry(curve, path, Expiry::Never);
 
         let data: [u8; 52] = path_and_curve.clone().into();
         let derived = Bip32PathAndCurve::try_from_bytes(&data);
@@ -509,6 +792,80 @@ mod tests {
         assert_eq!(derived.unwrap().unwrap(), path_and_curve);
     }
 
+    #[test]
+    fn check_bip32andpath_expiry() {
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+
+        for expiry in [Expiry::Never, Expiry::Signatures(3), Expiry::Level(1729)] {
+            let path_and_curve =
+                Bip32PathAndCurve::with_expiry(crypto::Curve::Ed25519, path, expiry);
+
+            let data: [u8; 52] = path_and_curve.clone().into();
+            let derived = Bip32PathAndCurve::try_from_bytes(&data)
+                .expect("couldn't read record")
+                .expect("record was empty");
+            assert_eq!(derived, path_and_curve);
+        }
+    }
+
+    #[test]
+    fn expiry_consume() {
+        assert_eq!(Expiry::Never.consume(u32::MAX), Ok(Expiry::Never));
+
+        assert_eq!(Expiry::Signatures(2).consume(0), Ok(Expiry::Signatures(1)));
+        assert_eq!(
+            Expiry::Signatures(0).consume(0),
+            Err(Error::ApduCodeConditionsNotSatisfied)
+        );
+
+        assert_eq!(Expiry::Level(10).consume(10), Ok(Expiry::Level(10)));
+        assert_eq!(
+            Expiry::Level(10).consume(11),
+            Err(Error::ApduCodeConditionsNotSatisfied)
+        );
+
+        assert_eq!(Expiry::read(&[1, 0, 0, 0, 7]), Ok(Expiry::Signatures(7)));
+        assert_eq!(Expiry::read(&[3, 0, 0, 0, 1]), Err(Error::DataInvalid));
+        assert_eq!(Expiry::read(&[2, 0, 0]), Err(Error::WrongLength));
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn derived_baking_key() {
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        let digest = [42; 32];
+
+        Baking::store_baking_key(crypto::Curve::Ed25519, path).expect("couldn't store baking key");
+        assert!(unsafe { BAKING_KEY.is_some() });
+
+        let (sz, sig) = Baking::sign(&digest).expect("couldn't sign");
+
+        let mut expected = [0; 100];
+        let expected_sz = crypto::Curve::Ed25519
+            .to_secret(&path)
+            .expect("couldn't derive key")
+            .sign(&digest, &mut expected)
+            .map_err(|_| ())
+            .expect("couldn't sign");
+        assert_eq!(&sig[..sz], &expected[..expected_sz]);
+
+        //key is derived again when missing
+        unsafe { BAKING_KEY.take() };
+        let (sz, again) = Baking::sign(&digest).expect("couldn't sign");
+        assert_eq!(&again[..sz], &expected[..expected_sz]);
+
+        Baking::remove_baking_key().expect("couldn't remove baking key");
+        assert!(unsafe { BAKING_KEY.is_none() });
+        assert!(Baking::sign(&digest).is_err());
+
+        Baking::load_baking_key().expect("couldn't load baking key");
+        assert!(unsafe { BAKING_KEY.is_none() });
+    }
+
     #[test]
     fn test_emmy_endorsement_data() {
         let mut v = std::vec::Vec::with_capacity(1 + 4 + 32 + 1 + 4);
@@ -518,9 +875,9 @@ mod tests {
         v.push(0x00); //emmy endorsement (without slot)
         v.extend_from_slice(&15_u32.to_be_bytes());
 
-        let (_, endorsement) = EndorsementData::from_bytes(&v[1..]).unwrap();
+        let (_, endorsement) = EndorsementData::from_bytes(&v[1..], Preemble::Endorsement).unwrap();
         assert!(!endorsement.is_tenderbake());
-        assert_eq!(endorsement.chain_id(), 1);
+        assert_eq!(endorsement.chain_id(), ChainID::Custom(1));
         assert_eq!(endorsement.branch(), &[0u8; 32]);
         assert_eq!(endorsement.level(), 15);
         assert_eq!(endorsement.endorsement_type(), b"Endorsement\x00");
@@ -538,9 +895,10 @@ mod tests {
         v.extend_from_slice(&42_u32.to_be_bytes()); //round
         v.extend_from_slice(&[0u8; 32]); //block payload hash
 
-        let (_, endorsement) = EndorsementData::from_bytes(&v[1..]).unwrap();
+        let (_, endorsement) =
+            EndorsementData::from_bytes(&v[1..], Preemble::TenderbakePreendorsement).unwrap();
         assert!(endorsement.is_tenderbake());
-        assert_eq!(endorsement.chain_id(), 1);
+        assert_eq!(endorsement.chain_id(), ChainID::Custom(1));
         assert_eq!(endorsement.branch(), &[0u8; 32]);
         assert_eq!(endorsement.level(), 15);
         assert_eq!(endorsement.round(), Some(42));
@@ -559,15 +917,299 @@ mod tests {
         v.extend_from_slice(&42_u32.to_be_bytes()); //round
         v.extend_from_slice(&[0u8; 32]); //block payload hash
 
-        let (_, endorsement) = EndorsementData::from_bytes(&v[1..]).unwrap();
+        let (_, endorsement) =
+            EndorsementData::from_bytes(&v[1..], Preemble::TenderbakeEndorsement).unwrap();
         assert!(endorsement.is_tenderbake());
-        assert_eq!(endorsement.chain_id(), 1);
+        assert_eq!(endorsement.chain_id(), ChainID::Custom(1));
         assert_eq!(endorsement.branch(), &[0u8; 32]);
         assert_eq!(endorsement.level(), 15);
         assert_eq!(endorsement.round(), Some(42));
         assert_eq!(endorsement.endorsement_type(), b"Endorsement\x00");
     }
 
+    #[test]
+    #[serial(hwm)]
+    fn apdu_endorsement_below_hwm() {
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        Baking::store_baking_key(crypto::Curve::Ed25519, path).expect("couldn't store baking key");
+        HWM::reset(100).expect("couldn't reset hwm");
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        let path = path.serialize();
+        buffer[..5].copy_from_slice(&[
+            CLA,
+            INS_BAKER_SIGN,
+            ZPacketType::Init.into(),
+            crypto::Curve::Ed25519.into(),
+            path.len() as u8,
+        ]);
+        buffer[5..5 + path.len()].copy_from_slice(&path);
+
+        handle_apdu(&mut flags, &mut tx, 5 + path.len() as u32, &mut buffer);
+        assert_error_code!(tx, buffer, Error::Success);
+
+        let mut v = std::vec::Vec::with_capacity(1 + 4 + 32 + 1 + 4);
+        v.push(Preemble::Endorsement as _);
+        v.extend_from_slice(&HWM::chain_id().unwrap().to_be_bytes());
+        v.extend_from_slice(&[0u8; 32]);
+        v.push(0x00); //emmy endorsement (without slot)
+        v.extend_from_slice(&15_u32.to_be_bytes());
+
+        buffer[..5].copy_from_slice(&[
+            CLA,
+            INS_BAKER_SIGN,
+            ZPacketType::Last.into(),
+            0,
+            v.len() as u8,
+        ]);
+        buffer[5..5 + v.len()].copy_from_slice(&v);
+
+        //level 15 is below the high watermark
+        handle_apdu(&mut flags, &mut tx, 5 + v.len() as u32, &mut buffer);
+        assert_error_code!(tx, buffer, Error::WrongValues);
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_endorsement_expired() {
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        Baking::store_baking_key_with_expiry(crypto::Curve::Ed25519, path, Expiry::Signatures(1))
+            .expect("couldn't store baking key");
+        HWM::reset(0).expect("couldn't reset hwm");
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        let path = path.serialize();
+        let mut sign_endorsement = |level: u32| {
+            buffer[..5].copy_from_slice(&[
+                CLA,
+                INS_BAKER_SIGN,
+                ZPacketType::Init.into(),
+                crypto::Curve::Ed25519.into(),
+                path.len() as u8,
+            ]);
+            buffer[5..5 + path.len()].copy_from_slice(&path);
+
+            handle_apdu(&mut flags, &mut tx, 5 + path.len() as u32, &mut buffer);
+            assert_error_code!(tx, buffer, Error::Success);
+
+            let mut v = std::vec::Vec::with_capacity(1 + 4 + 32 + 1 + 4);
+            v.push(Preemble::Endorsement as _);
+            v.extend_from_slice(&HWM::chain_id().unwrap().to_be_bytes());
+            v.extend_from_slice(&[0u8; 32]);
+            v.push(0x00); //emmy endorsement (without slot)
+            v.extend_from_slice(&level.to_be_bytes());
+
+            buffer[..5].copy_from_slice(&[
+                CLA,
+                INS_BAKER_SIGN,
+                ZPacketType::Last.into(),
+                0,
+                v.len() as u8,
+            ]);
+            buffer[5..5 + v.len()].copy_from_slice(&v);
+
+            handle_apdu(&mut flags, &mut tx, 5 + v.len() as u32, &mut buffer);
+            Error::try_from(&buffer[tx as usize - 2..tx as usize]).unwrap()
+        };
+
+        assert_eq!(sign_endorsement(15), Error::Success);
+        //the authorization only allowed a single signature
+        assert_eq!(sign_endorsement(16), Error::ApduCodeConditionsNotSatisfied);
+
+        let (curve, path) = Baking::read_baking_key().unwrap().unwrap();
+        Baking::store_baking_key(curve, path).expect("couldn't restore baking key");
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_endorsement_validation() {
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        Baking::store_baking_key(crypto::Curve::Ed25519, path).expect("couldn't store baking key");
+        HWM::reset(0).expect("couldn't reset hwm");
+
+        let mut fixture = ApduFixture::new();
+
+        let path = path.serialize();
+        let mut sign_endorsement = |level: u32, extra: &[u8]| {
+            let mut v = std::vec::Vec::with_capacity(1 + 4 + 32 + 1 + 4 + extra.len());
+            v.push(Preemble::Endorsement as _);
+            v.extend_from_slice(&HWM::chain_id().unwrap().to_be_bytes());
+            v.extend_from_slice(&[0u8; 32]);
+            v.push(0x00); //emmy endorsement (without slot)
+            v.extend_from_slice(&level.to_be_bytes());
+            v.extend_from_slice(extra);
+
+            fixture
+                .send_chunks(INS_BAKER_SIGN, crypto::Curve::Ed25519.into(), &path, &v)
+                .code()
+        };
+
+        assert_eq!(sign_endorsement(15, &[0xFF]), Error::ParseError);
+        //trailing bytes are rejected before the watermark is touched
+        assert_eq!(sign_endorsement(15, &[]), Error::Success);
+    }
+
+    const DELEGATION_HEX: &str = "6e\
+                                  0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                  904e\
+                                  01\
+                                  0a\
+                                  0a\
+                                  ff\
+                                  0035e993d8c7aaa42b5e3ccd86a33390ececc73abd";
+
+    /// Asks the baking key to sign an operation group made of `ops`,
+    /// returning the status word of the command or of the review
+    fn baker_sign_operations(ops: &[&str]) -> Error {
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        Baking::store_baking_key(crypto::Curve::Ed25519, path).expect("couldn't store baking key");
+
+        let mut v = std::vec![Preemble::Operation as u8];
+        v.extend_from_slice(&[0u8; 32]);
+        for op in ops {
+            v.extend_from_slice(&hex::decode(op).expect("invalid input hex"));
+        }
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        let path = path.serialize();
+        buffer[..5].copy_from_slice(&[
+            CLA,
+            INS_BAKER_SIGN,
+            ZPacketType::Init.into(),
+            crypto::Curve::Ed25519.into(),
+            path.len() as u8,
+        ]);
+        buffer[5..5 + path.len()].copy_from_slice(&path);
+
+        handle_apdu(&mut flags, &mut tx, 5 + path.len() as u32, &mut buffer);
+        assert_error_code!(tx, buffer, Error::Success);
+
+        buffer[..5].copy_from_slice(&[
+            CLA,
+            INS_BAKER_SIGN,
+            ZPacketType::Last.into(),
+            crypto::Curve::Ed25519.into(),
+            v.len() as u8,
+        ]);
+        buffer[5..5 + v.len()].copy_from_slice(&v);
+
+        handle_apdu(&mut flags, &mut tx, 5 + v.len() as u32, &mut buffer);
+        match zemu_sys::get_out() {
+            //the review was shown and accepted
+            Some((len, out)) => Error::try_from(&out[len - 2..len]).unwrap(),
+            None => Error::try_from(&buffer[tx as usize - 2..tx as usize]).unwrap(),
+        }
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_single_operation() {
+        const SEED_NONCE_REVELATION_HEX: &str = "01\
+                                                 0000002a\
+                                                 0000000000000000000000000000000000000000000000000000000000000000";
+        const TRANSFER_HEX: &str = "6c\
+                                    0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                    904e\
+                                    01\
+                                    0a\
+                                    0a\
+                                    e807\
+                                    000035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                    00";
+
+        assert_eq!(baker_sign_operations(&[DELEGATION_HEX]), Error::Success);
+        assert_eq!(
+            baker_sign_operations(&[SEED_NONCE_REVELATION_HEX]),
+            Error::Success
+        );
+
+        //only the first operation would be reviewed
+        assert_eq!(
+            baker_sign_operations(&[DELEGATION_HEX, DELEGATION_HEX]),
+            Error::CommandNotAllowed
+        );
+        assert_eq!(
+            baker_sign_operations(&[SEED_NONCE_REVELATION_HEX, TRANSFER_HEX]),
+            Error::CommandNotAllowed
+        );
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_single_set_deposits_limit() {
+        const SET_DEPOSITS_LIMIT_HEX: &str = "70\
+                                              0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                              904e\
+                                              01\
+                                              0a\
+                                              0a\
+                                              ff\
+                                              c0843d";
+
+        assert_eq!(
+            baker_sign_operations(&[SET_DEPOSITS_LIMIT_HEX]),
+            Error::Success
+        );
+        assert_eq!(
+            baker_sign_operations(&[SET_DEPOSITS_LIMIT_HEX, DELEGATION_HEX]),
+            Error::CommandNotAllowed
+        );
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_single_update_consensus_key() {
+        const UPDATE_CONSENSUS_KEY_HEX: &str = "72\
+                                                0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                                904e\
+                                                01\
+                                                0a\
+                                                0a\
+                                                00\
+                                                0000000000000000000000000000000000000000000000000000000000000000";
+
+        assert_eq!(
+            baker_sign_operations(&[UPDATE_CONSENSUS_KEY_HEX]),
+            Error::Success
+        );
+        assert_eq!(
+            baker_sign_operations(&[UPDATE_CONSENSUS_KEY_HEX, DELEGATION_HEX]),
+            Error::CommandNotAllowed
+        );
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_single_drain_delegate() {
+        const DRAIN_DELEGATE_HEX: &str = "09\
+                                          0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                          016a7d4a43f51be0934a441fba4f13f9beaa475751\
+                                          026a7d4a43f51be0934a441fba4f13f9beaa475751";
+
+        assert_eq!(baker_sign_operations(&[DRAIN_DELEGATE_HEX]), Error::Success);
+        assert_eq!(
+            baker_sign_operations(&[DRAIN_DELEGATE_HEX, DELEGATION_HEX]),
+            Error::CommandNotAllowed
+        );
+    }
+
     #[test]
     fn known_delegation() {
         const PARTIAL_INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
@@ -619,4 +1261,42 @@ mod tests {
         //verify that the message is the same as the name we expect in the test
         assert_eq!(title, KNOWN_BAKER_NAME);
     }
+
+    #[test]
+    fn seed_nonce_revelation_ui() {
+        const INPUT_HEX: &str = "0040623e\
+                                 e004ce0c3edf0bef8101fb9aa43fd91ce4908120b1794fa649aad5fc967447bc";
+
+        let input = &*hex::decode(INPUT_HEX).expect("invalid input hex").leak();
+        let (_, snr) =
+            SeedNonceRevelation::from_bytes(input).expect("couldn't parse seed nonce revelation");
+
+        let ui = BakingSignUI {
+            send_hash: false,
+            digest: [0; 32],
+            branch: &[0; 32],
+            data: BakingTransactionType::SeedNonceRevelation(snr),
+        };
+        let mut driver = MockDriver::<_, 18, 4096>::new(ui);
+        driver.drive();
+
+        let produced_ui = driver.out_ui();
+        let messages = produced_ui
+            .iter()
+            .map(|item_pages| {
+                item_pages
+                    .iter()
+                    .map(|Page { message, .. }| {
+                        message
+                            .to_string_with_check_null()
+                            .expect("message was invalid UTF8")
+                    })
+                    .collect::<std::string::String>()
+            })
+            .collect::<std::vec::Vec<_>>();
+
+        assert_eq!(messages[1], "Seed Nonce Revelation");
+        assert_eq!(messages[2], "4,219,454");
+        assert_eq!(&messages[3], &INPUT_HEX[8..]);
+    }
 }
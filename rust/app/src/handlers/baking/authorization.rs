@@ -30,7 +30,7 @@ use bolos::{pic_str, PIC};
 use core::convert::TryFrom;
 use zemu_sys::{Show, ViewError, Viewable};
 
-use super::Baking;
+use super::{Baking, Expiry};
 
 pub struct AuthorizeBaking;
 
@@ -39,10 +39,11 @@ impl AuthorizeBaking {
     pub fn authorize(
         curve: Curve,
         path: BIP32Path<BIP32_MAX_LENGTH>,
+        expiry: Expiry,
         flags: &mut u32,
     ) -> Result<u32, Error> {
         sys::zemu_log_stack("AuthorizeBaking::auth\x00");
-        let ui = AuthorizeUI::new(curve, path).map_err(|_| Error::ExecutionError)?;
+        let ui = AuthorizeUI::new(curve, path, expiry).map_err(|_| Error::ExecutionError)?;
 
         unsafe { ui.show(flags) }
             .map_err(|_| Error::ExecutionError)
@@ -53,6 +54,7 @@ impl AuthorizeBaking {
 struct AuthorizeUI {
     curve: Curve,
     path: BIP32Path<BIP32_MAX_LENGTH>,
+    expiry: Expiry,
     //this is a bit reduntant info
     // but it helps speed up the UI
     addr: Addr,
@@ -60,7 +62,11 @@ struct AuthorizeUI {
 
 impl AuthorizeUI {
     #[inline(never)]
-    pub fn new(curve: Curve, path: BIP32Path<BIP32_MAX_LENGTH>) -> Result<Self, Error> {
+    pub fn new(
+        curve: Curve,
+        path: BIP32Path<BIP32_MAX_LENGTH>,
+        expiry: Expiry,
+    ) -> Result<Self, Error> {
         sys::zemu_log_stack("AuthorizeUI::new\x00");
 
         let mut addr = core::mem::MaybeUninit::uninit();
@@ -69,6 +75,7 @@ impl AuthorizeUI {
         Ok(Self {
             curve,
             path,
+            expiry,
             //safe because we have initialized this above with `Addr::new_into`
             addr: unsafe { addr.assume_init() },
         })
@@ -83,20 +90,32 @@ impl ApduHandler for AuthorizeBaking {
         buffer: ApduBufferRead<'apdu>,
     ) -> Result<(), Error> {
         sys::zemu_log_stack("AuthorizeBaking::handle\x00");
-        let req_confirmation = buffer.p1() >= 1;
-
-        if !req_confirmation {
-            //confirmation is mandatory
-            return Err(Error::ApduCodeConditionsNotSatisfied);
-        }
 
         let curve = Curve::try_from(buffer.p2()).map_err(|_| Error::InvalidP1P2)?;
 
         let cdata = buffer.payload().map_err(|_| Error::DataInvalid)?;
+        let path_len = 1 + 4 * *cdata.first().ok_or(Error::DataInvalid)? as usize;
+        let (path, extra) = if cdata.len() >= path_len {
+            cdata.split_at(path_len)
+        } else {
+            return Err(Error::DataInvalid);
+        };
+
         let bip32_path =
-            BIP32Path::<BIP32_MAX_LENGTH>::read(cdata).map_err(|_| Error::DataInvalid)?;
+            BIP32Path::<BIP32_MAX_LENGTH>::read(path).map_err(|_| Error::DataInvalid)?;
+
+        //the expiry is optional, without it the authorization is valid until revoked
+        let expiry = match extra.len() {
+            0 => Expiry::Never,
+            Expiry::LEN => Expiry::read(extra)?,
+            _ => return Err(Error::DataInvalid),
+        };
+        //an authorization that can't sign anything is surely a mistake
+        if expiry == Expiry::Signatures(0) {
+            return Err(Error::DataInvalid);
+        }
 
-        *tx = Self::authorize(curve, bip32_path, flags)?;
+        *tx = Self::authorize(curve, bip32_path, expiry, flags)?;
 
         Ok(())
     }
@@ -104,7 +123,10 @@ impl ApduHandler for AuthorizeBaking {
 
 impl Viewable for AuthorizeUI {
     fn num_items(&mut self) -> Result<u8, ViewError> {
-        Ok(2)
+        match self.expiry {
+            Expiry::Never => Ok(2),
+            _ => Ok(3),
+        }
     }
 
     #[inline(never)]
@@ -130,6 +152,30 @@ impl Viewable for AuthorizeUI {
                 let (len, mex) = self.addr.base58();
                 handle_ui_message(&mex[..len], message, page)
             }
+            2 => {
+                use lexical_core::{write as itoa, Number};
+
+                let title_content = pic_str!(b"Expires after");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (prefix, limit, suffix): (&[u8], _, &[u8]) = match self.expiry {
+                    Expiry::Never => return Err(ViewError::NoData),
+                    Expiry::Signatures(n) => (&[], n, &pic_str!(b" signatures"!)[..]),
+                    Expiry::Level(level) => (&pic_str!(b"level "!)[..], level, &[]),
+                };
+
+                let mut itoa_buf = [0u8; u32::FORMATTED_SIZE_DECIMAL];
+                let limit = itoa(limit, &mut itoa_buf);
+
+                let mut mex = [0u8; 6 + u32::FORMATTED_SIZE_DECIMAL + 11];
+                let mut len = 0;
+                for part in [prefix, limit, suffix] {
+                    mex[len..len + part.len()].copy_from_slice(part);
+                    len += part.len();
+                }
+
+                handle_ui_message(&mex[..len], message, page)
+            }
             _ => Err(ViewError::NoData),
         }
     }
@@ -145,7 +191,7 @@ impl Viewable for AuthorizeUI {
         }
 
         //store in memory
-        if Baking::store_baking_key(self.curve, self.path).is_err() {
+        if Baking::store_baking_key_with_expiry(self.curve, self.path, self.expiry).is_err() {
             return (0, Error::ExecutionError as _);
         }
 
@@ -173,8 +219,8 @@ pub struct DeAuthorizeBaking;
 impl DeAuthorizeBaking {
     #[inline(never)]
     pub fn deauthorize(flags: &mut u32) -> Result<u32, Error> {
-        let (curve, path) =
-            Baking::read_baking_key()?.ok_or(Error::ApduCodeConditionsNotSatisfied)?;
+        //the dispatcher made sure a key is authorized
+        let (curve, path) = Baking::read_baking_key()?.ok_or(Error::ExecutionError)?;
 
         let mut addr = core::mem::MaybeUninit::uninit();
         GetAddress::new_addr_into(curve, &path, &mut addr).map_err(|_| Error::ExecutionError)?;
@@ -196,20 +242,9 @@ struct DeAuthorizeUI {
 
 impl ApduHandler for DeAuthorizeBaking {
     #[inline(never)]
-    fn handle<'apdu>(
-        flags: &mut u32,
-        tx: &mut u32,
-        buffer: ApduBufferRead<'apdu>,
-    ) -> Result<(), Error> {
+    fn handle<'apdu>(flags: &mut u32, tx: &mut u32, _: ApduBufferRead<'apdu>) -> Result<(), Error> {
         *tx = 0;
-
-        let req_confirmation = buffer.p1() >= 1;
-
-        //confirmation mandatory
-        if !req_confirmation {
-            return Err(Error::ApduCodeConditionsNotSatisfied);
-        }
-
+        //confirmation is required by the dispatcher
         *tx = Self::deauthorize(flags)?;
 
         Ok(())
@@ -0,0 +1,133 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use crate::handlers::prelude::*;
+
+use super::hwm::{WaterMark, HWM};
+
+/// Retrieves the last block or endorsement refused because of the high watermark
+///
+/// Useful to find out which signer is lagging behind in a multi-signer setup
+pub struct QueryRejectedHWM;
+
+impl QueryRejectedHWM {
+    pub const KIND_BLOCK: u8 = 0;
+    pub const KIND_PREENDORSEMENT: u8 = 1;
+    pub const KIND_ENDORSEMENT: u8 = 2;
+
+    /// Level, round and kind of operation
+    pub const RESPONSE_LEN: usize = 4 + 4 + 1;
+
+    fn kind(wm: &WaterMark) -> u8 {
+        match wm {
+            WaterMark::Emmy {
+                had_endorsement: true,
+                ..
+            }
+            | WaterMark::Tenderbake {
+                had_endorsement: true,
+                ..
+            } => Self::KIND_ENDORSEMENT,
+            WaterMark::Tenderbake {
+                had_preendorsement: true,
+                ..
+            } => Self::KIND_PREENDORSEMENT,
+            _ => Self::KIND_BLOCK,
+        }
+    }
+}
+
+impl ApduHandler for QueryRejectedHWM {
+    #[inline(never)]
+    fn handle<'apdu>(
+        _: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        *tx = 0;
+
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let wm = match HWM::read_rejected() {
+            Some(wm) => wm,
+            //nothing was rejected since the app was started
+            None => return Ok(()),
+        };
+
+        let out = buffer.write();
+        out[..4].copy_from_slice(&wm.level().to_be_bytes()[..]);
+        out[4..8].copy_from_slice(&wm.round().to_be_bytes()[..]);
+        out[8] = Self::kind(&wm);
+
+        *tx = Self::RESPONSE_LEN as u32;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto,
+        dispatcher::{INS_BAKER_SIGN, INS_QUERY_REJECTED_HWM},
+        handlers::baking::Baking,
+        parser::Preemble,
+        sys::crypto::bip32::BIP32Path,
+        utils::ApduFixture,
+    };
+
+    use serial_test::serial;
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_query_rejected() {
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n)).unwrap();
+        Baking::store_baking_key(crypto::Curve::Ed25519, path).expect("couldn't store baking key");
+        HWM::reset(100).expect("couldn't reset hwm");
+
+        let mut v = std::vec::Vec::with_capacity(1 + 4 + 32 + 1 + 4);
+        v.push(Preemble::Endorsement as _);
+        v.extend_from_slice(&HWM::chain_id().unwrap().to_be_bytes());
+        v.extend_from_slice(&[0u8; 32]);
+        v.push(0x00); //emmy endorsement (without slot)
+        v.extend_from_slice(&42_u32.to_be_bytes());
+
+        let mut fixture = ApduFixture::new();
+
+        //level 42 is below the high watermark
+        fixture
+            .send_chunks(
+                INS_BAKER_SIGN,
+                crypto::Curve::Ed25519.into(),
+                &path.serialize(),
+                &v,
+            )
+            .assert_code(Error::WrongValues);
+
+        let out = fixture
+            .send(INS_QUERY_REJECTED_HWM, 0, 0, &[])
+            .assert_success();
+
+        assert_eq!(out.len(), QueryRejectedHWM::RESPONSE_LEN);
+        assert_eq!(&out[..4], &42_u32.to_be_bytes()[..]);
+        assert_eq!(&out[4..8], &0_u32.to_be_bytes()[..]);
+        assert_eq!(out[8], QueryRejectedHWM::KIND_ENDORSEMENT);
+
+        //the high watermark itself is untouched
+        assert_eq!(HWM::read().unwrap().level(), 100);
+    }
+}
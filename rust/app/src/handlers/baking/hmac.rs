@@ -39,34 +39,45 @@ const KEY_SHA256: &[u8] = &[
 ];
 
 impl HMAC {
+    /// Derives an HMAC key from the signature of `domain` with the given key
+    ///
+    /// Different domains give unrelated keys, so each use gets its own
     #[inline(never)]
-    fn sig_and_hash_hmac_key(
+    pub fn derive_key(
         curve: Curve,
-        path: BIP32Path<BIP32_MAX_LENGTH>,
+        path: &BIP32Path<BIP32_MAX_LENGTH>,
+        domain: &[u8],
     ) -> Result<[u8; 64], Error> {
-        //sign the hmac key
-        let (sig_size, sig_hmac_key) = Sign::sign(curve, &path, KEY_SHA256)?;
+        //sign the domain
+        let (sig_size, sig) = Sign::sign(curve, path, domain)?;
 
         //and hash the signature
-        Sha512::digest(&sig_hmac_key[..sig_size]).map_err(|_| Error::ExecutionError)
+        Sha512::digest(&sig[..sig_size]).map_err(|_| Error::ExecutionError)
     }
 
+    /// Computes the HMAC of `input` with `key` into `out`
     #[inline(never)]
-    fn do_hmac(key: [u8; 64], offset: usize, buffer: ApduBufferRead<'_>) -> Result<u32, Error> {
+    pub fn digest_into(key: &[u8; 64], input: &[u8], out: &mut [u8; 32]) -> Result<(), Error> {
         let mut hmac = {
             let mut loc = core::mem::MaybeUninit::uninit();
             Sha256HMAC::new_gce(&mut loc, &key[..]).map_err(|_| Error::ExecutionError)?;
             unsafe { loc.assume_init() }
         };
 
+        hmac.update(input).map_err(|_| Error::ExecutionError)?;
+        hmac.finalize_hmac_into(out)
+            .map_err(|_| Error::ExecutionError)
+    }
+
+    #[inline(never)]
+    fn do_hmac(key: [u8; 64], offset: usize, buffer: ApduBufferRead<'_>) -> Result<u32, Error> {
+        let mut out = [0; 32];
         {
             let input = &buffer.payload().map_err(|_| Error::DataInvalid)?[offset..];
-            hmac.update(input).map_err(|_| Error::ExecutionError)?;
+            Self::digest_into(&key, input, &mut out)?;
         }
 
-        let buffer = buffer.write();
-        hmac.finalize_hmac_into(arrayref::array_mut_ref!(buffer, 0, 32))
-            .map_err(|_| Error::ExecutionError)?;
+        buffer.write()[..32].copy_from_slice(&out[..]);
 
         Ok(32)
     }
@@ -79,7 +90,7 @@ impl HMAC {
         offset: usize,
         buffer: ApduBufferRead<'_>,
     ) -> Result<u32, Error> {
-        let hash_hmac_key_sig = Self::sig_and_hash_hmac_key(curve, path)?;
+        let hash_hmac_key_sig = Self::derive_key(curve, &path, KEY_SHA256)?;
 
         Self::do_hmac(hash_hmac_key_sig, offset, buffer)
     }
@@ -145,7 +156,6 @@ mod tests {
     pub fn apdu_hmac() {
         let mut flags = 0u32;
         let mut tx = 0u32;
-        let rx = 5;
         let mut buffer = [0u8; 260];
 
         const HMAC_MSG: &[u8] = b"zondax.ch";
@@ -154,6 +164,8 @@ mod tests {
         let offset = prepare_buffer::<4>(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
 
         buffer[offset..offset + HMAC_MSG.len()].copy_from_slice(HMAC_MSG);
+        buffer[4] += HMAC_MSG.len() as u8;
+        let rx = (offset + HMAC_MSG.len()) as u32;
 
         handle_apdu(&mut flags, &mut tx, rx, &mut buffer);
 
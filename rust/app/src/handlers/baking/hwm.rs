@@ -13,16 +13,19 @@
 *  See the License for the specific language governing permissions and
 *  limitations under the License.
 ********************************************************************************/
+use nom::{number::complete::be_u32, IResult};
+
 use crate::{
     constants::{tzprefix::NET, ApduError as Error},
+    handlers::{
+        intstr_to_fpstr_inplace, parser_common::ParserError, sha256x2, MAX_THOUSANDS_SEPARATORS,
+    },
     sys::{flash_slot::Wear, new_flash_slot, pic::PIC},
-    utils::ApduPanic,
+    utils::{read_u32_be, ApduPanic},
 };
 
 pub use crate::sys::flash_slot::WearError;
 
-use super::sha256x2;
-
 const N_PAGES: usize = 8;
 
 type WearLeveller = Wear<'static, N_PAGES>;
@@ -30,10 +33,16 @@ type WearLeveller = Wear<'static, N_PAGES>;
 pub const MAIN_HWM_LEN: usize = 4;
 pub const ALL_HWM_LEN: usize = 12;
 
+//"level " + "4,294,967,295" + " round " + "4,294,967,295"
+pub const HWM_TEXT_MAX_LEN: usize = 6 + 13 + 7 + 13;
+
 // Mainnet Chain ID: NetXdQprcVkpaWU
 // types.h:61,0
 pub const MAINNET_CHAIN_ID: u32 = 0x7A06A770;
 
+// Ghostnet Chain ID: NetXnHfVqm9iesp
+pub const GHOSTNET_CHAIN_ID: u32 = 0xAF1864D9;
+
 #[bolos::lazy_static]
 static mut MAIN: WearLeveller = new_flash_slot!(N_PAGES).apdu_expect("NVM might be corrupted");
 
@@ -43,6 +52,10 @@ static mut TEST: WearLeveller = new_flash_slot!(N_PAGES).apdu_expect("NVM might
 #[bolos::lazy_static]
 static mut CHAIN_ID: WearLeveller = new_flash_slot!(N_PAGES).apdu_expect("NVM might be corrupted");
 
+//last block or endorsement refused because of the watermark, for diagnostics
+// kept in RAM only, so a baker stuck behind the watermark doesn't wear out the NVM
+static mut REJECTED: Option<WaterMark> = None;
+
 #[allow(clippy::upper_case_acronyms)]
 pub struct HWM;
 
@@ -62,6 +75,7 @@ impl HWM {
                 CHAIN_ID
                     .write(ChainID::from(MAINNET_CHAIN_ID).into())
                     .map_err(|_| Error::ExecutionError)?;
+                Self::show_chain();
             }
         }
 
@@ -84,15 +98,30 @@ impl HWM {
         let mut data = [0; 52];
         data[..4].copy_from_slice(&id.to_be_bytes()[..]);
 
-        unsafe { CHAIN_ID.write(data) }.map_err(|_| Error::ExecutionError)
+        unsafe { CHAIN_ID.write(data) }.map_err(|_| Error::ExecutionError)?;
+        Self::show_chain();
+
+        Ok(())
+    }
+
+    /// Shows the chain the signer is pinned to on the idle screen
+    pub fn show_chain() {
+        let chain = match Self::chain_id() {
+            Ok(id) => ChainID::from(id),
+            //not provisioned yet, keep the default status
+            Err(_) => return,
+        };
+
+        let mut alias = [0; ChainID::BASE58_LEN];
+        if let Ok(len) = chain.to_alias(&mut alias) {
+            zemu_sys::set_idle_status(&alias[..len]);
+        }
     }
 
     pub fn chain_id() -> Result<u32, WearError> {
         let data = unsafe { CHAIN_ID.read() }?;
 
-        let data = arrayref::array_ref!(data, 0, 4);
-
-        Ok(u32::from_be_bytes(*data))
+        Ok(read_u32_be(data, 0).apdu_unwrap())
     }
 
     /// Meant to be used with legacy API
@@ -160,6 +189,22 @@ impl HWM {
         let main_wm: WaterMark = unsafe { MAIN.read() }?.into();
         Ok(main_wm)
     }
+
+    pub fn read_test() -> Result<WaterMark, WearError> {
+        let test_wm: WaterMark = unsafe { TEST.read() }?.into();
+        Ok(test_wm)
+    }
+
+    /// Records the watermark of a block or endorsement refused
+    /// because it was not above the high watermark, until the app is closed
+    pub fn write_rejected(wm: WaterMark) {
+        unsafe { REJECTED = Some(wm) }
+    }
+
+    /// Retrieves the last watermark recorded with [`HWM::write_rejected`]
+    pub fn read_rejected() -> Option<WaterMark> {
+        unsafe { REJECTED.clone() }
+    }
 }
 
 #[derive(PartialEq, Clone)]
@@ -189,6 +234,43 @@ impl WaterMark {
             WaterMark::Emmy { level, .. } | WaterMark::Tenderbake { level, .. } => *level,
         }
     }
+
+    /// Emmy watermarks don't have a round, so it's always 0
+    pub fn round(&self) -> u32 {
+        match self {
+            WaterMark::Emmy { .. } => 0,
+            WaterMark::Tenderbake { round, .. } => *round,
+        }
+    }
+
+    /// Writes the watermark as text (ie: "level 4,212,345 round 0") to `out`
+    ///
+    /// returns the number of bytes written
+    pub fn write_text(&self, out: &mut [u8; HWM_TEXT_MAX_LEN]) -> usize {
+        use lexical_core::{write as itoa, Number};
+
+        let mut write = 0;
+
+        let mut append = |bytes: &[u8]| {
+            out[write..write + bytes.len()].copy_from_slice(bytes);
+            write += bytes.len();
+        };
+
+        let mut append_number = |prefix: &[u8], n: u32| {
+            append(prefix);
+
+            //+1 for the null terminator
+            let mut num = [0; u32::FORMATTED_SIZE_DECIMAL + 1 + MAX_THOUSANDS_SEPARATORS];
+            itoa(n, &mut num);
+            //a u32 always fits in the buffer
+            append(intstr_to_fpstr_inplace(&mut num, 0, true).apdu_unwrap());
+        };
+
+        append_number(&PIC::new(b"level ").into_inner()[..], self.level());
+        append_number(&PIC::new(b" round ").into_inner()[..], self.round());
+
+        write
+    }
 }
 
 impl From<&[u8; 52]> for WaterMark {
@@ -206,10 +288,9 @@ impl From<&[u8; 52]> for WaterMark {
         let had_endorsement = from[read] >= 1;
         read += 1;
 
-        let level = {
-            let mut array = [0; 4];
-            array.copy_from_slice(&from[read..read + 4]);
-            u32::from_be_bytes(array)
+        let level = match read_u32_be(from, read) {
+            Ok(level) => level,
+            Err(_) => return this,
         };
         read += 4;
 
@@ -217,10 +298,9 @@ impl From<&[u8; 52]> for WaterMark {
             let had_preendorsement = from[read] >= 1;
             read += 1;
 
-            let round = {
-                let mut array = [0; 4];
-                array.copy_from_slice(&from[read..read + 4]);
-                u32::from_be_bytes(array)
+            let round = match read_u32_be(from, read) {
+                Ok(round) => round,
+                Err(_) => return this,
             };
 
             this = Self::Tenderbake {
@@ -314,17 +394,19 @@ impl Default for WaterMark {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+/// Identifier of a Tezos chain, shown as `NetXdQprcVkpaWU`
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(test, derive(Debug))]
 pub enum ChainID {
     Any,
     Mainnet,
+    Ghostnet,
     Custom(u32),
 }
 
 impl From<&[u8; 52]> for ChainID {
     fn from(from: &[u8; 52]) -> Self {
-        let data = arrayref::array_ref!(from, 0, 4);
-        let from = u32::from_be_bytes(*data);
+        let from = read_u32_be(from, 0).apdu_unwrap();
 
         ChainID::from(from)
     }
@@ -346,6 +428,7 @@ impl From<u32> for ChainID {
         match from {
             0 => Self::Any,
             MAINNET_CHAIN_ID => Self::Mainnet,
+            GHOSTNET_CHAIN_ID => Self::Ghostnet,
             id => Self::Custom(id),
         }
     }
@@ -356,6 +439,7 @@ impl From<ChainID> for u32 {
         match from {
             ChainID::Any => 0,
             ChainID::Mainnet => MAINNET_CHAIN_ID,
+            ChainID::Ghostnet => GHOSTNET_CHAIN_ID,
             ChainID::Custom(n) => n,
         }
     }
@@ -364,6 +448,17 @@ impl From<ChainID> for u32 {
 impl ChainID {
     pub const BASE58_LEN: usize = 16;
 
+    /// Reads the 4 bytes chain id of consensus operations
+    pub fn from_bytes(input: &[u8]) -> IResult<&[u8], Self, ParserError> {
+        let (rem, id) = be_u32(input)?;
+
+        Ok((rem, Self::from(id)))
+    }
+
+    pub fn base58(self) -> Result<(usize, [u8; ChainID::BASE58_LEN]), bolos::Error> {
+        Self::id_to_base58(self.into())
+    }
+
     #[inline(never)]
     pub fn id_to_base58(chain_id: u32) -> Result<(usize, [u8; ChainID::BASE58_LEN]), bolos::Error> {
         let mut checksum = [0; 4];
@@ -403,6 +498,12 @@ impl ChainID {
 
                 Ok(content.len())
             }
+            Self::Ghostnet => {
+                let content = pic_str!(b"ghostnet");
+                out[..content.len()].copy_from_slice(&content[..]);
+
+                Ok(content.len())
+            }
             Self::Custom(id) => {
                 let (len, content) = Self::id_to_base58(id)?;
                 out[..len].copy_from_slice(&content[..len]);
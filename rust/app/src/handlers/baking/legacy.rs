@@ -0,0 +1,209 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Legacy baking instructions, kept for compatibility with existing baking software
+
+use crate::{
+    constants::BIP32_MAX_LENGTH,
+    crypto::Curve,
+    handlers::{
+        baking::{
+            AuthorizeBaking, Baking, DeAuthorizeBaking, Expiry, QueryAuthKey, QueryAuthKeyWithCurve,
+        },
+        prelude::*,
+    },
+};
+
+use bolos::crypto::bip32::BIP32Path;
+use core::convert::TryFrom;
+
+pub mod hwm;
+pub use hwm::{LegacyQueryAllHWM, LegacyQueryMainHWM, LegacyResetHWM};
+
+mod hmac;
+pub use hmac::LegacyHMAC;
+
+mod setup;
+pub use setup::LegacySetup;
+
+pub struct LegacyAuthorize;
+pub struct LegacyDeAuthorize;
+pub struct LegacyQueryAuthKey;
+pub struct LegacyQueryAuthKeyWithCurve;
+
+impl ApduHandler for LegacyAuthorize {
+    #[inline(never)]
+    fn handle<'apdu>(
+        flags: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        //like the legacy app, P1 is ignored and
+        // the authorization is always confirmed by the user
+        let cdata = buffer.payload().map_err(|_| Error::DataInvalid)?;
+
+        let (curve, bip32_path) = if cdata.is_empty() {
+            //without a path the currently authorized key is authorized again
+            Baking::read_baking_key()?.ok_or(Error::WrongLength)?
+        } else {
+            let curve = Curve::try_from(buffer.p2()).map_err(|_| Error::InvalidP1P2)?;
+            let bip32_path =
+                BIP32Path::<BIP32_MAX_LENGTH>::read(cdata).map_err(|_| Error::DataInvalid)?;
+
+            (curve, bip32_path)
+        };
+
+        *tx = AuthorizeBaking::authorize(curve, bip32_path, Expiry::Never, flags)?;
+
+        Ok(())
+    }
+}
+
+impl ApduHandler for LegacyDeAuthorize {
+    #[inline(never)]
+    fn handle<'apdu>(
+        flags: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        *tx = 0;
+
+        //legacy deauthorize doesn't take any argument
+        if buffer.p1() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        if !buffer.payload().map_err(|_| Error::DataInvalid)?.is_empty() {
+            return Err(Error::WrongLength);
+        }
+
+        //removing the key disables the double signing protection,
+        // so it's never done without the user's confirmation
+        //
+        //do not reset watermark
+        // see https://github.com/Zondax/ledger-tezos/issues/181
+        *tx = DeAuthorizeBaking::deauthorize(flags)?;
+
+        Ok(())
+    }
+}
+
+impl ApduHandler for LegacyQueryAuthKey {
+    #[inline(never)]
+    fn handle<'apdu>(
+        flags: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        let req_confirmation = buffer.p1() >= 1;
+
+        *tx = QueryAuthKey::query(req_confirmation, buffer.write(), flags)?;
+
+        Ok(())
+    }
+}
+
+impl ApduHandler for LegacyQueryAuthKeyWithCurve {
+    #[inline(never)]
+    fn handle<'apdu>(
+        flags: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        let req_confirmation = buffer.p1() >= 1;
+
+        *tx = QueryAuthKeyWithCurve::query(req_confirmation, buffer.write(), flags)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assert_error_code,
+        constants::ApduError as Error,
+        crypto::Curve,
+        dispatcher::{handle_apdu, CLA, INS_LEGACY_AUTHORIZE_BAKING, INS_LEGACY_DEAUTHORIZE},
+        handlers::baking::Baking,
+        utils::ApduFixture,
+    };
+
+    use bolos::crypto::bip32::BIP32Path;
+    use serial_test::serial;
+    use std::convert::TryInto;
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_legacy_deauthorize() {
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n)).unwrap();
+        Baking::store_baking_key(Curve::Ed25519, path).expect("couldn't store baking key");
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        //no arguments are supported
+        buffer[..5].copy_from_slice(&[CLA, INS_LEGACY_DEAUTHORIZE, 1, 0, 0]);
+        handle_apdu(&mut flags, &mut tx, 5, &mut buffer);
+        assert_error_code!(tx, buffer, Error::InvalidP1P2);
+
+        buffer[..5].copy_from_slice(&[CLA, INS_LEGACY_DEAUTHORIZE, 0, 0, 0]);
+        handle_apdu(&mut flags, &mut tx, 5, &mut buffer);
+        assert_error_code!(tx, buffer, Error::Success);
+        assert_eq!(tx, 2);
+
+        assert!(matches!(Baking::read_baking_key(), Ok(None)));
+
+        //like INS_DEAUTHORIZE_BAKING, a key has to be authorized
+        buffer[..5].copy_from_slice(&[CLA, INS_LEGACY_DEAUTHORIZE, 0, 0, 0]);
+        handle_apdu(&mut flags, &mut tx, 5, &mut buffer);
+        assert_error_code!(tx, buffer, Error::ApduCodeConditionsNotSatisfied);
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_legacy_authorize() {
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n)).unwrap();
+        let other = BIP32Path::<10>::new([44, 1729, 1, 0].iter().map(|n| 0x8000_0000 + n)).unwrap();
+        Baking::remove_baking_key().expect("couldn't remove baking key");
+
+        let mut fixture = ApduFixture::new();
+
+        //nothing to authorize again
+        fixture
+            .send(INS_LEGACY_AUTHORIZE_BAKING, 0, 0, &[])
+            .assert_code(Error::WrongLength);
+
+        //P1 is ignored, the key is always confirmed
+        let payload = other.serialize();
+        fixture
+            .send(
+                INS_LEGACY_AUTHORIZE_BAKING,
+                0,
+                Curve::Secp256K1.into(),
+                &payload,
+            )
+            .assert_success();
+        assert!(Baking::read_baking_key().unwrap() == Some((Curve::Secp256K1, other)));
+
+        //without a path, the current key is authorized again
+        Baking::store_baking_key(Curve::Ed25519, path).expect("couldn't store baking key");
+        fixture
+            .send(INS_LEGACY_AUTHORIZE_BAKING, 1, Curve::Secp256K1.into(), &[])
+            .assert_success();
+        assert!(Baking::read_baking_key().unwrap() == Some((Curve::Ed25519, path)));
+    }
+}
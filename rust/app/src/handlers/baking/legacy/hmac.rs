@@ -14,14 +14,8 @@
 *  limitations under the License.
 ********************************************************************************/
 
-use crate::handlers::baking::HMAC;
-use crate::{
-    constants::{ApduError as Error, BIP32_MAX_LENGTH},
-    crypto::Curve,
-    dispatcher::ApduHandler,
-    sys::crypto::bip32::BIP32Path,
-    utils::ApduBufferRead,
-};
+use crate::handlers::{baking::HMAC, prelude::*};
+use crate::{constants::BIP32_MAX_LENGTH, crypto::Curve, sys::crypto::bip32::BIP32Path};
 
 use core::convert::TryFrom;
 
@@ -18,13 +18,22 @@
 //! * Handler
 //! * Legacy Handler
 
+use bolos::{pic_str, PIC};
+use zemu_sys::{Show, ViewError, Viewable};
+
 use crate::{
     constants::ApduError as Error,
     dispatcher::ApduHandler,
-    handlers::hwm::{WearError, HWM},
-    utils::ApduBufferRead,
+    handlers::{
+        baking::hwm::{WaterMark, WearError, HWM, HWM_TEXT_MAX_LEN},
+        handle_ui_message, intstr_to_fpstr_inplace, MAX_THOUSANDS_SEPARATORS,
+    },
+    utils::{read_u32_be, ApduBufferRead},
 };
 
+/// P1 for [`LegacyQueryMainHWM`] to retrieve the HWM as text
+pub const P1_HWM_TEXT: u8 = 0x01;
+
 pub struct LegacyResetHWM;
 pub struct LegacyQueryMainHWM;
 pub struct LegacyQueryAllHWM;
@@ -32,25 +41,90 @@ pub struct LegacyQueryAllHWM;
 impl ApduHandler for LegacyResetHWM {
     #[inline(never)]
     fn handle<'apdu>(
-        _: &mut u32,
+        flags: &mut u32,
         tx: &mut u32,
         buffer: ApduBufferRead<'apdu>,
     ) -> Result<(), Error> {
         *tx = 0;
 
-        let payload = buffer
-            .payload()
-            .map_err(|_| Error::DataInvalid)?
-            .get(..4)
-            .ok_or(Error::WrongLength)?;
+        let payload = buffer.payload().map_err(|_| Error::DataInvalid)?;
 
-        let level = {
-            let mut array = [0; 4];
-            array.copy_from_slice(payload);
-            u32::from_be_bytes(array)
-        };
+        let level = read_u32_be(payload, 0).map_err(|_| Error::WrongLength)?;
+        //levels are signed in the protocol, so the top bit can't be set
+        if !WaterMark::is_valid_blocklevel(level) {
+            return Err(Error::ParseError);
+        }
+
+        //lowering the HWM disables the double signing protection,
+        // so it's never done without the user's confirmation
+        let ui = ResetUI { level };
+
+        unsafe { ui.show(flags) }.map_err(|_| Error::ExecutionError)
+    }
+}
+
+struct ResetUI {
+    level: u32,
+}
+
+impl Viewable for ResetUI {
+    fn num_items(&mut self) -> Result<u8, ViewError> {
+        Ok(3)
+    }
+
+    fn render_item(
+        &mut self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use lexical_core::{write as itoa, Number};
+
+        match item_n {
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"Reset HWM")[..], message, page)
+            }
+            1 => {
+                let title_content = pic_str!(b"WARNING");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(
+                    &pic_str!(b"Blocks above this level can be signed again")[..],
+                    message,
+                    page,
+                )
+            }
+            2 => {
+                let title_content = pic_str!(b"Level");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                //+1 for the null terminator
+                let mut buf = [0; u32::FORMATTED_SIZE_DECIMAL + 1 + MAX_THOUSANDS_SEPARATORS];
+                itoa(self.level, &mut buf);
+
+                handle_ui_message(
+                    intstr_to_fpstr_inplace(&mut buf, 0, true).map_err(|_| ViewError::Unknown)?,
+                    message,
+                    page,
+                )
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+
+    fn accept(&mut self, _: &mut [u8]) -> (usize, u16) {
+        match HWM::reset(self.level) {
+            Ok(()) => (0, Error::Success as _),
+            Err(e) => (0, e as _),
+        }
+    }
 
-        HWM::reset(level)
+    fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
+        (0, Error::CommandNotAllowed as _)
     }
 }
 
@@ -63,6 +137,12 @@ impl ApduHandler for LegacyQueryMainHWM {
     ) -> Result<(), Error> {
         *tx = 0;
 
+        match buffer.p1() {
+            0 => {}
+            P1_HWM_TEXT => return Self::handle_text(tx, buffer),
+            _ => return Err(Error::InvalidP1P2),
+        }
+
         let hwm = match HWM::hwm() {
             Ok(hwm) => hwm,
             Err(WearError::Uninitialized) => HWM::hwm_default(),
@@ -82,6 +162,30 @@ impl ApduHandler for LegacyQueryMainHWM {
     }
 }
 
+impl LegacyQueryMainHWM {
+    #[inline(never)]
+    fn handle_text(tx: &mut u32, buffer: ApduBufferRead<'_>) -> Result<(), Error> {
+        let wm = match HWM::read() {
+            Ok(wm) => wm,
+            Err(WearError::Uninitialized) => WaterMark::default(),
+            Err(_) => return Err(Error::ExecutionError),
+        };
+
+        let mut text = [0; HWM_TEXT_MAX_LEN];
+        let len = wm.write_text(&mut text);
+
+        let buffer = buffer.write();
+        if buffer.len() < len {
+            return Err(Error::OutputBufferTooSmall);
+        }
+
+        buffer[..len].copy_from_slice(&text[..len]);
+        *tx = len as u32;
+
+        Ok(())
+    }
+}
+
 impl ApduHandler for LegacyQueryAllHWM {
     #[inline(never)]
     fn handle<'apdu>(
@@ -112,16 +216,19 @@ impl ApduHandler for LegacyQueryAllHWM {
 
 #[cfg(test)]
 mod tests {
+    use super::{ResetUI, P1_HWM_TEXT};
     use crate::{
         assert_error_code,
         constants::ApduError,
         dispatcher::{
             handle_apdu, CLA, INS_LEGACY_QUERY_ALL_HWM, INS_LEGACY_QUERY_MAIN_HWM, INS_LEGACY_RESET,
         },
-        handlers::hwm::*,
+        handlers::baking::hwm::*,
+        utils::{read_u32_be, MaybeNullTerminatedToString},
     };
     use serial_test::serial;
     use std::convert::TryInto;
+    use zuit::{MockDriver, Page};
 
     #[test]
     fn test_watermark() {
@@ -157,6 +264,41 @@ mod tests {
         let hwm = HWM::all_hwm().expect("failed retrieving all hwm");
         assert_eq!(&reset_level[..], &hwm[..4]); //main
         assert_eq!(&reset_level[..], &hwm[4..8]); //test
+
+        //negative level
+        buffer[..5].copy_from_slice(&[CLA, INS_LEGACY_RESET, 0, 0, 4]);
+        buffer[5..rx].copy_from_slice(&0xFFFF_FFFFu32.to_be_bytes()[..]);
+        handle_apdu(&mut flags, &mut tx, rx as u32, &mut buffer);
+
+        assert_error_code!(tx, buffer, ApduError::ParseError);
+        let hwm = HWM::all_hwm().expect("failed retrieving all hwm");
+        assert_eq!(&reset_level[..], &hwm[..4]);
+    }
+
+    #[test]
+    fn reset_hwm_ui() {
+        let ui = ResetUI { level: 4200 };
+
+        let mut driver = MockDriver::<_, 18, 4096>::new(ui);
+        driver.drive();
+
+        let produced_ui = driver.out_ui();
+        let messages = produced_ui
+            .iter()
+            .map(|item_pages| {
+                item_pages
+                    .iter()
+                    .map(|Page { message, .. }| {
+                        message
+                            .to_string_with_check_null()
+                            .expect("message was invalid UTF8")
+                    })
+                    .collect::<std::string::String>()
+            })
+            .collect::<std::vec::Vec<_>>();
+
+        assert!(messages.iter().any(|m| m == "Reset HWM"));
+        assert!(messages.iter().any(|m| m == "4,200"));
     }
 
     #[test]
@@ -210,6 +352,42 @@ mod tests {
         assert_eq!(&buffer[..len], &hwm[..])
     }
 
+    #[test]
+    fn watermark_text() {
+        let mut text = [0; HWM_TEXT_MAX_LEN];
+
+        let len = WaterMark::reset(4_212_345, true).write_text(&mut text);
+        assert_eq!(&text[..len], b"level 4,212,345 round 0");
+
+        let len = WaterMark::default().write_text(&mut text);
+        assert_eq!(&text[..len], b"level 4,294,967,295 round 0");
+
+        let len = WaterMark::reset(999, false).write_text(&mut text);
+        assert_eq!(&text[..len], b"level 999 round 0");
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn apdu_legacy_get_hwm_text() {
+        let mut flags = 0;
+        let mut tx = 0;
+        let rx = 5;
+        let mut buffer = [0; 260];
+
+        //reset state (problematic with other tests)
+        HWM::format().expect("couldn't format");
+        HWM::reset(1_000).expect("couldn't reset");
+
+        buffer[..rx].copy_from_slice(&[CLA, INS_LEGACY_QUERY_MAIN_HWM, P1_HWM_TEXT, 0, 0]);
+        handle_apdu(&mut flags, &mut tx, rx as u32, &mut buffer);
+
+        assert_error_code!(tx, buffer, ApduError::Success);
+
+        const EXPECTED: &[u8] = b"level 1,000 round 0";
+        assert_eq!(tx as usize, EXPECTED.len() + 2);
+        assert_eq!(&buffer[..EXPECTED.len()], EXPECTED);
+    }
+
     #[test]
     #[serial(hwm)]
     fn apdu_get_all_hwm() {
@@ -236,16 +414,26 @@ mod tests {
         assert_eq!(&buffer[..4], &main[..]); //main
         assert_eq!(&buffer[4..8], &main[..]); //main == test in this case because we reset
 
-        let chain_id = {
-            let mut array = [0; 4];
-            array.copy_from_slice(&buffer[8..12]);
-            u32::from_be_bytes(array)
-        };
+        let chain_id = read_u32_be(&buffer, 8).unwrap();
         assert_eq!(chain_id, MAINNET_CHAIN_ID);
 
         assert_eq!(&buffer[..12], &hwm[..]);
     }
 
+    #[test]
+    fn known_chains() {
+        let (len, mainnet) = ChainID::id_to_base58(MAINNET_CHAIN_ID).unwrap();
+        assert_eq!(&mainnet[..len], b"NetXdQprcVkpaWU");
+
+        let (len, ghostnet) = ChainID::id_to_base58(GHOSTNET_CHAIN_ID).unwrap();
+        assert_eq!(&ghostnet[..len], b"NetXnHfVqm9iesp");
+
+        assert!(matches!(
+            ChainID::from(GHOSTNET_CHAIN_ID),
+            ChainID::Ghostnet
+        ));
+    }
+
     #[test]
     #[serial(hwm)]
     pub fn trash_01() {
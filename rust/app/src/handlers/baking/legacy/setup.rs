@@ -17,23 +17,23 @@ use std::{mem::MaybeUninit, ptr::addr_of_mut};
 
 use crate::{
     constants::{ApduError as Error, BIP32_MAX_LENGTH},
-    crypto::Curve,
+    crypto::{Curve, PUBLIC_KEY_BASE58_LEN},
     dispatcher::ApduHandler,
     handlers::{
-        baking::Baking,
+        baking::{
+            hwm::{ChainID, WaterMark, HWM},
+            Baking,
+        },
         handle_ui_message,
-        hwm::{ChainID, WaterMark, HWM},
         public_key::{Addr, GetAddress},
     },
     sys::crypto::bip32::BIP32Path,
-    utils::{ApduBufferRead, ApduPanic},
+    utils::{read_u32_be, ApduBufferRead, ApduPanic},
 };
 use zemu_sys::{Show, ViewError, Viewable};
 
 use core::convert::TryFrom;
 
-use arrayref::array_ref;
-
 pub struct LegacySetup;
 
 impl LegacySetup {
@@ -72,9 +72,9 @@ impl ApduHandler for LegacySetup {
             return Err(Error::WrongLength);
         }
 
-        let chain = u32::from_be_bytes(*array_ref!(cdata, 0, 4));
-        let main = u32::from_be_bytes(*array_ref!(cdata, 4, 4));
-        let test = u32::from_be_bytes(*array_ref!(cdata, 8, 4));
+        let chain = read_u32_be(cdata, 0).map_err(|_| Error::WrongLength)?;
+        let main = read_u32_be(cdata, 4).map_err(|_| Error::WrongLength)?;
+        let test = read_u32_be(cdata, 8).map_err(|_| Error::WrongLength)?;
 
         let path_len = cdata[12] as usize;
         let path = BIP32Path::<BIP32_MAX_LENGTH>::read(
@@ -139,7 +139,7 @@ impl SetupUI {
 
 impl Viewable for SetupUI {
     fn num_items(&mut self) -> Result<u8, ViewError> {
-        Ok(5)
+        Ok(6)
     }
 
     #[inline(never)]
@@ -170,6 +170,20 @@ impl Viewable for SetupUI {
                 handle_ui_message(&mex[..len], message, page)
             }
             2 => {
+                let title_content = pic_str!(b"Public Key");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut pk = MaybeUninit::uninit();
+                GetAddress::new_key_into(self.curve, &self.path, &mut pk)
+                    .map_err(|_| ViewError::Unknown)?;
+                //safe because we initialized it above
+                let pk = unsafe { pk.assume_init() };
+
+                let mut mex = [0; PUBLIC_KEY_BASE58_LEN];
+                let len = pk.base58(&mut mex).map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
+            3 => {
                 let title_content = pic_str!(b"Chain");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -181,13 +195,13 @@ impl Viewable for SetupUI {
 
                 handle_ui_message(&mex[..len], message, page)
             }
-            3 => {
+            4 => {
                 let title_content = pic_str!(b"Main Chain HWM");
                 title[..title_content.len()].copy_from_slice(title_content);
 
                 handle_ui_message(itoa(self.main_hwm, &mut hwm_buf), message, page)
             }
-            4 => {
+            5 => {
                 let title_content = pic_str!(b"Test Chain HWM");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -232,7 +246,7 @@ impl Viewable for SetupUI {
         out[0] = len as u8;
         out[1..1 + len].copy_from_slice(key);
 
-        (1 + len as usize, Error::Success as _)
+        (1 + len, Error::Success as _)
     }
 
     fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
@@ -321,6 +335,17 @@ mod tests {
 
         let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n)).unwrap();
 
+        let pk_base58 = {
+            let mut pk = MaybeUninit::uninit();
+            GetAddress::new_key_into(Curve::Bip32Ed25519, &path, &mut pk)
+                .expect("couldn't derive key");
+            let pk = unsafe { pk.assume_init() };
+
+            let mut out = [0; PUBLIC_KEY_BASE58_LEN];
+            let len = pk.base58(&mut out).expect("couldn't encode key");
+            std::string::String::from_utf8(out[..len].to_vec()).expect("pk base58 was not utf8")
+        };
+
         let expected_ui = [
             ("Type".to_string(), "Setup Baking".to_string()),
             (
@@ -328,6 +353,7 @@ mod tests {
                 std::string::String::from_utf8(addr_base58[..len].to_vec())
                     .expect("addr base58 was not utf8"),
             ),
+            ("Public Key".to_string(), pk_base58),
             ("Chain".to_string(), "".to_string()),
             ("Main Chain HWM".to_string(), format!("{}", 42)),
             ("Test Chain HWM".to_string(), format!("{}", 1)),
@@ -335,6 +361,7 @@ mod tests {
 
         for chain_id in [
             ChainID::Mainnet,
+            ChainID::Ghostnet,
             ChainID::Any,
             ChainID::Custom(1234),
             ChainID::Custom(420),
@@ -350,7 +377,7 @@ mod tests {
             };
 
             let mut expected_ui = expected_ui.clone();
-            expected_ui[2].1 = chain_id_alias;
+            expected_ui[3].1 = chain_id_alias;
 
             let ui = SetupUI {
                 curve: Curve::Bip32Ed25519,
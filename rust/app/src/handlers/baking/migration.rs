@@ -0,0 +1,568 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use core::{convert::TryFrom, mem::MaybeUninit};
+
+use bolos::{pic_str, PIC};
+use zemu_sys::{Show, ViewError, Viewable};
+
+use crate::{
+    constants::BIP32_MAX_LENGTH,
+    crypto::Curve,
+    handlers::{
+        handle_ui_message,
+        prelude::*,
+        public_key::{Addr, GetAddress},
+    },
+    sys::{self, crypto::bip32::BIP32Path},
+    utils::read_u32_be,
+};
+
+use super::{
+    hwm::{ChainID, WaterMark, HWM, HWM_TEXT_MAX_LEN},
+    Baking, Expiry, HMAC,
+};
+
+/// Baking state carried over when replacing a device
+///
+/// The snapshot is authenticated with an HMAC keyed by the baking key,
+/// so only a device with the same seed can import it
+#[cfg_attr(test, derive(Debug))]
+struct BakingSnapshot {
+    chain_id: u32,
+    main: WaterMark,
+    test_level: u32,
+    expiry: Expiry,
+    curve: Curve,
+    path: BIP32Path<BIP32_MAX_LENGTH>,
+}
+
+impl BakingSnapshot {
+    const VERSION: u8 = 2;
+
+    const KIND_EMMY: u8 = 0;
+    const KIND_TENDERBAKE: u8 = 1;
+
+    const MAC_LEN: usize = 32;
+
+    /// Offset of the curve, after version, chain id, main watermark kind,
+    /// level and round, test level and expiry
+    const CURVE_OFFSET: usize = 1 + 4 + 1 + 4 + 4 + 4 + Expiry::LEN;
+
+    /// Header, curve and path
+    const MAX_LEN: usize = Self::CURVE_OFFSET + 1 + 1 + 4 * BIP32_MAX_LENGTH;
+
+    /// Returns the snapshot of the current baking state
+    fn current() -> Result<Self, Error> {
+        //the dispatcher made sure a key is authorized
+        let (curve, path) = Baking::read_baking_key()?.ok_or(Error::ExecutionError)?;
+
+        let main = HWM::read().map_err(|_| Error::ApduCodeConditionsNotSatisfied)?;
+        let test = HWM::read_test().map_err(|_| Error::ApduCodeConditionsNotSatisfied)?;
+        let chain_id = HWM::chain_id().map_err(|_| Error::ApduCodeConditionsNotSatisfied)?;
+        let expiry = Baking::read_baking_expiry()?.ok_or(Error::ExecutionError)?;
+
+        Ok(Self {
+            chain_id,
+            main,
+            test_level: test.level(),
+            expiry,
+            curve,
+            path,
+        })
+    }
+
+    /// Writes the snapshot to `out`, returning the number of bytes written
+    fn write(&self, out: &mut [u8; Self::MAX_LEN]) -> usize {
+        let kind = match self.main {
+            WaterMark::Emmy { .. } => Self::KIND_EMMY,
+            WaterMark::Tenderbake { .. } => Self::KIND_TENDERBAKE,
+        };
+
+        out[0] = Self::VERSION;
+        out[1..5].copy_from_slice(&self.chain_id.to_be_bytes()[..]);
+        out[5] = kind;
+        out[6..10].copy_from_slice(&self.main.level().to_be_bytes()[..]);
+        out[10..14].copy_from_slice(&self.main.round().to_be_bytes()[..]);
+        out[14..18].copy_from_slice(&self.test_level.to_be_bytes()[..]);
+        self.expiry
+            .write(arrayref::array_mut_ref!(out, 18, Expiry::LEN));
+        out[Self::CURVE_OFFSET] = self.curve.into();
+
+        let path_offset = Self::CURVE_OFFSET + 1;
+        let components = self.path.components();
+        out[path_offset] = components.len() as u8;
+        out[path_offset + 1..]
+            .chunks_exact_mut(4)
+            .zip(components)
+            .for_each(|(chunk, comp)| chunk.copy_from_slice(&comp.to_be_bytes()[..]));
+
+        path_offset + 1 + 4 * components.len()
+    }
+
+    /// Reads a snapshot written with [`BakingSnapshot::write`]
+    ///
+    /// The imported watermarks are considered as already used for
+    /// both endorsing and preendorsing, so the new device
+    /// won't sign anything at the exported level and round
+    fn read(input: &[u8]) -> Result<Self, Error> {
+        if input.len() < Self::CURVE_OFFSET + 2 {
+            return Err(Error::WrongLength);
+        }
+
+        if input[0] != Self::VERSION {
+            return Err(Error::DataInvalid);
+        }
+
+        let chain_id = read_u32_be(input, 1).map_err(|_| Error::WrongLength)?;
+        let level = read_u32_be(input, 6).map_err(|_| Error::WrongLength)?;
+        let round = read_u32_be(input, 10).map_err(|_| Error::WrongLength)?;
+        let test_level = read_u32_be(input, 14).map_err(|_| Error::WrongLength)?;
+
+        let main = match input[5] {
+            Self::KIND_EMMY if round == 0 => WaterMark::Emmy {
+                level,
+                had_endorsement: true,
+            },
+            Self::KIND_TENDERBAKE => WaterMark::Tenderbake {
+                level,
+                round,
+                had_endorsement: true,
+                had_preendorsement: true,
+            },
+            _ => return Err(Error::DataInvalid),
+        };
+
+        let expiry = Expiry::read(&input[18..Self::CURVE_OFFSET])?;
+
+        let curve = Curve::try_from(input[Self::CURVE_OFFSET]).map_err(|_| Error::DataInvalid)?;
+        let path = BIP32Path::<BIP32_MAX_LENGTH>::read(&input[Self::CURVE_OFFSET + 1..])
+            .map_err(|_| Error::DataInvalid)?;
+
+        Ok(Self {
+            chain_id,
+            main,
+            test_level,
+            expiry,
+            curve,
+            path,
+        })
+    }
+
+    /// Computes the MAC of the serialized snapshot `bytes`
+    #[inline(never)]
+    fn mac(&self, bytes: &[u8], out: &mut [u8; Self::MAC_LEN]) -> Result<(), Error> {
+        let domain = pic_str!(b"baking state snapshot"!);
+        let key = HMAC::derive_key(self.curve, &self.path, &domain[..])?;
+
+        HMAC::digest_into(&key, bytes, out)
+    }
+}
+
+/// Exports the baking state as a snapshot to be imported on another device
+///
+/// The response is the snapshot followed by its MAC
+pub struct ExportBakingState;
+
+impl ApduHandler for ExportBakingState {
+    #[inline(never)]
+    fn handle<'apdu>(
+        _: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        sys::zemu_log_stack("ExportBakingState::handle\x00");
+        *tx = 0;
+
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let snapshot = BakingSnapshot::current()?;
+
+        let mut bytes = [0; BakingSnapshot::MAX_LEN];
+        let len = snapshot.write(&mut bytes);
+
+        let mut mac = [0; BakingSnapshot::MAC_LEN];
+        snapshot.mac(&bytes[..len], &mut mac)?;
+
+        let out = buffer.write();
+        out[..len].copy_from_slice(&bytes[..len]);
+        out[len..len + BakingSnapshot::MAC_LEN].copy_from_slice(&mac[..]);
+
+        *tx = (len + BakingSnapshot::MAC_LEN) as u32;
+        Ok(())
+    }
+}
+
+/// Imports a snapshot produced by [`ExportBakingState`], after confirmation
+///
+/// The snapshot can't lower the high watermarks of the device,
+/// neither the main chain's nor the test chain's
+pub struct ImportBakingState;
+
+impl ApduHandler for ImportBakingState {
+    #[inline(never)]
+    fn handle<'apdu>(
+        flags: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        sys::zemu_log_stack("ImportBakingState::handle\x00");
+        *tx = 0;
+
+        let cdata = buffer.payload().map_err(|_| Error::DataInvalid)?;
+        let bytes_len = cdata
+            .len()
+            .checked_sub(BakingSnapshot::MAC_LEN)
+            .ok_or(Error::WrongLength)?;
+        let (bytes, mac) = cdata.split_at(bytes_len);
+
+        let snapshot = BakingSnapshot::read(bytes)?;
+
+        if let Ok(current) = HWM::read() {
+            let current = (current.level(), current.round());
+            if current > (snapshot.main.level(), snapshot.main.round()) {
+                return Err(Error::WrongValues);
+            }
+        }
+        if let Ok(current) = HWM::read_test() {
+            if current.level() > snapshot.test_level {
+                return Err(Error::WrongValues);
+            }
+        }
+
+        let mut expected = [0; BakingSnapshot::MAC_LEN];
+        snapshot.mac(bytes, &mut expected)?;
+
+        //avoid leaking how many bytes matched
+        let diff = expected
+            .iter()
+            .zip(mac)
+            .fold(0, |diff, (a, b)| diff | (a ^ b));
+        if diff != 0 {
+            return Err(Error::SignVerifyError);
+        }
+
+        let mut addr = MaybeUninit::uninit();
+        GetAddress::new_addr_into(snapshot.curve, &snapshot.path, &mut addr)
+            .map_err(|_| Error::ExecutionError)?;
+
+        let ui = ImportUI {
+            snapshot,
+            //safe because it was initialized above
+            addr: unsafe { addr.assume_init() },
+        };
+
+        unsafe { ui.show(flags) }.map_err(|_| Error::ExecutionError)
+    }
+}
+
+struct ImportUI {
+    snapshot: BakingSnapshot,
+    //reduntant but makes ui faster
+    addr: Addr,
+}
+
+impl Viewable for ImportUI {
+    fn num_items(&mut self) -> Result<u8, ViewError> {
+        Ok(5)
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &mut self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use lexical_core::{write as itoa, Number};
+
+        match item_n {
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"Import Baking")[..], message, page)
+            }
+            1 => {
+                let title_content = pic_str!(b"Address");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (len, mex) = self.addr.base58();
+                handle_ui_message(&mex[..len], message, page)
+            }
+            2 => {
+                let title_content = pic_str!(b"Chain");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; ChainID::BASE58_LEN];
+                let len = ChainID::from(self.snapshot.chain_id)
+                    .to_alias(&mut mex)
+                    .map_err(|_| ViewError::Unknown)?;
+
+                handle_ui_message(&mex[..len], message, page)
+            }
+            3 => {
+                let title_content = pic_str!(b"Main Chain HWM");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; HWM_TEXT_MAX_LEN];
+                let len = self.snapshot.main.write_text(&mut mex);
+
+                handle_ui_message(&mex[..len], message, page)
+            }
+            4 => {
+                let title_content = pic_str!(b"Test Chain HWM");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut hwm_buf = [0; u32::FORMATTED_SIZE_DECIMAL];
+                handle_ui_message(itoa(self.snapshot.test_level, &mut hwm_buf), message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+
+    fn accept(&mut self, out: &mut [u8]) -> (usize, u16) {
+        let snapshot = &self.snapshot;
+
+        //get public key
+        let mut pk = MaybeUninit::uninit();
+        if GetAddress::new_key_into(snapshot.curve, &snapshot.path, &mut pk).is_err() {
+            return (0, Error::ExecutionError as _);
+        }
+
+        if Baking::store_baking_key_with_expiry(snapshot.curve, snapshot.path, snapshot.expiry)
+            .is_err()
+        {
+            return (0, Error::ExecutionError as _);
+        }
+
+        if HWM::write(snapshot.main.clone()).is_err() {
+            return (0, Error::Busy as _);
+        }
+
+        let test = WaterMark::Emmy {
+            level: snapshot.test_level,
+            had_endorsement: true,
+        };
+        if HWM::write_test(test).is_err() {
+            return (0, Error::Busy as _);
+        }
+
+        if HWM::set_chain_id(snapshot.chain_id).is_err() {
+            return (0, Error::Busy as _);
+        }
+
+        //write PK to out
+        // safe because it's initialized
+        let pk = unsafe { pk.assume_init() };
+        let key = pk.as_ref();
+        let len = key.len();
+        out[0] = len as u8;
+        out[1..1 + len].copy_from_slice(key);
+
+        (1 + len, Error::Success as _)
+    }
+
+    fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
+        (0, Error::CommandNotAllowed as _)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dispatcher::{INS_EXPORT_BAKING_STATE, INS_IMPORT_BAKING_STATE},
+        sys::get_out,
+        utils::ApduFixture,
+    };
+
+    use serial_test::serial;
+
+    fn path() -> BIP32Path<BIP32_MAX_LENGTH> {
+        BIP32Path::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n)).unwrap()
+    }
+
+    #[test]
+    fn snapshot_roundtrip() {
+        let snapshot = BakingSnapshot {
+            chain_id: 0x7A06A770,
+            main: WaterMark::Tenderbake {
+                level: 42,
+                round: 3,
+                had_endorsement: false,
+                had_preendorsement: false,
+            },
+            test_level: 7,
+            expiry: Expiry::Signatures(5),
+            curve: Curve::Ed25519,
+            path: path(),
+        };
+
+        let mut bytes = [0; BakingSnapshot::MAX_LEN];
+        let len = snapshot.write(&mut bytes);
+        assert_eq!(len, BakingSnapshot::CURVE_OFFSET + 2 + 4 * 4);
+
+        let read = BakingSnapshot::read(&bytes[..len]).expect("couldn't read snapshot");
+        assert_eq!(read.chain_id, snapshot.chain_id);
+        assert_eq!(read.test_level, snapshot.test_level);
+        assert_eq!(read.expiry, snapshot.expiry);
+        assert_eq!(read.path, snapshot.path);
+        assert_eq!(
+            read.main,
+            WaterMark::Tenderbake {
+                level: 42,
+                round: 3,
+                had_endorsement: true,
+                had_preendorsement: true,
+            }
+        );
+
+        //unknown version
+        bytes[0] = 0xFF;
+        assert_eq!(
+            BakingSnapshot::read(&bytes[..len]).unwrap_err(),
+            Error::DataInvalid
+        );
+    }
+
+    #[test]
+    #[serial(hwm)]
+    //the accepted import goes thru the mocked UI
+    #[serial(ui)]
+    fn apdu_export_import() {
+        Baking::store_baking_key_with_expiry(Curve::Ed25519, path(), Expiry::Level(1000))
+            .expect("couldn't store baking key");
+        HWM::reset(100).expect("couldn't reset hwm");
+        //discard the output of previous reviews
+        get_out();
+
+        let mut fixture = ApduFixture::new();
+        let exported = fixture
+            .send(INS_EXPORT_BAKING_STATE, 0, 0, &[])
+            .assert_success()
+            .to_vec();
+        assert_eq!(
+            exported.len(),
+            BakingSnapshot::CURVE_OFFSET + 2 + 4 * 4 + BakingSnapshot::MAC_LEN
+        );
+        assert_eq!(&exported[6..10], &100_u32.to_be_bytes()[..]);
+
+        //confirmation is mandatory
+        fixture
+            .send(INS_IMPORT_BAKING_STATE, 0, 0, &exported)
+            .assert_code(Error::ApduCodeConditionsNotSatisfied);
+
+        //tampered level
+        let mut tampered = exported.clone();
+        tampered[9] += 1;
+        fixture
+            .send(INS_IMPORT_BAKING_STATE, 1, 0, &tampered)
+            .assert_code(Error::SignVerifyError);
+
+        //tampered expiry
+        let mut tampered = exported.clone();
+        tampered[18] = 0;
+        fixture
+            .send(INS_IMPORT_BAKING_STATE, 1, 0, &tampered)
+            .assert_code(Error::SignVerifyError);
+        assert!(get_out().is_none());
+
+        //the untouched snapshot is accepted, expiry included
+        Baking::store_baking_key(Curve::Ed25519, path()).expect("couldn't store baking key");
+        fixture
+            .send(INS_IMPORT_BAKING_STATE, 1, 0, &exported)
+            .assert_success();
+        let (_, out) = get_out().expect("UI mock used");
+        let len = 1 + out[0] as usize;
+        assert_eq!(
+            &out[len..len + 2],
+            &(Error::Success as u16).to_be_bytes()[..]
+        );
+        assert_eq!(Baking::read_baking_expiry(), Ok(Some(Expiry::Level(1000))));
+
+        //the snapshot can't lower the watermark, not even by round
+        HWM::write(WaterMark::Tenderbake {
+            level: 100,
+            round: 1,
+            had_endorsement: false,
+            had_preendorsement: false,
+        })
+        .expect("couldn't write hwm");
+        fixture
+            .send(INS_IMPORT_BAKING_STATE, 1, 0, &exported)
+            .assert_code(Error::WrongValues);
+
+        HWM::reset(200).expect("couldn't reset hwm");
+        fixture
+            .send(INS_IMPORT_BAKING_STATE, 1, 0, &exported)
+            .assert_code(Error::WrongValues);
+        assert!(get_out().is_none());
+
+        //nor the test chain watermark
+        HWM::reset(100).expect("couldn't reset hwm");
+        HWM::write_test(WaterMark::reset(101, false)).expect("couldn't write test hwm");
+        fixture
+            .send(INS_IMPORT_BAKING_STATE, 1, 0, &exported)
+            .assert_code(Error::WrongValues);
+        assert!(get_out().is_none());
+        assert_eq!(HWM::read_test().unwrap().level(), 101);
+
+        Baking::remove_baking_key().expect("couldn't remove baking key");
+    }
+
+    #[test]
+    #[serial(hwm)]
+    fn import_accept() {
+        HWM::format().expect("couldn't format");
+
+        let snapshot = BakingSnapshot {
+            chain_id: 0x7A06A770,
+            main: WaterMark::Emmy {
+                level: 42,
+                had_endorsement: true,
+            },
+            test_level: 7,
+            expiry: Expiry::Signatures(3),
+            curve: Curve::Ed25519,
+            path: path(),
+        };
+
+        let mut ui = ImportUI {
+            snapshot,
+            addr: Addr::from_hash(&[0; 20], Curve::Ed25519).unwrap(),
+        };
+
+        let mut out = [0; 260];
+        let (len, code) = ui.accept(&mut out);
+        assert_eq!(code, Error::Success as u16);
+        assert_eq!(len, 1 + out[0] as usize);
+
+        assert_eq!(HWM::read().unwrap(), ui.snapshot.main);
+        assert_eq!(HWM::read_test().unwrap().level(), 7);
+        assert_eq!(HWM::chain_id().unwrap(), 0x7A06A770);
+        match Baking::read_baking_key() {
+            Ok(Some((Curve::Ed25519, p))) if p == path() => {}
+            other => panic!("failed to verify stored baking key: {:?}", other),
+        }
+        assert_eq!(
+            Baking::read_baking_expiry(),
+            Ok(Some(Expiry::Signatures(3)))
+        );
+    }
+}
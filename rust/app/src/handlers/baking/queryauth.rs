@@ -15,7 +15,7 @@
 ********************************************************************************/
 use crate::{
     constants::{ApduError as Error, BIP32_MAX_LENGTH},
-    crypto::Curve,
+    crypto::{Curve, PUBLIC_KEY_BASE58_LEN},
     dispatcher::ApduHandler,
     handlers::{
         handle_ui_message,
@@ -38,14 +38,10 @@ fn query(
     buffer: &mut [u8],
     flags: &mut u32,
 ) -> Result<u32, Error> {
-    //Check if the current baking path in NVM is initialized
-    let current_path =
-        unsafe { BAKINGPATH.read() }.map_err(|_| Error::ApduCodeConditionsNotSatisfied)?;
-
-    //path seems to be initialized so we can return it
-    //check if it is a good path
-    let curve_and_path = Bip32PathAndCurve::try_from_bytes(current_path)?
-        .ok_or(Error::ApduCodeConditionsNotSatisfied)?;
+    //the dispatcher made sure a key is authorized
+    let current_path = unsafe { BAKINGPATH.read() }.map_err(|_| Error::ExecutionError)?;
+    let curve_and_path =
+        Bip32PathAndCurve::try_from_bytes(current_path)?.ok_or(Error::ExecutionError)?;
 
     let mut ui = QueryAuthUI::new(curve_and_path, with_curve)?;
 
@@ -135,7 +131,7 @@ impl QueryAuthUI {
 
 impl Viewable for QueryAuthUI {
     fn num_items(&mut self) -> Result<u8, ViewError> {
-        Ok(2)
+        Ok(3)
     }
 
     fn render_item(
@@ -159,6 +155,20 @@ impl Viewable for QueryAuthUI {
                 let (len, mex) = self.addr.base58();
                 handle_ui_message(&mex[..len], message, page)
             }
+            2 => {
+                let title_content = pic_str!(b"Public Key");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut pk = core::mem::MaybeUninit::uninit();
+                GetAddress::new_key_into(self.curve, &self.path, &mut pk)
+                    .map_err(|_| ViewError::Unknown)?;
+                //safe because we initialized it above
+                let pk = unsafe { pk.assume_init() };
+
+                let mut mex = [0; PUBLIC_KEY_BASE58_LEN];
+                let len = pk.base58(&mut mex).map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
             _ => Err(ViewError::NoData),
         }
     }
@@ -27,3 +27,32 @@ pub use blind_sign::BlindSign;
 
 mod debug;
 pub use debug::Debug;
+
+pub mod metrics;
+pub use metrics::SignMetrics;
+
+use crate::handlers::prelude::*;
+
+/// Handles the development instructions
+///
+/// Returns `None` if `ins` is not one of them
+#[inline(never)]
+pub fn dispatch<'apdu>(
+    ins: u8,
+    flags: &mut u32,
+    tx: &mut u32,
+    buffer: ApduBufferRead<'apdu>,
+) -> Option<Result<(), Error>> {
+    use crate::apdu_spec::*;
+
+    let res = match ins {
+        INS_DEV_HASH => Sha256::handle(flags, tx, buffer),
+        INS_DEV_EXCEPT => Except::handle(flags, tx, buffer),
+        INS_DEV_ECHO_UI => Echo::handle(flags, tx, buffer),
+        INS_BLIND_SIGN => BlindSign::handle(flags, tx, buffer),
+        INS_DEV_SIGN_METRICS => SignMetrics::handle(flags, tx, buffer),
+        _ => return None,
+    };
+
+    Some(res)
+}
@@ -26,6 +26,8 @@ use crate::{
     constants::{ApduError as Error, BIP32_MAX_LENGTH},
     crypto::Curve,
     dispatcher::ApduHandler,
+    handlers::app_config::SignatureCounter,
+    parser::Preemble,
     sys,
     utils::{ApduBufferRead, Uploader},
 };
@@ -52,7 +54,7 @@ impl BlindSign {
         path: &BIP32Path<LEN>,
         data: &[u8],
     ) -> Result<(usize, [u8; 100]), Error> {
-        let sk = curve.to_secret(path);
+        let sk = curve.to_secret(path).map_err(|_| Error::ExecutionError)?;
 
         let mut out = [0; 100];
         let sz = sk
@@ -79,6 +81,14 @@ impl BlindSign {
         let path =
             BIP32Path::<BIP32_MAX_LENGTH>::read(init_data).map_err(|_| Error::DataInvalid)?;
 
+        //any payload can be signed, except for consensus operations
+        // as they would be signed without the watermark checks
+        if let Ok((_, preemble)) = Preemble::from_bytes(data) {
+            if preemble.is_consensus() {
+                return Err(Error::ConsensusNotAllowed);
+            }
+        }
+
         unsafe {
             PATH.replace((path, curve));
         }
@@ -176,6 +186,10 @@ impl Viewable for BlindSignUi {
             Ok(k) => k,
         };
 
+        if let Err(e) = SignatureCounter::increment() {
+            return (0, e as _);
+        }
+
         let mut tx = 0;
 
         //reset globals to avoid skipping `Init`
@@ -197,9 +211,13 @@ impl Viewable for BlindSignUi {
     }
 
     fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
-        let _ = cleanup_globals();
+        Self::cleanup();
         (0, Error::CommandNotAllowed as _)
     }
+
+    fn cleanup() {
+        let _ = cleanup_globals();
+    }
 }
 
 fn cleanup_globals() -> Result<(), Error> {
@@ -268,4 +286,26 @@ mod tests {
         let expected = Blake2b::<32>::digest(MSG).unwrap();
         assert_eq!(&expected, out_hash);
     }
+
+    #[test]
+    #[serial(ui)]
+    fn apdu_blind_sign_consensus() {
+        use crate::{dispatcher::INS_BLIND_SIGN, utils::ApduFixture};
+
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+            .unwrap()
+            .serialize();
+
+        let mut fixture = ApduFixture::new();
+        for preemble in [0x01, 0x02, 0x11, 0x12, 0x13] {
+            fixture
+                .send_chunks(
+                    INS_BLIND_SIGN,
+                    Curve::Ed25519.into(),
+                    &path,
+                    &[preemble, 0xAA, 0xBB],
+                )
+                .assert_code(Error::ConsensusNotAllowed);
+        }
+    }
 }
@@ -54,19 +54,6 @@ impl ApduHandler for Debug {
 }
 
 impl Debug {
-    fn cleanup(&mut self) {
-        unsafe {
-            if let Ok(zbuffer) = BUFFER.acquire(Self) {
-                zbuffer.reset();
-
-                //we managed to acquire so we should release too
-                let _ = BUFFER.release(Self);
-            }
-
-            //couldn't acquire BUFFER so someone is trying to use it
-        }
-    }
-
     fn get_buf() -> Result<&'static [u8], Error> {
         let zbuffer = unsafe { BUFFER.acquire(Self).map_err(|_| Error::ExecutionError)? };
         Ok(zbuffer.read_exact())
@@ -99,12 +86,25 @@ impl Viewable for Debug {
     }
 
     fn accept(&mut self, _: &mut [u8]) -> (usize, u16) {
-        self.cleanup();
+        Self::cleanup();
         (0, Error::Success as _)
     }
 
     fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
-        self.cleanup();
+        Self::cleanup();
         (0, Error::CommandNotAllowed as _)
     }
+
+    fn cleanup() {
+        unsafe {
+            if let Ok(zbuffer) = BUFFER.acquire(Self) {
+                zbuffer.reset();
+
+                //we managed to acquire so we should release too
+                let _ = BUFFER.release(Self);
+            }
+
+            //couldn't acquire BUFFER so someone is trying to use it
+        }
+    }
 }
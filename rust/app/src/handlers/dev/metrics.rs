@@ -0,0 +1,219 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Durations of the last signing reviews, to diagnose reports of slow signing
+//!
+//! Time is measured in ticker events, so every duration
+//! has the resolution of the ticker period. Parsing and signing run
+//! within a single APDU, which is always shorter than a ticker period,
+//! so only the review is timed
+
+use crate::{
+    constants::ApduError as Error,
+    dispatcher::{ApduHandler, INS_DEV_SIGN_METRICS},
+    utils::ApduBufferRead,
+};
+
+/// Number of reviews kept
+pub const HISTORY: usize = 4;
+
+/// Period of the ticker event, in milliseconds
+pub const TICK_MS: u32 = 100;
+
+static mut TICKS: u32 = 0;
+static mut METRICS: Metrics = Metrics::new();
+
+struct Metrics {
+    //tick at which the review in progress started, if any
+    started: Option<u32>,
+    //durations of the last reviews, in ticks
+    history: [u32; HISTORY],
+    //number of reviews completed since the app started
+    completed: u32,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            started: None,
+            history: [0; HISTORY],
+            completed: 0,
+        }
+    }
+
+    /// Records the duration of the review in progress in the history
+    fn complete(&mut self, now: u32) {
+        if let Some(start) = self.started.take() {
+            self.history[self.completed as usize % HISTORY] = now.wrapping_sub(start);
+            self.completed = self.completed.wrapping_add(1);
+        }
+    }
+
+    /// Number of reviews in the history
+    fn recorded(&self) -> usize {
+        core::cmp::min(self.completed as usize, HISTORY)
+    }
+
+    /// Average duration of the reviews in the history, in milliseconds
+    fn average(&self) -> u32 {
+        let n = self.recorded();
+        if n == 0 {
+            return 0;
+        }
+
+        let sum = self.history[..n]
+            .iter()
+            .fold(0u64, |sum, &ticks| sum + ticks as u64);
+
+        (sum * TICK_MS as u64 / n as u64) as u32
+    }
+}
+
+/// Advances the clock by one ticker period
+pub fn tick() {
+    unsafe {
+        TICKS = TICKS.wrapping_add(1);
+    }
+}
+
+/// Marks the beginning of the review of a signing request
+pub fn review_started() {
+    unsafe {
+        METRICS.started = Some(TICKS);
+    }
+}
+
+/// Marks the approval of the review, recording it in the history
+pub fn reviewed() {
+    unsafe {
+        METRICS.complete(TICKS);
+    }
+}
+
+/// Discards the review in progress, if any
+pub fn aborted() {
+    unsafe {
+        METRICS.started = None;
+    }
+}
+
+/// Retrieves the number of completed signing reviews and their average
+/// duration over the last ones
+pub struct SignMetrics;
+
+impl SignMetrics {
+    /// Completed reviews, reviews averaged and average duration in milliseconds
+    pub const RESPONSE_LEN: usize = 4 + 1 + 4;
+}
+
+impl ApduHandler for SignMetrics {
+    #[inline(never)]
+    fn handle<'apdu>(
+        _: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        *tx = 0;
+        if buffer.ins() != INS_DEV_SIGN_METRICS {
+            return Err(Error::InsNotSupported);
+        }
+
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let metrics = unsafe { &METRICS };
+
+        let out = buffer.write();
+        if out.len() < Self::RESPONSE_LEN {
+            return Err(Error::OutputBufferTooSmall);
+        }
+
+        out[..4].copy_from_slice(&metrics.completed.to_be_bytes()[..]);
+        out[4] = metrics.recorded() as u8;
+        out[5..9].copy_from_slice(&metrics.average().to_be_bytes()[..]);
+
+        *tx = Self::RESPONSE_LEN as u32;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ApduFixture;
+
+    use serial_test::serial;
+    use std::convert::TryInto;
+
+    fn advance(ticks: u32) {
+        for _ in 0..ticks {
+            tick();
+        }
+    }
+
+    #[test]
+    fn average() {
+        let mut metrics = Metrics::new();
+        assert_eq!(metrics.average(), 0);
+
+        //not started
+        metrics.complete(10);
+        assert_eq!(metrics.recorded(), 0);
+
+        for n in 1..=HISTORY as u32 + 2 {
+            metrics.started = Some(n);
+            metrics.complete(n * 11);
+        }
+
+        //only the last reviews are kept: 30, 40, 50 and 60 ticks
+        assert_eq!(metrics.recorded(), HISTORY);
+        assert_eq!(metrics.average(), 4500);
+    }
+
+    #[test]
+    #[serial(sign_metrics)]
+    fn apdu_sign_metrics() {
+        let mut fixture = ApduFixture::new();
+
+        fixture
+            .send(INS_DEV_SIGN_METRICS, 1, 0, &[])
+            .assert_code(Error::InvalidP1P2);
+
+        let before = fixture
+            .send(INS_DEV_SIGN_METRICS, 0, 0, &[])
+            .assert_success()[..4]
+            .try_into()
+            .map(u32::from_be_bytes)
+            .unwrap();
+
+        //rejected reviews aren't recorded
+        review_started();
+        aborted();
+        reviewed();
+
+        review_started();
+        advance(20);
+        reviewed();
+
+        let response = fixture
+            .send(INS_DEV_SIGN_METRICS, 0, 0, &[])
+            .assert_success();
+        assert_eq!(response.len(), SignMetrics::RESPONSE_LEN);
+        //other tests might have completed reviews in the meantime
+        assert!(u32::from_be_bytes(response[..4].try_into().unwrap()) > before);
+        assert!(response[4] >= 1);
+    }
+}
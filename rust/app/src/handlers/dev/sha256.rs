@@ -66,12 +66,8 @@ impl ApduHandler for Sha256 {
 #[cfg(test)]
 mod tests {
     use crate::{
-        assert_error_code,
-        constants::ApduError as Error,
-        dispatcher::{handle_apdu, CLA, INS_DEV_HASH},
-        handlers::ZPacketType,
+        constants::ApduError, dispatcher::INS_DEV_HASH, handlers::ZPacketType, utils::ApduFixture,
     };
-    use std::convert::TryInto;
 
     use serial_test::serial;
     use sha2::{Digest, Sha256};
@@ -79,91 +75,58 @@ mod tests {
     #[test]
     #[serial(dev_hash)]
     fn apdu_dev_hash() {
-        const MSG: [u8; 0xFF] = [42; 0xFF];
-
-        let mut flags = 0;
-        let mut tx = 0;
-        let mut buffer = [0; 260];
-
-        //Init
-        buffer[0] = CLA;
-        buffer[1] = INS_DEV_HASH;
-        buffer[2] = ZPacketType::Init.into();
-        buffer[3] = 0;
-        buffer[4] = 255;
-        buffer[5..].copy_from_slice(&MSG[..255]);
-
-        handle_apdu(&mut flags, &mut tx, 260, &mut buffer);
-        assert_error_code!(tx, buffer, Error::Success);
-
-        //Add
-        MSG[255..].chunks(255).enumerate().for_each(|(i, c)| {
-            let len = c.len();
-            flags = 0;
-            tx = 0;
-
-            buffer[0] = CLA;
-            buffer[1] = INS_DEV_HASH;
-            buffer[2] = ZPacketType::Add.into();
-            buffer[3] = 0;
-            buffer[4] = len as u8;
-
-            let msg_sent = (i + 1) * 255; //send 255 bytes * i chunks + 1 (init)
-            buffer[5..len].copy_from_slice(&MSG[msg_sent..msg_sent + len]);
-
-            handle_apdu(&mut flags, &mut tx, 5 + len as u32, &mut buffer);
-            assert_error_code!(tx, buffer, Error::Success);
-        });
-
-        //Last
-        flags = 0;
-        tx = 0;
-        buffer[0] = CLA;
-        buffer[1] = INS_DEV_HASH;
-        buffer[2] = ZPacketType::Last.into();
-        buffer[3] = 0;
-        buffer[4] = 0;
-
-        handle_apdu(&mut flags, &mut tx, 5, &mut buffer);
-        assert_error_code!(tx, buffer, Error::Success);
+        const MSG: [u8; 0xFF * 3] = [42; 0xFF * 3];
+
+        let mut fixture = ApduFixture::new();
+        let response = fixture.send_chunks(INS_DEV_HASH, 0, &MSG[..0xFF], &MSG[0xFF..]);
 
         let expected = Sha256::digest(&MSG);
-        let digest = &buffer[..tx as usize - 2];
-        assert_eq!(digest, expected.as_slice());
+        assert_eq!(response.assert_success(), expected.as_slice());
     }
 
     #[test]
     #[serial(dev_hash)]
     fn apdu_dev_hash_short() {
         const MSG: &[u8] = b"support.tezos@zondax.ch";
-        let len = MSG.len();
 
-        let mut flags = 0;
-        let mut tx = 0;
-        let mut buffer = [0; 260];
+        let mut fixture = ApduFixture::new();
+        let response = fixture.send_chunks(INS_DEV_HASH, 0, MSG, &[]);
 
-        //Init
-        buffer[0] = CLA;
-        buffer[1] = INS_DEV_HASH;
-        buffer[2] = ZPacketType::Init.into();
-        buffer[3] = 0;
-        buffer[4] = len as u8;
-        buffer[5..5 + len].copy_from_slice(MSG);
+        let expected = Sha256::digest(MSG);
+        assert_eq!(response.assert_success(), expected.as_slice());
+    }
 
-        handle_apdu(&mut flags, &mut tx, 5 + len as u32, &mut buffer);
-        assert_error_code!(tx, buffer, Error::Success);
+    #[test]
+    #[serial(dev_hash)]
+    fn apdu_dev_hash_checked() {
+        const MSG: &[u8] = b"support.tezos@zondax.ch";
+        const MSG_CRC32: u32 = 0x37465D76;
 
-        buffer[0] = CLA;
-        buffer[1] = INS_DEV_HASH;
-        buffer[2] = ZPacketType::Last.into();
-        buffer[3] = 0;
-        buffer[4] = 0;
+        let mut fixture = ApduFixture::new();
+        fixture
+            .send(INS_DEV_HASH, ZPacketType::Init.into(), 0, &MSG[..4])
+            .assert_success();
+        fixture
+            .send(INS_DEV_HASH, ZPacketType::Add.into(), 0, &MSG[4..10])
+            .assert_success();
 
-        handle_apdu(&mut flags, &mut tx, 5, &mut buffer);
-        assert_error_code!(tx, buffer, Error::Success);
+        let mut last = MSG[10..].to_vec();
+        last.extend_from_slice(&MSG_CRC32.to_be_bytes()[..]);
+        let response = fixture.send(INS_DEV_HASH, ZPacketType::LastChecked.into(), 0, &last);
 
         let expected = Sha256::digest(MSG);
-        let digest = &buffer[..tx as usize - 2];
-        assert_eq!(digest, expected.as_slice());
+        assert_eq!(response.assert_success(), expected.as_slice());
+
+        //a chunk went missing
+        fixture
+            .send(INS_DEV_HASH, ZPacketType::Init.into(), 0, &MSG[..4])
+            .assert_success();
+        fixture
+            .send(INS_DEV_HASH, ZPacketType::LastChecked.into(), 0, &last)
+            .assert_code(ApduError::ChecksumMismatch);
+
+        //the upload was discarded
+        let response = fixture.send(INS_DEV_HASH, ZPacketType::Last.into(), 0, &[]);
+        assert_ne!(response.code(), ApduError::Success);
     }
 }
@@ -17,12 +17,3 @@
 pub mod public_key;
 pub mod signing;
 pub mod version;
-
-#[cfg(feature = "baking")]
-pub mod hwm;
-
-#[cfg(feature = "baking")]
-pub mod baking;
-
-#[cfg(feature = "baking")]
-pub mod hmac;
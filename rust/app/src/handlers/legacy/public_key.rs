@@ -115,7 +115,8 @@ impl ApduHandler for LegacyPromptAddress {
         }
 
         //safe because pointers are all valid, initialize with_addr
-        unsafe { addr_of_mut!((*ui.as_mut_ptr()).with_addr).write(true) }
+        //like the legacy app, only the public key is sent back
+        unsafe { addr_of_mut!((*ui.as_mut_ptr()).with_addr).write(false) }
 
         //safe because it's all initialized now
         let ui = unsafe { ui.assume_init() };
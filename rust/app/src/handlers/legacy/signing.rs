@@ -23,8 +23,18 @@ use crate::{
 pub struct LegacySign;
 pub struct LegacySignWithHash;
 
-#[cfg(feature = "wallet")]
-pub struct LegacySignUnsafe;
+/// Splits the data to sign from the derivation path when both were sent
+/// in a single `InitAndLast` packet, so short payloads need only one packet
+///
+/// The path is prefixed by its number of components, 4 bytes each
+fn split_path(first: &'static [u8], data: &'static [u8]) -> (&'static [u8], &'static [u8]) {
+    match first.first() {
+        Some(&components) if data.is_empty() => {
+            first.split_at(first.len().min(1 + 4 * components as usize))
+        }
+        _ => (first, data),
+    }
+}
 
 #[cfg(not(feature = "baking"))]
 impl ApduHandler for LegacySign {
@@ -37,7 +47,8 @@ impl ApduHandler for LegacySign {
         use crate::handlers::signing::Sign;
 
         if let Some(upload) = Uploader::new(Sign).upload(&buffer)? {
-            *tx = Sign::start_sign(false, upload.p2, upload.first, upload.data, flags)?;
+            let (path, data) = split_path(upload.first, upload.data);
+            *tx = Sign::start_sign(false, upload.p2, path, data, flags)?;
         }
 
         Ok(())
@@ -57,14 +68,8 @@ impl ApduHandler for LegacySign {
         use crate::handlers::baking::Baking;
 
         if let Some(upload) = Uploader::new(Baking).upload(&buffer)? {
-            *tx = Baking::baker_sign(
-                false,
-                upload.p2,
-                upload.first,
-                upload.data,
-                buffer.write(),
-                flags,
-            )?;
+            let (path, data) = split_path(upload.first, upload.data);
+            *tx = Baking::baker_sign(false, upload.p2, path, data, buffer.write(), flags)?;
         }
 
         Ok(())
@@ -82,7 +87,8 @@ impl ApduHandler for LegacySignWithHash {
         use crate::handlers::signing::Sign;
 
         if let Some(upload) = Uploader::new(Sign).upload(&buffer)? {
-            *tx = Sign::start_sign(true, upload.p2, upload.first, upload.data, flags)?;
+            let (path, data) = split_path(upload.first, upload.data);
+            *tx = Sign::start_sign(true, upload.p2, path, data, flags)?;
         }
 
         Ok(())
@@ -102,32 +108,8 @@ impl ApduHandler for LegacySignWithHash {
         use crate::handlers::baking::Baking;
 
         if let Some(upload) = Uploader::new(Baking).upload(&buffer)? {
-            *tx = Baking::baker_sign(
-                true,
-                upload.p2,
-                upload.first,
-                upload.data,
-                buffer.write(),
-                flags,
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-#[cfg(feature = "wallet")]
-impl ApduHandler for LegacySignUnsafe {
-    #[inline(never)]
-    fn handle<'apdu>(
-        flags: &mut u32,
-        tx: &mut u32,
-        buffer: ApduBufferRead<'apdu>,
-    ) -> Result<(), Error> {
-        use crate::handlers::signing::Sign;
-
-        if let Some(upload) = Uploader::new(Sign).upload(&buffer)? {
-            *tx = Sign::start_sign(false, upload.p2, upload.first, upload.data, flags)?;
+            let (path, data) = split_path(upload.first, upload.data);
+            *tx = Baking::baker_sign(true, upload.p2, path, data, buffer.write(), flags)?;
         }
 
         Ok(())
@@ -164,6 +146,104 @@ mod tests {
         path.len()
     }
 
+    #[cfg(feature = "wallet")]
+    const OP_HEX: &str = "03\
+                          a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                          6e\
+                          0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                          904e\
+                          01\
+                          0a\
+                          0a\
+                          ff\
+                          0035e993d8c7aaa42b5e3ccd86a33390ececc73abd";
+
+    /// Checks the hash and signature of `op` returned by the mocked UI
+    #[cfg(feature = "wallet")]
+    fn assert_signed(op: &[u8]) {
+        use crate::handlers::signing::Sign;
+
+        let (len, out) = get_out().expect("UI mock used");
+        assert_error_code!(len, out, Error::Success);
+
+        let hash = Blake2b::<32>::digest(op).unwrap();
+        assert_eq!(&out[..32], &hash[..]);
+
+        //same path and curve as `prepare_buffer`
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n)).unwrap();
+        let curve = u8::from(Curve::Ed25519).try_into().unwrap();
+        let (sig_len, sig) = Sign::sign(curve, &path, &hash).unwrap();
+        assert_eq!(len, 32 + sig_len + 2);
+        assert_eq!(&out[32..32 + sig_len], &sig[..sig_len]);
+    }
+
+    #[test]
+    #[serial(ui)]
+    #[cfg(feature = "wallet")]
+    fn apdu_legacy_init_and_last() {
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        buffer[0] = CLA;
+        buffer[1] = INS_LEGACY_SIGN_WITH_HASH;
+        buffer[2] = LegacyPacketType::InitAndLast.into();
+        let len = prepare_buffer(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+
+        //the only packet is also the last one so the upload is complete
+        // but there's no payload to sign
+        handle_apdu(&mut flags, &mut tx, 5 + len as u32, &mut buffer);
+        assert_error_code!(tx, buffer, Error::ParseError);
+        assert!(get_out().is_none());
+
+        //the payload follows the path in the same packet
+        buffer[0] = CLA;
+        buffer[1] = INS_LEGACY_SIGN_WITH_HASH;
+        buffer[2] = LegacyPacketType::InitAndLast.into();
+        let len = prepare_buffer(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+        buffer[4] += op.len() as u8;
+        buffer[5 + len..5 + len + op.len()].copy_from_slice(&op);
+
+        handle_apdu(
+            &mut flags,
+            &mut tx,
+            5 + (len + op.len()) as u32,
+            &mut buffer,
+        );
+        assert_signed(&op);
+    }
+
+    #[test]
+    #[serial(ui)]
+    #[cfg(feature = "wallet")]
+    fn apdu_legacy_init_add_and_last() {
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        buffer[0] = CLA;
+        buffer[1] = INS_LEGACY_SIGN_WITH_HASH;
+        buffer[2] = LegacyPacketType::Init.into();
+        let len = prepare_buffer(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+
+        handle_apdu(&mut flags, &mut tx, 5 + len as u32, &mut buffer);
+        assert_error_code!(tx, buffer, Error::Success);
+
+        buffer[0] = CLA;
+        buffer[1] = INS_LEGACY_SIGN_WITH_HASH;
+        buffer[2] = LegacyPacketType::AddAndLast.into();
+        buffer[3] = 0;
+        buffer[4] = op.len() as u8;
+        buffer[5..5 + op.len()].copy_from_slice(&op);
+
+        handle_apdu(&mut flags, &mut tx, 5 + op.len() as u32, &mut buffer);
+        assert_signed(&op);
+    }
+
     #[test]
     #[ignore]
     #[serial(ui)]
@@ -13,22 +13,55 @@
 *  See the License for the specific language governing permissions and
 *  limitations under the License.
 ********************************************************************************/
+pub mod app_config;
 pub mod public_key;
 pub mod signing;
+pub mod supported_operations;
 pub mod version;
 
 pub mod parser_common;
 
 pub mod legacy;
 
-#[cfg(feature = "baking")]
-pub mod hwm;
-
+//feature specific handlers are gated here, as a whole module
+// so the handlers themselves don't need to care about it
 #[cfg(feature = "dev")]
 pub mod dev;
 
 #[cfg(feature = "baking")]
 pub mod baking;
+#[cfg(feature = "baking")]
+pub use baking as mode;
+
+#[cfg(feature = "wallet")]
+pub mod wallet;
+#[cfg(feature = "wallet")]
+pub use wallet as mode;
+
+/// Items used by most handlers
+pub mod prelude {
+    pub use crate::{
+        constants::ApduError as Error, dispatcher::ApduHandler, utils::ApduBufferRead,
+    };
+}
+
+/// Handles the instructions specific to the enabled features
+///
+/// Returns `None` if `ins` is not one of them
+#[inline(never)]
+pub fn dispatch_features<'apdu>(
+    ins: u8,
+    flags: &mut u32,
+    tx: &mut u32,
+    mut buffer: prelude::ApduBufferRead<'apdu>,
+) -> Option<Result<(), prelude::Error>> {
+    #[cfg(feature = "dev")]
+    if let Some(res) = dev::dispatch(ins, flags, tx, buffer.reborrow()) {
+        return Some(res);
+    }
+
+    mode::dispatch(ins, flags, tx, buffer.reborrow())
+}
 
 mod utils;
 pub use utils::*;
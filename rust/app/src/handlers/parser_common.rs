@@ -8,7 +8,7 @@ use nom::error::ErrorKind;
 /// we redeclare it here, just for interpolation
 /// purposes
 
-#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
 pub enum ParserError {
     // Generic errors
     parser_no_data,
@@ -56,12 +56,18 @@ pub enum ParserError {
     ProposalsLengthInvalid,
     InvalidEndorsementType,
     InvalidProtocolVersion,
+    ZarithTooLong,
+    /// The input ended before the parser was done with it
+    ///
+    /// Parsers never report `nom::Err::Incomplete`, a truncated input
+    /// is always reported with this error instead
+    UnexpectedEof,
 }
 
 impl From<ErrorKind> for ParserError {
     fn from(err: ErrorKind) -> Self {
         match err {
-            ErrorKind::Eof => ParserError::parser_unexpected_buffer_end,
+            ErrorKind::Eof | ErrorKind::Complete => ParserError::UnexpectedEof,
             ErrorKind::Permutation => ParserError::parser_unexpected_type,
             ErrorKind::TooLarge => ParserError::parser_value_out_of_range,
             _ => ParserError::parser_unexpected_error,
@@ -86,3 +92,13 @@ impl From<ParserError> for nom::Err<ParserError> {
         nom::Err::Error(error)
     }
 }
+
+impl From<nom::Err<ParserError>> for ParserError {
+    fn from(error: nom::Err<ParserError>) -> Self {
+        match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => error,
+            //never reported by our parsers, see `UnexpectedEof`
+            nom::Err::Incomplete(_) => ParserError::UnexpectedEof,
+        }
+    }
+}
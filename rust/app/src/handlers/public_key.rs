@@ -41,7 +41,7 @@ impl GetAddress {
         out: &mut MaybeUninit<crypto::PublicKey>,
     ) -> Result<(), SysError> {
         sys::zemu_log_stack("GetAddres::new_key\x00");
-        curve.to_secret(path).into_public_into(out)?;
+        curve.to_secret(path)?.into_public_into(out)?;
 
         //this is safe because it's initialized
         // also unwrapping is fine because the ptr is valid
@@ -60,7 +60,7 @@ impl GetAddress {
         sys::zemu_log_stack("GetAddres::new_addr_into\x00");
 
         let mut pkey = MaybeUninit::uninit();
-        curve.to_secret(path).into_public_into(&mut pkey)?;
+        curve.to_secret(path)?.into_public_into(&mut pkey)?;
 
         //safe because we initialized it above
         let mut pkey = unsafe { pkey.assume_init() };
@@ -141,6 +141,65 @@ impl ApduHandler for GetAddress {
     }
 }
 
+pub struct GetAllAddresses;
+
+impl GetAllAddresses {
+    const CURVES: [crypto::Curve; 4] = [
+        crypto::Curve::Ed25519,
+        crypto::Curve::Secp256K1,
+        crypto::Curve::Secp256R1,
+        crypto::Curve::Bip32Ed25519,
+    ];
+}
+
+impl ApduHandler for GetAllAddresses {
+    #[inline(never)]
+    fn handle<'apdu>(
+        _: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        sys::zemu_log_stack("GetAllAddresses::handle\x00");
+
+        *tx = 0;
+
+        //no confirmation and no curve, since we return all of them
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let cdata = buffer.payload().map_err(|_| Error::DataInvalid)?;
+        let bip32_path =
+            sys::crypto::bip32::BIP32Path::<6>::read(cdata).map_err(|_| Error::DataInvalid)?;
+
+        let out = buffer.write();
+        let mut written = 0;
+
+        //write curve, key length and key for each curve
+        for &curve in Self::CURVES.iter() {
+            let mut pkey = MaybeUninit::uninit();
+            GetAddress::new_key_into(curve, &bip32_path, &mut pkey)
+                .map_err(|_| Error::ExecutionError)?;
+
+            //safe because it was initialized above
+            let pkey = unsafe { pkey.assume_init() };
+            let pkey = pkey.as_ref();
+
+            let entry = out
+                .get_mut(written..written + 2 + pkey.len())
+                .ok_or(Error::OutputBufferTooSmall)?;
+            entry[0] = curve.into();
+            entry[1] = pkey.len() as u8;
+            entry[2..].copy_from_slice(pkey);
+
+            written += entry.len();
+        }
+
+        *tx = written as u32;
+        Ok(())
+    }
+}
+
 #[derive(Default, Clone, Copy)]
 pub struct Addr {
     prefix: [u8; 3],
@@ -258,9 +317,18 @@ pub struct AddrUI {
     pub pkey: crypto::PublicKey,
 
     /// indicates whether to write `add` to out or not
+    ///
+    /// this also appends the checksum of `pkey`, see [`AddrUI::PKEY_CHECKSUM_LEN`]
     pub with_addr: bool,
 }
 
+impl AddrUI {
+    /// Length of the public key checksum, the first bytes of sha256(sha256(pkey))
+    ///
+    /// Lets the host detect a corrupted public key
+    pub const PKEY_CHECKSUM_LEN: usize = 4;
+}
+
 impl Viewable for AddrUI {
     fn num_items(&mut self) -> Result<u8, ViewError> {
         Ok(1)
@@ -300,6 +368,13 @@ impl Viewable for AddrUI {
             out[tx..tx + len].copy_from_slice(&addr[..len]);
 
             tx += len;
+
+            let checksum = arrayref::array_mut_ref!(out, tx, AddrUI::PKEY_CHECKSUM_LEN);
+            if super::sha256x2(&[pkey], checksum).is_err() {
+                return (0, Error::ExecutionError as _);
+            }
+
+            tx += Self::PKEY_CHECKSUM_LEN;
         }
 
         (tx, Error::Success as _)
@@ -340,7 +415,11 @@ mod tests {
     use crate::{
         assert_error_code,
         constants::ApduError,
-        dispatcher::{handle_apdu, CLA, INS_LEGACY_GET_PUBLIC_KEY},
+        dispatcher::{
+            handle_apdu, CLA, INS_GET_ADDRESS, INS_GET_ALL_ADDRESSES, INS_LEGACY_GET_PUBLIC_KEY,
+            INS_LEGACY_PROMPT_PUBLIC_KEY,
+        },
+        sys::get_out,
     };
 
     #[test]
@@ -375,16 +454,61 @@ mod tests {
     fn apdu_legacy_get_public_key() {
         let mut flags = 0u32;
         let mut tx = 0u32;
-        let rx = 5;
         let mut buffer = [0u8; 260];
 
         buffer[..3].copy_from_slice(&[CLA, INS_LEGACY_GET_PUBLIC_KEY, 0]);
         prepare_buffer::<4>(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+        let rx = 5 + buffer[4] as u32;
+
+        handle_apdu(&mut flags, &mut tx, rx, &mut buffer);
+
+        assert_error_code!(tx, buffer, ApduError::Success);
+        assert_eq!(tx as usize, 1 + 33 + 2); //33 bytes for ed25519, tagged with 0x02
+    }
+
+    #[test]
+    #[serial_test::serial(ui)]
+    fn apdu_legacy_prompt_public_key() {
+        let mut flags = 0u32;
+        let mut tx = 0u32;
+        let mut buffer = [0u8; 260];
+
+        buffer[..3].copy_from_slice(&[CLA, INS_LEGACY_PROMPT_PUBLIC_KEY, 0]);
+        prepare_buffer::<4>(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+        let rx = 5 + buffer[4] as u32;
 
+        get_out();
         handle_apdu(&mut flags, &mut tx, rx, &mut buffer);
 
+        //same response as the legacy app: no address nor checksum
+        let (len, out) = get_out().expect("UI mock used");
+        assert_error_code!(len, out, ApduError::Success);
+        assert_eq!(len, 1 + 33 + 2);
+    }
+
+    #[test]
+    fn apdu_get_address_checksum() {
+        let mut flags = 0u32;
+        let mut tx = 0u32;
+        let mut buffer = [0u8; 260];
+
+        buffer[..3].copy_from_slice(&[CLA, INS_GET_ADDRESS, 0]);
+        prepare_buffer::<4>(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+        let rx = 5 + buffer[4] as u32;
+
+        handle_apdu(&mut flags, &mut tx, rx, &mut buffer);
         assert_error_code!(tx, buffer, ApduError::Success);
-        assert_eq!(tx as usize, 1 + 32 + 2); //32 bytes for ed25519
+
+        let out = &buffer[..tx as usize - 2];
+        let pkey = &out[1..1 + out[0] as usize];
+        let (_, checksum) = out.split_at(out.len() - AddrUI::PKEY_CHECKSUM_LEN);
+
+        let mut expected = [0; AddrUI::PKEY_CHECKSUM_LEN];
+        crate::handlers::sha256x2(&[pkey], &mut expected).unwrap();
+        assert_eq!(checksum, &expected[..]);
+
+        //the checksum comes right after the 36 bytes of the tz1 address
+        assert_eq!(out.len(), 1 + pkey.len() + 36 + AddrUI::PKEY_CHECKSUM_LEN);
     }
 
     #[test]
@@ -395,13 +519,40 @@ mod tests {
 
         let mut flags = 0u32;
         let mut tx = 0u32;
-        let rx = 5;
+        let rx = PAYLOAD.len() as u32;
         let mut buffer = [0u8; 260];
         buffer[..PAYLOAD.len()].copy_from_slice(PAYLOAD);
 
         handle_apdu(&mut flags, &mut tx, rx, &mut buffer);
 
         assert_error_code!(tx, buffer, ApduError::Success);
-        assert_eq!(tx as usize, 1 + 32 + 2);
+        assert_eq!(tx as usize, 1 + 33 + 2);
+    }
+
+    #[test]
+    fn apdu_get_all_addresses() {
+        let mut flags = 0u32;
+        let mut tx = 0u32;
+        let mut buffer = [0u8; 260];
+
+        buffer[..3].copy_from_slice(&[CLA, INS_GET_ALL_ADDRESSES, 0]);
+        prepare_buffer::<4>(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+        buffer[3] = 0;
+        let rx = 5 + buffer[4] as u32;
+
+        handle_apdu(&mut flags, &mut tx, rx, &mut buffer);
+        assert_error_code!(tx, buffer, ApduError::Success);
+
+        //the keys themselves are checked against the vectors in tests/vectors.rs
+        let mut out = &buffer[..tx as usize - 2];
+        for &curve in GetAllAddresses::CURVES.iter() {
+            assert_eq!(out[0], u8::from(curve));
+
+            let len = out[1] as usize;
+            assert_ne!(len, 0);
+
+            out = &out[2 + len..];
+        }
+        assert!(out.is_empty());
     }
 }
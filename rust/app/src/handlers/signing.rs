@@ -28,10 +28,10 @@ use crate::{
     constants::{ApduError as Error, BIP32_MAX_LENGTH},
     crypto::Curve,
     dispatcher::ApduHandler,
-    handlers::handle_ui_message,
+    handlers::{app_config::SignatureCounter, handle_ui_message, parser_common::ParserError},
     parser::{
-        operations::{Operation, OperationType},
-        DisplayableItem, Preemble,
+        operations::{EncodedOperations, Operation, OperationType},
+        DisplayableItem, Preemble, Zarith,
     },
     sys,
     utils::{ApduBufferRead, ApduPanic, Uploader},
@@ -45,6 +45,23 @@ pub struct Sign;
 impl Sign {
     pub const SIGN_HASH_SIZE: usize = 32;
 
+    /// Maximum length of the origin label
+    ///
+    /// Kept below the size of the parameters hash so the two can't be confused
+    pub const MAX_ORIGIN_LEN: usize = 24;
+
+    /// Number of bytes of the signed payload shown in each raw data item
+    ///
+    /// Kept small enough for the pages of an item to be counted in a `u8`
+    pub const RAW_BYTES_PER_ITEM: usize = 256;
+
+    /// Mutez burned for each byte of storage paid by an operation
+    pub const STORAGE_COST_PER_BYTE: u64 = 250;
+
+    /// Length of the details sent with a parse error:
+    /// offset of the rejected bytes and error code
+    pub const PARSE_FAILURE_LEN: usize = 4 + 1;
+
     fn get_derivation_info() -> Result<&'static (BIP32Path<BIP32_MAX_LENGTH>, Curve), Error> {
         match unsafe { &*PATH } {
             None => Err(Error::ApduCodeConditionsNotSatisfied),
@@ -59,7 +76,7 @@ impl Sign {
         path: &BIP32Path<LEN>,
         data: &[u8],
     ) -> Result<(usize, [u8; 100]), Error> {
-        let sk = curve.to_secret(path);
+        let sk = curve.to_secret(path).map_err(|_| Error::ExecutionError)?;
 
         let mut out = [0; 100];
         let sz = sk
@@ -74,6 +91,315 @@ impl Sign {
         Blake2b::digest(buffer).map_err(|_| Error::ExecutionError)
     }
 
+    /// Checks if any of the operations can only be signed in expert mode
+    #[inline(never)]
+    fn requires_expert(parsed: &Operation<'_>) -> Result<bool, Error> {
+        let mut ops = *parsed.ops();
+
+        while let Some(op) = ops.parse_next().map_err(|_| Error::ParseError)? {
+            bolos::heartbeat();
+
+            if let OperationType::Transfer(tx) = op {
+                if tx.requires_expert() {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Counts the operations of the group and sums their fees
+    #[inline(never)]
+    fn count_fees(parsed: &Operation<'_>) -> Result<(usize, u64), Error> {
+        let mut ops = *parsed.ops();
+        let mut count = 0;
+        let mut fees = 0u64;
+
+        while let Some(op) = ops.parse_next().map_err(|_| Error::ParseError)? {
+            bolos::heartbeat();
+
+            if !op.is_reviewed() {
+                continue;
+            }
+            count += 1;
+
+            if let Some(fee) = op.fee() {
+                let fee = fee.to_u64().map_err(|_| Error::ParseError)?;
+                fees = fees.checked_add(fee).ok_or(Error::DataInvalid)?;
+            }
+        }
+
+        Ok((count, fees))
+    }
+
+    /// Counts the operations of the group and sums their fees,
+    /// for the summary shown before them
+    ///
+    /// Returns None if there's only one operation to review
+    fn batch_summary(parsed: &Operation<'_>) -> Result<Option<(usize, u64)>, Error> {
+        let (count, fees) = Self::count_fees(parsed)?;
+
+        if count > 1 {
+            Ok(Some((count, fees)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Sums the maximum storage burn of the operations,
+    /// as every byte of storage they pay for is burned
+    #[inline(never)]
+    fn storage_burn(parsed: &Operation<'_>) -> Result<u64, Error> {
+        let mut ops = *parsed.ops();
+        let mut burn = 0u64;
+
+        while let Some(op) = ops.parse_next().map_err(|_| Error::ParseError)? {
+            bolos::heartbeat();
+
+            if let Some(limit) = op.storage_limit() {
+                let limit = limit.to_u64().map_err(|_| Error::ParseError)?;
+                let cost = limit
+                    .checked_mul(Self::STORAGE_COST_PER_BYTE)
+                    .ok_or(Error::DataInvalid)?;
+                burn = burn.checked_add(cost).ok_or(Error::DataInvalid)?;
+            }
+        }
+
+        Ok(burn)
+    }
+
+    /// Checks if the operations, `size` bytes once forged,
+    /// pay more than the thresholds set by the user:
+    /// * `total`: mutez of fees and storage burn in total
+    /// * `per_byte`: mutez of fees for each byte
+    ///
+    /// A threshold of 0 disables its check
+    fn exceeds_fee_threshold(
+        parsed: &Operation<'_>,
+        size: usize,
+        total: u64,
+        per_byte: u64,
+    ) -> Result<bool, Error> {
+        if total == 0 && per_byte == 0 {
+            return Ok(false);
+        }
+
+        let (_, fees) = Self::count_fees(parsed)?;
+
+        if per_byte != 0 && fees > per_byte.saturating_mul(size as u64) {
+            return Ok(true);
+        }
+
+        if total != 0 {
+            let burn = Self::storage_burn(parsed)?;
+            return Ok(fees.saturating_add(burn) > total);
+        }
+
+        Ok(false)
+    }
+
+    /// Checks if any of the operations reveals a key other than
+    /// the one derived from `path`, which is signing them
+    #[inline(never)]
+    fn reveals_other_key(
+        parsed: &Operation<'_>,
+        path: &BIP32Path<BIP32_MAX_LENGTH>,
+        curve: Curve,
+    ) -> Result<bool, Error> {
+        let mut ops = *parsed.ops();
+        let mut signing_key = None;
+
+        while let Some(op) = ops.parse_next().map_err(|_| Error::ParseError)? {
+            bolos::heartbeat();
+
+            if let OperationType::Reveal(reveal) = op {
+                //only derived if there's something to compare
+                let key = match signing_key {
+                    Some(key) => key,
+                    None => {
+                        let mut key = curve
+                            .to_secret(path)
+                            .and_then(|sk| sk.into_public())
+                            .map_err(|_| Error::ExecutionError)?;
+                        key.compress().map_err(|_| Error::ExecutionError)?;
+
+                        *signing_key.insert(key)
+                    }
+                };
+
+                if !key.matches(*reveal.public_key()) {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Checks that the parameters of all the contract calls
+    /// match the expected expr hash
+    ///
+    /// Returns false if there are no contract calls
+    #[inline(never)]
+    fn check_parameters(parsed: &Operation<'_>, expected: &[u8; 32]) -> Result<bool, Error> {
+        let mut ops = *parsed.ops();
+        let mut found = false;
+
+        while let Some(op) = ops.parse_next().map_err(|_| Error::ParseError)? {
+            bolos::heartbeat();
+
+            if let OperationType::Transfer(tx) = op {
+                if let Some(params) = tx.parameters() {
+                    let hash = params.expr_hash().map_err(|_| Error::ExecutionError)?;
+                    if &hash != expected {
+                        return Ok(false);
+                    }
+
+                    found = true;
+                }
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Locates the part of the operation group in `data` that couldn't be parsed
+    ///
+    /// Returns the offset in `data` of the first byte the parser could tell was invalid,
+    /// or the end of `data` if it was truncated, with the reason it was rejected
+    #[inline(never)]
+    fn locate_parse_failure(data: &[u8]) -> Option<(usize, ParserError)> {
+        let (rem, preemble) = Preemble::from_bytes(data).ok()?;
+        if preemble != Preemble::Operation {
+            return None;
+        }
+
+        let ops_offset = data.len() - rem.len() + 32;
+        let parsed = match Operation::new(rem) {
+            Ok(parsed) => parsed,
+            Err(err) => return Some((data.len(), err)),
+        };
+
+        let tolerant = sys::Settings::expert();
+        let mut ops = *parsed.ops();
+        ops.set_tolerant(tolerant);
+        loop {
+            let op_start = ops.source_index();
+
+            match ops.parse_next().map_err(ParserError::from) {
+                Ok(Some(_)) => bolos::heartbeat(),
+                Ok(None) => return None,
+                Err(ParserError::UnexpectedEof) => {
+                    return Some((data.len(), ParserError::UnexpectedEof))
+                }
+                Err(err) => {
+                    let invalid = Self::first_invalid_byte(&data[ops_offset..], op_start, tolerant);
+                    return Some((ops_offset + invalid, err));
+                }
+            }
+        }
+    }
+
+    /// Finds the first byte of the operation starting at `start` in `ops`
+    /// the parser rejects, by looking for the shortest input that isn't just truncated
+    ///
+    /// The operation is parsed after the preceding ones, in `tolerant` mode
+    /// if the group was (see [`EncodedOperations::set_tolerant`])
+    ///
+    /// Relies on truncated inputs always being reported as [`ParserError::UnexpectedEof`]
+    fn first_invalid_byte(ops: &[u8], start: usize, tolerant: bool) -> usize {
+        let is_invalid = |end: usize| {
+            bolos::heartbeat();
+
+            let mut truncated = EncodedOperations::new(&ops[..end]);
+            truncated.set_tolerant(tolerant);
+            //safe because `start` is where an operation of `ops` begins
+            unsafe { truncated.set_source_index(start) };
+
+            let op = truncated.peek_next();
+            matches!(op.map_err(ParserError::from), Err(err) if err != ParserError::UnexpectedEof)
+        };
+
+        //binary search the shortest invalid input
+        let (mut low, mut high) = (start + 1, ops.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if is_invalid(mid) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        high - 1
+    }
+
+    /// Writes the location of the parse failure in `data`, if any, to `out`
+    ///
+    /// Returns the number of bytes written
+    fn write_parse_failure(data: &[u8], out: &mut [u8]) -> usize {
+        match Self::locate_parse_failure(data) {
+            Some((offset, error)) if out.len() >= Self::PARSE_FAILURE_LEN => {
+                out[..4].copy_from_slice(&(offset as u32).to_be_bytes()[..]);
+                out[4] = error as u8;
+                Self::PARSE_FAILURE_LEN
+            }
+            _ => 0,
+        }
+    }
+
+    /// Reads the optional parameters hash and origin label
+    /// that can follow the derivation path
+    ///
+    /// The origin is prefixed by its length and must be printable ASCII
+    ///
+    /// Anything after the origin is a display hint from the host (ticker, decimals...)
+    /// and is dropped, so what's reviewed only comes from the signed bytes,
+    /// the embedded tables and the origin, which is always shown as such
+    #[allow(clippy::type_complexity)]
+    fn read_extras(
+        extras: &[u8],
+    ) -> Result<
+        (
+            Option<&[u8; 32]>,
+            Option<(usize, [u8; Self::MAX_ORIGIN_LEN])>,
+        ),
+        Error,
+    > {
+        let (expected_params, origin) = if extras.len() >= 32 {
+            let (hash, origin) = extras.split_at(32);
+            (Some(arrayref::array_ref!(hash, 0, 32)), origin)
+        } else {
+            (None, extras)
+        };
+
+        let origin = match origin.split_first() {
+            None => None,
+            Some((&len, label)) => {
+                let len = len as usize;
+                if len == 0 || len > Self::MAX_ORIGIN_LEN || label.len() < len {
+                    return Err(Error::DataInvalid);
+                }
+
+                let (label, hints) = label.split_at(len);
+                if !hints.is_empty() {
+                    crate::sys::zemu_log("Sign: ignoring host display hints\n\x00");
+                }
+
+                if !label.iter().all(|&c| (0x20..0x7F).contains(&c)) {
+                    return Err(Error::DataInvalid);
+                }
+
+                let mut out = [0; Self::MAX_ORIGIN_LEN];
+                out[..len].copy_from_slice(label);
+                Some((len, out))
+            }
+        };
+
+        Ok((expected_params, origin))
+    }
+
     #[inline(never)]
     pub fn start_sign(
         send_hash: bool,
@@ -82,34 +408,97 @@ impl Sign {
         data: &'static [u8],
         flags: &mut u32,
     ) -> Result<u32, Error> {
+        let ui = Self::prepare_review(send_hash, p2, init_data, data)?;
+
+        #[cfg(feature = "dev")]
+        crate::handlers::dev::metrics::review_started();
+
+        unsafe { ui.show(flags) }
+            .map_err(|_| Error::ExecutionError)
+            .map(|_| 0)
+    }
+
+    /// Parses and checks the signing request, preparing its review
+    #[inline(never)]
+    fn prepare_review(
+        send_hash: bool,
+        p2: u8,
+        init_data: &[u8],
+        data: &'static [u8],
+    ) -> Result<SignUI<'static>, Error> {
         let curve = Curve::try_from(p2).map_err(|_| Error::InvalidP1P2)?;
-        let path =
-            BIP32Path::<BIP32_MAX_LENGTH>::read(init_data).map_err(|_| Error::DataInvalid)?;
+
+        //the path can optionally be followed by the expected parameters hash
+        // and by the origin label
+        let path_len = 1 + 4 * *init_data.first().ok_or(Error::DataInvalid)? as usize;
+        let (path, extras) = init_data
+            .get(..path_len)
+            .map(|path| (path, &init_data[path_len..]))
+            .ok_or(Error::DataInvalid)?;
+        let path = BIP32Path::<BIP32_MAX_LENGTH>::read(path).map_err(|_| Error::DataInvalid)?;
+        let (expected_params, origin) = Self::read_extras(extras)?;
+
+        let (rem, preemble) = Preemble::from_bytes(data).map_err(|_| Error::ParseError)?;
+        //consensus operations are never signed here, as they would be without watermark
+        if preemble.is_consensus() {
+            return Err(Error::ConsensusNotAllowed);
+        }
 
         unsafe {
             PATH.replace((path, curve));
         }
 
         let unsigned_hash = Self::blake2b_digest(data)?;
-        let (rem, preemble) = Preemble::from_bytes(data).map_err(|_| Error::DataInvalid)?;
 
         let mut ui = SignUI {
             hash: unsigned_hash,
             send_hash,
+            data,
             parsed: None,
+            params_check: None,
+            origin,
+            other_key_revealed: false,
+            high_fee: false,
+            summary: None,
+            expert: sys::Settings::expert(),
+            raw: None,
         };
 
         match preemble {
             Preemble::Operation => {
-                ui.parsed = Some(Operation::new(rem).map_err(|_| Error::DataInvalid)?)
+                let mut parsed = Operation::new(rem).map_err(|_| Error::ParseError)?;
+                //let newer protocols add fields without requiring blind signing
+                parsed.mut_ops().set_tolerant(sys::Settings::expert());
+
+                if !sys::Settings::expert() && Self::requires_expert(&parsed)? {
+                    return Err(Error::CommandNotAllowed);
+                }
+
+                if let Some(expected) = expected_params {
+                    ui.params_check = Some(Self::check_parameters(&parsed, expected)?);
+                }
+
+                ui.other_key_revealed = Self::reveals_other_key(&parsed, &path, curve)?;
+                ui.high_fee = Self::exceeds_fee_threshold(
+                    &parsed,
+                    rem.len(),
+                    sys::Settings::fee_threshold(),
+                    sys::Settings::fee_per_byte_threshold(),
+                )?;
+                ui.summary = Self::batch_summary(&parsed)?;
+
+                //let auditors see every signed byte, not just the parsed fields
+                if sys::Settings::expert() {
+                    ui.raw = Some(data);
+                }
+
+                ui.parsed = Some(parsed)
             }
             Preemble::Michelson => {}
             _ => return Err(Error::CommandNotAllowed),
         };
 
-        unsafe { ui.show(flags) }
-            .map_err(|_| Error::ExecutionError)
-            .map(|_| 0)
+        Ok(ui)
     }
 }
 
@@ -125,39 +514,190 @@ impl ApduHandler for Sign {
         *tx = 0;
 
         if let Some(upload) = Uploader::new(Self).upload(&buffer)? {
-            *tx = Self::start_sign(true, upload.p2, upload.first, upload.data, flags)?;
+            let data = upload.data;
+
+            match Self::start_sign(true, upload.p2, upload.first, data, flags) {
+                Ok(len) => *tx = len,
+                //tell the host which part of the operation was rejected
+                Err(Error::ParseError) => {
+                    *tx = Self::write_parse_failure(data, buffer.write()) as u32;
+                    return Err(Error::ParseError);
+                }
+                Err(err) => return Err(err),
+            }
         }
 
         Ok(())
     }
 }
 
-pub(crate) struct SignUI {
+pub(crate) struct SignUI<'b> {
     hash: [u8; Sign::SIGN_HASH_SIZE],
     send_hash: bool,
-    parsed: Option<Operation<'static>>,
+    //whole signed payload, parsed again before signing
+    data: &'b [u8],
+    parsed: Option<Operation<'b>>,
+    //result of the check against the host-provided parameters hash, if any
+    params_check: Option<bool>,
+    //label of the application requesting the signature, if any
+    origin: Option<(usize, [u8; Sign::MAX_ORIGIN_LEN])>,
+    //an operation reveals a key other than the signing one
+    other_key_revealed: bool,
+    //the fees exceed the threshold set by the user
+    high_fee: bool,
+    //number of operations and total fee, when there's more than one operation
+    summary: Option<(usize, u64)>,
+    //secondary details of the operations are shown, like the gas limit
+    expert: bool,
+    //whole signed payload, hex dumped after the operations in expert mode
+    raw: Option<&'b [u8]>,
 }
 
-#[cfg(test)]
-impl Operation<'static> {
-    pub(crate) fn to_sign_ui(self) -> SignUI {
-        SignUI {
+#[cfg(any(test, feature = "std"))]
+impl<'b> Operation<'b> {
+    /// Prepares the review of the operation,
+    /// without the checks depending on the signing request
+    pub(crate) fn try_to_sign_ui(self, expert: bool) -> Result<SignUI<'b>, Error> {
+        Ok(SignUI {
             hash: [0; Sign::SIGN_HASH_SIZE],
             send_hash: false,
+            data: &[],
             parsed: Some(self),
-        }
+            params_check: None,
+            origin: None,
+            other_key_revealed: false,
+            high_fee: false,
+            summary: Sign::batch_summary(&self)?,
+            expert,
+            raw: None,
+        })
+    }
+
+    /// Prepares the review of the operation, showing every item
+    #[cfg(test)]
+    pub(crate) fn to_sign_ui(self) -> SignUI<'b> {
+        self.try_to_sign_ui(true)
+            .expect("couldn't summarize operations")
     }
 }
 
-impl SignUI {
+impl<'b> SignUI<'b> {
+    /// Checks that the buffered payload is still the one that was hashed,
+    /// as the review parses it again for every item
+    #[inline(never)]
+    fn check_payload(&self) -> Result<(), Error> {
+        if Sign::blake2b_digest(self.data)? == self.hash {
+            Ok(())
+        } else {
+            Err(Error::DataInvalid)
+        }
+    }
+
+    /// Number of items used to show the raw payload
+    fn raw_items(&self) -> usize {
+        self.raw
+            .map(|raw| raw.len().div_ceil(Sign::RAW_BYTES_PER_ITEM))
+            .unwrap_or_default()
+    }
+
+    #[inline(never)]
+    fn render_raw(
+        raw: &[u8],
+        idx: usize,
+        n_items: usize,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use lexical_core::{write as itoa, Number};
+
+        let chunk = raw
+            .chunks(Sign::RAW_BYTES_PER_ITEM)
+            .nth(idx)
+            .ok_or(ViewError::NoData)?;
+
+        let title_content = pic_str!(b"Raw Data "!);
+        let mut len = title_content.len();
+        title[..len].copy_from_slice(title_content);
+        //at most 32 items, so they fit in a u8 (and in the title)
+        len += itoa(
+            (idx + 1) as u8,
+            &mut title[len..len + u8::FORMATTED_SIZE_DECIMAL],
+        )
+        .len();
+        title[len] = b'/';
+        len += 1;
+        len += itoa(
+            n_items as u8,
+            &mut title[len..len + u8::FORMATTED_SIZE_DECIMAL],
+        )
+        .len();
+        title[len] = 0;
+
+        let mut hex_buf = [0; Sign::RAW_BYTES_PER_ITEM * 2];
+        //chunks are never bigger than the buffer
+        hex::encode_to_slice(chunk, &mut hex_buf[..chunk.len() * 2]).apdu_unwrap();
+
+        handle_ui_message(&hex_buf[..chunk.len() * 2], message, page)
+    }
+
+    /// Index of the revealed key warning, after the branch and parameters check
+    fn other_key_item(&self) -> u8 {
+        1 + self.params_check.is_some() as u8
+    }
+
+    /// Index of the fee warning, after the other warnings
+    fn high_fee_item(&self) -> u8 {
+        self.other_key_item() + self.other_key_revealed as u8
+    }
+
+    /// Index of the summary, after all the warnings
+    fn summary_item(&self) -> u8 {
+        self.high_fee_item() + self.high_fee as u8
+    }
+
+    /// Number of items shown before the operations, after the origin:
+    /// branch, parameters check, warnings and summary
+    fn header_items(&self) -> u8 {
+        self.summary_item() + self.summary.is_some() as u8
+    }
+
+    #[inline(never)]
+    fn render_summary(
+        count: usize,
+        fees: u64,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use lexical_core::{write as itoa, Number};
+
+        let title_content = pic_str!(b"Summary");
+        title[..title_content.len()].copy_from_slice(title_content);
+
+        let mut mex = [0; usize::FORMATTED_SIZE_DECIMAL + 23 + Zarith::MUTEZ_FORMATTED_SIZE];
+        let mut len = itoa(count, &mut mex[..]).len();
+
+        let separator = pic_str!(b" operations, total fee "!);
+        mex[len..len + separator.len()].copy_from_slice(separator);
+        len += separator.len();
+
+        let mut fee_buf = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+        let fees = Zarith::format_mutez(fees, &mut fee_buf).ok_or(ViewError::Unknown)?;
+        mex[len..len + fees.len()].copy_from_slice(fees);
+        len += fees.len();
+
+        handle_ui_message(&mex[..len], message, page)
+    }
+
     // Will find the operation that contains said item, as well as
     // return the index of the item in the operation
     fn find_op_with_item(
         &self,
         mut item_idx: u8,
-        op: &mut MaybeUninit<OperationType<'static>>,
+        op: &mut MaybeUninit<OperationType<'b>>,
     ) -> Result<Option<u8>, ViewError> {
-        item_idx -= 1; //remove branch idx
+        item_idx -= self.header_items(); //remove branch, checks and summary
 
         //we shouldn't be here if parsed is None
         let mut parsed = self.parsed.ok_or(ViewError::Unknown)?;
@@ -178,12 +718,15 @@ impl SignUI {
                     None => unreachable_unchecked(),
                 }
             };
-            let n = op_ref.ui_items() as u8;
+            let n = op_ref.num_shown_items(self.expert) as u8;
 
             if n > item_idx {
                 //we return the remaining item_idx so we can navigate to it
                 // we don't want to drop `op` here so it can be used by the caller!!!
-                return Ok(Some(item_idx));
+                return op_ref
+                    .shown_item(item_idx, self.expert)
+                    .map(Some)
+                    .ok_or(ViewError::Unknown);
             } else {
                 //decrease item_idx by n items and check next operation
                 item_idx -= n;
@@ -200,14 +743,18 @@ impl SignUI {
     }
 }
 
-impl Viewable for SignUI {
+impl Viewable for SignUI<'_> {
     fn num_items(&mut self) -> Result<u8, ViewError> {
+        let origin_items = self.origin.is_some() as u8;
+
         match self.parsed {
-            None => Ok(1),
+            None => Ok(origin_items + 1),
             Some(mut parsed) => {
                 let ops = parsed.mut_ops();
 
-                let mut items_counter = 1; //start with branch
+                //start with branch, checks and summary
+                let mut items_counter = self.header_items() as usize;
+                let initial_items = items_counter;
                 let mut op = MaybeUninit::uninit();
 
                 while ops
@@ -219,7 +766,7 @@ impl Viewable for SignUI {
                     //safe because the pointer is valid and we have initialized this
                     // also, we are the only ones with access at this point
                     let op_ref = unsafe { op.as_mut().unwrap() };
-                    items_counter += op_ref.ui_items();
+                    items_counter += op_ref.num_shown_items(self.expert);
 
                     //this is safe to drop because
                     // pointer is valid, aligned
@@ -228,7 +775,7 @@ impl Viewable for SignUI {
                     unsafe { op.drop_in_place() }
                 }
 
-                if items_counter > 1 {
+                if items_counter > initial_items {
                     //this means we have parsed at least once and have reached the end
                     // so we need to drop this manually
                     //This IS initialized (from the last loop)
@@ -237,7 +784,7 @@ impl Viewable for SignUI {
                     unsafe { op.as_mut_ptr().drop_in_place() }
                 }
 
-                Ok(items_counter as u8)
+                Ok(origin_items + (items_counter + self.raw_items()) as u8)
             }
         }
     }
@@ -252,6 +799,18 @@ impl Viewable for SignUI {
     ) -> Result<u8, ViewError> {
         let mut op = MaybeUninit::uninit();
 
+        //the origin is always shown first
+        let item_n = match (item_n, self.origin) {
+            (0, Some((len, origin))) => {
+                let title_content = pic_str!(b"Origin");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                return handle_ui_message(&origin[..len], message, page);
+            }
+            (n, Some(_)) => n - 1,
+            (n, None) => n,
+        };
+
         match self.parsed {
             None => match item_n {
                 0 => {
@@ -273,41 +832,47 @@ impl Viewable for SignUI {
 
                     let (len, mex) = parsed.get_base58_branch().map_err(|_| ViewError::Unknown)?;
                     handle_ui_message(&mex[..len], message, page)
+                } else if let (1, Some(matches)) = (item_n, self.params_check) {
+                    let title_content = pic_str!(b"Parameters Check");
+                    title[..title_content.len()].copy_from_slice(title_content);
+
+                    let mex = if matches {
+                        pic_str!("Match")
+                    } else {
+                        pic_str!("MISMATCH")
+                    };
+
+                    handle_ui_message(mex.as_bytes(), message, page)
+                } else if self.other_key_revealed && item_n == self.other_key_item() {
+                    let title_content = pic_str!(b"Warning");
+                    title[..title_content.len()].copy_from_slice(title_content);
+
+                    let mex = pic_str!(b"Revealed key is not the signing key");
+                    handle_ui_message(&mex[..], message, page)
+                } else if self.high_fee && item_n == self.high_fee_item() {
+                    let title_content = pic_str!(b"Warning");
+                    title[..title_content.len()].copy_from_slice(title_content);
+
+                    let mex = pic_str!(b"High fee!");
+                    handle_ui_message(&mex[..], message, page)
+                } else if let (true, Some((count, fees))) =
+                    (item_n == self.summary_item(), self.summary)
+                {
+                    Self::render_summary(count, fees, title, message, page)
                 } else if let Some(item_n) = self.find_op_with_item(item_n, &mut op)? {
                     //this is safe as we have initialized `op` in `self.find_op_with_item`
                     let op = unsafe { op.assume_init() };
-                    match op {
-                        OperationType::Transfer(tx) => tx.render_item(item_n, title, message, page),
-                        OperationType::Delegation(delegation) => {
-                            delegation.render_item(item_n, title, message, page)
-                        }
-                        OperationType::Endorsement(endorsement) => {
-                            endorsement.render_item(item_n, title, message, page)
-                        }
-                        OperationType::EndorsementWithSlot(endorsement) => {
-                            endorsement.render_item(item_n, title, message, page)
-                        }
-                        OperationType::Ballot(vote) => {
-                            vote.render_item(item_n, title, message, page)
-                        }
-                        OperationType::Reveal(rev) => rev.render_item(item_n, title, message, page),
-                        OperationType::Proposals(props) => {
-                            props.render_item(item_n, title, message, page)
-                        }
-                        OperationType::Origination(orig) => {
-                            orig.render_item(item_n, title, message, page)
-                        }
-                        OperationType::ActivateAccount(act) => {
-                            act.render_item(item_n, title, message, page)
-                        }
-                        OperationType::FailingNoop(fail) => {
-                            fail.render_item(item_n, title, message, page)
-                        }
-                        OperationType::UnknownOp(unk) => {
-                            OperationType::render_unknown(unk, item_n, title, message, page)
-                        }
-                        OperationType::AnonymousOp(_) => Err(ViewError::Reject),
-                    }
+                    op.render_item(item_n, title, message, page)
+                } else if let Some(raw) = self.raw {
+                    //the raw data is shown after all the operations
+                    let n_raw = self.raw_items();
+                    let first_raw =
+                        self.num_items()? as usize - self.origin.is_some() as usize - n_raw;
+                    let idx = (item_n as usize)
+                        .checked_sub(first_raw)
+                        .ok_or(ViewError::NoData)?;
+
+                    Self::render_raw(raw, idx, n_raw, title, message, page)
                 } else {
                     Err(ViewError::NoData)
                 }
@@ -315,14 +880,51 @@ impl Viewable for SignUI {
         }
     }
 
+    fn next_section(&mut self, item_n: u8) -> Option<u8> {
+        let mut parsed = self.parsed?;
+        let ops = parsed.mut_ops();
+
+        //each operation is a section, starting after origin, branch, checks and summary
+        let mut start = self.origin.is_some() as usize + self.header_items() as usize;
+        let mut op = MaybeUninit::uninit();
+
+        while ops.parse_next_into(&mut op).ok()?.is_some() {
+            let op = op.as_mut_ptr();
+            //safe because the pointer is valid and we have initialized this
+            // also, we are the only ones with access at this point
+            let n = unsafe { op.as_mut()? }.num_shown_items(self.expert);
+
+            //pointer is valid, aligned and initialized
+            // and we will be writing to this location before reading again
+            unsafe { op.drop_in_place() }
+
+            if start > item_n as usize {
+                return Some(start as u8);
+            }
+            start += n;
+        }
+
+        None
+    }
+
     fn accept(&mut self, out: &mut [u8]) -> (usize, u16) {
-        let (path, curve) = match Sign::get_derivation_info() {
-            Err(e) => return (0, e as _),
-            Ok(k) => k,
-        };
+        #[cfg(feature = "dev")]
+        crate::handlers::dev::metrics::reviewed();
+
+        //refuse to sign if the buffer was modified since the payload was hashed
+        let signed = self.check_payload().and_then(|_| {
+            let (path, curve) = Sign::get_derivation_info()?;
+            let signed = Sign::sign(*curve, path, &self.hash[..])?;
+            SignatureCounter::increment()?;
 
-        let (sig_size, sig) = match Sign::sign(*curve, path, &self.hash[..]) {
-            Err(e) => return (0, e as _),
+            Ok(signed)
+        });
+
+        let (sig_size, sig) = match signed {
+            Err(e) => {
+                let _ = cleanup_globals();
+                return (0, e as _);
+            }
             Ok(k) => k,
         };
 
@@ -347,9 +949,16 @@ impl Viewable for SignUI {
     }
 
     fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
-        let _ = cleanup_globals();
+        Self::cleanup();
         (0, Error::CommandNotAllowed as _)
     }
+
+    fn cleanup() {
+        #[cfg(feature = "dev")]
+        crate::handlers::dev::metrics::aborted();
+
+        let _ = cleanup_globals();
+    }
 }
 
 fn cleanup_globals() -> Result<(), Error> {
@@ -417,4 +1026,734 @@ mod tests {
         let expected = Blake2b::<32>::digest(MSG).unwrap();
         assert_eq!(&expected, out_hash);
     }
+
+    /// Sets expert mode until dropped, when the previous setting is restored
+    /// so it doesn't leak into other tests, even if the test fails
+    struct ExpertMode(bool);
+
+    impl ExpertMode {
+        fn set(expert: bool) -> Self {
+            let previous = sys::Settings::expert();
+            sys::Settings::set_expert(expert);
+
+            Self(previous)
+        }
+    }
+
+    impl Drop for ExpertMode {
+        fn drop(&mut self) {
+            sys::Settings::set_expert(self.0);
+        }
+    }
+
+    #[test]
+    #[serial(ui)]
+    fn apdu_sign_dangerous_entrypoint() {
+        const OP_HEX: &str = "03\
+                              a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                              6c\
+                              0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                              904e\
+                              01\
+                              0a\
+                              0a\
+                              e807\
+                              000035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                              ff\
+                              ff097365745f61646d696e\
+                              00000002030b";
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+
+        let sign = |flags: &mut u32, tx: &mut u32, buffer: &mut [u8; 260]| {
+            buffer[0] = CLA;
+            buffer[1] = INS_SIGN;
+            buffer[2] = ZPacketType::Init.into();
+            let len = prepare_buffer(buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+
+            handle_apdu(flags, tx, 5 + len as u32, buffer);
+            assert_error_code!(*tx, buffer, Error::Success);
+
+            buffer[0] = CLA;
+            buffer[1] = INS_SIGN;
+            buffer[2] = ZPacketType::Last.into();
+            buffer[3] = 0;
+            buffer[4] = op.len() as u8;
+            buffer[5..5 + op.len()].copy_from_slice(&op);
+
+            handle_apdu(flags, tx, 5 + op.len() as u32, buffer);
+        };
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        let _expert = ExpertMode::set(false);
+        sign(&mut flags, &mut tx, &mut buffer);
+        assert_error_code!(tx, buffer, Error::CommandNotAllowed);
+
+        let _expert = ExpertMode::set(true);
+        sign(&mut flags, &mut tx, &mut buffer);
+
+        let (len, out) = get_out().expect("UI mock used");
+        assert_error_code!(len, out, Error::Success);
+    }
+
+    #[test]
+    #[serial(ui)]
+    fn apdu_sign_consensus() {
+        //tenderbake endorsement
+        const OP_HEX: &str = "13\
+                              7a06a770\
+                              a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                              15\
+                              0000\
+                              00000001\
+                              00000000\
+                              a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561";
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        buffer[0] = CLA;
+        buffer[1] = INS_SIGN;
+        buffer[2] = ZPacketType::Init.into();
+        let len = prepare_buffer(&mut buffer, &[44, 1729, 0, 0], Curve::Ed25519);
+
+        handle_apdu(&mut flags, &mut tx, 5 + len as u32, &mut buffer);
+        assert_error_code!(tx, buffer, Error::Success);
+
+        buffer[0] = CLA;
+        buffer[1] = INS_SIGN;
+        buffer[2] = ZPacketType::Last.into();
+        buffer[3] = 0;
+        buffer[4] = op.len() as u8;
+        buffer[5..5 + op.len()].copy_from_slice(&op);
+
+        handle_apdu(&mut flags, &mut tx, 5 + op.len() as u32, &mut buffer);
+        assert_error_code!(tx, buffer, Error::ConsensusNotAllowed);
+        assert!(get_out().is_none());
+    }
+
+    #[test]
+    #[serial(ui)]
+    fn apdu_sign_parse_failure() {
+        const TRANSFER_HEX: &str = "6c\
+                                    0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                    904e\
+                                    01\
+                                    0a\
+                                    0a\
+                                    e807\
+                                    000035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                    ff\
+                                    00\
+                                    00000002030b";
+        //offset of the destination in the transfer
+        const DESTINATION: usize = 29;
+        let transfer = hex::decode(TRANSFER_HEX).expect("invalid input hex");
+
+        let mut op =
+            hex::decode("03a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561")
+                .expect("invalid input hex");
+        let second = op.len() + transfer.len();
+        op.extend_from_slice(&transfer);
+        op.extend_from_slice(&transfer);
+
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+            .unwrap()
+            .serialize();
+        let curve: u8 = Curve::Ed25519.into();
+
+        let mut fixture = crate::utils::ApduFixture::new();
+
+        //truncated in the middle of the second transfer
+        let details = fixture
+            .send_chunks(INS_SIGN, curve, &path, &op[..second + 2])
+            .assert_code(Error::ParseError);
+        assert_eq!(details.len(), Sign::PARSE_FAILURE_LEN);
+        assert_eq!(&details[..4], &(second as u32 + 2).to_be_bytes()[..]);
+        assert_eq!(details[4], ParserError::UnexpectedEof as u8);
+
+        //truncated branch
+        let details = fixture
+            .send_chunks(INS_SIGN, curve, &path, &op[..20])
+            .assert_code(Error::ParseError);
+        assert_eq!(&details[..4], &20u32.to_be_bytes()[..]);
+        assert_eq!(details[4], ParserError::UnexpectedEof as u8);
+
+        //invalid destination tag in the second transfer,
+        // the same field is reported when newer fields are tolerated
+        op[second + DESTINATION] = 0x05;
+        for expert in [false, true] {
+            let _expert = ExpertMode::set(expert);
+
+            let details = fixture
+                .send_chunks(INS_SIGN, curve, &path, &op)
+                .assert_code(Error::ParseError);
+            assert_eq!(
+                &details[..4],
+                &(second as u32 + DESTINATION as u32).to_be_bytes()[..]
+            );
+            assert_ne!(details[4], ParserError::UnexpectedEof as u8);
+        }
+        assert!(get_out().is_none());
+
+        //fields added by a newer protocol before the invalid transfer
+        // are only skipped when they are tolerated
+        let fields = hex::decode("00000002cafe").expect("invalid input hex");
+        let mut op = op[..second].to_vec();
+        op.extend_from_slice(&fields);
+        let third = op.len();
+        op.extend_from_slice(&transfer);
+        op[third + DESTINATION] = 0x05;
+
+        for (expert, invalid) in [(false, second + 5), (true, third + DESTINATION)] {
+            let _expert = ExpertMode::set(expert);
+
+            let details = fixture
+                .send_chunks(INS_SIGN, curve, &path, &op)
+                .assert_code(Error::ParseError);
+            assert_eq!(&details[..4], &(invalid as u32).to_be_bytes()[..]);
+            assert_ne!(details[4], ParserError::UnexpectedEof as u8);
+        }
+        assert!(get_out().is_none());
+    }
+
+    #[test]
+    #[serial(ui)]
+    fn apdu_sign_oversized() {
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+            .unwrap()
+            .serialize();
+        let curve: u8 = Curve::Ed25519.into();
+
+        //the whole upload (p2 and first packet included) has to fit in the buffer
+        let mut op = std::vec![0; 0x1FFF - 1 - path.len() + 1];
+        op[0] = 0x03;
+
+        let _ = get_out();
+        crate::utils::ApduFixture::new()
+            .send_chunks(INS_SIGN, curve, &path, &op)
+            .assert_code(Error::DataInvalid);
+        assert!(get_out().is_none());
+    }
+
+    #[test]
+    fn parameters_check() {
+        const OP_HEX: &str = "a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                              6c\
+                              0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                              904e\
+                              01\
+                              0a\
+                              0a\
+                              e807\
+                              000035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                              ff\
+                              00\
+                              00000002030b";
+        //expr hash of `Unit`
+        const UNIT_HASH: &str = "8b456a4530fb6d0fea9a0dcd0e9d6ff6b30d13b05d05cb4a375dd91bf2a7eadc";
+
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+        //leak to obtain 'static, it's fine in tests
+        let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+        let parsed = Operation::new(op).expect("couldn't parse operation");
+
+        let expected: [u8; 32] = hex::decode(UNIT_HASH).unwrap().try_into().unwrap();
+        assert!(Sign::check_parameters(&parsed, &expected).unwrap());
+        assert!(!Sign::check_parameters(&parsed, &[0; 32]).unwrap());
+
+        let mut ui = parsed.to_sign_ui();
+        let items = ui.num_items().unwrap();
+        ui.params_check = Some(false);
+        assert_eq!(ui.num_items().unwrap(), items + 1);
+
+        let mut title = [0; 32];
+        let mut message = [0; 32];
+        ui.render_item(1, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Parameters Check"));
+        assert!(message.starts_with(b"MISMATCH\x00"));
+
+        //the operation's items come after the check
+        ui.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Type"));
+    }
+
+    #[test]
+    fn origin_label() {
+        const LABEL: &[u8] = b"My dApp";
+
+        let mut extras = std::vec![LABEL.len() as u8];
+        extras.extend_from_slice(LABEL);
+
+        let (params, origin) = Sign::read_extras(&extras).unwrap();
+        assert!(params.is_none());
+        let (len, label) = origin.unwrap();
+        assert_eq!(&label[..len], LABEL);
+
+        //after the parameters hash
+        let mut with_hash = std::vec![0xAA; 32];
+        with_hash.extend_from_slice(&extras);
+        let (params, origin) = Sign::read_extras(&with_hash).unwrap();
+        assert_eq!(params, Some(&[0xAA; 32]));
+        assert!(origin.is_some());
+
+        //host display hints are dropped
+        let mut with_hints = extras.clone();
+        with_hints.extend_from_slice(b"\x03tzBTC\x08");
+        let (params, hinted) = Sign::read_extras(&with_hints).unwrap();
+        assert!(params.is_none());
+        let (hinted_len, hinted) = hinted.unwrap();
+        assert_eq!(&hinted[..hinted_len], LABEL);
+
+        //empty, wrong length, too long and non printable labels
+        for bad in [
+            &[0][..],
+            &[LABEL.len() as u8 + 1, b'a', b'b'][..],
+            &[Sign::MAX_ORIGIN_LEN as u8 + 1; Sign::MAX_ORIGIN_LEN + 2][..],
+            &[2, b'a', b'\n'][..],
+        ] {
+            assert_eq!(Sign::read_extras(bad).unwrap_err(), Error::DataInvalid);
+        }
+
+        const OP_HEX: &str = "a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                              6e\
+                              0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                              904e\
+                              01\
+                              0a\
+                              0a\
+                              00";
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+        //leak to obtain 'static, it's fine in tests
+        let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+        let parsed = Operation::new(op).expect("couldn't parse operation");
+
+        let mut ui = parsed.to_sign_ui();
+        let items = ui.num_items().unwrap();
+        ui.origin = Some((len, label));
+        assert_eq!(ui.num_items().unwrap(), items + 1);
+
+        let mut title = [0; 32];
+        let mut message = [0; 32];
+        ui.render_item(0, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Origin"));
+        assert!(message.starts_with(b"My dApp\x00"));
+
+        //everything else is shifted by one
+        ui.render_item(1, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Operation"));
+    }
+
+    #[test]
+    fn sections() {
+        const DELEGATION_HEX: &str = "6e\
+                                      0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                      904e\
+                                      01\
+                                      0a\
+                                      0a\
+                                      00";
+        let delegation = hex::decode(DELEGATION_HEX).expect("invalid input hex");
+
+        let mut op =
+            hex::decode("a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561")
+                .expect("invalid input hex");
+        op.extend_from_slice(&delegation);
+        op.extend_from_slice(&delegation);
+        //leak to obtain 'static, it's fine in tests
+        let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+        let parsed = Operation::new(op).expect("couldn't parse operation");
+
+        let mut ui = parsed.to_sign_ui();
+        let items = ui.num_items().unwrap();
+        //branch + summary + 2 operations of the same size
+        let per_op = (items - 2) / 2;
+
+        assert_eq!(ui.next_section(0), Some(2));
+        assert_eq!(ui.next_section(2), Some(2 + per_op));
+        assert_eq!(ui.next_section(1 + per_op), Some(2 + per_op));
+        assert_eq!(ui.next_section(2 + per_op), None);
+
+        //sections are shifted by the origin
+        ui.origin = Some((1, [b'a'; Sign::MAX_ORIGIN_LEN]));
+        assert_eq!(ui.next_section(0), Some(3));
+        assert_eq!(ui.next_section(3), Some(3 + per_op));
+    }
+
+    #[test]
+    fn reveal_other_key() {
+        const REVEAL_HEX: &str = "6b\
+                                  0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                  904e\
+                                  01\
+                                  0a\
+                                  0a\
+                                  00";
+
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        let mut key = Curve::Ed25519
+            .to_secret(&path)
+            .and_then(|sk| sk.into_public())
+            .expect("couldn't derive key");
+        key.compress().expect("couldn't compress key");
+        let key = key.as_ref();
+
+        let reveal_of = |pk: &[u8]| {
+            let mut op =
+                hex::decode("a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561")
+                    .expect("invalid input hex");
+            op.extend_from_slice(&hex::decode(REVEAL_HEX).expect("invalid input hex"));
+            op.extend_from_slice(pk);
+
+            //leak to obtain 'static, it's fine in tests
+            let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+            Operation::new(op).expect("couldn't parse operation")
+        };
+
+        let own = reveal_of(&key[key.len() - 32..]);
+        assert_eq!(
+            Sign::reveals_other_key(&own, &path, Curve::Ed25519),
+            Ok(false)
+        );
+
+        let other = reveal_of(&[0x42; 32]);
+        assert_eq!(
+            Sign::reveals_other_key(&other, &path, Curve::Ed25519),
+            Ok(true)
+        );
+        //same key, but signing with another curve
+        assert_eq!(
+            Sign::reveals_other_key(&own, &path, Curve::Secp256K1),
+            Ok(true)
+        );
+
+        //the warning is shown right after the branch
+        let mut ui = other.to_sign_ui();
+        ui.other_key_revealed = true;
+        let mut title = [0; 32];
+        let mut message = [0; 64];
+        ui.render_item(1, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Warning\x00"));
+        assert!(message.starts_with(b"Revealed key is not the signing key\x00"));
+
+        ui.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(!title.starts_with(b"Warning"));
+    }
+
+    #[test]
+    fn high_fee() {
+        const DELEGATION_HEX: &str = "6e\
+                                      0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                      904e\
+                                      01\
+                                      0a\
+                                      0a\
+                                      00";
+        let delegation = hex::decode(DELEGATION_HEX).expect("invalid input hex");
+
+        let mut op =
+            hex::decode("a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561")
+                .expect("invalid input hex");
+        op.extend_from_slice(&delegation);
+        op.extend_from_slice(&delegation);
+        //leak to obtain 'static, it's fine in tests
+        let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+        let parsed = Operation::new(op).expect("couldn't parse operation");
+
+        let size = op.len();
+        //no threshold set
+        assert_eq!(Sign::exceeds_fee_threshold(&parsed, size, 0, 0), Ok(false));
+        //the total of both fees and storage burns is checked, not the single fees
+        let total = 2 * 10000 + 2 * 10 * Sign::STORAGE_COST_PER_BYTE;
+        assert_eq!(
+            Sign::exceeds_fee_threshold(&parsed, size, total, 0),
+            Ok(false)
+        );
+        assert_eq!(
+            Sign::exceeds_fee_threshold(&parsed, size, total - 1, 0),
+            Ok(true)
+        );
+        //fees only, against the size of the forged operations
+        let per_byte = (2 * 10000u64).div_ceil(size as u64);
+        assert_eq!(
+            Sign::exceeds_fee_threshold(&parsed, size, 0, per_byte),
+            Ok(false)
+        );
+        assert_eq!(
+            Sign::exceeds_fee_threshold(&parsed, size, 0, per_byte - 1),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    #[serial(ui)]
+    fn high_fee_review() {
+        use crate::{dispatcher::INS_SET_FEE_THRESHOLD, utils::ApduFixture};
+
+        const OP_HEX: &str = "03\
+                              a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                              6e\
+                              0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                              904e\
+                              01\
+                              0a\
+                              0a\
+                              00";
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+        //leak to obtain 'static, it's fine in tests
+        let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+            .unwrap()
+            .serialize();
+
+        let set_threshold = |total: u64, per_byte: u64| {
+            let mut payload = total.to_be_bytes().to_vec();
+            payload.extend_from_slice(&per_byte.to_be_bytes());
+
+            ApduFixture::new()
+                .send(INS_SET_FEE_THRESHOLD, 0, 0, &payload)
+                .assert_success();
+            let (_, out) = get_out().expect("UI mock used");
+            assert_eq!(&out[..2], &(Error::Success as u16).to_be_bytes()[..]);
+        };
+        let review = || {
+            let mut ui = Sign::prepare_review(true, Curve::Ed25519.into(), &path, op)
+                .expect("couldn't prepare review");
+            let items = ui.num_items().unwrap();
+
+            (0..items)
+                .map(|item_n| {
+                    let mut title = [0; 32];
+                    let mut message = [0; 64];
+                    ui.render_item(item_n, &mut title, &mut message, 0)
+                        .expect("couldn't render item");
+                    (title, message)
+                })
+                .collect::<std::vec::Vec<_>>()
+        };
+
+        //storage burn included
+        set_threshold(10000 + 10 * Sign::STORAGE_COST_PER_BYTE - 1, 0);
+        let items = review();
+        //shown right after the branch
+        assert!(items[1].0.starts_with(b"Warning\x00"));
+        assert!(items[1].1.starts_with(b"High fee!\x00"));
+        assert!(items[2].0.starts_with(b"Type\x00"));
+
+        set_threshold(10000 + 10 * Sign::STORAGE_COST_PER_BYTE, 0);
+        assert!(review()
+            .iter()
+            .all(|(_, message)| !message.starts_with(b"High fee!")));
+
+        set_threshold(0, 1);
+        assert!(review()
+            .iter()
+            .any(|(_, message)| message.starts_with(b"High fee!\x00")));
+
+        set_threshold(0, 0);
+        cleanup_globals().unwrap();
+    }
+
+    #[test]
+    fn expert_items() {
+        const DELEGATION_HEX: &str = "6e\
+                                      0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                      904e\
+                                      01\
+                                      0a\
+                                      0a\
+                                      00";
+        let delegation = hex::decode(DELEGATION_HEX).expect("invalid input hex");
+
+        let mut op =
+            hex::decode("a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561")
+                .expect("invalid input hex");
+        op.extend_from_slice(&delegation);
+        op.extend_from_slice(&delegation);
+        //leak to obtain 'static, it's fine in tests
+        let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+        let parsed = Operation::new(op).expect("couldn't parse operation");
+
+        let mut expert = parsed.to_sign_ui();
+        let mut normal = parsed
+            .try_to_sign_ui(false)
+            .expect("couldn't prepare review");
+
+        //gas limit, storage limit and counter of both operations
+        assert_eq!(
+            normal.num_items().unwrap() + 2 * 3,
+            expert.num_items().unwrap()
+        );
+
+        let mut title = [0; 32];
+        let mut message = [0; 64];
+        for item_n in 0..normal.num_items().unwrap() {
+            normal
+                .render_item(item_n, &mut title, &mut message, 0)
+                .expect("couldn't render item");
+            assert!(!title.starts_with(b"Gas Limit\x00"));
+            assert!(!title.starts_with(b"Storage Limit\x00"));
+            assert!(!title.starts_with(b"Counter\x00"));
+        }
+
+        //the second operation starts right after the visible items of the first
+        let first = normal.next_section(0).expect("no operations");
+        let second = normal.next_section(first).expect("no second operation");
+        assert_eq!(second - first, 4);
+        normal
+            .render_item(second, &mut title, &mut message, 0)
+            .expect("couldn't render item");
+        assert!(title.starts_with(b"Type\x00"));
+    }
+
+    #[test]
+    fn batch_summary() {
+        const DELEGATION_HEX: &str = "6e\
+                                      0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                      904e\
+                                      01\
+                                      0a\
+                                      0a\
+                                      00";
+        let delegation = hex::decode(DELEGATION_HEX).expect("invalid input hex");
+
+        let mut op =
+            hex::decode("a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561")
+                .expect("invalid input hex");
+        op.extend_from_slice(&delegation);
+        //leak to obtain 'static, it's fine in tests
+        let single: &'static [u8] = std::boxed::Box::leak(op.clone().into_boxed_slice());
+        op.extend_from_slice(&delegation);
+        op.extend_from_slice(&delegation);
+        let batch: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+
+        //nothing to summarize with a single operation
+        let parsed = Operation::new(single).expect("couldn't parse operation");
+        assert_eq!(Sign::batch_summary(&parsed), Ok(None));
+
+        let parsed = Operation::new(batch).expect("couldn't parse operation");
+        assert_eq!(Sign::batch_summary(&parsed), Ok(Some((3, 3 * 10000))));
+
+        //shown right after the branch
+        let mut ui = parsed.to_sign_ui();
+        let mut title = [0; 32];
+        let mut message = [0; 64];
+        ui.render_item(1, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Summary\x00"));
+        assert!(message.starts_with(b"3 operations, total fee 0.03 XTZ\x00"));
+
+        ui.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(!title.starts_with(b"Summary"));
+    }
+
+    #[test]
+    #[serial(ui)]
+    fn payload_modified_before_signing() {
+        const DELEGATION_HEX: &str = "03\
+                                      a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                                      6e\
+                                      0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                      904e\
+                                      01\
+                                      0a\
+                                      0a\
+                                      00";
+        let data = hex::decode(DELEGATION_HEX).expect("invalid input hex");
+        //leak to obtain 'static, it's fine in tests
+        let data: &'static [u8] = std::boxed::Box::leak(data.into_boxed_slice());
+        let parsed = Operation::new(&data[1..]).expect("couldn't parse operation");
+
+        let mut ui = parsed.to_sign_ui();
+        ui.data = data;
+        ui.hash = Sign::blake2b_digest(data).unwrap();
+
+        //nothing changed since the payload was hashed
+        assert_eq!(ui.check_payload(), Ok(()));
+
+        //the fee was modified afterwards
+        let mut modified = data.to_vec();
+        modified[1 + 32 + 1 + 21] ^= 0x01;
+        ui.data = std::boxed::Box::leak(modified.into_boxed_slice());
+        assert_eq!(ui.check_payload(), Err(Error::DataInvalid));
+
+        //refused, and the pending request is cleaned up
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        unsafe { PATH.replace((path, Curve::Ed25519)) };
+
+        let mut out = [0; 260];
+        assert_eq!(ui.accept(&mut out), (0, Error::DataInvalid as u16));
+        assert!(Sign::get_derivation_info().is_err());
+    }
+
+    #[test]
+    #[serial(ui)]
+    fn cancelled_review_cleanup() {
+        let path =
+            BIP32Path::<BIP32_MAX_LENGTH>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        unsafe { PATH.replace((path, Curve::Ed25519)) };
+        assert!(Sign::get_derivation_info().is_ok());
+
+        //a cancelled review releases the pending request like a rejected one,
+        // even when the review itself was overwritten
+        SignUI::cleanup();
+        assert!(Sign::get_derivation_info().is_err());
+    }
+
+    #[test]
+    fn raw_dump() {
+        const OP_HEX: &str = "a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                              6e\
+                              0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                              904e\
+                              01\
+                              0a\
+                              0a\
+                              00";
+        const RAW: [u8; Sign::RAW_BYTES_PER_ITEM + 2] = {
+            let mut raw = [0xAB; Sign::RAW_BYTES_PER_ITEM + 2];
+            raw[Sign::RAW_BYTES_PER_ITEM] = 0x01;
+            raw
+        };
+
+        let op = hex::decode(OP_HEX).expect("invalid input hex");
+        //leak to obtain 'static, it's fine in tests
+        let op: &'static [u8] = std::boxed::Box::leak(op.into_boxed_slice());
+        let parsed = Operation::new(op).expect("couldn't parse operation");
+
+        let mut ui = parsed.to_sign_ui();
+        let items = ui.num_items().unwrap();
+        ui.raw = Some(&RAW);
+        assert_eq!(ui.num_items().unwrap(), items + 2);
+
+        let mut title = [0; 18];
+        let mut message = [0; 32];
+
+        //the operation's items are unchanged
+        ui.render_item(items - 1, &mut title, &mut message, 0)
+            .unwrap();
+        assert!(!title.starts_with(b"Raw Data"));
+
+        let pages = ui.render_item(items, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Raw Data 1/2\x00"));
+        assert!(message.starts_with(b"abababab"));
+        assert!(pages > 1);
+
+        ui.render_item(items + 1, &mut title, &mut message, 0)
+            .unwrap();
+        assert!(title.starts_with(b"Raw Data 2/2\x00"));
+        assert!(message.starts_with(b"01ab\x00"));
+
+        assert!(ui
+            .render_item(items + 2, &mut title, &mut message, 0)
+            .is_err());
+    }
 }
@@ -0,0 +1,122 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use crate::apdu_spec::SUPPORTED_INSTRUCTIONS;
+use crate::constants::ApduError as Error;
+use crate::dispatcher::ApduHandler;
+use crate::parser::operations::SUPPORTED_OPERATIONS;
+use crate::utils::ApduBufferRead;
+
+use bolos::PIC;
+
+/// Reports the tags of the operations that this build can review and sign
+pub struct GetSupportedOperations;
+
+impl ApduHandler for GetSupportedOperations {
+    #[inline(never)]
+    fn handle<'apdu>(
+        _: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        *tx = 0;
+
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let tags = PIC::new(SUPPORTED_OPERATIONS).into_inner();
+
+        let out = buffer.write();
+        out[0] = tags.len() as u8;
+        out[1..1 + tags.len()].copy_from_slice(tags);
+        *tx = 1 + tags.len() as u32;
+
+        Ok(())
+    }
+}
+
+/// Reports the instructions that this build understands
+pub struct GetSupportedInstructions;
+
+impl ApduHandler for GetSupportedInstructions {
+    #[inline(never)]
+    fn handle<'apdu>(
+        _: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        *tx = 0;
+
+        if buffer.p1() != 0 || buffer.p2() != 0 {
+            return Err(Error::InvalidP1P2);
+        }
+
+        let instructions = PIC::new(SUPPORTED_INSTRUCTIONS).into_inner();
+
+        let out = buffer.write();
+        out[0] = instructions.len() as u8;
+        out[1..1 + instructions.len()].copy_from_slice(instructions);
+        *tx = 1 + instructions.len() as u32;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::apdu_spec::SUPPORTED_INSTRUCTIONS;
+    use crate::assert_error_code;
+    use crate::constants::ApduError::Success;
+    use crate::dispatcher::{
+        handle_apdu, CLA, INS_GET_SUPPORTED_INSTRUCTIONS, INS_GET_SUPPORTED_OPERATIONS,
+    };
+    use crate::parser::operations::SUPPORTED_OPERATIONS;
+    use crate::utils::ApduFixture;
+    use std::convert::TryInto;
+
+    #[test]
+    fn apdu_get_supported_operations() {
+        let flags = &mut 0u32;
+        let tx = &mut 0u32;
+        let rx = 5u32;
+        let buffer = &mut [0u8; 260];
+
+        buffer[..5].copy_from_slice(&[CLA, INS_GET_SUPPORTED_OPERATIONS, 0, 0, 0]);
+
+        handle_apdu(flags, tx, rx, buffer);
+
+        let n = SUPPORTED_OPERATIONS.len();
+        assert_eq!(*tx as usize, 1 + n + 2);
+        assert_error_code!(*tx, buffer, Success);
+
+        assert_eq!(buffer[0] as usize, n);
+        assert_eq!(&buffer[1..1 + n], SUPPORTED_OPERATIONS);
+    }
+
+    #[test]
+    fn apdu_get_supported_instructions() {
+        let mut fixture = ApduFixture::new();
+        let response = fixture
+            .send(INS_GET_SUPPORTED_INSTRUCTIONS, 0, 0, &[])
+            .assert_success();
+
+        let n = SUPPORTED_INSTRUCTIONS.len();
+        assert_eq!(response.len(), 1 + n);
+        assert_eq!(response[0] as usize, n);
+        assert_eq!(&response[1..], SUPPORTED_INSTRUCTIONS);
+        assert!(response[1..].contains(&INS_GET_SUPPORTED_INSTRUCTIONS));
+    }
+}
@@ -22,6 +22,8 @@ pub enum ZPacketType {
     Init = 0,
     Add = 1,
     Last = 2,
+    /// Last packet, followed by the CRC32 of all the uploaded data
+    LastChecked = 4,
 }
 
 impl std::convert::TryFrom<u8> for ZPacketType {
@@ -32,6 +34,7 @@ impl std::convert::TryFrom<u8> for ZPacketType {
             0 => Ok(Self::Init),
             1 => Ok(Self::Add),
             2 => Ok(Self::Last),
+            4 => Ok(Self::LastChecked),
             _ => Err(()),
         }
     }
@@ -90,7 +93,7 @@ impl PacketType for ZPacketType {
     }
 
     fn is_last(&self) -> bool {
-        matches!(self, Self::Last)
+        matches!(self, Self::Last | Self::LastChecked)
     }
 }
 
@@ -231,15 +234,24 @@ fn strlen(bytes: &[u8]) -> usize {
     bytes.split(|&n| n == 0).next().unwrap_or(bytes).len()
 }
 
+/// Maximum number of thousands separators inserted in a 64 bit number
+///
+/// Useful to size the buffers given to [`intstr_to_fpstr_inplace`]
+pub const MAX_THOUSANDS_SEPARATORS: usize = 6;
+
 #[inline(never)]
 /// Converts an integer number string
 /// to a fixed point number string, in place
 ///
+/// If `separators` is set, the integer part is also grouped
+/// by thousands (ie: "4,212,345.5")
+///
 /// Returns Ok(subslice) which is the subslice with actual content,
 /// trimming excess bytes
 pub fn intstr_to_fpstr_inplace(
     s: &mut [u8],
     decimals: usize,
+    separators: bool,
 ) -> Result<&mut [u8], IntStrToFpStrError> {
     //find the length of the string
     // if no 0s are found then the entire string is full with digits
@@ -295,14 +307,23 @@ pub fn intstr_to_fpstr_inplace(
         s[1..].fill(0);
     }
 
-    //we can return early if we have no decimals
-    if decimals == 0 {
+    if decimals != 0 {
+        num_chars = insert_decimal_point(s, num_chars, decimals);
+    } else {
         num_chars = strlen(s);
-        return Ok(&mut s[..num_chars]);
     }
 
-    // Now insert decimal point
+    if separators {
+        num_chars = insert_thousands_separators(s, num_chars)?;
+    }
+
+    Ok(&mut s[..num_chars])
+}
 
+/// Inserts the decimal point in the `num_chars` digits of `s`
+///
+/// Returns the new length of the string
+fn insert_decimal_point(s: &mut [u8], mut num_chars: usize, decimals: usize) -> usize {
     //        0123456789012     <-decimal places
     //        abcd              < numChars = 4
     //                 abcd     < shift
@@ -329,14 +350,49 @@ pub fn intstr_to_fpstr_inplace(
     );
     s[point_position] = b'.';
 
-    num_chars = strlen(s);
+    strlen(s)
+}
 
-    Ok(&mut s[..num_chars])
+/// Inserts a ',' every 3 digits in the integer part of the number in `s`
+///
+/// Returns the new length of the string
+fn insert_thousands_separators(
+    s: &mut [u8],
+    num_chars: usize,
+) -> Result<usize, IntStrToFpStrError> {
+    let int_len = s[..num_chars]
+        .iter()
+        .position(|&c| c == b'.')
+        .unwrap_or(num_chars);
+    let n_separators = int_len.saturating_sub(1) / 3;
+
+    //keep space for the null terminator
+    if num_chars + n_separators >= s.len() {
+        return Err(IntStrToFpStrError::BufferFull);
+    }
+
+    //move the decimals out of the way first
+    s.copy_within(int_len..num_chars, int_len + n_separators);
+
+    //then move the integer digits, starting from the last,
+    // so we never overwrite a digit not moved yet
+    let mut write = int_len + n_separators;
+    for (i, read) in (0..int_len).rev().enumerate() {
+        if i != 0 && i % 3 == 0 {
+            write -= 1;
+            s[write] = b',';
+        }
+
+        write -= 1;
+        s[write] = s[read];
+    }
+
+    Ok(num_chars + n_separators)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::intstr_to_fpstr_inplace;
+    use super::{intstr_to_fpstr_inplace, MAX_THOUSANDS_SEPARATORS};
 
     const SUITE: &[(&[u8], usize, &str)] = &[
         //NORMAL
@@ -384,10 +440,44 @@ mod tests {
             let mut input = std::vec::Vec::from(input);
             input.resize(input.len() + decimals + 2, 0);
 
-            let out = intstr_to_fpstr_inplace(&mut input, decimals).unwrap();
+            let out = intstr_to_fpstr_inplace(&mut input, decimals, false).unwrap();
+            let out = core::str::from_utf8(out).unwrap();
+
+            assert_eq!(out, expected_output)
+        }
+    }
+
+    const SEPARATORS_SUITE: &[(&[u8], usize, &str)] = &[
+        (b"1", 0, "1"),
+        (b"123", 0, "123"),
+        (b"1234", 0, "1,234"),
+        (b"4212345", 0, "4,212,345"),
+        (b"123456", 0, "123,456"),
+        (b"0001234", 0, "1,234"),
+        (b"123", 5, "0.00123"),
+        (b"1234567", 6, "1.234567"),
+        (b"1234567890", 6, "1,234.567890"),
+        (b"18446744073709551615", 6, "18,446,744,073,709.551615"),
+        (b"", 6, "0.000000"),
+    ];
+
+    #[test]
+    fn intstr_to_fpstr_inplace_separators() {
+        for &(input, decimals, expected_output) in SEPARATORS_SUITE.iter() {
+            let mut buf = std::vec::Vec::from(input);
+            buf.resize(input.len() + decimals + 2 + MAX_THOUSANDS_SEPARATORS, 0);
+
+            let out = intstr_to_fpstr_inplace(&mut buf, decimals, true).unwrap();
             let out = core::str::from_utf8(out).unwrap();
 
             assert_eq!(out, expected_output)
         }
     }
+
+    #[test]
+    fn intstr_to_fpstr_inplace_separators_no_space() {
+        //exactly enough space for the digits and the null terminator
+        let mut buf = *b"1234\0";
+        assert!(intstr_to_fpstr_inplace(&mut buf, 0, true).is_err());
+    }
 }
@@ -0,0 +1,56 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Wallet-only handlers
+
+use crate::handlers::{prelude::*, signing::Sign};
+use crate::utils::Uploader;
+
+pub struct LegacySignUnsafe;
+
+impl ApduHandler for LegacySignUnsafe {
+    #[inline(never)]
+    fn handle<'apdu>(
+        flags: &mut u32,
+        tx: &mut u32,
+        buffer: ApduBufferRead<'apdu>,
+    ) -> Result<(), Error> {
+        if let Some(upload) = Uploader::new(Sign).upload(&buffer)? {
+            *tx = Sign::start_sign(false, upload.p2, upload.first, upload.data, flags)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Handles the wallet-only instructions
+///
+/// Returns `None` if `ins` is not one of them
+#[inline(never)]
+pub fn dispatch<'apdu>(
+    ins: u8,
+    flags: &mut u32,
+    tx: &mut u32,
+    buffer: ApduBufferRead<'apdu>,
+) -> Option<Result<(), Error>> {
+    use crate::apdu_spec::*;
+
+    let res = match ins {
+        INS_LEGACY_SIGN_UNSAFE => LegacySignUnsafe::handle(flags, tx, buffer),
+        _ => return None,
+    };
+
+    Some(res)
+}
@@ -0,0 +1,153 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Host-side access to the operation parser
+//!
+//! Enabled with the `std` feature, so wallets can test against the very same
+//! parsing and review the device performs. The device APIs are backed by
+//! `bolos-mock` when not building for the device.
+//!
+//! The items are the ones shown when signing, without those depending
+//! on the signing request (origin, parameters check and key warnings)
+
+use std::prelude::v1::*;
+
+use zemu_sys::Viewable;
+
+use crate::parser::operations::Operation;
+
+pub use crate::handlers::parser_common::ParserError;
+
+/// Item shown for review
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub title: String,
+    /// The whole content of the item, regardless of how many pages
+    /// it's split into on screen
+    pub message: String,
+}
+
+/// Parses a forged operation, without watermark, from its hex encoding
+/// and lists the items that would be shown for review
+pub fn review_forged_hex(forged: &str, expert: bool) -> Result<Vec<Item>, ParserError> {
+    let forged = hex::decode(forged).map_err(|_| ParserError::parser_context_invalid_chars)?;
+
+    review_forged(&forged, expert)
+}
+
+/// Parses a forged operation, without watermark,
+/// and lists the items that would be shown for review
+///
+/// `expert` is the expert mode setting, which shows the secondary details
+/// of the operations too
+pub fn review_forged(forged: &[u8], expert: bool) -> Result<Vec<Item>, ParserError> {
+    let parsed = Operation::new(forged)?;
+
+    //the review parses lazily, so make sure the whole batch is valid first
+    let mut ops = *parsed.ops();
+    while ops.parse_next()?.is_some() {}
+
+    let mut ui = parsed
+        .try_to_sign_ui(expert)
+        .map_err(|_| ParserError::parser_value_out_of_range)?;
+
+    let num_items = ui
+        .num_items()
+        .map_err(|_| ParserError::parser_unexpected_error)?;
+
+    (0..num_items)
+        .map(|item_n| render_item(&mut ui, item_n))
+        .collect()
+}
+
+fn render_item<V: Viewable>(ui: &mut V, item_n: u8) -> Result<Item, ParserError> {
+    let mut item = Item {
+        title: String::new(),
+        message: String::new(),
+    };
+
+    let mut page = 0;
+    loop {
+        let mut title = [0; 64];
+        let mut message = [0; 4096];
+
+        let n_pages = ui
+            .render_item(item_n, &mut title, &mut message, page)
+            .map_err(|_| ParserError::parser_display_idx_out_of_range)?;
+
+        if page == 0 {
+            item.title = until_nul(&title);
+        }
+        item.message.push_str(&until_nul(&message));
+
+        page += 1;
+        if page >= n_pages {
+            break Ok(item);
+        }
+    }
+}
+
+fn until_nul(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELEGATION_HEX: &str = "a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                                  6e\
+                                  0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                  904e\
+                                  01\
+                                  0a\
+                                  0a\
+                                  00";
+
+    #[test]
+    fn review_delegation() {
+        let items = review_forged_hex(DELEGATION_HEX, false).expect("couldn't review operation");
+
+        assert_eq!(items[0].title, "Operation");
+        assert_eq!(
+            items[0].message,
+            "BLzyjjHKEKMULtvkpSHxuZxx6ei6fpntH2BTkYZiLgs8zLVstvX"
+        );
+        assert_eq!(items[1].title, "Type");
+        assert_eq!(items[1].message, "Delegation Withdrawal");
+        assert_eq!(items[3].title, "Delegation");
+        assert_eq!(items[3].message, "<REVOKED>");
+        assert!(items.iter().all(|item| item.title != "Counter"));
+
+        let expert = review_forged_hex(DELEGATION_HEX, true).expect("couldn't review operation");
+        assert_eq!(expert.len(), items.len() + 3);
+        assert_eq!(expert[..items.len()], items[..]);
+        assert_eq!(expert[items.len() + 2].title, "Counter");
+    }
+
+    #[test]
+    fn review_invalid() {
+        assert_eq!(
+            review_forged_hex("not hex", false),
+            Err(ParserError::parser_context_invalid_chars)
+        );
+
+        //branch only, with a truncated operation after it
+        let truncated = &DELEGATION_HEX[..DELEGATION_HEX.len() - 4];
+        assert!(review_forged_hex(truncated, false).is_err());
+    }
+}
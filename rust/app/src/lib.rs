@@ -19,15 +19,20 @@
 
 extern crate no_std_compat as std;
 
+pub mod apdu_spec;
 pub mod constants;
 pub mod dispatcher;
 mod handlers;
+mod policy;
 mod sys;
 
 pub use handlers::ZPacketType as PacketType;
 
 pub mod parser;
 
+#[cfg(any(test, feature = "std"))]
+pub mod host;
+
 #[cfg(not(fuzzing))]
 sys::panic_handler! {}
 
@@ -75,6 +80,29 @@ pub unsafe extern "C" fn rs_handle_apdu(
     check_canary();
 }
 
+/// # Safety
+///
+/// This function is called once by the minimal C stub,
+/// before the idle screen is shown for the first time
+#[no_mangle]
+pub unsafe extern "C" fn rs_app_init() {
+    #[cfg(feature = "baking")]
+    handlers::baking::hwm::HWM::show_chain();
+
+    //derive the authorized key now instead of on the first signature
+    #[cfg(feature = "baking")]
+    let _ = handlers::baking::Baking::load_baking_key();
+}
+
+/// # Safety
+///
+/// This function is called by the minimal C stub on every ticker event
+#[no_mangle]
+pub unsafe extern "C" fn rs_ticker() {
+    #[cfg(feature = "dev")]
+    handlers::dev::metrics::tick();
+}
+
 #[cfg(test)]
 pub fn handle_apdu_raw(bytes: &[u8]) -> (u32, u32, std::vec::Vec<u8>) {
     let mut flags = 0;
@@ -14,11 +14,17 @@
 *  limitations under the License.
 ********************************************************************************/
 use arrayref::array_ref;
+use bolos::{pic_str, PIC};
+use core::convert::TryFrom;
 use nom::{bytes::complete::take, number::complete::le_u8, sequence::tuple, IResult};
 use zemu_sys::ViewError;
 
-use crate::{crypto::Curve, handlers::parser_common::ParserError};
+use crate::{
+    crypto::Curve,
+    handlers::{intstr_to_fpstr_inplace, parser_common::ParserError, MAX_THOUSANDS_SEPARATORS},
+};
 
+pub mod michelson;
 pub mod operations;
 
 #[cfg(feature = "baking")]
@@ -50,6 +56,29 @@ pub trait DisplayableItem {
         message: &mut [u8],
         page: u8,
     ) -> Result<u8, ViewError>;
+
+    /// Returns whether `item_n` is a secondary detail (ie: the gas limit),
+    /// only shown when expert mode is enabled
+    ///
+    /// guarantee: 0 <= item_n < self.num_items()
+    fn is_expert_item(&self, _item_n: u8) -> bool {
+        false
+    }
+
+    /// Returns the number of items to display, skipping the expert ones
+    /// unless `expert` is set
+    fn num_shown_items(&self, expert: bool) -> usize {
+        (0..self.num_items())
+            .filter(|&item_n| expert || !self.is_expert_item(item_n as u8))
+            .count()
+    }
+
+    /// Retrieves the item shown as `shown_n`, see [`DisplayableItem::num_shown_items`]
+    fn shown_item(&self, shown_n: u8, expert: bool) -> Option<u8> {
+        (0..self.num_items() as u8)
+            .filter(|&item_n| expert || !self.is_expert_item(item_n))
+            .nth(shown_n as usize)
+    }
 }
 
 //legacy app stored in a uint64 always, we have `read_as`
@@ -66,13 +95,36 @@ impl<'b> Zarith<'b> {
         self.bytes.len()
     }
 
-    #[cfg(not(test))]
-    pub fn from_bytes(input: &'b [u8], want_sign: bool) -> IResult<&[u8], Self, ParserError> {
-        //keep taking bytes while the MSB is 1
-        let (_, bytes) = nom::bytes::complete::take_till(|byte| byte & 0x80 == 0)(input)?;
+    /// Maximum length of mutez amounts, which are int64 in the protocol
+    pub const MUTEZ_MAX_LEN: usize = 10;
+
+    /// Maximum length accepted for counters and gas/storage limits
+    pub const COUNTER_MAX_LEN: usize = 10;
+
+    /// Maximum length accepted for ints in Michelson values
+    pub const MICHELSON_INT_MAX_LEN: usize = 32;
+
+    /// Finds the length of the number at the start of `input`,
+    /// without looking past `max_len` bytes
+    fn encoded_len(input: &[u8], max_len: usize) -> Result<usize, nom::Err<ParserError>> {
+        //keep going while the MSB is 1
+        match input.iter().take(max_len).position(|byte| byte & 0x80 == 0) {
+            Some(last) => Ok(last + 1),
+            None if input.len() >= max_len => Err(nom::Err::Error(ParserError::ZarithTooLong)),
+            //unterminated
+            None => Err(nom::Err::Error(ParserError::UnexpectedEof)),
+        }
+    }
 
-        //take bytes + 1 since we miss the last byte with `take_till`
-        let (rem, bytes) = take(bytes.len() + 1)(input)?;
+    /// Parses a number of at most `max_len` bytes
+    #[cfg(not(test))]
+    pub fn from_bytes(
+        input: &'b [u8],
+        want_sign: bool,
+        max_len: usize,
+    ) -> IResult<&[u8], Self, ParserError> {
+        let len = Self::encoded_len(input, max_len)?;
+        let (rem, bytes) = take(len)(input)?;
 
         let is_negative = if want_sign {
             //if the second bit of the first byte is set, then it's negative
@@ -132,20 +184,202 @@ impl<'b> Zarith<'b> {
 
         Some((self.is_negative.unwrap_or_default(), out))
     }
+
+    /// Reads the absolute value of the number
+    ///
+    /// Unlike [`Zarith::read_as`], leading zeros don't count against the size
+    fn magnitude(&self) -> Result<u128, ParserError> {
+        let mut out = 0u128;
+        let mut shift = 0;
+
+        for (i, b) in self.bytes.iter().enumerate() {
+            let (bits, n) = if i == 0 && self.is_negative.is_some() {
+                (b & 0x3F, 6)
+            } else {
+                (b & 0x7F, 7)
+            };
+
+            if bits != 0 {
+                let used = 8 - bits.leading_zeros() as usize;
+                if shift + used > 128 {
+                    return Err(ParserError::parser_value_out_of_range);
+                }
+
+                out |= (bits as u128) << shift;
+            }
+
+            shift += n;
+        }
+
+        Ok(out)
+    }
+
+    /// Reads the number as an u128
+    ///
+    /// Fails with [`ParserError::parser_value_out_of_range`]
+    /// if the number is negative or too big
+    pub fn to_u128(&self) -> Result<u128, ParserError> {
+        let magnitude = self.magnitude()?;
+
+        //-0 is just 0
+        if self.is_negative == Some(true) && magnitude != 0 {
+            return Err(ParserError::parser_value_out_of_range);
+        }
+
+        Ok(magnitude)
+    }
+
+    /// Reads the number as an u64, see [`Zarith::to_u128`]
+    pub fn to_u64(&self) -> Result<u64, ParserError> {
+        u64::try_from(self.to_u128()?).map_err(|_| ParserError::parser_value_out_of_range)
+    }
+
+    /// Reads the number as an i128
+    ///
+    /// Fails with [`ParserError::parser_value_out_of_range`] if the number is too big
+    pub fn to_i128(&self) -> Result<i128, ParserError> {
+        const MIN_MAGNITUDE: u128 = 1 << 127;
+
+        let magnitude = self.magnitude()?;
+        match self.is_negative {
+            //i128::MIN is the only value whose magnitude doesn't fit in an i128
+            Some(true) if magnitude == MIN_MAGNITUDE => Ok(i128::MIN),
+            Some(true) if magnitude < MIN_MAGNITUDE => Ok(-(magnitude as i128)),
+            Some(true) => Err(ParserError::parser_value_out_of_range),
+            _ => i128::try_from(magnitude).map_err(|_| ParserError::parser_value_out_of_range),
+        }
+    }
+
+    /// Reads the number as an i64, see [`Zarith::to_i128`]
+    pub fn to_i64(&self) -> Result<i64, ParserError> {
+        i64::try_from(self.to_i128()?).map_err(|_| ParserError::parser_value_out_of_range)
+    }
+
+    /// Maximum length of an amount formatted with [`Zarith::format_mutez_into`]
+    pub const MUTEZ_FORMATTED_SIZE: usize =
+        <u64 as lexical_core::Number>::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS + 4;
+
+    /// Formats the amount of mutez as tez, grouping the thousands and
+    /// without the trailing zeros of the decimals (ie: "1,234.5 XTZ")
+    ///
+    /// Returns None if the amount is negative or doesn't fit in an u64
+    pub fn format_mutez_into<'o>(
+        &self,
+        out: &'o mut [u8; Zarith::MUTEZ_FORMATTED_SIZE],
+    ) -> Option<&'o [u8]> {
+        Self::format_mutez(self.to_u64().ok()?, out)
+    }
+
+    /// Formats `amount` mutez like [`Zarith::format_mutez_into`],
+    /// for amounts computed from multiple numbers
+    pub fn format_mutez(
+        amount: u64,
+        out: &mut [u8; Zarith::MUTEZ_FORMATTED_SIZE],
+    ) -> Option<&[u8]> {
+        //the fonts don't have ꜩ
+        let unit = pic_str!(b" XTZ"!);
+
+        let room = out.len() - unit.len();
+
+        out.fill(0);
+        lexical_core::write(amount, &mut out[..room]);
+        let mut len = intstr_to_fpstr_inplace(&mut out[..room], 6, true)
+            .ok()?
+            .len();
+
+        //there's always a decimal point, drop it too if there are no decimals left
+        while out[len - 1] == b'0' {
+            len -= 1;
+        }
+        if out[len - 1] == b'.' {
+            len -= 1;
+        }
+
+        out[len..len + unit.len()].copy_from_slice(unit);
+        Some(&out[..len + unit.len()])
+    }
+
+    /// Maximum length of the number formatted with [`Zarith::format_into`]
+    ///
+    /// [`Zarith::MICHELSON_INT_MAX_LEN`] groups hold at most 224 bits,
+    /// which is 68 decimal digits, plus the sign
+    pub const FORMATTED_SIZE: usize = 68 + 1;
+
+    /// Formats the number as a decimal string into `out`,
+    /// with a leading `-` when the number is negative
+    ///
+    /// The digits are computed directly on the 7-bit groups
+    /// (by repeatedly dividing them by 10), so any number is shown exactly
+    ///
+    /// Returns None if the number is longer than [`Zarith::MICHELSON_INT_MAX_LEN`]
+    pub fn format_into<'o>(&self, out: &'o mut [u8; Zarith::FORMATTED_SIZE]) -> Option<&'o [u8]> {
+        if self.bytes.len() > Self::MICHELSON_INT_MAX_LEN {
+            return None;
+        }
+
+        //groups of bits, least significant first
+        let mut groups = [0u8; Self::MICHELSON_INT_MAX_LEN];
+        let groups = &mut groups[..self.bytes.len()];
+        for (group, byte) in groups.iter_mut().zip(self.bytes) {
+            *group = byte & 0x7F;
+        }
+
+        //the first group also holds the sign bit
+        let signed = self.is_negative.is_some();
+        if let Some(first) = groups.first_mut().filter(|_| signed) {
+            *first &= 0x3F;
+        }
+        let radix = |i: usize| if i == 0 && signed { 64 } else { 128 };
+
+        //digits are found least significant first, so `out` is filled from the end
+        let mut start = out.len();
+        let mut top = groups.len();
+        loop {
+            while top > 0 && groups[top - 1] == 0 {
+                top -= 1;
+            }
+            //always at least one digit
+            if top == 0 && start < out.len() {
+                break;
+            }
+
+            let mut rem = 0u16;
+            for i in (0..top).rev() {
+                let cur = rem * radix(i) + groups[i] as u16;
+                groups[i] = (cur / 10) as u8;
+                rem = cur % 10;
+            }
+
+            start -= 1;
+            out[start] = b'0' + rem as u8;
+        }
+
+        //-0 is just 0
+        let is_zero = start == out.len() - 1 && out[start] == b'0';
+        if self.is_negative == Some(true) && !is_zero {
+            start -= 1;
+            out[start] = b'-';
+        }
+
+        Some(&out[start..])
+    }
 }
 
 #[cfg(test)]
 impl<'b> Zarith<'b> {
     #[cfg(test)]
-    pub fn from_bytes(input: &'b [u8], want_sign: bool) -> IResult<&[u8], Self, ParserError> {
-        use nom::{dbg_basic, take, take_till};
+    pub fn from_bytes(
+        input: &'b [u8],
+        want_sign: bool,
+        max_len: usize,
+    ) -> IResult<&[u8], Self, ParserError> {
+        use nom::{call, dbg_basic};
         use std::println;
 
-        //keep taking bytes while the MSB is 1
-        let (_, bytes) = dbg_basic!(input, take_till!(|byte| byte & 0x80 == 0))?;
-
-        //take bytes + 1 since we miss the last byte with `take_till`
-        let (rem, bytes) = dbg_basic!(input, take!(bytes.len() + 1))?;
+        let len = Self::encoded_len(input, max_len).inspect_err(|_| {
+            println!("zarith of more than {} bytes in {:x?}", max_len, input);
+        })?;
+        let (rem, bytes) = dbg_basic!(input, call!(take(len)))?;
 
         let is_negative = if want_sign {
             //if the second bit of the first byte is set, then it's negative
@@ -192,6 +426,7 @@ pub fn public_key_hash(input: &[u8]) -> IResult<&[u8], (Curve, &[u8; 20]), Parse
         0x00 => Curve::Bip32Ed25519,
         0x01 => Curve::Secp256K1,
         0x02 => Curve::Secp256R1,
+        0x03 => Curve::Bls12_381,
         _ => return Err(ParserError::parser_invalid_pubkey_encoding.into()),
     };
 
@@ -266,13 +501,25 @@ impl Preemble {
             _ => Err(ParserError::parser_unexpected_type.into()),
         }
     }
+
+    /// Returns true if the data is a consensus operation (block or endorsement)
+    pub fn is_consensus(&self) -> bool {
+        matches!(
+            self,
+            Self::Block
+                | Self::Endorsement
+                | Self::TenderbakeBlock
+                | Self::TenderbakePreendorsement
+                | Self::TenderbakeEndorsement
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         crypto::Curve,
-        handlers::public_key::Addr,
+        handlers::{parser_common::ParserError, public_key::Addr},
         parser::{boolean, public_key, public_key_hash},
     };
 
@@ -295,6 +542,28 @@ mod tests {
         assert_eq!(&addr[..len], PKH_BASE58.as_bytes());
     }
 
+    #[test]
+    fn pkh_bls() {
+        const PKH_BASE58: &str = "tz4YLrZzFXK2THqsophsj6v7Cvw3NkN6Ad3n";
+        const INPUT_HEX: &str = "03ffffffffffffffffffffffffffffffffffffffff";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, (crv, hash)) = public_key_hash(&input).expect("failed to parse input");
+
+        assert_eq!(rem.len(), 0);
+        assert_eq!(crv, Curve::Bls12_381);
+
+        let addr = Addr::from_hash(hash, crv).unwrap();
+        let (len, addr) = addr.base58();
+        assert_eq!(&addr[..len], PKH_BASE58.as_bytes());
+
+        //unknown tag
+        let mut input = input;
+        input[0] = 0x04;
+        assert!(public_key_hash(&input).is_err());
+    }
+
     #[test]
     #[should_panic(expected = "failed to parse pkh input")]
     fn pkh_ed_eof() {
@@ -347,7 +616,8 @@ mod tests {
         //should ignore last byte
         let end_early = &[0b1000_0001, 0x11, 0x33][..];
 
-        let (_, num) = Zarith::from_bytes(end_early, false).expect("invalid input");
+        let (_, num) =
+            Zarith::from_bytes(end_early, false, Zarith::MUTEZ_MAX_LEN).expect("invalid input");
         assert_eq!(num.len(), 2);
         assert_eq!(num.is_negative(), None);
         let num = num.read_as::<usize>().expect("didn't fit in usize").1;
@@ -356,7 +626,8 @@ mod tests {
         //should get a single byte
         let single_byte = &[0x0a][..];
 
-        let (_, num) = Zarith::from_bytes(single_byte, false).expect("invalid input");
+        let (_, num) =
+            Zarith::from_bytes(single_byte, false, Zarith::MUTEZ_MAX_LEN).expect("invalid input");
         assert_eq!(num.len(), 1);
         assert_eq!(num.is_negative(), None);
 
@@ -366,7 +637,8 @@ mod tests {
         //should get a bunch of bytes
         let multi_byte = &[0x8a, 0x90, 0xf2, 0xe4, 0x88, 0x00][..];
 
-        let (_, num) = Zarith::from_bytes(multi_byte, false).expect("invalid input");
+        let (_, num) =
+            Zarith::from_bytes(multi_byte, false, Zarith::MUTEZ_MAX_LEN).expect("invalid input");
         assert_eq!(num.len(), 6);
         assert_eq!(num.is_negative(), None);
 
@@ -376,7 +648,8 @@ mod tests {
         //should be considered negative
         let negative = &[0b1100_0011, 0x23][..];
 
-        let (_, num) = Zarith::from_bytes(negative, true).expect("invalid input");
+        let (_, num) =
+            Zarith::from_bytes(negative, true, Zarith::MUTEZ_MAX_LEN).expect("invalid input");
         assert_eq!(num.len(), 2);
         assert_eq!(num.is_negative(), Some(true));
 
@@ -387,7 +660,8 @@ mod tests {
         //should be considered positive
         let positive = &[0b1000_0011, 0x23][..];
 
-        let (_, num) = Zarith::from_bytes(positive, true).expect("invalid input");
+        let (_, num) =
+            Zarith::from_bytes(positive, true, Zarith::MUTEZ_MAX_LEN).expect("invalid input");
         assert_eq!(num.len(), 2);
         assert_eq!(num.is_negative(), Some(false));
 
@@ -395,6 +669,162 @@ mod tests {
         assert!(!neg);
         assert_eq!(num, 0x8C3);
     }
+
+    #[test]
+    fn zarith_max_len() {
+        let long = &[0x80, 0x80, 0x80, 0x01][..];
+
+        let (_, num) = Zarith::from_bytes(long, false, 4).expect("invalid input");
+        assert_eq!(num.len(), 4);
+
+        assert_eq!(
+            Zarith::from_bytes(long, false, 3).unwrap_err(),
+            nom::Err::Error(ParserError::ZarithTooLong)
+        );
+
+        //unterminated but shorter than the bound
+        assert_eq!(
+            Zarith::from_bytes(&long[..3], false, 4).unwrap_err(),
+            nom::Err::Error(ParserError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn zarith_format() {
+        let mut out = [0; Zarith::FORMATTED_SIZE];
+
+        let (_, num) = Zarith::from_bytes(&[0b1100_0011, 0x23], true, Zarith::MUTEZ_MAX_LEN)
+            .expect("invalid input");
+        assert_eq!(num.format_into(&mut out), Some(&b"-2243"[..]));
+
+        let (_, num) = Zarith::from_bytes(&[0b1000_0011, 0x23], true, Zarith::MUTEZ_MAX_LEN)
+            .expect("invalid input");
+        assert_eq!(num.format_into(&mut out), Some(&b"2243"[..]));
+
+        let (_, num) =
+            Zarith::from_bytes(&[0b0100_0000], true, Zarith::MUTEZ_MAX_LEN).expect("invalid input");
+        assert_eq!(num.format_into(&mut out), Some(&b"0"[..]));
+
+        //unsigned numbers use all the bits of the first byte
+        let (_, num) = Zarith::from_bytes(&[0b1100_0011, 0x23], false, Zarith::MUTEZ_MAX_LEN)
+            .expect("invalid input");
+        assert_eq!(num.format_into(&mut out), Some(&b"4547"[..]));
+
+        //2^128, too big for any integer type
+        let mut input = [0x80; 19];
+        input[18] = 0b0000_0100;
+        let (_, num) = Zarith::from_bytes(&input, false, Zarith::MICHELSON_INT_MAX_LEN)
+            .expect("invalid input");
+        assert_eq!(
+            num.format_into(&mut out),
+            Some(&b"340282366920938463463374607431768211456"[..])
+        );
+
+        //-(2^223 - 1), the biggest michelson int
+        let mut input = [0xFF; Zarith::MICHELSON_INT_MAX_LEN];
+        input[Zarith::MICHELSON_INT_MAX_LEN - 1] = 0x7F;
+        let (_, num) =
+            Zarith::from_bytes(&input, true, Zarith::MICHELSON_INT_MAX_LEN).expect("invalid input");
+        assert_eq!(
+            num.format_into(&mut out),
+            Some(&b"-13479973333575319897333507543509815336818572211270286240551805124607"[..])
+        );
+
+        //leading zero groups are not digits
+        let (_, num) = Zarith::from_bytes(&[0x85, 0x80, 0x00], false, Zarith::MUTEZ_MAX_LEN)
+            .expect("invalid input");
+        assert_eq!(num.format_into(&mut out), Some(&b"5"[..]));
+    }
+
+    #[test]
+    fn zarith_format_mutez() {
+        let mut out = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+
+        let mutez = |n: u64, out: &mut [u8; Zarith::MUTEZ_FORMATTED_SIZE]| {
+            let mut encoded = std::vec![];
+            let mut n = n;
+            loop {
+                let byte = (n & 0x7F) as u8;
+                n >>= 7;
+                if n == 0 {
+                    encoded.push(byte);
+                    break;
+                }
+                encoded.push(byte | 0x80);
+            }
+
+            let (_, num) = Zarith::from_bytes(&encoded, false, Zarith::MUTEZ_MAX_LEN).unwrap();
+            num.format_mutez_into(out)
+                .map(|s| std::string::String::from_utf8(s.to_vec()).unwrap())
+        };
+
+        assert_eq!(mutez(0, &mut out).unwrap(), "0 XTZ");
+        assert_eq!(mutez(1, &mut out).unwrap(), "0.000001 XTZ");
+        assert_eq!(mutez(1_000_000, &mut out).unwrap(), "1 XTZ");
+        assert_eq!(mutez(1_234_567, &mut out).unwrap(), "1.234567 XTZ");
+        assert_eq!(mutez(1_234_500_000, &mut out).unwrap(), "1,234.5 XTZ");
+        assert_eq!(
+            mutez(i64::MAX as u64, &mut out).unwrap(),
+            "9,223,372,036,854.775807 XTZ"
+        );
+        assert_eq!(
+            mutez(u64::MAX, &mut out).unwrap(),
+            "18,446,744,073,709.551615 XTZ"
+        );
+
+        //amounts are never negative
+        let (_, num) = Zarith::from_bytes(&[0b0100_0001], true, Zarith::MUTEZ_MAX_LEN).unwrap();
+        assert_eq!(num.format_mutez_into(&mut out), None);
+    }
+
+    #[test]
+    fn zarith_to_integer() {
+        let (_, num) = Zarith::from_bytes(&[0b1100_0011, 0x23], true, Zarith::MUTEZ_MAX_LEN)
+            .expect("invalid input");
+        assert_eq!(num.to_i64(), Ok(-2243));
+        assert_eq!(num.to_i128(), Ok(-2243));
+        assert_eq!(num.to_u64(), Err(ParserError::parser_value_out_of_range));
+
+        let (_, num) = Zarith::from_bytes(&[0b1100_0011, 0x23], false, Zarith::MUTEZ_MAX_LEN)
+            .expect("invalid input");
+        assert_eq!(num.to_u64(), Ok(4547));
+        assert_eq!(num.to_u128(), Ok(4547));
+
+        //-0 is just 0
+        let (_, num) =
+            Zarith::from_bytes(&[0b0100_0000], true, Zarith::MUTEZ_MAX_LEN).expect("invalid input");
+        assert_eq!(num.to_u64(), Ok(0));
+
+        //u64::MAX, 64 bits in 10 bytes
+        let mut max = [0xFF; 10];
+        max[9] = 0x01;
+        let (_, num) = Zarith::from_bytes(&max, false, 10).expect("invalid input");
+        assert_eq!(num.to_u64(), Ok(u64::MAX));
+        assert_eq!(num.to_i64(), Err(ParserError::parser_value_out_of_range));
+        assert_eq!(num.to_i128(), Ok(u64::MAX as i128));
+
+        //u64::MAX + 1
+        let mut over = [0x80; 10];
+        over[9] = 0x02;
+        let (_, num) = Zarith::from_bytes(&over, false, 10).expect("invalid input");
+        assert_eq!(num.to_u64(), Err(ParserError::parser_value_out_of_range));
+        assert_eq!(num.to_u128(), Ok(u64::MAX as u128 + 1));
+
+        //leading zeros don't count
+        let padded = [
+            0x81, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00,
+        ];
+        let (_, num) = Zarith::from_bytes(&padded, false, 11).expect("invalid input");
+        assert_eq!(num.to_u64(), Ok(1));
+
+        //i128::MIN, 6 + 7 * 17 + 2 bits
+        let mut min = [0x80; 19];
+        min[0] = 0b1100_0000;
+        min[18] = 0b0000_0100;
+        let (_, num) = Zarith::from_bytes(&min, true, 19).expect("invalid input");
+        assert_eq!(num.to_i128(), Ok(i128::MIN));
+        assert_eq!(num.to_i64(), Err(ParserError::parser_value_out_of_range));
+    }
 }
 
 #[cfg(test)]
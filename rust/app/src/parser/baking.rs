@@ -20,7 +20,12 @@ mod emmy;
 pub use emmy::EmmyEndorsement;
 
 use crate::{
-    handlers::{handle_ui_message, hwm::WaterMark, parser_common::ParserError},
+    handlers::{
+        baking::hwm::{ChainID, WaterMark},
+        handle_ui_message, intstr_to_fpstr_inplace,
+        parser_common::ParserError,
+        MAX_THOUSANDS_SEPARATORS,
+    },
     utils::ApduPanic,
 };
 use bolos::{pic_str, PIC};
@@ -34,12 +39,12 @@ use nom::{
 
 use self::{emmy::EmmyFitness, tenderbake::TenderbakeFitness};
 
-use super::DisplayableItem;
+use super::{DisplayableItem, Preemble};
 
 #[derive(Clone)]
 #[cfg_attr(test, derive(Debug))]
 pub struct BlockData<'b> {
-    pub chain_id: u32,
+    pub chain_id: ChainID,
     pub level: u32,
     pub proto: u8,
     pub predecessor: &'b [u8; 32],
@@ -52,7 +57,7 @@ pub struct BlockData<'b> {
 impl<'b> BlockData<'b> {
     #[inline(never)]
     pub fn from_bytes(bytes: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
-        let (rem, chain_id) = be_u32(bytes)?;
+        let (rem, chain_id) = ChainID::from_bytes(bytes)?;
         let (rem, level) = be_u32(rem)?;
         let (rem, proto) = le_u8(rem)?;
         let (rem, predecessor) = take(32usize)(rem)?;
@@ -145,17 +150,24 @@ impl<'b> DisplayableItem for BlockData<'b> {
                 let title_content = pic_str!(b"ChainID");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let mut itoa_buf = [0u8; u32::FORMATTED_SIZE_DECIMAL];
-
-                handle_ui_message(itoa(self.chain_id, &mut itoa_buf), message, page)
+                let (len, mex) = self.chain_id.base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
             }
             2 => {
                 let title_content = pic_str!(b"Blocklevel");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let mut itoa_buf = [0u8; u32::FORMATTED_SIZE_DECIMAL];
+                //+1 for the null terminator
+                let mut itoa_buf =
+                    [0u8; u32::FORMATTED_SIZE_DECIMAL + 1 + MAX_THOUSANDS_SEPARATORS];
+                itoa(self.level, &mut itoa_buf);
 
-                handle_ui_message(itoa(self.level, &mut itoa_buf), message, page)
+                handle_ui_message(
+                    intstr_to_fpstr_inplace(&mut itoa_buf, 0, true)
+                        .map_err(|_| ViewError::Unknown)?,
+                    message,
+                    page,
+                )
             }
             _ => Err(ViewError::NoData),
         }
@@ -218,7 +230,7 @@ impl<'b> EndorsementData<'b> {
         }
     }
 
-    pub fn chain_id(&self) -> u32 {
+    pub fn chain_id(&self) -> ChainID {
         match self {
             EndorsementData::Emmy(EmmyEndorsement { chain_id, .. })
             | EndorsementData::Tenderbake(TenderbakeEndorsement { chain_id, .. }) => *chain_id,
@@ -239,14 +251,22 @@ impl<'b> EndorsementData<'b> {
         }
     }
 
+    /// Parses the endorsement with the format of the protocol
+    /// indicated by `preemble`
+    ///
+    /// Emmy endorsements are still accepted for older chains
     #[inline(never)]
-    pub fn from_bytes(bytes: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
-        match EmmyEndorsement::from_bytes(bytes) {
-            Ok((rem, emmy)) => Ok((rem, Self::Emmy(emmy))),
-            Err(_) => match TenderbakeEndorsement::from_bytes(bytes) {
-                Ok((rem, tenderbake)) => Ok((rem, Self::Tenderbake(tenderbake))),
-                Err(err) => Err(err),
-            },
+    pub fn from_bytes(bytes: &'b [u8], preemble: Preemble) -> IResult<&[u8], Self, ParserError> {
+        match preemble {
+            Preemble::Endorsement => {
+                let (rem, emmy) = EmmyEndorsement::from_bytes(bytes)?;
+                Ok((rem, Self::Emmy(emmy)))
+            }
+            Preemble::TenderbakePreendorsement | Preemble::TenderbakeEndorsement => {
+                let (rem, tenderbake) = TenderbakeEndorsement::from_bytes(bytes)?;
+                Ok((rem, Self::Tenderbake(tenderbake)))
+            }
+            _ => Err(ParserError::parser_unexpected_type.into()),
         }
     }
 
@@ -325,17 +345,24 @@ impl<'b> DisplayableItem for EndorsementData<'b> {
                 let title_content = pic_str!(b"Blocklevel");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let mut itoa_buf = [0u8; u32::FORMATTED_SIZE_DECIMAL];
+                //+1 for the null terminator
+                let mut itoa_buf =
+                    [0u8; u32::FORMATTED_SIZE_DECIMAL + 1 + MAX_THOUSANDS_SEPARATORS];
+                itoa(self.level(), &mut itoa_buf);
 
-                handle_ui_message(itoa(self.level(), &mut itoa_buf), message, page)
+                handle_ui_message(
+                    intstr_to_fpstr_inplace(&mut itoa_buf, 0, true)
+                        .map_err(|_| ViewError::Unknown)?,
+                    message,
+                    page,
+                )
             }
             3 => {
                 let title_content = pic_str!(b"ChainID");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let mut itoa_buf = [0u8; u32::FORMATTED_SIZE_DECIMAL];
-
-                handle_ui_message(itoa(self.chain_id(), &mut itoa_buf), message, page)
+                let (len, mex) = self.chain_id().base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
             }
             4 if self.is_tenderbake() => {
                 let title_content = pic_str!(b"Round");
@@ -357,7 +384,7 @@ impl<'b> DisplayableItem for EndorsementData<'b> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser::Preemble;
+    use crate::handlers::baking::hwm::MAINNET_CHAIN_ID;
 
     const INPUT_HEXES: &[&str] = include!("misc_tb_blocks.bin");
 
@@ -376,4 +403,99 @@ mod tests {
             assert!(matches!(blockdata.fitness, Fitness::Tenderbake(_)));
         }
     }
+
+    fn tenderbake_endorsement(ty: EndorsementType, level: u32, round: u32) -> std::vec::Vec<u8> {
+        let mut v = std::vec::Vec::with_capacity(4 + 32 + 1 + 2 + 4 + 4 + 32);
+        v.extend_from_slice(&1u32.to_be_bytes());
+        v.extend_from_slice(&[0; 32]);
+        v.push(ty as u8);
+        v.extend_from_slice(&7u16.to_be_bytes());
+        v.extend_from_slice(&level.to_be_bytes());
+        v.extend_from_slice(&round.to_be_bytes());
+        v.extend_from_slice(&[0xAA; 32]);
+        v
+    }
+
+    #[test]
+    fn preendorsement_watermark() {
+        let input = tenderbake_endorsement(EndorsementType::PreEndorsement, 42, 3);
+        let (rem, pre) = EndorsementData::from_bytes(&input, Preemble::TenderbakePreendorsement)
+            .expect("couldn't parse");
+        assert!(rem.is_empty());
+
+        match &pre {
+            EndorsementData::Tenderbake(tb) => {
+                assert_eq!(tb.ty, EndorsementType::PreEndorsement);
+                assert_eq!(tb.slot, 7);
+                assert_eq!(tb.block_payload_hash, &[0xAA; 32]);
+            }
+            EndorsementData::Emmy(_) => panic!("parsed as emmy endorsement"),
+        }
+        assert_eq!(pre.level(), 42);
+        assert_eq!(pre.round(), Some(3));
+
+        let hw = pre.derive_watermark();
+        //no double preendorsement
+        assert!(!pre.validate_with_watermark(&hw));
+
+        //endorsing after preendorsing the same round is allowed
+        let input = tenderbake_endorsement(EndorsementType::Endorsement, 42, 3);
+        let (_, endorsement) = EndorsementData::from_bytes(&input, Preemble::TenderbakeEndorsement)
+            .expect("couldn't parse");
+        assert!(endorsement.validate_with_watermark(&hw));
+
+        //but preendorsing after endorsing isn't
+        let hw = endorsement.derive_watermark();
+        assert!(!pre.validate_with_watermark(&hw));
+
+        //until the round moves forward
+        let input = tenderbake_endorsement(EndorsementType::PreEndorsement, 42, 4);
+        let (_, next) = EndorsementData::from_bytes(&input, Preemble::TenderbakePreendorsement)
+            .expect("couldn't parse");
+        assert!(next.validate_with_watermark(&hw));
+    }
+
+    #[test]
+    fn chain_id_base58() {
+        let mut input = tenderbake_endorsement(EndorsementType::Endorsement, 1, 0);
+        input[..4].copy_from_slice(&MAINNET_CHAIN_ID.to_be_bytes());
+
+        let (_, endorsement) = EndorsementData::from_bytes(&input, Preemble::TenderbakeEndorsement)
+            .expect("couldn't parse");
+        assert_eq!(endorsement.chain_id(), ChainID::Mainnet);
+
+        let (len, base58) = endorsement.chain_id().base58().unwrap();
+        assert_eq!(&base58[..len], b"NetXdQprcVkpaWU");
+    }
+
+    #[test]
+    fn emmy_endorsement_with_slot() {
+        let mut input = std::vec::Vec::new();
+        input.extend_from_slice(&1u32.to_be_bytes());
+        input.extend_from_slice(&[0; 32]);
+        input.push(10); //endorsement_with_slot
+        input.extend_from_slice(&(32 + 1 + 4 + 64u32).to_be_bytes());
+        input.extend_from_slice(&[0xBB; 32]);
+        input.push(0); //inlined endorsement
+        input.extend_from_slice(&42u32.to_be_bytes());
+        input.extend_from_slice(&[0xCC; 64]); //signature
+        input.extend_from_slice(&7u16.to_be_bytes());
+
+        let (rem, endorsement) =
+            EndorsementData::from_bytes(&input, Preemble::Endorsement).expect("couldn't parse");
+        assert!(rem.is_empty());
+        assert_eq!(endorsement.level(), 42);
+        match &endorsement {
+            EndorsementData::Emmy(emmy) => assert_eq!(emmy.slot, Some(7)),
+            EndorsementData::Tenderbake(_) => panic!("parsed as tenderbake endorsement"),
+        }
+
+        let hw = endorsement.derive_watermark();
+        assert!(!endorsement.validate_with_watermark(&hw));
+
+        //the format is picked from the preemble
+        assert!(EndorsementData::from_bytes(&input, Preemble::TenderbakeEndorsement).is_err());
+        let input = tenderbake_endorsement(EndorsementType::Endorsement, 42, 0);
+        assert!(EndorsementData::from_bytes(&input, Preemble::Endorsement).is_err());
+    }
 }
@@ -17,28 +17,48 @@
 use nom::{
     branch::alt,
     bytes::complete::{tag, take},
-    number::complete::be_u32,
+    number::complete::{be_u16, be_u32, le_u8},
     IResult,
 };
 
-use crate::handlers::{hwm::WaterMark, parser_common::ParserError};
+use crate::handlers::{
+    baking::hwm::{ChainID, WaterMark},
+    parser_common::ParserError,
+};
 
 pub struct EmmyEndorsement<'b> {
-    pub chain_id: u32,
+    pub chain_id: ChainID,
     pub branch: &'b [u8; 32],
     pub level: u32,
+    /// Only present in `endorsement_with_slot`
+    pub slot: Option<u16>,
 }
 
 impl<'b> EmmyEndorsement<'b> {
     const EMMY_ENDORSEMENT_TAG: u8 = 0;
+    const EMMY_ENDORSEMENT_WITH_SLOT_TAG: u8 = 10;
+
+    const SIGNATURE_LEN: usize = 64;
 
     #[inline(never)]
     pub fn from_bytes(bytes: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
-        let (rem, chain_id) = be_u32(bytes)?;
+        let (rem, chain_id) = ChainID::from_bytes(bytes)?;
         let (rem, branch) = take(32usize)(rem)?;
         let branch = arrayref::array_ref!(branch, 0, 32);
-        let (rem, _) = tag(&[Self::EMMY_ENDORSEMENT_TAG])(rem)?;
-        let (rem, level) = be_u32(rem)?;
+        let (rem, kind) = le_u8(rem)?;
+
+        let (rem, level, slot) = match kind {
+            Self::EMMY_ENDORSEMENT_TAG => {
+                let (rem, level) = be_u32(rem)?;
+                (rem, level, None)
+            }
+            Self::EMMY_ENDORSEMENT_WITH_SLOT_TAG => {
+                let (rem, level) = Self::inlined_endorsement(rem)?;
+                let (rem, slot) = be_u16(rem)?;
+                (rem, level, Some(slot))
+            }
+            _ => return Err(ParserError::InvalidEndorsementType.into()),
+        };
 
         Ok((
             rem,
@@ -46,10 +66,30 @@ impl<'b> EmmyEndorsement<'b> {
                 chain_id,
                 branch,
                 level,
+                slot,
             },
         ))
     }
 
+    /// Reads the signed endorsement wrapped by `endorsement_with_slot`,
+    /// returning its level
+    fn inlined_endorsement(input: &'b [u8]) -> IResult<&[u8], u32, ParserError> {
+        let (rem, len) = be_u32(input)?;
+        let (rem, inlined) = take(len as usize)(rem)?;
+
+        //branch, then the endorsement itself and its signature
+        let (inlined, _) = take(32usize)(inlined)?;
+        let (inlined, _) = tag(&[Self::EMMY_ENDORSEMENT_TAG])(inlined)?;
+        let (inlined, level) = be_u32(inlined)?;
+        let (inlined, _) = take(Self::SIGNATURE_LEN)(inlined)?;
+
+        if !inlined.is_empty() {
+            return Err(ParserError::parser_unexpected_value.into());
+        }
+
+        Ok((rem, level))
+    }
+
     #[inline(never)]
     pub fn validate_with_watermark(&self, hw: &WaterMark) -> bool {
         WaterMark::is_valid_blocklevel(self.level)
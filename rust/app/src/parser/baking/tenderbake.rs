@@ -19,7 +19,10 @@ use nom::{
     IResult,
 };
 
-use crate::handlers::{hwm::WaterMark, parser_common::ParserError};
+use crate::handlers::{
+    baking::hwm::{ChainID, WaterMark},
+    parser_common::ParserError,
+};
 
 #[repr(u8)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -42,7 +45,7 @@ impl EndorsementType {
 }
 
 pub struct TenderbakeEndorsement<'b> {
-    pub chain_id: u32,
+    pub chain_id: ChainID,
     pub branch: &'b [u8; 32],
 
     pub ty: EndorsementType,
@@ -55,7 +58,7 @@ pub struct TenderbakeEndorsement<'b> {
 impl<'b> TenderbakeEndorsement<'b> {
     #[inline(never)]
     pub fn from_bytes(bytes: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
-        let (rem, chain_id) = be_u32(bytes)?;
+        let (rem, chain_id) = ChainID::from_bytes(bytes)?;
         let (rem, branch) = take(32usize)(rem)?;
         let branch = arrayref::array_ref!(branch, 0, 32);
 
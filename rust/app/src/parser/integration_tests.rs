@@ -21,6 +21,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{Map, Value};
 use zuit::MockDriver;
 
+use crate::handlers::parser_common::ParserError;
 use crate::parser::operations::{AnonymousOp, Operation};
 use crate::utils::strlen;
 
@@ -365,6 +366,64 @@ fn test_vectors() {
     )
 }
 
+//parses `blob` until the end or the first error,
+// returning the offset where each operation ends
+fn parse_all(blob: &[u8]) -> Result<Vec<usize>, nom::Err<ParserError>> {
+    let mut parsed = Operation::new(blob).map_err(nom::Err::Error)?;
+
+    let ops = parsed.mut_ops();
+    //the branch alone is a valid input
+    let mut ends = vec![32];
+    while ops.parse_next()?.is_some() {
+        ends.push(32 + ops.source_index());
+    }
+
+    Ok(ends)
+}
+
+#[test]
+fn truncated_samples() {
+    let samples: Vec<Sample> = get_json_from_data(data_dir_path().join("samples.json"));
+
+    for (i, Sample { blob, .. }) in samples.into_iter().enumerate() {
+        let blob = hex::decode(&blob)
+            .unwrap_or_else(|e| panic!("sample #{} .blob wasn't a hex string; err: {:?}", i, e));
+
+        let ends = parse_all(&blob)
+            .unwrap_or_else(|e| panic!("sample #{} couldn't be parsed; err: {:?}", i, e));
+        assert_eq!(
+            ends.last(),
+            Some(&blob.len()),
+            "sample #{} wasn't fully parsed",
+            i
+        );
+
+        //cutting the blob between 2 operations is still a valid input
+        // but anywhere else it should always be reported as an unexpected EOF
+        for len in 0..blob.len() {
+            let result = parse_all(&blob[..len]);
+
+            match ends.iter().position(|&end| end == len) {
+                //only the operations before the cut are parsed
+                Some(n) => assert_eq!(
+                    result,
+                    Ok(ends[..=n].to_vec()),
+                    "sample #{} truncated to {} bytes between operations",
+                    i,
+                    len
+                ),
+                None => assert_eq!(
+                    result,
+                    Err(nom::Err::Error(ParserError::UnexpectedEof)),
+                    "sample #{} truncated to {} bytes wasn't an unexpected EOF",
+                    i,
+                    len
+                ),
+            }
+        }
+    }
+}
+
 fn verify_operation<'b>(
     op: OperationType<'b>,
     json: &Map<String, Value>,
@@ -394,6 +453,25 @@ fn verify_operation<'b>(
         (OperationType::Origination(orig), "origination") => orig.is(json),
         (OperationType::ActivateAccount(act), "activate_account") => act.is(json),
         (OperationType::FailingNoop(fail), "failing_noop") => fail.is(json),
+        (OperationType::SeedNonceRevelation(snr), "seed_nonce_revelation") => snr.is(json),
+        (OperationType::RegisterGlobalConstant(reg), "register_global_constant") => reg.is(json),
+        (OperationType::SetDepositsLimit(limit), "set_deposits_limit") => limit.is(json),
+        (OperationType::IncreasePaidStorage(storage), "increase_paid_storage") => storage.is(json),
+        (OperationType::UpdateConsensusKey(key), "update_consensus_key") => key.is(json),
+        (OperationType::DrainDelegate(drain), "drain_delegate") => drain.is(json),
+        (OperationType::SmartRollupOriginate(orig), "smart_rollup_originate") => orig.is(json),
+        (OperationType::SmartRollupAddMessages(add), "smart_rollup_add_messages") => add.is(json),
+        (
+            OperationType::SmartRollupExecuteOutboxMessage(exec),
+            "smart_rollup_execute_outbox_message",
+        ) => exec.is(json),
+        (OperationType::SmartRollupCement(cement), "smart_rollup_cement") => cement.is(json),
+        (OperationType::SmartRollupPublish(publish), "smart_rollup_publish") => publish.is(json),
+        (OperationType::SmartRollupRefute(refute), "smart_rollup_refute") => refute.is(json),
+        (OperationType::SmartRollupTimeout(timeout), "smart_rollup_timeout") => timeout.is(json),
+        (OperationType::SmartRollupRecoverBond(recover), "smart_rollup_recover_bond") => {
+            recover.is(json)
+        }
         (
             OperationType::AnonymousOp(AnonymousOp::DoubleBakingEvidence(bak)),
             "double_baking_evidence",
@@ -402,10 +480,6 @@ fn verify_operation<'b>(
             OperationType::AnonymousOp(AnonymousOp::DoubleEndorsementEvidence(endorsement)),
             "double_endorsement_evidence",
         ) => endorsement.is(json),
-        (
-            OperationType::AnonymousOp(AnonymousOp::SeedNonceRevelation(snr)),
-            "seed_nonce_revelation",
-        ) => snr.is(json),
         (op, other) => panic!(
             "sample {}[{}]; expected op kind: {}, parsed as: {:?}",
             sample_name, op_n, other, op
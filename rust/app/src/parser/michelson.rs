@@ -0,0 +1,1498 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Decoding and rendering of Michelson values found in contract call parameters
+
+use bolos::PIC;
+
+use crate::handlers::parser_common::ParserError;
+
+use super::{operations::ContractID, public_key_hash, Zarith};
+
+/// Tags of the binary Micheline encoding
+mod tag {
+    pub const INT: u8 = 0x00;
+    pub const STRING: u8 = 0x01;
+    pub const SEQ: u8 = 0x02;
+    pub const PRIM: u8 = 0x03;
+    pub const PRIM_ANNOTS: u8 = 0x04;
+    pub const PRIM_1_ARG: u8 = 0x05;
+    pub const PRIM_1_ARG_ANNOTS: u8 = 0x06;
+    pub const PRIM_2_ARGS: u8 = 0x07;
+    pub const PRIM_2_ARGS_ANNOTS: u8 = 0x08;
+    pub const PRIM_N_ARGS: u8 = 0x09;
+    pub const BYTES: u8 = 0x0A;
+}
+
+/// Michelson primitives codes
+mod prim {
+    pub const FALSE: u8 = 0x03;
+    pub const LEFT: u8 = 0x05;
+    pub const NONE: u8 = 0x06;
+    pub const PAIR: u8 = 0x07;
+    pub const RIGHT: u8 = 0x08;
+    pub const SOME: u8 = 0x09;
+    pub const TRUE: u8 = 0x0A;
+    pub const UNIT: u8 = 0x0B;
+}
+
+/// Michelson instructions codes
+mod instr {
+    pub const CONS: u8 = 0x1B;
+    pub const IMPLICIT_ACCOUNT: u8 = 0x1E;
+    pub const DROP: u8 = 0x20;
+    pub const FAILWITH: u8 = 0x27;
+    pub const IF_NONE: u8 = 0x2F;
+    pub const NIL: u8 = 0x3D;
+    pub const NONE: u8 = 0x3E;
+    pub const PUSH: u8 = 0x43;
+    pub const SOME: u8 = 0x46;
+    pub const TRANSFER_TOKENS: u8 = 0x4D;
+    pub const SET_DELEGATE: u8 = 0x4E;
+    pub const UNIT: u8 = 0x4F;
+    pub const CONTRACT: u8 = 0x55;
+}
+
+/// Michelson types codes
+mod ty {
+    pub const KEY_HASH: u8 = 0x5D;
+    pub const MUTEZ: u8 = 0x6A;
+    pub const UNIT: u8 = 0x6C;
+    pub const OPERATION: u8 = 0x6D;
+    pub const ADDRESS: u8 = 0x6E;
+}
+
+/// Names of the Michelson primitives, separated by spaces and ordered by code
+const PRIM_NAMES: &[u8] = b"parameter storage code False Elt Left None Pair Right Some True Unit \
+PACK UNPACK BLAKE2B SHA256 SHA512 ABS ADD AMOUNT AND BALANCE CAR CDR CHECK_SIGNATURE COMPARE \
+CONCAT CONS CREATE_ACCOUNT CREATE_CONTRACT IMPLICIT_ACCOUNT DIP DROP DUP EDIV EMPTY_MAP EMPTY_SET \
+EQ EXEC FAILWITH GE GET GT HASH_KEY IF IF_CONS IF_LEFT IF_NONE INT LAMBDA LE LEFT LOOP LSL LSR LT \
+MAP MEM MUL NEG NEQ NIL NONE NOT NOW OR PAIR PUSH RIGHT SIZE SOME SOURCE SENDER SELF \
+STEPS_TO_QUOTA SUB SWAP TRANSFER_TOKENS SET_DELEGATE UNIT UPDATE XOR ITER LOOP_LEFT ADDRESS \
+CONTRACT ISNAT CAST RENAME bool contract int key key_hash lambda list map big_map nat option or \
+pair set signature string bytes mutez timestamp unit operation address SLICE DIG DUG \
+EMPTY_BIG_MAP APPLY chain_id CHAIN_ID LEVEL SELF_ADDRESS never NEVER UNPAIR VOTING_POWER \
+TOTAL_VOTING_POWER KECCAK SHA3 PAIRING_CHECK bls12_381_g1 bls12_381_g2 bls12_381_fr \
+sapling_state sapling_transaction_deprecated SAPLING_EMPTY_STATE SAPLING_VERIFY_UPDATE ticket \
+TICKET_DEPRECATED READ_TICKET SPLIT_TICKET JOIN_TICKETS GET_AND_UPDATE chest chest_key \
+OPEN_CHEST VIEW view constant SUB_MUTEZ tx_rollup_l2_address MIN_BLOCK_TIME sapling_transaction \
+EMIT Lambda_rec LAMBDA_REC TICKET BYTES NAT";
+
+/// Retrieve the name of the primitive with the given code
+pub fn prim_name(code: u8) -> Option<&'static [u8]> {
+    PIC::new(PRIM_NAMES)
+        .into_inner()
+        .split(|&c| c == b' ')
+        .nth(code as usize)
+}
+
+pub const MAX_RENDERED_LEN: usize = 128;
+
+/// Maximum nesting of sequences and primitive applications supported by [`render`]
+pub const MAX_DEPTH: usize = 16;
+
+struct Writer<'o> {
+    out: &'o mut [u8; MAX_RENDERED_LEN],
+    len: usize,
+}
+
+impl<'o> Writer<'o> {
+    fn write(&mut self, bytes: &[u8]) -> Option<()> {
+        let dest = self.out.get_mut(self.len..self.len + bytes.len())?;
+        dest.copy_from_slice(bytes);
+        self.len += bytes.len();
+
+        Some(())
+    }
+
+    /// Writes a Michelson string literal, quoted and escaped
+    ///
+    /// Returns None if the string isn't valid, see [`is_printable`]
+    fn write_string(&mut self, string: &[u8]) -> Option<()> {
+        self.write(b"\"")?;
+        for &c in string {
+            match c {
+                b'"' => self.write(b"\\\"")?,
+                b'\\' => self.write(b"\\\\")?,
+                b'\n' => self.write(b"\\n")?,
+                b' '..=b'~' => self.write(&[c])?,
+                _ => return None,
+            }
+        }
+        self.write(b"\"")
+    }
+
+    fn write_hex(&mut self, bytes: &[u8]) -> Option<()> {
+        let dest = self.out.get_mut(self.len..self.len + bytes.len() * 2)?;
+        hex::encode_to_slice(bytes, dest).ok()?;
+        self.len += bytes.len() * 2;
+
+        Some(())
+    }
+}
+
+/// Michelson strings can only have printable ASCII characters and newlines
+fn is_printable(string: &[u8]) -> bool {
+    string
+        .iter()
+        .all(|&c| c == b'\n' || (b' '..=b'~').contains(&c))
+}
+
+/// Read a 4 bytes big endian length prefix, checking that as many bytes follow
+fn read_len(input: &[u8]) -> Option<(&[u8], usize)> {
+    if input.len() < 4 {
+        return None;
+    }
+
+    let (len, rest) = input.split_at(4);
+    let len = u32::from_be_bytes(*arrayref::array_ref!(len, 0, 4)) as usize;
+
+    if len > rest.len() {
+        None
+    } else {
+        Some((rest, len))
+    }
+}
+
+/// Where the children of an open node end
+#[derive(Clone, Copy)]
+enum Children {
+    /// Number of arguments left to render
+    Args(u8),
+    /// Children end when the remaining input is this long
+    UpTo(usize),
+}
+
+/// Node with children which hasn't been closed yet
+#[derive(Clone, Copy)]
+struct Frame {
+    children: Children,
+    seq: bool,
+    first: bool,
+    wrapped: bool,
+    annots: bool,
+}
+
+impl Frame {
+    const EMPTY: Self = Self {
+        children: Children::Args(0),
+        seq: false,
+        first: true,
+        wrapped: false,
+        annots: false,
+    };
+
+    fn has_children(&self, remaining: usize) -> bool {
+        match self.children {
+            Children::Args(n) => n > 0,
+            Children::UpTo(end) => remaining > end,
+        }
+    }
+
+    /// Advance to the next child, returning false once all children were rendered
+    ///
+    /// Returns None if the last child overran the node
+    fn next_child(&mut self, remaining: usize) -> Option<bool> {
+        let next = match &mut self.children {
+            Children::Args(0) => false,
+            Children::Args(n) => {
+                *n -= 1;
+                true
+            }
+            Children::UpTo(end) if remaining > *end => true,
+            Children::UpTo(end) if remaining == *end => false,
+            Children::UpTo(_) => return None,
+        };
+
+        Some(next)
+    }
+}
+
+/// Renders a single node, returning the frame to open
+/// if the node can have children
+fn render_node<'i>(
+    input: &'i [u8],
+    w: &mut Writer,
+    in_prim: bool,
+) -> Option<(&'i [u8], Option<Frame>)> {
+    let (tag, rest) = input.split_first()?;
+
+    match *tag {
+        tag::INT => {
+            let (rest, num) = Zarith::from_bytes(rest, true, Zarith::MICHELSON_INT_MAX_LEN).ok()?;
+
+            let mut buf = [0; Zarith::FORMATTED_SIZE];
+            match num.format_into(&mut buf) {
+                Some(num) => w.write(num)?,
+                //too big to be formatted
+                None => w.write(PIC::new(b"...").into_inner())?,
+            }
+
+            Some((rest, None))
+        }
+        tag::STRING => {
+            let (rest, len) = read_len(rest)?;
+            let (string, rest) = rest.split_at(len);
+
+            w.write_string(string)?;
+
+            Some((rest, None))
+        }
+        tag::BYTES => {
+            let (rest, len) = read_len(rest)?;
+            let (bytes, rest) = rest.split_at(len);
+
+            w.write(PIC::new(b"0x").into_inner())?;
+            w.write_hex(bytes)?;
+
+            Some((rest, None))
+        }
+        tag::SEQ => {
+            let (rest, len) = read_len(rest)?;
+            w.write(b"{")?;
+
+            let frame = Frame {
+                children: Children::UpTo(rest.len() - len),
+                seq: true,
+                ..Frame::EMPTY
+            };
+
+            Some((rest, Some(frame)))
+        }
+        tag::PRIM..=tag::PRIM_N_ARGS => {
+            let (code, rest) = rest.split_first()?;
+            let name = prim_name(*code)?;
+
+            let (rest, children) = match *tag {
+                tag::PRIM | tag::PRIM_ANNOTS => (rest, Children::Args(0)),
+                tag::PRIM_1_ARG | tag::PRIM_1_ARG_ANNOTS => (rest, Children::Args(1)),
+                tag::PRIM_2_ARGS | tag::PRIM_2_ARGS_ANNOTS => (rest, Children::Args(2)),
+                _ => {
+                    let (rest, len) = read_len(rest)?;
+                    (rest, Children::UpTo(rest.len() - len))
+                }
+            };
+
+            let mut frame = Frame {
+                children,
+                annots: matches!(
+                    *tag,
+                    tag::PRIM_ANNOTS
+                        | tag::PRIM_1_ARG_ANNOTS
+                        | tag::PRIM_2_ARGS_ANNOTS
+                        | tag::PRIM_N_ARGS
+                ),
+                ..Frame::EMPTY
+            };
+
+            //applications are wrapped when they are arguments themselves
+            frame.wrapped = in_prim && frame.has_children(rest.len());
+            if frame.wrapped {
+                w.write(b"(")?;
+            }
+            w.write(name)?;
+
+            Some((rest, Some(frame)))
+        }
+        _ => None,
+    }
+}
+
+/// Renders a Micheline expression using the Michelson syntax
+/// (ie: "Pair 1 (Some \"foo\")", "{1; 2}")
+///
+/// The expression is traversed iteratively, keeping the open nodes
+/// in a fixed-size worklist, so the stack usage doesn't depend on the input
+///
+/// Annotations are skipped
+///
+/// Strings are escaped like in Michelson (ie: "\"a\\nb\"")
+///
+/// Returns None if the expression is malformed (including strings
+/// Michelson doesn't allow), if it's nested deeper than [`MAX_DEPTH`]
+/// or if it doesn't fit in `out`
+pub fn render(input: &[u8], out: &mut [u8; MAX_RENDERED_LEN]) -> Option<usize> {
+    let mut w = Writer { out, len: 0 };
+    let mut stack = [Frame::EMPTY; MAX_DEPTH];
+    let mut depth = 0;
+    let mut rest = input;
+
+    loop {
+        let in_prim = depth > 0 && !stack[depth - 1].seq;
+        let (remaining, frame) = render_node(rest, &mut w, in_prim)?;
+        rest = remaining;
+
+        if let Some(frame) = frame {
+            *stack.get_mut(depth)? = frame;
+            depth += 1;
+        }
+
+        //close the completed nodes, until there's a child to render
+        loop {
+            let top = match depth.checked_sub(1) {
+                Some(i) => &mut stack[i],
+                //the whole expression has been rendered
+                None if rest.is_empty() => return Some(w.len),
+                None => return None,
+            };
+
+            let first = top.first;
+            if top.next_child(rest.len())? {
+                top.first = false;
+
+                match (top.seq, first) {
+                    (true, true) => {}
+                    (true, false) => w.write(PIC::new(b"; ").into_inner())?,
+                    (false, _) => w.write(b" ")?,
+                }
+                break;
+            }
+
+            if top.seq {
+                w.write(b"}")?;
+            } else if top.wrapped {
+                w.write(b")")?;
+            }
+
+            if top.annots {
+                let (remaining, len) = read_len(rest)?;
+                rest = &remaining[len..];
+            }
+
+            depth -= 1;
+        }
+    }
+}
+
+/// Computes the hash of a Michelson value, as used in `expr` hashes
+///
+/// This is the blake2b digest of the packed value
+pub fn expr_hash(michelson: &[u8]) -> Result<[u8; 32], bolos::Error> {
+    use bolos::hash::{Blake2b, Hasher};
+    use core::mem::MaybeUninit;
+
+    //pack prefix
+    const PACKED: u8 = 0x05;
+
+    let mut hasher = {
+        let mut loc = MaybeUninit::<Blake2b<32>>::uninit();
+        Blake2b::new_gce(&mut loc)?;
+
+        unsafe { loc.assume_init() }
+    };
+
+    hasher.update(&[PACKED])?;
+    hasher.update(michelson)?;
+
+    let mut out = [0; 32];
+    hasher.finalize_into(&mut out)?;
+
+    Ok(out)
+}
+
+/// Micheline node decoded from its binary form
+///
+/// The children of sequences and primitive applications
+/// are decoded on demand with [`Args`], so no allocation is required
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Node<'i> {
+    Int(Zarith<'i>),
+    String(&'i [u8]),
+    Bytes(&'i [u8]),
+    /// Application of the primitive with the given code
+    ///
+    /// `annots` are the space separated annotations (ie: "%from :owner")
+    Prim {
+        code: u8,
+        args: Args<'i>,
+        annots: &'i [u8],
+    },
+    Seq(Args<'i>),
+}
+
+/// Kind of a Michelson annotation, given by its first character
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum AnnotKind {
+    /// `%to`
+    Field,
+    /// `:amount`
+    Type,
+    /// `@sender`
+    Variable,
+}
+
+/// Annotation of a primitive application, without its prefix
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Annot<'i> {
+    pub kind: AnnotKind,
+    pub name: &'i [u8],
+}
+
+/// Annotations of a primitive application, yielded one by one
+///
+/// Annotations with an unknown prefix are skipped
+#[derive(Clone, Copy)]
+pub struct Annots<'i> {
+    input: &'i [u8],
+}
+
+impl<'i> Iterator for Annots<'i> {
+    type Item = Annot<'i>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (annot, rest) = match self.input.iter().position(|&c| c == b' ') {
+                Some(i) => (&self.input[..i], &self.input[i + 1..]),
+                None => (self.input, &[][..]),
+            };
+            self.input = rest;
+
+            let kind = match annot.split_first() {
+                Some((b'%', _)) => AnnotKind::Field,
+                Some((b':', _)) => AnnotKind::Type,
+                Some((b'@', _)) => AnnotKind::Variable,
+                Some(_) => continue,
+                None if self.input.is_empty() => return None,
+                None => continue,
+            };
+
+            return Some(Annot {
+                kind,
+                name: &annot[1..],
+            });
+        }
+    }
+}
+
+/// Encoded children of a [`Node`], yielded one by one
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Args<'i> {
+    input: &'i [u8],
+}
+
+impl<'i> Args<'i> {
+    /// Children of the primitive application `input` starts with,
+    /// which has `n` arguments
+    fn take(input: &'i [u8], n: u8) -> Result<(&'i [u8], Self), nom::Err<ParserError>> {
+        let mut rest = input;
+        for _ in 0..n {
+            rest = skip_node(rest)?;
+        }
+
+        let args = Self {
+            input: &input[..input.len() - rest.len()],
+        };
+        Ok((rest, args))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+}
+
+impl<'i> Iterator for Args<'i> {
+    type Item = Result<Node<'i>, nom::Err<ParserError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+
+        match Node::from_bytes(self.input) {
+            Ok((rem, node)) => {
+                self.input = rem;
+                Some(Ok(node))
+            }
+            Err(e) => {
+                //don't yield anything else after an error
+                self.input = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Like [`read_len`], but splits the content from the input
+fn take_len_prefixed(input: &[u8]) -> Result<(&[u8], &[u8]), nom::Err<ParserError>> {
+    let (rest, len) = read_len(input).ok_or(nom::Err::Error(ParserError::UnexpectedEof))?;
+    let (content, rest) = rest.split_at(len);
+
+    Ok((rest, content))
+}
+
+/// Skips the node `input` starts with, returning what follows it
+///
+/// Like [`render`], the nodes are traversed iteratively and
+/// applications can't be nested deeper than [`MAX_DEPTH`]
+fn skip_node(input: &[u8]) -> Result<&[u8], nom::Err<ParserError>> {
+    //arguments left to skip for each open application,
+    // and whether annotations follow them
+    let mut stack = [(0u8, false); MAX_DEPTH];
+    let mut depth = 0;
+    let mut rest = input;
+
+    loop {
+        let (tag, after) = rest
+            .split_first()
+            .ok_or(nom::Err::Error(ParserError::UnexpectedEof))?;
+
+        rest = match *tag {
+            tag::INT => Zarith::from_bytes(after, true, Zarith::MICHELSON_INT_MAX_LEN)?.0,
+            //sequences are skipped at once thanks to their length prefix
+            tag::STRING | tag::BYTES | tag::SEQ => take_len_prefixed(after)?.0,
+            tag::PRIM..=tag::PRIM_N_ARGS => {
+                let (_, after) = after
+                    .split_first()
+                    .ok_or(nom::Err::Error(ParserError::UnexpectedEof))?;
+
+                match *tag {
+                    tag::PRIM => after,
+                    tag::PRIM_ANNOTS => take_len_prefixed(after)?.0,
+                    tag::PRIM_N_ARGS => take_len_prefixed(take_len_prefixed(after)?.0)?.0,
+                    _ => {
+                        let n = if *tag <= tag::PRIM_1_ARG_ANNOTS { 1 } else { 2 };
+                        let annots =
+                            matches!(*tag, tag::PRIM_1_ARG_ANNOTS | tag::PRIM_2_ARGS_ANNOTS);
+
+                        *stack
+                            .get_mut(depth)
+                            .ok_or(nom::Err::Error(ParserError::parser_value_out_of_range))? =
+                            (n, annots);
+                        depth += 1;
+
+                        after
+                    }
+                }
+            }
+            _ => return Err(ParserError::parser_unexpected_type.into()),
+        };
+
+        //close the completed applications, until there's an argument to skip
+        loop {
+            let (args, annots) = match depth.checked_sub(1) {
+                Some(i) => &mut stack[i],
+                None => return Ok(rest),
+            };
+
+            if *args > 0 {
+                *args -= 1;
+                break;
+            }
+
+            if *annots {
+                rest = take_len_prefixed(rest)?.0;
+            }
+            depth -= 1;
+        }
+    }
+}
+
+impl<'i> Node<'i> {
+    /// Annotations of the node, only primitive applications can have some
+    pub fn annots(&self) -> Annots<'i> {
+        match self {
+            Self::Prim { annots, .. } => Annots { input: annots },
+            _ => Annots { input: &[] },
+        }
+    }
+
+    /// Name of the first annotation of the given kind, if any
+    ///
+    /// Field annotations are the names of the entrypoints' parameters,
+    /// useful to label their values (ie: "to", "amount")
+    pub fn annot(&self, kind: AnnotKind) -> Option<&'i [u8]> {
+        self.annots()
+            .find(|annot| annot.kind == kind)
+            .map(|annot| annot.name)
+    }
+
+    #[inline(never)]
+    pub fn from_bytes(input: &'i [u8]) -> nom::IResult<&'i [u8], Self, ParserError> {
+        let (tag, rest) = input
+            .split_first()
+            .ok_or(nom::Err::Error(ParserError::UnexpectedEof))?;
+
+        match *tag {
+            tag::INT => {
+                let (rest, num) = Zarith::from_bytes(rest, true, Zarith::MICHELSON_INT_MAX_LEN)?;
+                Ok((rest, Self::Int(num)))
+            }
+            tag::STRING => {
+                let (rest, string) = take_len_prefixed(rest)?;
+                Ok((rest, Self::String(string)))
+            }
+            tag::BYTES => {
+                let (rest, bytes) = take_len_prefixed(rest)?;
+                Ok((rest, Self::Bytes(bytes)))
+            }
+            tag::SEQ => {
+                let (rest, input) = take_len_prefixed(rest)?;
+                Ok((rest, Self::Seq(Args { input })))
+            }
+            tag::PRIM..=tag::PRIM_N_ARGS => {
+                let (code, rest) = rest
+                    .split_first()
+                    .ok_or(nom::Err::Error(ParserError::UnexpectedEof))?;
+
+                let (rest, args) = match *tag {
+                    tag::PRIM | tag::PRIM_ANNOTS => Args::take(rest, 0)?,
+                    tag::PRIM_1_ARG | tag::PRIM_1_ARG_ANNOTS => Args::take(rest, 1)?,
+                    tag::PRIM_2_ARGS | tag::PRIM_2_ARGS_ANNOTS => Args::take(rest, 2)?,
+                    _ => {
+                        let (rest, input) = take_len_prefixed(rest)?;
+                        (rest, Args { input })
+                    }
+                };
+
+                let (rest, annots) = match *tag {
+                    tag::PRIM_ANNOTS
+                    | tag::PRIM_1_ARG_ANNOTS
+                    | tag::PRIM_2_ARGS_ANNOTS
+                    | tag::PRIM_N_ARGS => take_len_prefixed(rest)?,
+                    _ => (rest, &[][..]),
+                };
+
+                let node = Self::Prim {
+                    code: *code,
+                    args,
+                    annots,
+                };
+                Ok((rest, node))
+            }
+            _ => Err(ParserError::parser_unexpected_type.into()),
+        }
+    }
+}
+
+/// Skips an address, either in its readable (string)
+/// or optimized (bytes) form
+fn skip_address(input: &[u8]) -> Option<&[u8]> {
+    match input.split_first()? {
+        (&tag::STRING, rest) | (&tag::BYTES, rest) => {
+            let (rest, len) = read_len(rest)?;
+            Some(&rest[len..])
+        }
+        _ => None,
+    }
+}
+
+/// Finds the amount in the parameters of a FA1.2 `transfer` or `approve`
+///
+/// `transfer` takes `Pair from (Pair to value)`
+/// and `approve` takes `Pair spender value`
+///
+/// Returns None if the entrypoint is neither, or if the parameters
+/// don't have the expected shape
+pub fn fa12_amount<'i>(entrypoint: &[u8], input: &'i [u8]) -> Option<Zarith<'i>> {
+    const PAIR: [u8; 2] = [tag::PRIM_2_ARGS, prim::PAIR];
+
+    let rest = input.strip_prefix(&PAIR[..])?;
+    let rest = skip_address(rest)?;
+
+    let rest = if entrypoint == PIC::new(b"transfer").into_inner() {
+        let rest = rest.strip_prefix(&PAIR[..])?;
+        skip_address(rest)?
+    } else if entrypoint == PIC::new(b"approve").into_inner() {
+        rest
+    } else {
+        return None;
+    };
+
+    match rest.split_first()? {
+        (&tag::INT, num) => match Zarith::from_bytes(num, true, Zarith::MICHELSON_INT_MAX_LEN) {
+            //amounts are nats
+            Ok(([], num)) if num.is_negative() != Some(true) => Some(num),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Address found in a Michelson value
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Address<'i> {
+    /// Base58 string, as written by the user
+    Readable(&'i [u8]),
+    /// Binary encoded contract id
+    Optimized(ContractID<'i>),
+}
+
+impl<'i> Address<'i> {
+    /// Length of the base58 form of tz and KT1 addresses
+    const READABLE_LEN: usize = 36;
+
+    /// Reads the address in `node`, without entrypoint
+    fn from_node(node: Node<'i>) -> Option<Self> {
+        match node {
+            Node::String(s) if Self::is_valid_base58(s) => Some(Self::Readable(s)),
+            Node::Bytes(b) => match ContractID::from_bytes(b) {
+                Ok(([], id)) => Some(Self::Optimized(id)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Checks that `s` is the base58check encoding of a tz or KT1 address
+    fn is_valid_base58(s: &[u8]) -> bool {
+        use crate::{
+            constants::tzprefix::{KT1, TZ1, TZ2, TZ3, TZ4},
+            handlers::sha256x2,
+        };
+
+        if s.len() != Self::READABLE_LEN {
+            return false;
+        }
+
+        //prefix, hash and checksum
+        let mut decoded = [0; 3 + 20 + 4];
+        match bs58::decode(s).into(&mut decoded[..]) {
+            Ok(len) if len == decoded.len() => {}
+            _ => return false,
+        }
+
+        let (payload, checksum) = decoded.split_at(3 + 20);
+        if ![TZ1, TZ2, TZ3, TZ4, KT1].contains(&&payload[..3]) {
+            return false;
+        }
+
+        let mut expected = [0; 4];
+        sha256x2(&[payload], &mut expected).is_ok() && expected[..] == *checksum
+    }
+
+    /// Reads the key hash in `node`
+    fn from_key_hash_node(node: Node<'i>) -> Option<Self> {
+        match node {
+            Node::Bytes(b) => match public_key_hash(b) {
+                Ok(([], (curve, hash))) => Some(Self::Optimized(ContractID::Implicit(curve, hash))),
+                _ => None,
+            },
+            Node::String([b't', b'z', ..]) => Self::from_node(node),
+            _ => None,
+        }
+    }
+
+    /// Writes the base58 form of the address to `out`, returning its length
+    pub fn base58_into(
+        &self,
+        out: &mut [u8; ContractID::BASE58_LEN],
+    ) -> Result<usize, bolos::Error> {
+        match self {
+            Self::Readable(s) => {
+                out[..s.len()].copy_from_slice(s);
+                Ok(s.len())
+            }
+            Self::Optimized(id) => {
+                let (len, base58) = id.base58()?;
+                out.copy_from_slice(&base58[..]);
+                Ok(len)
+            }
+        }
+    }
+}
+
+/// Arguments of `node` if it's an application of `code`
+fn application(node: Node<'_>, code: u8) -> Option<Args<'_>> {
+    match node {
+        Node::Prim { code: c, args, .. } if c == code => Some(args),
+        _ => None,
+    }
+}
+
+/// Checks that `node` is an application of `code` without arguments
+fn nullary(node: Node<'_>, code: u8) -> Option<()> {
+    if application(node, code)?.is_empty() {
+        Some(())
+    } else {
+        None
+    }
+}
+
+/// Argument of `node` if it's an application of `code` with a single argument
+fn unary(node: Node<'_>, code: u8) -> Option<Node<'_>> {
+    let mut args = application(node, code)?;
+    let arg = args.next()?.ok()?;
+
+    match args.next() {
+        None => Some(arg),
+        Some(_) => None,
+    }
+}
+
+/// Arguments of `node` if it's an application of `code` with two arguments
+fn binary(node: Node<'_>, code: u8) -> Option<(Node<'_>, Node<'_>)> {
+    let mut args = application(node, code)?;
+    let first = args.next()?.ok()?;
+    let second = args.next()?.ok()?;
+
+    match args.next() {
+        None => Some((first, second)),
+        Some(_) => None,
+    }
+}
+
+/// Unwraps the sequence octez expands macros into (ie: `{ IF_NONE {..} {..} }`)
+///
+/// Returns `node` itself if it's not a sequence of a single node
+fn expanded(node: Node<'_>) -> Option<Node<'_>> {
+    let mut seq = match node {
+        Node::Seq(seq) => seq,
+        node => return Some(node),
+    };
+
+    let inner = match seq.next() {
+        Some(inner) => inner.ok()?,
+        None => return Some(node),
+    };
+    match seq.next() {
+        None => Some(inner),
+        Some(_) => Some(node),
+    }
+}
+
+/// Checks that `node` is the expansion of the `FAIL` macro, `{ UNIT ; FAILWITH }`
+fn fail(node: Node<'_>) -> Option<()> {
+    let mut seq = match expanded(node)? {
+        Node::Seq(seq) => seq,
+        _ => return None,
+    };
+
+    nullary(seq.next()?.ok()?, instr::UNIT)?;
+    nullary(seq.next()?.ok()?, instr::FAILWITH)?;
+    match seq.next() {
+        None => Some(()),
+        Some(_) => None,
+    }
+}
+
+/// Splits `Pair a b` in its two elements
+fn pair(node: Node<'_>) -> Option<(Node<'_>, Node<'_>)> {
+    binary(node, prim::PAIR)
+}
+
+/// Parameters of a FA1.2 `transfer`
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Fa12Transfer<'i> {
+    pub from: Address<'i>,
+    pub to: Address<'i>,
+    pub amount: Zarith<'i>,
+}
+
+impl<'i> Fa12Transfer<'i> {
+    /// Decodes the parameters of a `transfer`, `Pair from (Pair to value)`
+    ///
+    /// Returns None if the parameters don't have this shape
+    pub fn from_bytes(input: &'i [u8]) -> Option<Self> {
+        let node = match Node::from_bytes(input) {
+            Ok(([], node)) => node,
+            _ => return None,
+        };
+
+        let (from, rest) = pair(node)?;
+        let (to, amount) = pair(rest)?;
+
+        match amount {
+            //amounts are nats
+            Node::Int(amount) if amount.is_negative() != Some(true) => Some(Self {
+                from: Address::from_node(from)?,
+                to: Address::from_node(to)?,
+                amount,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Action of a legacy `manager.tz` contract, given as a lambda to its `do` entrypoint
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum ManagerAction<'i> {
+    /// Transfer of `amount` mutez from the manager contract
+    Transfer {
+        to: Address<'i>,
+        amount: Zarith<'i>,
+    },
+    SetDelegate(Address<'i>),
+    RemoveDelegate,
+}
+
+impl<'i> ManagerAction<'i> {
+    /// Decodes the lambdas built by the clients for the manager contracts:
+    ///
+    /// * `{ DROP ; NIL operation ; PUSH key_hash <to> ; IMPLICIT_ACCOUNT ;
+    ///      PUSH mutez <amount> ; UNIT ; TRANSFER_TOKENS ; CONS }`
+    /// * `{ DROP ; NIL operation ; PUSH address <to> ; CONTRACT unit ;
+    ///      IF_NONE { UNIT ; FAILWITH } {} ; PUSH mutez <amount> ; UNIT ; TRANSFER_TOKENS ; CONS }`,
+    ///   also with `ASSERT_SOME` expanded by octez (`{ IF_NONE { { UNIT ; FAILWITH } } {} }`)
+    /// * `{ DROP ; NIL operation ; PUSH key_hash <delegate> ; SOME ; SET_DELEGATE ; CONS }`
+    /// * `{ DROP ; NIL operation ; NONE key_hash ; SET_DELEGATE ; CONS }`
+    ///
+    /// Returns None for any other code
+    pub fn from_bytes(input: &'i [u8]) -> Option<Self> {
+        let mut code = match Node::from_bytes(input) {
+            Ok(([], Node::Seq(code))) => code,
+            _ => return None,
+        };
+        let mut next = move || code.next()?.ok();
+
+        nullary(next()?, instr::DROP)?;
+        nullary(unary(next()?, instr::NIL)?, ty::OPERATION)?;
+
+        let node = next()?;
+        let to = if let Some(typ) = unary(node, instr::NONE) {
+            nullary(typ, ty::KEY_HASH)?;
+            nullary(next()?, instr::SET_DELEGATE)?;
+            None
+        } else {
+            let (typ, value) = binary(node, instr::PUSH)?;
+
+            if nullary(typ, ty::ADDRESS).is_some() {
+                let to = Address::from_node(value)?;
+                nullary(unary(next()?, instr::CONTRACT)?, ty::UNIT)?;
+
+                //ASSERT_SOME
+                let (none, some) = binary(expanded(next()?)?, instr::IF_NONE)?;
+                fail(none)?;
+                match some {
+                    Node::Seq(some) if some.is_empty() => {}
+                    _ => return None,
+                }
+
+                Some(to)
+            } else {
+                nullary(typ, ty::KEY_HASH)?;
+                let key_hash = Address::from_key_hash_node(value)?;
+
+                let node = next()?;
+                if nullary(node, instr::SOME).is_some() {
+                    nullary(next()?, instr::SET_DELEGATE)?;
+                    nullary(next()?, instr::CONS)?;
+
+                    return match next() {
+                        None => Some(Self::SetDelegate(key_hash)),
+                        Some(_) => None,
+                    };
+                }
+
+                nullary(node, instr::IMPLICIT_ACCOUNT)?;
+                Some(key_hash)
+            }
+        };
+
+        let action = match to {
+            None => Self::RemoveDelegate,
+            Some(to) => {
+                let (typ, amount) = binary(next()?, instr::PUSH)?;
+                nullary(typ, ty::MUTEZ)?;
+                nullary(next()?, instr::UNIT)?;
+                nullary(next()?, instr::TRANSFER_TOKENS)?;
+
+                match amount {
+                    Node::Int(amount) if amount.is_negative() != Some(true) => {
+                        Self::Transfer { to, amount }
+                    }
+                    _ => return None,
+                }
+            }
+        };
+
+        nullary(next()?, instr::CONS)?;
+        match next() {
+            None => Some(action),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Renders `Some/None` and `Left/Right` constructors compactly
+/// (ie: "None", "Left: Some: 42")
+///
+/// Only constructors of simple values (unit, booleans, ints, strings)
+/// are rendered, anything else is left to [`render`]
+///
+/// Returns None if the value isn't an option or an or of a simple value,
+/// if that value is malformed, if its string isn't valid (see [`is_printable`])
+/// or if it doesn't fit in `out`
+pub fn render_constructors(input: &[u8], out: &mut [u8; MAX_RENDERED_LEN]) -> Option<usize> {
+    let mut w = Writer { out, len: 0 };
+    let mut input = input;
+
+    //unwrap all the constructors iteratively
+    while let [tag::PRIM_1_ARG, prim, arg @ ..] = input {
+        let label = match *prim {
+            prim::SOME => &PIC::new(b"Some: ").into_inner()[..],
+            prim::LEFT => &PIC::new(b"Left: ").into_inner()[..],
+            prim::RIGHT => &PIC::new(b"Right: ").into_inner()[..],
+            _ => break,
+        };
+
+        w.write(label)?;
+        input = arg;
+    }
+
+    let is_constructor = w.len != 0;
+    match input {
+        [tag::PRIM, prim::NONE] => w.write(&PIC::new(b"None").into_inner()[..])?,
+        //not an option or an or
+        _ if !is_constructor => return None,
+        [tag::PRIM, prim::UNIT] => w.write(&PIC::new(b"Unit").into_inner()[..])?,
+        [tag::PRIM, prim::TRUE] => w.write(&PIC::new(b"True").into_inner()[..])?,
+        [tag::PRIM, prim::FALSE] => w.write(&PIC::new(b"False").into_inner()[..])?,
+        [tag::INT, num @ ..] => {
+            let (rem, num) = Zarith::from_bytes(num, true, Zarith::MICHELSON_INT_MAX_LEN).ok()?;
+            if !rem.is_empty() {
+                return None;
+            }
+
+            let mut buf = [0; Zarith::FORMATTED_SIZE];
+            w.write(num.format_into(&mut buf)?)?
+        }
+        [tag::STRING, len @ ..] if len.len() >= 4 => {
+            let (len, string) = len.split_at(4);
+            let len = u32::from_be_bytes(*arrayref::array_ref!(len, 0, 4)) as usize;
+
+            if string.len() != len || !is_printable(string) {
+                return None;
+            }
+            w.write_string(string)?
+        }
+        _ => return None,
+    }
+
+    Some(w.len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fa12_amount, prim_name, render, render_constructors, AnnotKind, Node, MAX_DEPTH,
+        MAX_RENDERED_LEN,
+    };
+    use crate::{handlers::parser_common::ParserError, parser::Zarith};
+
+    fn constructors(input_hex: &str) -> Option<std::string::String> {
+        let input = hex::decode(input_hex).expect("invalid input hex");
+        let mut out = [0; MAX_RENDERED_LEN];
+
+        render_constructors(&input, &mut out)
+            .map(|len| std::string::String::from_utf8(out[..len].to_vec()).unwrap())
+    }
+
+    fn expression(input_hex: &str) -> Option<std::string::String> {
+        let input = hex::decode(input_hex).expect("invalid input hex");
+        let mut out = [0; MAX_RENDERED_LEN];
+
+        render(&input, &mut out)
+            .map(|len| std::string::String::from_utf8(out[..len].to_vec()).unwrap())
+    }
+
+    #[test]
+    fn option() {
+        assert_eq!(constructors("0306").as_deref(), Some("None"));
+        assert_eq!(constructors("0509000a").as_deref(), Some("Some: 10"));
+        assert_eq!(constructors("0509004a").as_deref(), Some("Some: -10"));
+    }
+
+    #[test]
+    fn or() {
+        assert_eq!(constructors("0505030b").as_deref(), Some("Left: Unit"));
+        assert_eq!(
+            constructors("0508010000000568656c6c6f").as_deref(),
+            Some("Right: \"hello\"")
+        );
+        assert_eq!(
+            constructors("05080509030a").as_deref(),
+            Some("Right: Some: True")
+        );
+    }
+
+    #[test]
+    fn complex_argument() {
+        //Left (Pair 1 2)
+        assert_eq!(constructors("050507070001000002"), None);
+        //Some 0xcafe
+        assert_eq!(constructors("05090a00000002cafe"), None);
+        //Right (Some {1})
+        assert_eq!(constructors("0508050902000000020001"), None);
+    }
+
+    #[test]
+    fn malformed_argument() {
+        //truncated int
+        assert_eq!(constructors("050900"), None);
+        //trailing bytes after the int
+        assert_eq!(constructors("0509000a00"), None);
+        //string shorter than its length
+        assert_eq!(constructors("05090100000005616263"), None);
+        //string longer than its length
+        assert_eq!(constructors("0509010000000261626364"), None);
+    }
+
+    #[test]
+    fn argument_too_long() {
+        let string = "61".repeat(MAX_RENDERED_LEN);
+        let input = std::format!("050901{:08x}{}", MAX_RENDERED_LEN, string);
+        assert_eq!(constructors(&input), None);
+    }
+
+    #[test]
+    fn not_constructor() {
+        assert_eq!(constructors("030b"), None);
+        assert_eq!(constructors("0001"), None);
+        assert_eq!(constructors("07070001000002"), None);
+    }
+
+    #[test]
+    fn full_simple() {
+        assert_eq!(expression("030b").as_deref(), Some("Unit"));
+        assert_eq!(expression("004a").as_deref(), Some("-10"));
+        assert_eq!(
+            expression("010000000568656c6c6f").as_deref(),
+            Some("\"hello\"")
+        );
+        assert_eq!(expression("0a00000002cafe").as_deref(), Some("0xcafe"));
+    }
+
+    #[test]
+    fn strings() {
+        //"a\"b\\c\nd"
+        assert_eq!(
+            expression("01000000076122625c630a64").as_deref(),
+            Some(r#""a\"b\\c\nd""#)
+        );
+        assert_eq!(
+            constructors("050901000000076122625c630a64").as_deref(),
+            Some(r#"Some: "a\"b\\c\nd""#)
+        );
+
+        //control characters and non ASCII bytes aren't valid Michelson
+        assert_eq!(expression("01000000026101"), None);
+        assert_eq!(expression("0100000002c3a9"), None);
+        assert_eq!(constructors("050901000000026101"), None);
+        assert_eq!(constructors("05090100000002c3a9"), None);
+    }
+
+    #[test]
+    fn full_nested() {
+        //Pair 1 (Some "a")
+        assert_eq!(
+            expression("070700010509010000000161").as_deref(),
+            Some("Pair 1 (Some \"a\")")
+        );
+        //{ 1 ; Left Unit ; {} }
+        assert_eq!(
+            expression("020000000b00010505030b0200000000").as_deref(),
+            Some("{1; Left Unit; {}}")
+        );
+        //Pair with 3 arguments through the generic encoding
+        assert_eq!(
+            expression("09070000000600010002000300000000").as_deref(),
+            Some("Pair 1 2 3")
+        );
+    }
+
+    #[test]
+    fn full_skips_annotations() {
+        //pair (nat %amount) (address %to)
+        assert_eq!(
+            expression("076504620000000725616d6f756e74046e0000000325746f").as_deref(),
+            Some("pair nat address")
+        );
+    }
+
+    #[test]
+    fn full_malformed() {
+        //missing argument
+        assert_eq!(expression("0509"), None);
+        //trailing bytes
+        assert_eq!(expression("030b00"), None);
+        //sequence element overrunning the sequence
+        assert_eq!(expression("0200000001030b"), None);
+        //unknown primitive
+        assert_eq!(expression("03ff"), None);
+    }
+
+    #[test]
+    fn full_depth_bounded() {
+        let nested = |depth: usize| {
+            let mut input = "0509".repeat(depth - 1);
+            input.push_str("030b");
+            input
+        };
+
+        assert!(expression(&nested(MAX_DEPTH)).is_some());
+        assert_eq!(expression(&nested(MAX_DEPTH + 1)), None);
+
+        //much deeper than the worklist, still handled without recursion
+        assert_eq!(expression(&nested(2000)), None);
+    }
+
+    fn amount(entrypoint: &str, input_hex: &str) -> Option<std::string::String> {
+        let input = hex::decode(input_hex).expect("invalid input hex");
+        let mut out = [0; Zarith::FORMATTED_SIZE];
+
+        fa12_amount(entrypoint.as_bytes(), &input).map(|num| {
+            let num = num.format_into(&mut out).expect("amount too big");
+            std::string::String::from_utf8(num.to_vec()).unwrap()
+        })
+    }
+
+    #[test]
+    fn fa12_amounts() {
+        //Pair "abc" (Pair 0x0102 100)
+        const TRANSFER: &str = "0707010000000361626307070a00000002010200a401";
+        assert_eq!(amount("transfer", TRANSFER).as_deref(), Some("100"));
+        //not the right shape
+        assert_eq!(amount("approve", TRANSFER), None);
+        assert_eq!(amount("mint", TRANSFER), None);
+
+        //Pair 0x0102 10
+        assert_eq!(
+            amount("approve", "07070a000000020102000a").as_deref(),
+            Some("10")
+        );
+        //negative amount
+        assert_eq!(amount("approve", "07070a000000020102004a"), None);
+        //trailing bytes
+        assert_eq!(amount("approve", "07070a000000020102000a00"), None);
+    }
+
+    #[test]
+    fn decode_nodes() {
+        //Pair %transfer "abc" (Pair { 1 ; 2 } 0x0102)
+        let input = hex::decode(
+            "0807010000000361626307070200000004000100020a00000002010200000009257472616e73666572",
+        )
+        .expect("invalid input hex");
+
+        let (rem, node) = Node::from_bytes(&input).expect("couldn't decode");
+        assert!(rem.is_empty());
+
+        let (code, mut args, annots) = match node {
+            Node::Prim { code, args, annots } => (code, args, annots),
+            _ => panic!("expected an application"),
+        };
+        assert_eq!(prim_name(code), Some(&b"Pair"[..]));
+        assert_eq!(annots, b"%transfer");
+
+        assert_eq!(args.next(), Some(Ok(Node::String(b"abc"))));
+        let mut inner = match args.next() {
+            Some(Ok(Node::Prim {
+                args, annots: [], ..
+            })) => args,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(args.next(), None);
+
+        let seq = match inner.next() {
+            Some(Ok(Node::Seq(seq))) => seq,
+            other => panic!("unexpected {:?}", other),
+        };
+        let ints = seq
+            .map(|node| match node {
+                Ok(Node::Int(num)) => num.read_as::<u8>().unwrap().1,
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect::<std::vec::Vec<_>>();
+        assert_eq!(ints, [1, 2]);
+
+        assert_eq!(inner.next(), Some(Ok(Node::Bytes(&[1, 2]))));
+        assert_eq!(inner.next(), None);
+    }
+
+    #[test]
+    fn decode_malformed() {
+        let decode = |input_hex: &str| {
+            let input = hex::decode(input_hex).expect("invalid input hex");
+            Node::from_bytes(&input)
+                .map(|_| ())
+                .map_err(ParserError::from)
+        };
+
+        //missing argument
+        assert_eq!(decode("0509"), Err(ParserError::UnexpectedEof));
+        //truncated string
+        assert_eq!(decode("01000000056865"), Err(ParserError::UnexpectedEof));
+        //unknown tag
+        assert_eq!(decode("ff"), Err(ParserError::parser_unexpected_type));
+
+        //nesting is bounded, without recursion
+        let mut nested = "0509".repeat(2000);
+        nested.push_str("030b");
+        assert_eq!(decode(&nested), Err(ParserError::parser_value_out_of_range));
+    }
+
+    #[test]
+    fn annotations() {
+        //pair (address :owner %to) (nat %amount)
+        let input = hex::decode("0765046e0000000a3a6f776e65722025746f04620000000725616d6f756e74")
+            .expect("invalid input hex");
+
+        let (_, node) = Node::from_bytes(&input).expect("couldn't decode");
+        assert_eq!(node.annots().count(), 0);
+
+        let mut args = match node {
+            Node::Prim { args, .. } => args,
+            _ => panic!("expected an application"),
+        };
+
+        let to = args.next().unwrap().unwrap();
+        assert_eq!(to.annot(AnnotKind::Field), Some(&b"to"[..]));
+        assert_eq!(to.annot(AnnotKind::Type), Some(&b"owner"[..]));
+        assert_eq!(to.annot(AnnotKind::Variable), None);
+
+        let amount = args.next().unwrap().unwrap();
+        assert_eq!(amount.annot(AnnotKind::Field), Some(&b"amount"[..]));
+
+        //values can't have annotations
+        let (_, int) = Node::from_bytes(&[0, 1]).expect("couldn't decode");
+        assert_eq!(int.annot(AnnotKind::Field), None);
+    }
+
+    #[test]
+    fn fa12_transfer() {
+        use super::{Address, Fa12Transfer};
+
+        //Pair 0x000035e9...3abd (Pair "KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA" 150000000)
+        const INPUT_HEX: &str = "07070a00000016000035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 070701000000244b54314a487148516448536757424b6f364834556647386477334a6e5a53796a476b4841\
+                                 0080c6868f01";
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let transfer = Fa12Transfer::from_bytes(&input).expect("not a FA1.2 transfer");
+        assert!(matches!(transfer.from, Address::Optimized(id) if id.is_implicit()));
+        assert_eq!(
+            transfer.to,
+            Address::Readable(b"KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA")
+        );
+        assert_eq!(
+            transfer.amount.read_as::<usize>(),
+            Some((false, 150_000_000))
+        );
+
+        let mut out = [0; super::ContractID::BASE58_LEN];
+        let len = transfer.from.base58_into(&mut out).unwrap();
+        assert_eq!(&out[..len], b"tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn");
+
+        //trailing bytes
+        let mut long = input.clone();
+        long.push(0);
+        assert!(Fa12Transfer::from_bytes(&long).is_none());
+
+        //`approve` shape, Pair 0x0102 150000000
+        let input = hex::decode("07070a0000000201020080c6868f01").unwrap();
+        assert!(Fa12Transfer::from_bytes(&input).is_none());
+    }
+
+    #[test]
+    fn readable_address() {
+        use super::Address;
+
+        let address = |s: &'static str| Address::from_node(Node::String(s.as_bytes()));
+
+        assert!(address("KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA").is_some());
+        assert!(address("tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn").is_some());
+
+        //wrong checksum
+        assert_eq!(address("KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHB"), None);
+        assert_eq!(address("tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hm"), None);
+        //not base58
+        assert_eq!(address("KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkH0"), None);
+        //valid base58check, but not an address
+        assert_eq!(
+            address("BLzyjjHKEKMULtvkpSHxuZxx6ei6fpntH2BTkYZiLgs8zLVstvX"),
+            None
+        );
+    }
+
+    #[test]
+    fn manager_actions() {
+        use super::{Address, ManagerAction};
+
+        let bytes = |input_hex: &str| hex::decode(input_hex).expect("invalid input hex");
+
+        //transfer of 1 tez to tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn
+        const IMPLICIT: &str =
+            "02000000340320053d036d0743035d0a000000150035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                031e0743036a0080897a034f034d031b";
+        match ManagerAction::from_bytes(&bytes(IMPLICIT)) {
+            Some(ManagerAction::Transfer {
+                to: Address::Optimized(to),
+                amount,
+            }) => {
+                assert!(to.is_implicit());
+                assert_eq!(amount.read_as::<u64>(), Some((false, 1_000_000)));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        //transfer of 1 tez to KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA
+        const ORIGINATED: &str = "02000000470320053d036d0743036e0a00000016016a7d4a43f51be0934a441fba4f13f9beaa47575100\
+                                  0555036c072f0200000004034f032702000000000743036a0080897a034f034d031b";
+        match ManagerAction::from_bytes(&bytes(ORIGINATED)) {
+            Some(ManagerAction::Transfer {
+                to: Address::Optimized(to),
+                ..
+            }) => assert!(!to.is_implicit()),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        //same transfer, with the address as a string and ASSERT_SOME expanded by octez:
+        // { DROP ; NIL operation ; PUSH address "KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA" ;
+        //   CONTRACT unit ; { IF_NONE { { UNIT ; FAILWITH } } {} } ;
+        //   PUSH mutez 1000000 ; UNIT ; TRANSFER_TOKENS ; CONS }
+        const ORIGINATED_OCTEZ: &str = "020000005f0320053d036d0743036e01000000244b54314a487148516448536757424b6f3648345566\
+                                        47386477334a6e5a53796a476b48410555036c0200000015072f0200000009\
+                                        0200000004034f032702000000000743036a0080897a034f034d031b";
+        match ManagerAction::from_bytes(&bytes(ORIGINATED_OCTEZ)) {
+            Some(ManagerAction::Transfer {
+                to: Address::Readable(to),
+                amount,
+            }) => {
+                assert_eq!(to, &b"KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA"[..]);
+                assert_eq!(amount.read_as::<u64>(), Some((false, 1_000_000)));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        const SET_DELEGATE: &str =
+            "020000002a0320053d036d0743035d0a000000150035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                    0346034e031b";
+        assert!(matches!(
+            ManagerAction::from_bytes(&bytes(SET_DELEGATE)),
+            Some(ManagerAction::SetDelegate(Address::Optimized(_)))
+        ));
+
+        const REMOVE_DELEGATE: &str = "020000000e0320053d036d053e035d034e031b";
+        assert_eq!(
+            ManagerAction::from_bytes(&bytes(REMOVE_DELEGATE)),
+            Some(ManagerAction::RemoveDelegate)
+        );
+
+        //missing CONS
+        assert_eq!(
+            ManagerAction::from_bytes(&bytes("020000000c0320053d036d053e035d034e")),
+            None
+        );
+        //not a lambda
+        assert_eq!(ManagerAction::from_bytes(&bytes("030b")), None);
+    }
+}
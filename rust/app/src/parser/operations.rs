@@ -13,11 +13,15 @@
 *  See the License for the specific language governing permissions and
 *  limitations under the License.
 ********************************************************************************/
-use nom::{bytes::complete::take, number::complete::le_u8, Finish, IResult};
+use nom::{
+    bytes::complete::take,
+    number::complete::{be_u32, le_u8},
+    Finish, IResult,
+};
 use zemu_sys::ViewError;
 
 use crate::{
-    constants::tzprefix::{B, KT1, TZ1, TZ2, TZ3},
+    constants::tzprefix::{B, KT1, TZ1, TZ2, TZ3, TZ4},
     crypto::Curve,
     handlers::{parser_common::ParserError, sha256x2},
 };
@@ -96,11 +100,31 @@ pub struct EncodedOperations<'b> {
     source: &'b [u8],
     //number of bytes read
     read: usize,
+    //see `set_tolerant`
+    tolerant: bool,
 }
 
 impl<'b> EncodedOperations<'b> {
     pub fn new(source: &'b [u8]) -> Self {
-        Self { source, read: 0 }
+        Self {
+            source,
+            read: 0,
+            tolerant: false,
+        }
+    }
+
+    /// In tolerant mode, data that can't be parsed after at least one
+    /// operation was recognized is returned as [`OperationType::UnknownFields`]
+    /// instead of failing, assuming they are fields added to the previous
+    /// operation by a newer protocol
+    ///
+    /// The fields are only skipped if they carry their own length, like any
+    /// variable size field (4 bytes length, big endian), so the following
+    /// operations are still parsed; anything else is an error
+    ///
+    /// The fields can't be reviewed, so this is meant for expert mode only
+    pub fn set_tolerant(&mut self, tolerant: bool) {
+        self.tolerant = tolerant;
     }
 
     #[inline(never)]
@@ -116,6 +140,15 @@ impl<'b> EncodedOperations<'b> {
             return Ok(None);
         }
 
+        //a newer protocol might have added fields that happen to parse as an operation,
+        // so prefer skipping them if it's the only way to parse the rest of the group
+        if self.tolerant && self.read > 0 && !self.parses_strictly() {
+            if let Ok((rem, fields)) = Self::length_prefixed(input) {
+                *loc = MaybeUninit::new(OperationType::UnknownFields(fields));
+                return Ok(Some(self.source.len() - rem.len()));
+            }
+        }
+
         let rem = match OperationType::from_bytes_into(input, loc) {
             Ok(ok) => ok,
             //there was some remaing data but it's probably the signature
@@ -135,6 +168,32 @@ impl<'b> EncodedOperations<'b> {
         Ok(Some(read))
     }
 
+    /// Checks if the rest of the group can be parsed without skipping any field
+    fn parses_strictly(&self) -> bool {
+        let mut strict = Self {
+            tolerant: false,
+            ..*self
+        };
+
+        loop {
+            match strict.parse_next() {
+                Ok(Some(_)) => {}
+                Ok(None) => return true,
+                Err(_) => return false,
+            }
+        }
+    }
+
+    /// Splits the unknown fields from `input`, length prefix included
+    fn length_prefixed(input: &'b [u8]) -> IResult<&'b [u8], &'b [u8], ParserError> {
+        let (_, len) = be_u32(input)?;
+        let len = 4usize
+            .checked_add(len as usize)
+            .ok_or(ParserError::parser_value_out_of_range)?;
+
+        take(len)(input)
+    }
+
     pub fn peek_next(&self) -> Result<Option<OperationType<'b>>, nom::Err<ParserError>> {
         let mut out = MaybeUninit::uninit();
 
@@ -186,38 +245,84 @@ impl<'b> EncodedOperations<'b> {
     }
 }
 
+/// Yields each operation of the group in turn, parsing them lazily
+///
+/// Iteration ends after the first operation that can't be parsed
+impl<'b> Iterator for EncodedOperations<'b> {
+    type Item = Result<OperationType<'b>, nom::Err<ParserError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.parse_next() {
+            Ok(op) => op.map(Ok),
+            Err(err) => {
+                //nothing else can be read after an invalid operation
+                self.read = self.source.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
 mod operation_type;
 pub use operation_type::OperationType;
 
+mod tags;
+pub use tags::{tag, SUPPORTED_OPERATIONS};
+
 mod activate_account;
 mod ballot;
 mod delegation;
 mod double_baking_evidence;
+mod drain_delegate;
 mod endorsement;
 mod failing_noop;
+mod increase_paid_storage;
 mod origination;
 mod proposals;
+mod register_global_constant;
 mod reveal;
 mod seed_nonce_revelation;
+mod set_deposits_limit;
+mod smart_rollup_add_messages;
+mod smart_rollup_cement;
+mod smart_rollup_execute_outbox_message;
+mod smart_rollup_originate;
+mod smart_rollup_publish;
+mod smart_rollup_recover_bond;
+mod smart_rollup_refute;
+mod smart_rollup_timeout;
 mod transfer;
+mod update_consensus_key;
 
 pub use activate_account::ActivateAccount;
 pub use ballot::Ballot;
 pub use delegation::Delegation;
 pub use double_baking_evidence::DoubleBakingEvidence;
+pub use drain_delegate::DrainDelegate;
 pub use endorsement::{DoubleEndorsementEvidence, Endorsement, EndorsementWithSlot};
 pub use failing_noop::FailingNoop;
+pub use increase_paid_storage::IncreasePaidStorage;
 pub use origination::Origination;
 pub use proposals::Proposals;
+pub use register_global_constant::RegisterGlobalConstant;
 pub use reveal::Reveal;
 pub use seed_nonce_revelation::SeedNonceRevelation;
+pub use set_deposits_limit::SetDepositsLimit;
+pub use smart_rollup_add_messages::SmartRollupAddMessages;
+pub use smart_rollup_cement::SmartRollupCement;
+pub use smart_rollup_execute_outbox_message::SmartRollupExecuteOutboxMessage;
+pub use smart_rollup_originate::{PvmKind, SmartRollupOriginate};
+pub use smart_rollup_publish::SmartRollupPublish;
+pub use smart_rollup_recover_bond::SmartRollupRecoverBond;
+pub use smart_rollup_refute::{Refutation, SmartRollupRefute};
+pub use smart_rollup_timeout::SmartRollupTimeout;
 pub use transfer::Transfer;
+pub use update_consensus_key::UpdateConsensusKey;
 
 #[derive(Clone, Copy)]
 #[cfg_attr(test, derive(Debug))]
 pub enum AnonymousOp<'b> {
     DoubleEndorsementEvidence(DoubleEndorsementEvidence<'b>),
-    SeedNonceRevelation(SeedNonceRevelation<'b>),
     DoubleBakingEvidence(DoubleBakingEvidence<'b>),
 }
 
@@ -227,15 +332,11 @@ impl<'b> AnonymousOp<'b> {
     pub fn from_bytes(tag: u8, rem: &'b [u8]) -> Result<(&'b [u8], ()), nom::Err<ParserError>> {
         crate::sys::zemu_log_stack("AnonymousOp::from_bytes\x00");
         let rem = match tag {
-            0x01 => {
-                let (rem, _) = SeedNonceRevelation::from_bytes(rem)?;
-                rem
-            }
-            0x02 => {
+            tag::DOUBLE_ENDORSEMENT_EVIDENCE => {
                 let (rem, _) = DoubleEndorsementEvidence::from_bytes(rem)?;
                 rem
             }
-            0x03 => {
+            tag::DOUBLE_BAKING_EVIDENCE => {
                 let (rem, _) = DoubleBakingEvidence::from_bytes(rem)?;
                 rem
             }
@@ -250,15 +351,11 @@ impl<'b> AnonymousOp<'b> {
     pub fn from_bytes(tag: u8, rem: &'b [u8]) -> Result<(&'b [u8], Self), nom::Err<ParserError>> {
         crate::sys::zemu_log_stack("AnonymousOp::from_bytes\x00");
         let (rem, data) = match tag {
-            0x01 => {
-                let (rem, data) = SeedNonceRevelation::from_bytes(rem)?;
-                (rem, Self::SeedNonceRevelation(data))
-            }
-            0x02 => {
+            tag::DOUBLE_ENDORSEMENT_EVIDENCE => {
                 let (rem, data) = DoubleEndorsementEvidence::from_bytes(rem)?;
                 (rem, Self::DoubleEndorsementEvidence(data))
             }
-            0x03 => {
+            tag::DOUBLE_BAKING_EVIDENCE => {
                 let (rem, data) = DoubleBakingEvidence::from_bytes(rem)?;
                 (rem, Self::DoubleBakingEvidence(data))
             }
@@ -280,8 +377,8 @@ impl<'b> ContractID<'b> {
     pub const BASE58_LEN: usize = 37;
 
     #[cfg(test)]
-    fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
-        use nom::{dbg_basic, take, tuple as tuplem};
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        use nom::{call, dbg_basic, tuple as tuplem};
         use std::{eprintln, println};
 
         let (rem, tag) = dbg_basic!(input, le_u8)?;
@@ -292,7 +389,7 @@ impl<'b> ContractID<'b> {
             }
             0x01 => {
                 //discard last byte (padding)
-                let (rem, (hash, _)) = dbg_basic!(rem, tuplem!(take!(20usize), le_u8))?;
+                let (rem, (hash, _)) = dbg_basic!(rem, tuplem!(call!(take(20usize)), le_u8))?;
                 let hash = arrayref::array_ref!(hash, 0, 20);
                 Ok((rem, Self::Originated(hash)))
             }
@@ -309,7 +406,7 @@ impl<'b> ContractID<'b> {
     }
 
     #[cfg(not(test))]
-    fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
         let (rem, tag) = le_u8(input)?;
         match tag {
             0x00 => {
@@ -339,6 +436,7 @@ impl<'b> ContractID<'b> {
             Self::Implicit(Curve::Bip32Ed25519 | Curve::Ed25519, h) => (TZ1, h),
             Self::Implicit(Curve::Secp256K1, h) => (TZ2, h),
             Self::Implicit(Curve::Secp256R1, h) => (TZ3, h),
+            Self::Implicit(Curve::Bls12_381, h) => (TZ4, h),
         };
 
         let mut checksum = [0; 4];
@@ -370,7 +468,7 @@ mod tests {
     use crate::{
         crypto::Curve,
         handlers::public_key::Addr,
-        parser::operations::{ContractID, Operation, OperationType},
+        parser::operations::{ContractID, EncodedOperations, Operation, OperationType},
     };
 
     #[test]
@@ -394,6 +492,25 @@ mod tests {
         assert_eq!(&addr[..len], PKH_BASE58.as_bytes());
     }
 
+    #[test]
+    fn contract_id_bls() {
+        const INPUT_HEX: &str = "00030000000000000000000000000000000000000000";
+        const PKH_BASE58: &str = "tz491FasxEbqzR2SfjgTPnRyw9JY7og2HZUA";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, parsed) =
+            ContractID::from_bytes(&input).expect("failed to parse contract id input");
+
+        assert_eq!(rem.len(), 0);
+        assert_eq!(parsed, ContractID::Implicit(Curve::Bls12_381, &[0; 20]));
+
+        let (len, cid) = parsed
+            .base58()
+            .expect("couldn't encode contract id to base 58");
+        assert_eq!(&cid[..len], PKH_BASE58.as_bytes());
+    }
+
     #[test]
     fn contract_id_contract() {
         const INPUT_HEX: &str = "016a7d4a43f51be0934a441fba4f13f9beaa47575100";
@@ -449,6 +566,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tolerant_operations() {
+        //delegation followed by an unknown field, with its length
+        const INPUT_HEX: &str = "a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                                 6e0035e993d8c7aaa42b5e3ccd86a33390ececc73abd904e010a0a00\
+                                 000000026e00";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let mut parsed = Operation::new(&input).expect("couldn't parse branch");
+
+        let mut strict = *parsed.ops();
+        assert!(matches!(
+            strict.parse_next(),
+            Ok(Some(OperationType::Delegation(_)))
+        ));
+        assert!(strict.any(|op| op.is_err()));
+
+        let ops = parsed.mut_ops();
+        ops.set_tolerant(true);
+        assert!(matches!(
+            ops.parse_next(),
+            Ok(Some(OperationType::Delegation(_)))
+        ));
+        match ops.parse_next().expect("failed to parse operation") {
+            Some(OperationType::UnknownFields(fields)) => {
+                assert_eq!(fields, &input[input.len() - 6..])
+            }
+            other => panic!("expected unknown fields, found {:x?}", other),
+        }
+        assert!(ops
+            .parse_next()
+            .expect("failed to parse operation")
+            .is_none());
+
+        //only after a recognized operation
+        let mut ops = EncodedOperations::new(&input[input.len() - 6..]);
+        ops.set_tolerant(true);
+        assert!(ops.any(|op| op.is_err()));
+
+        //truncated fields are not skipped
+        let mut truncated = input[32..input.len() - 6].to_vec();
+        truncated.extend_from_slice(&[0, 0, 0, 9, 0xAA, 0xBB]);
+        let mut ops = EncodedOperations::new(&truncated);
+        ops.set_tolerant(true);
+        assert!(ops.any(|op| op.is_err()));
+
+        //nor is data without a length
+        let mut garbage = input[32..input.len() - 6].to_vec();
+        garbage.extend_from_slice(&[0x6e, 0x00]);
+        let mut ops = EncodedOperations::new(&garbage);
+        ops.set_tolerant(true);
+        assert!(ops.any(|op| op.is_err()));
+    }
+
+    #[test]
+    fn tolerant_operations_continue() {
+        //delegation, unknown fields and another delegation
+        const DELEGATION_HEX: &str = "6e0035e993d8c7aaa42b5e3ccd86a33390ececc73abd904e010a0a00";
+
+        let mut input = hex::decode(DELEGATION_HEX).expect("invalid input hex");
+        input.extend_from_slice(&[0, 0, 0, 3, 0xAA, 0xBB, 0xCC]);
+        input.extend_from_slice(&hex::decode(DELEGATION_HEX).expect("invalid input hex"));
+
+        let mut ops = EncodedOperations::new(&input);
+        ops.set_tolerant(true);
+        let kinds = (&mut ops)
+            .map(|op| match op.expect("failed to parse operation") {
+                OperationType::Delegation(_) => "delegation",
+                OperationType::UnknownFields(_) => "unknown",
+                other => panic!("unexpected operation {:x?}", other),
+            })
+            .collect::<std::vec::Vec<_>>();
+        assert_eq!(kinds, ["delegation", "unknown", "delegation"]);
+    }
+
     #[test]
     fn operations() {
         const INPUT_HEX: &str = "a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
@@ -491,4 +683,28 @@ mod tests {
             opt => panic!("not the expected operation type, found: {:x?}", opt),
         }
     }
+
+    #[test]
+    fn operations_iter() {
+        //reveal followed by a transfer
+        const INPUT_HEX: &str = "a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
+                                 6b0035e993d8c7aaa42b5e3ccd86a33390ececc73abd904e010a0a00\
+                                 6e2cd3a0f6c46f3d0b7ea8deb6e8a3dc3dbc5a7f2e4d2ea1c1d6e0f0e2b5e3d1\
+                                 6c0035e993d8c7aaa42b5e3ccd86a33390ececc73abd904e020a0ae807016a7d4a43f51be0934a441fba4f13f9beaa4757510000";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let parsed = Operation::new(&input).expect("couldn't parse branch");
+
+        let mut ops = *parsed.ops();
+        assert!(matches!(ops.next(), Some(Ok(OperationType::Reveal(_)))));
+        assert!(matches!(ops.next(), Some(Ok(OperationType::Transfer(_)))));
+        assert!(ops.next().is_none());
+
+        //iteration stops after an invalid operation
+        let input = &input[..input.len() - 1];
+        let parsed = Operation::new(input).expect("couldn't parse branch");
+        let results: std::vec::Vec<_> = (*parsed.ops()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[1].is_err());
+    }
 }
@@ -14,7 +14,7 @@
 *  limitations under the License.
 ********************************************************************************/
 use core::{mem::MaybeUninit, ptr::addr_of_mut};
-use nom::{do_parse, take, IResult};
+use nom::{bytes::complete::take, call, do_parse, IResult};
 use zemu_sys::ViewError;
 
 use arrayref::array_ref;
@@ -37,8 +37,8 @@ impl<'b> ActivateAccount<'b> {
     #[inline(never)]
     pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
         let (rem, (public_key_hash, secret)) = do_parse! {input,
-            public_key_hash: take!(20) >>
-            secret: take!(20) >>
+            public_key_hash: call!(take(20usize)) >>
+            secret: call!(take(20usize)) >>
             (public_key_hash, secret)
         }?;
 
@@ -60,8 +60,8 @@ impl<'b> ActivateAccount<'b> {
         out: &mut MaybeUninit<Self>,
     ) -> Result<&'b [u8], nom::Err<ParserError>> {
         let (rem, (public_key_hash, secret)) = do_parse! {input,
-            public_key_hash: take!(20) >>
-            secret: take!(20) >>
+            public_key_hash: call!(take(20usize)) >>
+            secret: call!(take(20usize)) >>
             (public_key_hash, secret)
         }?;
 
@@ -14,9 +14,10 @@
 *  limitations under the License.
 ********************************************************************************/
 use nom::{
-    do_parse,
+    bytes::complete::take,
+    call, do_parse,
     number::complete::{be_i32, be_u8},
-    take, IResult,
+    IResult,
 };
 use zemu_sys::ViewError;
 
@@ -75,7 +76,7 @@ impl<'b> Ballot<'b> {
         let (rem, (source, period, proposal, ballot)) = do_parse! {input,
             source: public_key_hash >>
             period: be_i32 >>
-            proposal: take!(PROPOSAL_BYTES_LEN) >>
+            proposal: call!(take(PROPOSAL_BYTES_LEN)) >>
             vote: be_u8 >>
             (source, period, proposal, vote)
         }?;
@@ -104,7 +105,7 @@ impl<'b> Ballot<'b> {
         let (rem, (source, period, proposal, ballot)) = do_parse! {input,
             source: public_key_hash >>
             period: be_i32 >>
-            proposal: take!(PROPOSAL_BYTES_LEN) >>
+            proposal: call!(take(PROPOSAL_BYTES_LEN)) >>
             vote: be_u8 >>
             (source, period, proposal, vote)
         }?;
@@ -271,7 +272,7 @@ impl<'b> Ballot<'b> {
 
 #[cfg(test)]
 mod tests {
-    use crate::crypto::Curve;
+    use crate::{crypto::Curve, handlers::parser_common::ParserError};
 
     use super::{Ballot, Vote};
 
@@ -295,4 +296,28 @@ mod tests {
         };
         assert_eq!(parsed, expected);
     }
+
+    #[test]
+    fn ballot_votes() {
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 00000010\
+                                 3e5e3a606afab74a59ca09e333633e2770b6492c5e594455b71e9a2f0ea92afb";
+
+        let mut input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        input.push(0);
+
+        for (byte, vote) in [(1, Vote::Nay), (2, Vote::Pass)] {
+            *input.last_mut().unwrap() = byte;
+
+            let (_, parsed) = Ballot::from_bytes(&input).expect("failed to parse ballot");
+            assert_eq!(*parsed.vote(), vote);
+            assert_eq!(parsed.period(), 16);
+        }
+
+        *input.last_mut().unwrap() = 3;
+        assert_eq!(
+            Ballot::from_bytes(&input).unwrap_err(),
+            nom::Err::Error(ParserError::InvalidBallotVote)
+        );
+    }
 }
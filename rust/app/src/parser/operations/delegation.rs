@@ -20,7 +20,7 @@ use zemu_sys::ViewError;
 use crate::{
     crypto::Curve,
     handlers::{
-        handle_ui_message, intstr_to_fpstr_inplace, parser_common::ParserError, public_key::Addr,
+        handle_ui_message, parser_common::ParserError, public_key::Addr, MAX_THOUSANDS_SEPARATORS,
     },
     parser::{boolean, public_key_hash, DisplayableItem, Zarith},
 };
@@ -38,16 +38,19 @@ pub struct Delegation<'b> {
 }
 
 impl<'b> Delegation<'b> {
+    /// Mutez burned for each byte of storage, shown as part of the cost
+    pub const STORAGE_COST_PER_BYTE: u64 = 250;
+
     #[inline(never)]
     pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
         crate::sys::zemu_log_stack("Delegation::from_bytes\x00");
 
         let (rem, (source, fee, counter, gas_limit, storage_limit, delegate)) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
             has_delegate: boolean >>
             delegate: cond!(has_delegate, public_key_hash) >>
             (source, fee, counter, gas_limit, storage_limit, delegate)
@@ -75,10 +78,10 @@ impl<'b> Delegation<'b> {
 
         let (rem, (source, fee, counter, gas_limit, storage_limit, delegate)) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
             has_delegate: boolean >>
             delegate: cond!(has_delegate, public_key_hash) >>
             (source, fee, counter, gas_limit, storage_limit, delegate)
@@ -99,11 +102,24 @@ impl<'b> Delegation<'b> {
     }
 }
 
+impl Delegation<'_> {
+    const GAS_LIMIT_ITEM: u8 = 4;
+    const STORAGE_LIMIT_ITEM: u8 = 5;
+    const COUNTER_ITEM: u8 = 6;
+}
+
 impl<'a> DisplayableItem for Delegation<'a> {
     fn num_items(&self) -> usize {
         1 + 6
     }
 
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
+    }
+
     #[inline(never)]
     fn render_item(
         &self,
@@ -115,7 +131,8 @@ impl<'a> DisplayableItem for Delegation<'a> {
         use bolos::{pic_str, PIC};
         use lexical_core::{write as itoa, Number};
 
-        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2]; //+2 for fixed point formatting
+        //+2 for fixed point formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
 
         match item_n {
             //home
@@ -163,22 +180,41 @@ impl<'a> DisplayableItem for Delegation<'a> {
                     None => handle_ui_message(&pic_str!(b"<REVOKED>")[..], message, page),
                 }
             }
-            //fee
+            //fee and storage burn
             3 => {
-                let title_content = pic_str!(b"Fee");
+                let title_content = pic_str!(b"Cost");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let (_, fee) = self.fee().read_as::<usize>().ok_or(ViewError::Unknown)?;
+                let (_, storage_limit) = self
+                    .storage_limit()
+                    .read_as::<u64>()
+                    .ok_or(ViewError::Unknown)?;
+                let burn = storage_limit
+                    .checked_mul(Self::STORAGE_COST_PER_BYTE)
+                    .ok_or(ViewError::Unknown)?;
+
+                let mut fee_buf = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let mut burn_buf = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut fee_buf)
+                    .ok_or(ViewError::Unknown)?;
+                let burn = Zarith::format_mutez(burn, &mut burn_buf).ok_or(ViewError::Unknown)?;
+
+                //"<fee> XTZ fee + <burn> XTZ burn"
+                let fee_sep = pic_str!(b" fee + "!);
+                let burn_sep = pic_str!(b" burn"!);
+                let mut mex = [0; 2 * Zarith::MUTEZ_FORMATTED_SIZE + 12];
+                let mut len = 0;
+                for part in [fee, fee_sep, burn, burn_sep] {
+                    mex[len..len + part.len()].copy_from_slice(part);
+                    len += part.len();
+                }
 
-                itoa(fee, &mut zarith_buf);
-                handle_ui_message(
-                    intstr_to_fpstr_inplace(&mut zarith_buf, 6).map_err(|_| ViewError::Unknown)?,
-                    message,
-                    page,
-                )
+                handle_ui_message(&mex[..len], message, page)
             }
             //gas_limit
-            4 => {
+            Self::GAS_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Gas Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -190,7 +226,7 @@ impl<'a> DisplayableItem for Delegation<'a> {
                 handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
             }
             //storage_limit
-            5 => {
+            Self::STORAGE_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Storage Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -202,7 +238,7 @@ impl<'a> DisplayableItem for Delegation<'a> {
                 handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
             }
             //counter
-            6 => {
+            Self::COUNTER_ITEM => {
                 let title_content = pic_str!(b"Counter");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -311,6 +347,33 @@ mod tests {
 
         assert_eq!(parsed, expected);
     }
+
+    #[test]
+    fn delegation_cost() {
+        use crate::parser::DisplayableItem;
+
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e\
+                                 01\
+                                 0a\
+                                 0a\
+                                 00";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = Delegation::from_bytes(&input).expect("couldn't parse delegation");
+
+        let mut title = [0; 32];
+        let mut message = [0; 64];
+        let n_pages = parsed
+            .render_item(3, &mut title, &mut message, 0)
+            .expect("couldn't render cost");
+        assert_eq!(n_pages, 1);
+        assert!(title.starts_with(b"Cost\x00"));
+
+        //10 bytes of storage at 250 mutez each
+        let len = message.iter().position(|&b| b == 0).unwrap();
+        assert_eq!(&message[..len], b"0.01 XTZ fee + 0.0025 XTZ burn");
+    }
 }
 
 mod known_bakers {
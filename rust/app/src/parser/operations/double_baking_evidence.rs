@@ -184,10 +184,10 @@ impl<'b> FullBlockHeader<'b> {
         };
 
         let (rem, seed_nonce_hash) = {
-            use nom::{cond, take};
+            use nom::{call, cond};
             let (rem, is_present) = boolean(rem)?;
 
-            let (rem, seed_nonce_hash) = cond!(rem, is_present, take!(32))?;
+            let (rem, seed_nonce_hash) = cond!(rem, is_present, call!(take(32usize)))?;
 
             (rem, seed_nonce_hash.map(|s| array_ref!(s, 0, 32)))
         };
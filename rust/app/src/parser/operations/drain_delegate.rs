@@ -0,0 +1,175 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use nom::{do_parse, IResult};
+use zemu_sys::ViewError;
+
+use crate::{
+    crypto::Curve,
+    handlers::{handle_ui_message, parser_common::ParserError, public_key::Addr},
+    parser::{public_key_hash, DisplayableItem},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, property::Property)]
+#[cfg_attr(test, derive(Debug))]
+#[property(mut(disable), get(public), set(disable))]
+pub struct DrainDelegate<'b> {
+    /// Consensus key of the delegate, which signs the operation
+    consensus_key: (Curve, &'b [u8; 20]),
+    delegate: (Curve, &'b [u8; 20]),
+    /// Receives all the spendable balance of the delegate
+    destination: (Curve, &'b [u8; 20]),
+}
+
+impl<'b> DrainDelegate<'b> {
+    #[inline(never)]
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        crate::sys::zemu_log_stack("DrainDelegate::from_bytes\x00");
+
+        let (rem, (consensus_key, delegate, destination)) = do_parse! {input,
+            consensus_key: public_key_hash >>
+            delegate: public_key_hash >>
+            destination: public_key_hash >>
+            (consensus_key, delegate, destination)
+        }?;
+
+        Ok((
+            rem,
+            Self {
+                consensus_key,
+                delegate,
+                destination,
+            },
+        ))
+    }
+}
+
+impl<'b> DisplayableItem for DrainDelegate<'b> {
+    fn num_items(&self) -> usize {
+        1 + 4
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+
+        let (title_content, (crv, hash)) = match item_n {
+            //home
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                return handle_ui_message(&pic_str!(b"Drain Delegate")[..], message, page);
+            }
+            //warning, shown before the addresses so it can't be missed
+            1 => {
+                let title_content = pic_str!(b"WARNING");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                return handle_ui_message(
+                    &pic_str!(b"Sends ALL the spendable balance of the delegate")[..],
+                    message,
+                    page,
+                );
+            }
+            2 => (&pic_str!(b"Destination")[..], self.destination),
+            3 => (&pic_str!(b"Delegate")[..], self.delegate),
+            4 => (&pic_str!(b"Consensus Key")[..], self.consensus_key),
+            _ => return Err(ViewError::NoData),
+        };
+
+        title[..title_content.len()].copy_from_slice(title_content);
+
+        let addr = Addr::from_hash(hash, crv).map_err(|_| ViewError::Unknown)?;
+
+        let (len, mex) = addr.base58();
+        handle_ui_message(&mex[..len], message, page)
+    }
+}
+
+#[cfg(test)]
+impl<'b> DrainDelegate<'b> {
+    pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
+        for (field, (crv, hash)) in [
+            ("consensus_key", self.consensus_key),
+            ("delegate", self.delegate),
+            ("destination", self.destination),
+        ] {
+            let (len, base58) = Addr::from_hash(hash, crv)
+                .expect("couldn't compute base58")
+                .base58();
+            let expected = json[field]
+                .as_str()
+                .unwrap_or_else(|| panic!("given json .{} is not a string", field));
+            assert_eq!(&base58[..len], expected.as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrainDelegate;
+    use crate::{crypto::Curve, parser::DisplayableItem};
+
+    const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                             016a7d4a43f51be0934a441fba4f13f9beaa475751\
+                             026a7d4a43f51be0934a441fba4f13f9beaa475751";
+
+    #[test]
+    fn drain_delegate() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, parsed) =
+            DrainDelegate::from_bytes(&input).expect("couldn't parse drain delegate");
+        assert_eq!(rem.len(), 0);
+
+        let expected = DrainDelegate {
+            consensus_key: (Curve::Bip32Ed25519, arrayref::array_ref!(input, 1, 20)),
+            delegate: (Curve::Secp256K1, arrayref::array_ref!(input, 22, 20)),
+            destination: (Curve::Secp256R1, arrayref::array_ref!(input, 43, 20)),
+        };
+        assert_eq!(parsed, expected);
+
+        //truncated destination
+        assert!(DrainDelegate::from_bytes(&input[..input.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn drain_delegate_ui() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = DrainDelegate::from_bytes(&input).expect("couldn't parse drain delegate");
+
+        let mut title = [0; 18];
+        let mut message = [0; 64];
+
+        parsed.render_item(0, &mut title, &mut message, 0).unwrap();
+        assert!(message.starts_with(b"Drain Delegate\x00"));
+
+        parsed.render_item(1, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"WARNING\x00"));
+
+        parsed.render_item(4, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Consensus Key\x00"));
+        assert!(message.starts_with(b"tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn\x00"));
+
+        assert!(parsed.render_item(5, &mut title, &mut message, 0).is_err());
+    }
+}
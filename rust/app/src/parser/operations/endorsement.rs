@@ -23,7 +23,10 @@ use nom::{
 use zemu_sys::ViewError;
 
 use crate::{
-    handlers::{handle_ui_message, parser_common::ParserError},
+    handlers::{
+        handle_ui_message, intstr_to_fpstr_inplace, parser_common::ParserError,
+        MAX_THOUSANDS_SEPARATORS,
+    },
     parser::DisplayableItem,
 };
 
@@ -38,6 +41,7 @@ impl Endorsement {
     #[inline(never)]
     pub fn from_bytes(input: &[u8]) -> IResult<&[u8], Self, ParserError> {
         let (rem, level) = be_i32(input)?;
+        Self::check_level(level)?;
 
         Ok((rem, Self { level }))
     }
@@ -48,6 +52,7 @@ impl Endorsement {
         out: &mut core::mem::MaybeUninit<Self>,
     ) -> Result<&'b [u8], nom::Err<ParserError>> {
         let (rem, level) = be_i32(input)?;
+        Self::check_level(level)?;
 
         let out = out.as_mut_ptr();
         unsafe {
@@ -56,6 +61,15 @@ impl Endorsement {
 
         Ok(rem)
     }
+
+    /// Levels are encoded as signed, but a negative one is never valid
+    fn check_level(level: i32) -> Result<(), nom::Err<ParserError>> {
+        if level < 0 {
+            Err(ParserError::parser_value_out_of_range.into())
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl DisplayableItem for Endorsement {
@@ -87,9 +101,17 @@ impl DisplayableItem for Endorsement {
                 let title_content = pic_str!(b"Level");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let mut zarith_buf = [0; i32::FORMATTED_SIZE_DECIMAL];
-
-                handle_ui_message(itoa(self.level, &mut zarith_buf), message, page)
+                //+1 for the null terminator
+                let mut zarith_buf =
+                    [0u8; i32::FORMATTED_SIZE_DECIMAL + 1 + MAX_THOUSANDS_SEPARATORS];
+                itoa(self.level, &mut zarith_buf);
+
+                handle_ui_message(
+                    intstr_to_fpstr_inplace(&mut zarith_buf, 0, true)
+                        .map_err(|_| ViewError::Unknown)?,
+                    message,
+                    page,
+                )
             }
             _ => Err(ViewError::NoData),
         }
@@ -335,6 +357,7 @@ mod tests {
     use crate::parser::operations::Operation;
 
     use super::{Endorsement, EndorsementWithSlot};
+    use crate::handlers::parser_common::ParserError;
     use arrayref::array_ref;
 
     #[test]
@@ -343,7 +366,7 @@ mod tests {
         const INPUT_HEX: &str = "00000027\
                                  a99b946c97ada0f42c1bdeae0383db7893351232a832d00d0cd716eb6f66e561\
                                  00\
-                                 fffffed4\
+                                 0000012c\
                                  0001\
                                  007b";
         const BRANCH_BASE58: &str = "BLzyjjHKEKMULtvkpSHxuZxx6ei6fpntH2BTkYZiLgs8zLVstvX";
@@ -360,7 +383,7 @@ mod tests {
 
         let expected = EndorsementWithSlot {
             branch: array_ref!(input, 4, 32),
-            endorsement: Endorsement { level: -300 },
+            endorsement: Endorsement { level: 300 },
             signature: &input[4 + 32 + 1 + 4..4 + 32 + 1 + 4 + 2],
             slot: 123,
         };
@@ -369,15 +392,22 @@ mod tests {
 
     #[test]
     fn endorsement() {
-        const INPUT_HEX: &str = "fffffed4";
+        const INPUT_HEX: &str = "0000012c";
 
         let input = hex::decode(INPUT_HEX).expect("invalid input hex");
 
         let (rem, parsed) = Endorsement::from_bytes(&input).expect("failed to parse endorsement");
         assert_eq!(rem.len(), 0);
 
-        let expected = Endorsement { level: -300 };
+        let expected = Endorsement { level: 300 };
         assert_eq!(parsed, expected);
+
+        //-300
+        let input = hex::decode("fffffed4").expect("invalid input hex");
+        assert_eq!(
+            Endorsement::from_bytes(&input),
+            Err(nom::Err::Error(ParserError::parser_value_out_of_range))
+        );
     }
 
     #[test]
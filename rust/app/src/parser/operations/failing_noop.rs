@@ -14,7 +14,7 @@
 *  limitations under the License.
 ********************************************************************************/
 use core::{mem::MaybeUninit, ptr::addr_of_mut};
-use nom::{do_parse, number::complete::be_u32, take, IResult};
+use nom::{bytes::complete::take, call, do_parse, number::complete::be_u32, IResult};
 use zemu_sys::ViewError;
 
 use crate::{
@@ -33,7 +33,7 @@ impl<'b> FailingNoop<'b> {
     #[inline(never)]
     pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
         let (rem, arbitrary) =
-            do_parse!(input, len: be_u32 >> arbitrary: take!(len) >> (arbitrary))?;
+            do_parse!(input, len: be_u32 >> arbitrary: call!(take(len)) >> (arbitrary))?;
 
         Ok((rem, Self { arbitrary }))
     }
@@ -44,7 +44,7 @@ impl<'b> FailingNoop<'b> {
         out: &mut MaybeUninit<Self>,
     ) -> Result<&'b [u8], nom::Err<ParserError>> {
         let (rem, arbitrary) =
-            do_parse!(input, len: be_u32 >> arbitrary: take!(len) >> (arbitrary))?;
+            do_parse!(input, len: be_u32 >> arbitrary: call!(take(len)) >> (arbitrary))?;
 
         let out = out.as_mut_ptr();
         //unsafe ptr valid and no uninit data read
@@ -54,6 +54,13 @@ impl<'b> FailingNoop<'b> {
     }
 }
 
+impl<'b> FailingNoop<'b> {
+    /// Whether the payload can be shown as is, instead of its hash
+    fn is_text(&self) -> bool {
+        !self.arbitrary.is_empty() && self.arbitrary.iter().all(|&c| (0x20..0x7F).contains(&c))
+    }
+}
+
 impl<'a> DisplayableItem for FailingNoop<'a> {
     fn num_items(&self) -> usize {
         1 + 1
@@ -78,13 +85,19 @@ impl<'a> DisplayableItem for FailingNoop<'a> {
                 let title_content = pic_str!(b"Type");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let mex = pic_str!("Failing Noop");
+                let mex = pic_str!("Sign proof message");
 
                 handle_ui_message(mex.as_bytes(), message, page)
             }
-            //source
+            //message, as text when possible
+            1 if self.is_text() => {
+                let title_content = pic_str!(b"Message");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(self.arbitrary, message, page)
+            }
             1 => {
-                let title_content = pic_str!(b"Data Hash");
+                let title_content = pic_str!(b"Message Hash");
                 title[..title_content.len()].copy_from_slice(title_content);
 
                 let sha = Sha256::digest(self.arbitrary).map_err(|_| ViewError::Unknown)?;
@@ -107,6 +120,7 @@ impl<'b> FailingNoop<'b> {
 #[cfg(test)]
 mod tests {
     use super::FailingNoop;
+    use crate::parser::DisplayableItem;
 
     #[test]
     fn failing_noop() {
@@ -123,4 +137,32 @@ mod tests {
         };
         assert_eq!(parsed, expected);
     }
+
+    #[test]
+    fn failing_noop_ui() {
+        let text = FailingNoop {
+            arbitrary: b"I own this address",
+        };
+        let binary = FailingNoop {
+            arbitrary: &[0x05, 0x01, 0x00],
+        };
+
+        let mut title = [0; 18];
+        let mut message = [0; 64];
+
+        text.render_item(0, &mut title, &mut message, 0).unwrap();
+        assert!(message.starts_with(b"Sign proof message\x00"));
+
+        text.render_item(1, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Message\x00"));
+        assert!(message.starts_with(b"I own this address\x00"));
+
+        binary.render_item(1, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Message Hash\x00"));
+        assert!(message[..message.len() - 1]
+            .iter()
+            .all(u8::is_ascii_hexdigit));
+
+        assert!(text.render_item(2, &mut title, &mut message, 0).is_err());
+    }
 }
@@ -17,6 +17,8 @@ use super::*;
 
 use core::ptr::addr_of_mut;
 
+use crate::parser::Zarith;
+
 #[repr(u8)] //IMPORTANT
             //see OperationType comment
 enum OperationTypeKind {
@@ -39,7 +41,6 @@ struct TransferVariant<'b>(OperationTypeKind, Transfer<'b>);
 struct DelegationVariant<'b>(OperationTypeKind, Delegation<'b>);
 
 #[repr(C)]
-
 struct EndorsementVariant(OperationTypeKind, Endorsement);
 
 #[repr(C)]
@@ -79,7 +80,22 @@ pub enum OperationType<'b> {
     Origination(Origination<'b>),
     ActivateAccount(ActivateAccount<'b>),
     FailingNoop(FailingNoop<'b>),
-    UnknownOp(&'b [u8]),
+    SeedNonceRevelation(SeedNonceRevelation<'b>),
+    RegisterGlobalConstant(RegisterGlobalConstant<'b>),
+    SetDepositsLimit(SetDepositsLimit<'b>),
+    IncreasePaidStorage(IncreasePaidStorage<'b>),
+    UpdateConsensusKey(UpdateConsensusKey<'b>),
+    DrainDelegate(DrainDelegate<'b>),
+    SmartRollupOriginate(SmartRollupOriginate<'b>),
+    SmartRollupAddMessages(SmartRollupAddMessages<'b>),
+    SmartRollupExecuteOutboxMessage(SmartRollupExecuteOutboxMessage<'b>),
+    SmartRollupCement(SmartRollupCement<'b>),
+    SmartRollupPublish(SmartRollupPublish<'b>),
+    SmartRollupRefute(SmartRollupRefute<'b>),
+    SmartRollupTimeout(SmartRollupTimeout<'b>),
+    SmartRollupRecoverBond(SmartRollupRecoverBond<'b>),
+    /// Trailing data skipped in tolerant mode, see [`EncodedOperations::set_tolerant`]
+    UnknownFields(&'b [u8]),
     #[cfg(not(test))]
     AnonymousOp(()),
     #[cfg(test)]
@@ -99,7 +115,7 @@ impl<'b> OperationType<'b> {
         let (rem, tag) = le_u8(input)?;
 
         let rem = match tag {
-            0x00 => {
+            tag::ENDORSEMENT => {
                 let out = out.as_mut_ptr() as *mut EndorsementVariant;
                 //valid pointer
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -113,12 +129,17 @@ impl<'b> OperationType<'b> {
 
                 rem
             }
-            anon @ 0x01 | anon @ 0x02 | anon @ 0x03 => {
+            tag::SEED_NONCE_REVELATION => {
+                let (rem, data) = SeedNonceRevelation::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SeedNonceRevelation(data));
+                rem
+            }
+            anon @ tag::DOUBLE_ENDORSEMENT_EVIDENCE | anon @ tag::DOUBLE_BAKING_EVIDENCE => {
                 let (rem, data) = AnonymousOp::from_bytes(anon, rem)?;
                 *out = MaybeUninit::new(Self::AnonymousOp(data));
                 rem
             }
-            0x04 => {
+            tag::ACTIVATE_ACCOUNT => {
                 let out = out.as_mut_ptr() as *mut ActivateAccountVariant;
                 //valid pointer
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -131,7 +152,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x05 => {
+            tag::PROPOSALS => {
                 let out = out.as_mut_ptr() as *mut ProposalsVariant;
                 //valid pointer
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -144,7 +165,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x06 => {
+            tag::BALLOT => {
                 let out = out.as_mut_ptr() as *mut BallotVariant;
                 //valid pointer
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -157,7 +178,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x0A => {
+            tag::ENDORSEMENT_WITH_SLOT => {
                 let out = out.as_mut_ptr() as *mut EndorsementWithSlotVariant;
                 //valid pointer
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -170,7 +191,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x11 => {
+            tag::FAILING_NOOP => {
                 let out = out.as_mut_ptr() as *mut FailingNoopVariant;
                 //valid pointer
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -183,7 +204,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x6B => {
+            tag::REVEAL => {
                 let out = out.as_mut_ptr() as *mut RevealVariant;
                 //valid pointer
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -196,7 +217,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x6C => {
+            tag::TRANSACTION => {
                 let out = out.as_mut_ptr() as *mut TransferVariant;
                 //valid ptr
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -209,7 +230,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x6D => {
+            tag::ORIGINATION => {
                 let out = out.as_mut_ptr() as *mut OriginationVariant;
                 //valid ptr
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -222,7 +243,7 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            0x6E => {
+            tag::DELEGATION => {
                 let out = out.as_mut_ptr() as *mut DelegationVariant;
                 //valid ptr
                 let data = unsafe { &mut *addr_of_mut!((*out).1).cast() };
@@ -235,10 +256,73 @@ impl<'b> OperationType<'b> {
                 }
                 rem
             }
-            _ => {
-                *out = MaybeUninit::new(Self::UnknownOp(rem));
-                &[] as _
+            tag::REGISTER_GLOBAL_CONSTANT => {
+                let (rem, data) = RegisterGlobalConstant::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::RegisterGlobalConstant(data));
+                rem
+            }
+            tag::SET_DEPOSITS_LIMIT => {
+                let (rem, data) = SetDepositsLimit::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SetDepositsLimit(data));
+                rem
+            }
+            tag::INCREASE_PAID_STORAGE => {
+                let (rem, data) = IncreasePaidStorage::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::IncreasePaidStorage(data));
+                rem
+            }
+            tag::UPDATE_CONSENSUS_KEY => {
+                let (rem, data) = UpdateConsensusKey::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::UpdateConsensusKey(data));
+                rem
+            }
+            tag::DRAIN_DELEGATE => {
+                let (rem, data) = DrainDelegate::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::DrainDelegate(data));
+                rem
+            }
+            tag::SMART_ROLLUP_ORIGINATE => {
+                let (rem, data) = SmartRollupOriginate::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupOriginate(data));
+                rem
+            }
+            tag::SMART_ROLLUP_ADD_MESSAGES => {
+                let (rem, data) = SmartRollupAddMessages::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupAddMessages(data));
+                rem
+            }
+            tag::SMART_ROLLUP_EXECUTE_OUTBOX_MESSAGE => {
+                let (rem, data) = SmartRollupExecuteOutboxMessage::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupExecuteOutboxMessage(data));
+                rem
+            }
+            tag::SMART_ROLLUP_CEMENT => {
+                let (rem, data) = SmartRollupCement::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupCement(data));
+                rem
             }
+            tag::SMART_ROLLUP_PUBLISH => {
+                let (rem, data) = SmartRollupPublish::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupPublish(data));
+                rem
+            }
+            tag::SMART_ROLLUP_REFUTE => {
+                let (rem, data) = SmartRollupRefute::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupRefute(data));
+                rem
+            }
+            tag::SMART_ROLLUP_TIMEOUT => {
+                let (rem, data) = SmartRollupTimeout::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupTimeout(data));
+                rem
+            }
+            tag::SMART_ROLLUP_RECOVER_BOND => {
+                let (rem, data) = SmartRollupRecoverBond::from_bytes(rem)?;
+                *out = MaybeUninit::new(Self::SmartRollupRecoverBond(data));
+                rem
+            }
+            //there's no telling how long an unknown operation is
+            _ => return Err(ParserError::UnknownOperation.into()),
         };
 
         Ok(rem)
@@ -248,25 +332,61 @@ impl<'b> OperationType<'b> {
         matches!(self, OperationType::Transfer(_))
     }
 
-    /// Returns the number of different items
-    /// in a given `OperationType`
-    ///
-    /// Usually, the number of fields of an operaton
-    pub fn ui_items(&self) -> usize {
-        match self {
-            Self::Transfer(tx) => tx.num_items(),
-            Self::Delegation(del) => del.num_items(),
-            Self::Endorsement(end) => end.num_items(),
-            Self::EndorsementWithSlot(end) => end.num_items(),
-            Self::Ballot(vote) => vote.num_items(),
-            Self::Reveal(rev) => rev.num_items(),
-            Self::Proposals(prop) => prop.num_items(),
-            Self::Origination(orig) => orig.num_items(),
-            Self::ActivateAccount(act) => act.num_items(),
-            Self::FailingNoop(fail) => fail.num_items(),
-            Self::UnknownOp(_) => 2,
-            Self::AnonymousOp(_) => 0,
-        }
+    /// Returns false for the data that is skipped instead of reviewed,
+    /// like unknown trailing fields and anonymous operations
+    pub fn is_reviewed(&self) -> bool {
+        !matches!(self, Self::UnknownFields(_) | Self::AnonymousOp(_))
+    }
+
+    /// Returns the fee paid by the operation, if it's a manager operation
+    pub fn fee(&self) -> Option<Zarith<'b>> {
+        let fee = match self {
+            Self::Transfer(tx) => tx.fee(),
+            Self::Delegation(del) => del.fee(),
+            Self::Reveal(rev) => rev.fee(),
+            Self::Origination(orig) => orig.fee(),
+            Self::RegisterGlobalConstant(reg) => reg.fee(),
+            Self::SetDepositsLimit(limit) => limit.fee(),
+            Self::IncreasePaidStorage(storage) => storage.fee(),
+            Self::UpdateConsensusKey(key) => key.fee(),
+            Self::SmartRollupOriginate(orig) => orig.fee(),
+            Self::SmartRollupAddMessages(add) => add.fee(),
+            Self::SmartRollupExecuteOutboxMessage(exec) => exec.fee(),
+            Self::SmartRollupCement(cement) => cement.fee(),
+            Self::SmartRollupPublish(publish) => publish.fee(),
+            Self::SmartRollupRefute(refute) => refute.fee(),
+            Self::SmartRollupTimeout(timeout) => timeout.fee(),
+            Self::SmartRollupRecoverBond(recover) => recover.fee(),
+            _ => return None,
+        };
+
+        Some(*fee)
+    }
+
+    /// Returns the maximum storage the operation can pay for, in bytes,
+    /// if it's a manager operation
+    pub fn storage_limit(&self) -> Option<Zarith<'b>> {
+        let limit = match self {
+            Self::Transfer(tx) => tx.storage_limit(),
+            Self::Delegation(del) => del.storage_limit(),
+            Self::Reveal(rev) => rev.storage_limit(),
+            Self::Origination(orig) => orig.storage_limit(),
+            Self::RegisterGlobalConstant(reg) => reg.storage_limit(),
+            Self::SetDepositsLimit(limit) => limit.storage_limit(),
+            Self::IncreasePaidStorage(storage) => storage.storage_limit(),
+            Self::UpdateConsensusKey(key) => key.storage_limit(),
+            Self::SmartRollupOriginate(orig) => orig.storage_limit(),
+            Self::SmartRollupAddMessages(add) => add.storage_limit(),
+            Self::SmartRollupExecuteOutboxMessage(exec) => exec.storage_limit(),
+            Self::SmartRollupCement(cement) => cement.storage_limit(),
+            Self::SmartRollupPublish(publish) => publish.storage_limit(),
+            Self::SmartRollupRefute(refute) => refute.storage_limit(),
+            Self::SmartRollupTimeout(timeout) => timeout.storage_limit(),
+            Self::SmartRollupRecoverBond(recover) => recover.storage_limit(),
+            _ => return None,
+        };
+
+        Some(*limit)
     }
 
     #[inline(never)]
@@ -297,7 +417,7 @@ impl<'b> OperationType<'b> {
     }
 
     #[inline(never)]
-    pub fn render_unknown(
+    pub fn render_unknown_fields(
         input: &'b [u8],
         item_n: u8,
         title: &mut [u8],
@@ -306,16 +426,22 @@ impl<'b> OperationType<'b> {
     ) -> Result<u8, ViewError> {
         use crate::handlers::handle_ui_message;
         use bolos::{pic_str, PIC};
+        use lexical_core::{write as itoa, Number};
 
         match item_n {
             0 => {
-                let title_content = pic_str!(b"Type");
+                let title_content = pic_str!(b"Unknown Fields");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                handle_ui_message(&pic_str!(b"Unknown Operation")[..], message, page)
+                let suffix = pic_str!(b" bytes"!);
+                let mut buf = [0; usize::FORMATTED_SIZE_DECIMAL + 6];
+                let len = itoa(input.len(), &mut buf).len();
+                buf[len..len + suffix.len()].copy_from_slice(&suffix[..]);
+
+                handle_ui_message(&buf[..len + suffix.len()], message, page)
             }
             1 => {
-                let title_content = pic_str!(b"Hash");
+                let title_content = pic_str!(b"Fields Hash");
                 title[..title_content.len()].copy_from_slice(title_content);
 
                 let (len, base58) = Self::hash_and_base58(input)?;
@@ -325,3 +451,109 @@ impl<'b> OperationType<'b> {
         }
     }
 }
+
+impl<'b> DisplayableItem for OperationType<'b> {
+    /// Returns the number of different items
+    /// in a given `OperationType`
+    ///
+    /// Usually, the number of fields of an operaton
+    fn num_items(&self) -> usize {
+        match self {
+            Self::Transfer(tx) => tx.num_items(),
+            Self::Delegation(del) => del.num_items(),
+            Self::Endorsement(end) => end.num_items(),
+            Self::EndorsementWithSlot(end) => end.num_items(),
+            Self::Ballot(vote) => vote.num_items(),
+            Self::Reveal(rev) => rev.num_items(),
+            Self::Proposals(prop) => prop.num_items(),
+            Self::Origination(orig) => orig.num_items(),
+            Self::ActivateAccount(act) => act.num_items(),
+            Self::FailingNoop(fail) => fail.num_items(),
+            Self::SeedNonceRevelation(snr) => snr.num_items(),
+            Self::RegisterGlobalConstant(reg) => reg.num_items(),
+            Self::SetDepositsLimit(limit) => limit.num_items(),
+            Self::IncreasePaidStorage(storage) => storage.num_items(),
+            Self::UpdateConsensusKey(key) => key.num_items(),
+            Self::DrainDelegate(drain) => drain.num_items(),
+            Self::SmartRollupOriginate(orig) => orig.num_items(),
+            Self::SmartRollupAddMessages(add) => add.num_items(),
+            Self::SmartRollupExecuteOutboxMessage(exec) => exec.num_items(),
+            Self::SmartRollupCement(cement) => cement.num_items(),
+            Self::SmartRollupPublish(publish) => publish.num_items(),
+            Self::SmartRollupRefute(refute) => refute.num_items(),
+            Self::SmartRollupTimeout(timeout) => timeout.num_items(),
+            Self::SmartRollupRecoverBond(recover) => recover.num_items(),
+            Self::UnknownFields(_) => 2,
+            Self::AnonymousOp(_) => 0,
+        }
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        match self {
+            Self::Transfer(tx) => tx.is_expert_item(item_n),
+            Self::Delegation(del) => del.is_expert_item(item_n),
+            Self::Reveal(rev) => rev.is_expert_item(item_n),
+            Self::Origination(orig) => orig.is_expert_item(item_n),
+            Self::RegisterGlobalConstant(reg) => reg.is_expert_item(item_n),
+            Self::SetDepositsLimit(limit) => limit.is_expert_item(item_n),
+            Self::IncreasePaidStorage(storage) => storage.is_expert_item(item_n),
+            Self::UpdateConsensusKey(key) => key.is_expert_item(item_n),
+            Self::SmartRollupOriginate(orig) => orig.is_expert_item(item_n),
+            Self::SmartRollupAddMessages(add) => add.is_expert_item(item_n),
+            Self::SmartRollupExecuteOutboxMessage(exec) => exec.is_expert_item(item_n),
+            Self::SmartRollupCement(cement) => cement.is_expert_item(item_n),
+            Self::SmartRollupPublish(publish) => publish.is_expert_item(item_n),
+            Self::SmartRollupRefute(refute) => refute.is_expert_item(item_n),
+            Self::SmartRollupTimeout(timeout) => timeout.is_expert_item(item_n),
+            Self::SmartRollupRecoverBond(recover) => recover.is_expert_item(item_n),
+            //not manager operations
+            _ => false,
+        }
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        match self {
+            Self::Transfer(tx) => tx.render_item(item_n, title, message, page),
+            Self::Delegation(delegation) => delegation.render_item(item_n, title, message, page),
+            Self::Endorsement(endorsement) => endorsement.render_item(item_n, title, message, page),
+            Self::EndorsementWithSlot(endorsement) => {
+                endorsement.render_item(item_n, title, message, page)
+            }
+            Self::Ballot(vote) => vote.render_item(item_n, title, message, page),
+            Self::Reveal(rev) => rev.render_item(item_n, title, message, page),
+            Self::Proposals(props) => props.render_item(item_n, title, message, page),
+            Self::Origination(orig) => orig.render_item(item_n, title, message, page),
+            Self::ActivateAccount(act) => act.render_item(item_n, title, message, page),
+            Self::FailingNoop(fail) => fail.render_item(item_n, title, message, page),
+            Self::SeedNonceRevelation(snr) => snr.render_item(item_n, title, message, page),
+            Self::RegisterGlobalConstant(reg) => reg.render_item(item_n, title, message, page),
+            Self::SetDepositsLimit(limit) => limit.render_item(item_n, title, message, page),
+            Self::IncreasePaidStorage(storage) => storage.render_item(item_n, title, message, page),
+            Self::UpdateConsensusKey(key) => key.render_item(item_n, title, message, page),
+            Self::DrainDelegate(drain) => drain.render_item(item_n, title, message, page),
+            Self::SmartRollupOriginate(orig) => orig.render_item(item_n, title, message, page),
+            Self::SmartRollupAddMessages(add) => add.render_item(item_n, title, message, page),
+            Self::SmartRollupExecuteOutboxMessage(exec) => {
+                exec.render_item(item_n, title, message, page)
+            }
+            Self::SmartRollupCement(cement) => cement.render_item(item_n, title, message, page),
+            Self::SmartRollupPublish(publish) => publish.render_item(item_n, title, message, page),
+            Self::SmartRollupRefute(refute) => refute.render_item(item_n, title, message, page),
+            Self::SmartRollupTimeout(timeout) => timeout.render_item(item_n, title, message, page),
+            Self::SmartRollupRecoverBond(recover) => {
+                recover.render_item(item_n, title, message, page)
+            }
+            Self::UnknownFields(unk) => {
+                Self::render_unknown_fields(unk, item_n, title, message, page)
+            }
+            Self::AnonymousOp(_) => Err(ViewError::Reject),
+        }
+    }
+}
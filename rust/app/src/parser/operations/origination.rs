@@ -14,15 +14,15 @@
 *  limitations under the License.
 ********************************************************************************/
 use core::{mem::MaybeUninit, ptr::addr_of_mut};
-use nom::{call, cond, do_parse, number::complete::be_u32, take, IResult};
+use nom::{bytes::complete::take, call, cond, do_parse, number::complete::be_u32, IResult};
 use zemu_sys::ViewError;
 
 use crate::{
     crypto::Curve,
     handlers::{
-        handle_ui_message, intstr_to_fpstr_inplace, parser_common::ParserError, public_key::Addr,
+        handle_ui_message, parser_common::ParserError, public_key::Addr, MAX_THOUSANDS_SEPARATORS,
     },
-    parser::{boolean, public_key_hash, DisplayableItem, Zarith},
+    parser::{boolean, michelson::expr_hash, public_key_hash, DisplayableItem, Zarith},
 };
 
 #[derive(Clone, Copy, PartialEq, Eq, property::Property)]
@@ -38,14 +38,29 @@ impl<'b> Script<'b> {
         let (rem, (code, storage)) = do_parse!(
             input,
             code_len: be_u32
-                >> code: take!(code_len)
+                >> code: call!(take(code_len))
                 >> storage_len: be_u32
-                >> storage: take!(storage_len)
+                >> storage: call!(take(storage_len))
                 >> (code, storage)
         )?;
 
         Ok((rem, Self { code, storage }))
     }
+
+    /// Number of bytes of the contract's code and initial storage
+    pub fn size(&self) -> usize {
+        self.code.len() + self.storage.len()
+    }
+
+    /// Computes the `expr` hash of the contract's code
+    pub fn code_hash(&self) -> Result<[u8; 32], bolos::Error> {
+        expr_hash(self.code)
+    }
+
+    /// Computes the `expr` hash of the contract's initial storage
+    pub fn storage_hash(&self) -> Result<[u8; 32], bolos::Error> {
+        expr_hash(self.storage)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, property::Property)]
@@ -67,11 +82,11 @@ impl<'b> Origination<'b> {
     pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
         let (rem, (source, fee, counter, gas_limit, storage_limit, balance, delegate, script)) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
-            balance: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            balance: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
             has_delegate: boolean >>
             delegate: cond!(has_delegate, public_key_hash) >>
             script: call!(Script::from_bytes) >>
@@ -100,11 +115,11 @@ impl<'b> Origination<'b> {
     ) -> Result<&'b [u8], nom::Err<ParserError>> {
         let (rem, (source, fee, counter, gas_limit, storage_limit, balance, delegate, script)) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
-            balance: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            balance: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
             has_delegate: boolean >>
             delegate: cond!(has_delegate, public_key_hash) >>
             script: call!(Script::from_bytes) >>
@@ -141,9 +156,22 @@ impl<'b> Origination<'b> {
     }
 }
 
+impl Origination<'_> {
+    const GAS_LIMIT_ITEM: u8 = 8;
+    const STORAGE_LIMIT_ITEM: u8 = 9;
+    const COUNTER_ITEM: u8 = 10;
+}
+
 impl<'a> DisplayableItem for Origination<'a> {
     fn num_items(&self) -> usize {
-        1 + 9
+        1 + 10
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
     }
 
     #[inline(never)]
@@ -154,13 +182,11 @@ impl<'a> DisplayableItem for Origination<'a> {
         message: &mut [u8],
         page: u8,
     ) -> Result<u8, ViewError> {
-        use bolos::{
-            hash::{Hasher, Sha256},
-            pic_str, PIC,
-        };
+        use bolos::{pic_str, PIC};
         use lexical_core::{write as itoa, Number};
 
-        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2]; //+2 for decimal formatting
+        //+2 for decimal formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
 
         match item_n {
             //home
@@ -185,14 +211,13 @@ impl<'a> DisplayableItem for Origination<'a> {
                 let title_content = pic_str!(b"Balance");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let (_, amount) = self.balance.read_as::<usize>().ok_or(ViewError::Unknown)?;
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let amount = self
+                    .balance
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
 
-                itoa(amount, &mut zarith_buf);
-                handle_ui_message(
-                    intstr_to_fpstr_inplace(&mut zarith_buf, 6).map_err(|_| ViewError::Unknown)?,
-                    message,
-                    page,
-                )
+                handle_ui_message(amount, message, page)
             }
             //delegate
             3 => {
@@ -209,41 +234,52 @@ impl<'a> DisplayableItem for Origination<'a> {
                 let title_content = pic_str!(b"Fee");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let (_, fee) = self.fee().read_as::<usize>().ok_or(ViewError::Unknown)?;
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
 
-                itoa(fee, &mut zarith_buf);
-                handle_ui_message(
-                    intstr_to_fpstr_inplace(&mut zarith_buf, 6).map_err(|_| ViewError::Unknown)?,
-                    message,
-                    page,
-                )
+                handle_ui_message(fee, message, page)
             }
-            //Script code
+            //Script code hash
             5 => {
-                let title_content = pic_str!(b"Code");
+                let title_content = pic_str!(b"Code Hash");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let sha = Sha256::digest(self.script.code).map_err(|_| ViewError::Unknown)?;
+                let hash = self.script.code_hash().map_err(|_| ViewError::Unknown)?;
                 let mut hex_buf = [0; 32 * 2];
                 //this is impossible that will error since the sizes are all checked
-                hex::encode_to_slice(&sha[..], &mut hex_buf).unwrap();
+                hex::encode_to_slice(&hash[..], &mut hex_buf).unwrap();
 
                 handle_ui_message(&hex_buf[..], message, page)
             }
-            //Script storage
+            //Script storage hash
             6 => {
-                let title_content = pic_str!(b"Storage");
+                let title_content = pic_str!(b"Storage Hash");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let sha = Sha256::digest(self.script.storage).map_err(|_| ViewError::Unknown)?;
+                let hash = self.script.storage_hash().map_err(|_| ViewError::Unknown)?;
                 let mut hex_buf = [0; 32 * 2];
                 //this is impossible that will error since the sizes are all checked
-                hex::encode_to_slice(&sha[..], &mut hex_buf).unwrap();
+                hex::encode_to_slice(&hash[..], &mut hex_buf).unwrap();
 
                 handle_ui_message(&hex_buf[..], message, page)
             }
-            //gas_limit
+            //Script size
             7 => {
+                let title_content = pic_str!(b"Script Size");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; usize::FORMATTED_SIZE_DECIMAL + 6];
+                let len = itoa(self.script.size(), &mut mex[..]).len();
+                let unit = pic_str!(b" bytes");
+                mex[len..len + unit.len()].copy_from_slice(unit);
+
+                handle_ui_message(&mex[..len + unit.len()], message, page)
+            }
+            //gas_limit
+            Self::GAS_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Gas Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -255,7 +291,7 @@ impl<'a> DisplayableItem for Origination<'a> {
                 handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
             }
             //storage_limit
-            8 => {
+            Self::STORAGE_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Storage Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -267,7 +303,7 @@ impl<'a> DisplayableItem for Origination<'a> {
                 handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
             }
             //counter
-            9 => {
+            Self::COUNTER_ITEM => {
                 let title_content = pic_str!(b"Counter");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -321,7 +357,10 @@ impl<'b> Origination<'b> {
 mod tests {
     use arrayref::array_ref;
 
-    use crate::{crypto::Curve, parser::Zarith};
+    use crate::{
+        crypto::Curve,
+        parser::{DisplayableItem, Zarith},
+    };
 
     use super::{Origination, Script};
 
@@ -374,4 +413,49 @@ mod tests {
         };
         assert_eq!(parsed, expected);
     }
+
+    #[test]
+    fn script_hashes() {
+        //code `{}`, storage `Unit`
+        let input = hex::decode("00000005020000000000000002030b").expect("invalid input hex");
+        let (rem, script) = Script::from_bytes(&input).expect("failed to parse script");
+        assert_eq!(rem.len(), 0);
+
+        let expected_code =
+            hex::decode("f0147b0fcd9ed958297e3e663f25d0427f7f70576f22047a8d52953941a8b057")
+                .unwrap();
+        let expected_storage =
+            hex::decode("8b456a4530fb6d0fea9a0dcd0e9d6ff6b30d13b05d05cb4a375dd91bf2a7eadc")
+                .unwrap();
+        assert_eq!(&script.code_hash().unwrap()[..], &expected_code[..]);
+        assert_eq!(&script.storage_hash().unwrap()[..], &expected_storage[..]);
+    }
+
+    #[test]
+    fn script_size() {
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e\
+                                 01\
+                                 0a\
+                                 0a\
+                                 00\
+                                 00\
+                                 00000005020000000000000002030b";
+
+        //a following operation should be left untouched
+        let mut input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        input.extend_from_slice(&[0x6c, 0x00]);
+
+        let (rem, parsed) = Origination::from_bytes(&input).expect("failed to parse origination");
+        assert_eq!(rem, &[0x6c, 0x00]);
+        assert_eq!(parsed.script.size(), 7);
+
+        let mut title = [0; 32];
+        let mut message = [0; 32];
+        parsed
+            .render_item(7, &mut title, &mut message, 0)
+            .expect("couldn't render script size");
+        assert!(title.starts_with(b"Script Size"));
+        assert!(message.starts_with(b"7 bytes\x00"));
+    }
 }
@@ -15,9 +15,10 @@
 ********************************************************************************/
 use core::{mem::MaybeUninit, ptr::addr_of_mut};
 use nom::{
-    do_parse,
+    bytes::complete::take,
+    call, do_parse,
     number::complete::{be_i32, be_u32},
-    take, IResult,
+    IResult,
 };
 use zemu_sys::ViewError;
 
@@ -48,7 +49,7 @@ impl<'b> Proposals<'b> {
             source: public_key_hash >>
             period: be_i32 >>
             proposals_len: be_u32 >>
-            proposals: take!(proposals_len) >>
+            proposals: call!(take(proposals_len)) >>
             (source, period, proposals)
         }?;
 
@@ -74,7 +75,7 @@ impl<'b> Proposals<'b> {
             source: public_key_hash >>
             period: be_i32 >>
             proposals_len: be_u32 >>
-            proposals: take!(proposals_len) >>
+            proposals: call!(take(proposals_len)) >>
             (source, period, proposals)
         }?;
 
@@ -0,0 +1,282 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use nom::{bytes::complete::take, call, do_parse, number::complete::be_u32, IResult};
+use zemu_sys::ViewError;
+
+use crate::{
+    crypto::Curve,
+    handlers::{
+        handle_ui_message, parser_common::ParserError, public_key::Addr, MAX_THOUSANDS_SEPARATORS,
+    },
+    parser::{public_key_hash, DisplayableItem, Zarith},
+};
+
+#[derive(Clone, Copy, PartialEq, property::Property)]
+#[cfg_attr(test, derive(Debug))]
+#[property(mut(disable), get(public), set(disable))]
+pub struct RegisterGlobalConstant<'b> {
+    source: (Curve, &'b [u8; 20]),
+    fee: Zarith<'b>,
+    counter: Zarith<'b>,
+    gas_limit: Zarith<'b>,
+    storage_limit: Zarith<'b>,
+    /// Michelson value, binary encoded
+    value: &'b [u8],
+}
+
+impl<'b> RegisterGlobalConstant<'b> {
+    #[inline(never)]
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        crate::sys::zemu_log_stack("RegisterGlobalConstant::from_bytes\x00");
+
+        let (rem, (source, fee, counter, gas_limit, storage_limit, value)) = do_parse! {input,
+            source: public_key_hash >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            len: be_u32 >>
+            value: call!(take(len)) >>
+            (source, fee, counter, gas_limit, storage_limit, value)
+        }?;
+
+        Ok((
+            rem,
+            Self {
+                source,
+                fee,
+                counter,
+                gas_limit,
+                storage_limit,
+                value,
+            },
+        ))
+    }
+}
+
+impl RegisterGlobalConstant<'_> {
+    const GAS_LIMIT_ITEM: u8 = 4;
+    const STORAGE_LIMIT_ITEM: u8 = 5;
+    const COUNTER_ITEM: u8 = 6;
+}
+
+impl<'a> DisplayableItem for RegisterGlobalConstant<'a> {
+    fn num_items(&self) -> usize {
+        1 + 6
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+        use lexical_core::{write as itoa, Number};
+
+        //+2 for fixed point formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
+
+        match item_n {
+            //home
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"Register Global Constant")[..], message, page)
+            }
+            //source
+            1 => {
+                let title_content = pic_str!(b"Source");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (crv, hash) = self.source();
+
+                let addr = Addr::from_hash(hash, *crv).map_err(|_| ViewError::Unknown)?;
+
+                let (len, mex) = addr.base58();
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //value
+            2 => {
+                let title_content = pic_str!(b"Value Size");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let suffix = pic_str!(b" bytes"!);
+                let len = itoa(self.value.len(), &mut zarith_buf).len();
+                zarith_buf[len..len + suffix.len()].copy_from_slice(&suffix[..]);
+
+                handle_ui_message(&zarith_buf[..len + suffix.len()], message, page)
+            }
+            //fee
+            3 => {
+                let title_content = pic_str!(b"Fee");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(fee, message, page)
+            }
+            //gas_limit
+            Self::GAS_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Gas Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, gas_limit) = self
+                    .gas_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
+            }
+            //storage_limit
+            Self::STORAGE_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Storage Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, storage_limit) = self
+                    .storage_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
+            }
+            //counter
+            Self::COUNTER_ITEM => {
+                let title_content = pic_str!(b"Counter");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, counter) = self
+                    .counter()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(counter, &mut zarith_buf), message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<'b> RegisterGlobalConstant<'b> {
+    pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
+        //verify source address of the operation
+        let (crv, hash) = self.source();
+        let (len, source_base58) = Addr::from_hash(hash, *crv)
+            .expect("couldn't compute source base58")
+            .base58();
+        let expected_source_base58 = json["source"]
+            .as_str()
+            .expect("given json .source is not a string");
+        assert_eq!(&source_base58[..len], expected_source_base58.as_bytes());
+
+        self.counter().is(&json["counter"]);
+        self.fee().is(&json["fee"]);
+        self.gas_limit().is(&json["gas_limit"]);
+        self.storage_limit().is(&json["storage_limit"]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegisterGlobalConstant;
+    use crate::{
+        crypto::Curve,
+        parser::{DisplayableItem, Zarith},
+    };
+
+    const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                             904e\
+                             01\
+                             0a\
+                             0a\
+                             00000005\
+                             0200000000";
+
+    #[test]
+    fn register_global_constant() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, parsed) = RegisterGlobalConstant::from_bytes(&input)
+            .expect("couldn't parse register global constant");
+        assert_eq!(rem.len(), 0);
+
+        let expected = RegisterGlobalConstant {
+            //0 is the 00 to identify implicit contract
+            source: (Curve::Bip32Ed25519, arrayref::array_ref!(input, 1, 20)),
+            fee: Zarith {
+                is_negative: None,
+                bytes: &input[21..23],
+            },
+            counter: Zarith {
+                is_negative: None,
+                bytes: &input[23..24],
+            },
+            gas_limit: Zarith {
+                is_negative: None,
+                bytes: &input[24..25],
+            },
+            storage_limit: Zarith {
+                is_negative: None,
+                bytes: &input[25..26],
+            },
+            //26..30 is the length of the value
+            value: &input[30..],
+        };
+
+        assert_eq!(parsed, expected);
+
+        //truncated value
+        assert!(RegisterGlobalConstant::from_bytes(&input[..input.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn register_global_constant_ui() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = RegisterGlobalConstant::from_bytes(&input)
+            .expect("couldn't parse register global constant");
+
+        let mut title = [0; 18];
+        let mut message = [0; 32];
+
+        parsed.render_item(0, &mut title, &mut message, 0).unwrap();
+        assert!(message.starts_with(b"Register Global Constant\x00"));
+
+        parsed.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Value Size\x00"));
+        assert!(message.starts_with(b"5 bytes\x00"));
+
+        parsed.render_item(3, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Fee\x00"));
+        assert!(message.starts_with(b"0.01 XTZ\x00"));
+
+        assert!(parsed.render_item(7, &mut title, &mut message, 0).is_err());
+    }
+}
@@ -18,10 +18,9 @@ use nom::{call, do_parse, IResult};
 use zemu_sys::ViewError;
 
 use crate::{
-    crypto::Curve,
+    crypto::{public_key_base58, Curve, PUBLIC_KEY_BASE58_LEN},
     handlers::{
-        handle_ui_message, intstr_to_fpstr_inplace, parser_common::ParserError, public_key::Addr,
-        sha256x2,
+        handle_ui_message, parser_common::ParserError, public_key::Addr, MAX_THOUSANDS_SEPARATORS,
     },
     parser::{public_key, public_key_hash, DisplayableItem, Zarith},
 };
@@ -43,10 +42,10 @@ impl<'b> Reveal<'b> {
     pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
         let (rem, (source, fee, counter, gas_limit, storage_limit, public_key)) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
             public_key: public_key >>
             (source, fee, counter, gas_limit, storage_limit, public_key)
         }?;
@@ -71,10 +70,10 @@ impl<'b> Reveal<'b> {
     ) -> Result<&'b [u8], nom::Err<ParserError>> {
         let (rem, (source, fee, counter, gas_limit, storage_limit, public_key)) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
             public_key: public_key >>
             (source, fee, counter, gas_limit, storage_limit, public_key)
         }?;
@@ -101,11 +100,24 @@ impl<'b> Reveal<'b> {
     }
 }
 
+impl Reveal<'_> {
+    const GAS_LIMIT_ITEM: u8 = 4;
+    const STORAGE_LIMIT_ITEM: u8 = 5;
+    const COUNTER_ITEM: u8 = 6;
+}
+
 impl<'b> DisplayableItem for Reveal<'b> {
     fn num_items(&self) -> usize {
         1 + 6
     }
 
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
+    }
+
     #[inline(never)]
     fn render_item(
         &self,
@@ -117,7 +129,8 @@ impl<'b> DisplayableItem for Reveal<'b> {
         use bolos::{pic_str, PIC};
         use lexical_core::{write as itoa, Number};
 
-        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2]; //+2 for decimal formatting
+        //+2 for decimal formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
 
         match item_n {
             //Homepage
@@ -140,8 +153,8 @@ impl<'b> DisplayableItem for Reveal<'b> {
                 let title_content = pic_str!("Public Key");
                 title[..title_content.len()].copy_from_slice(title_content.as_bytes());
 
-                let mut public_key = [0; MAX_PK_BASE58_LEN];
-                let pk_len = pk_to_base58(self.public_key, &mut public_key)
+                let mut public_key = [0; PUBLIC_KEY_BASE58_LEN];
+                let pk_len = public_key_base58(self.public_key, &mut public_key)
                     .map_err(|_| ViewError::Unknown)?;
 
                 handle_ui_message(&public_key[..pk_len], message, page)
@@ -151,17 +164,16 @@ impl<'b> DisplayableItem for Reveal<'b> {
                 let title_content = pic_str!(b"Fee");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let (_, fee) = self.fee().read_as::<usize>().ok_or(ViewError::Unknown)?;
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
 
-                itoa(fee, &mut zarith_buf);
-                handle_ui_message(
-                    intstr_to_fpstr_inplace(&mut zarith_buf, 6).map_err(|_| ViewError::Unknown)?,
-                    message,
-                    page,
-                )
+                handle_ui_message(fee, message, page)
             }
             //gas_limit
-            4 => {
+            Self::GAS_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Gas Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -173,7 +185,7 @@ impl<'b> DisplayableItem for Reveal<'b> {
                 handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
             }
             //storage_limit
-            5 => {
+            Self::STORAGE_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Storage Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -185,7 +197,7 @@ impl<'b> DisplayableItem for Reveal<'b> {
                 handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
             }
             //counter
-            6 => {
+            Self::COUNTER_ITEM => {
                 let title_content = pic_str!(b"Counter");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -201,34 +213,6 @@ impl<'b> DisplayableItem for Reveal<'b> {
     }
 }
 
-const MAX_PK_BASE58_LEN: usize = 56;
-/// Encodes a public key as base58 on the provided `out` buffer
-///
-/// returns the number of bytes written
-fn pk_to_base58(
-    (crv, bytes): (Curve, &[u8]),
-    out: &mut [u8; MAX_PK_BASE58_LEN],
-) -> Result<usize, bolos::Error> {
-    let prefix = crv.to_prefix();
-
-    let mut checksum = [0; 4];
-    sha256x2(&[prefix, bytes], &mut checksum)?;
-
-    let (len, input) = {
-        //initialize with max len
-        let mut array = [0; 4 + 33 + 4];
-        array[..4].copy_from_slice(prefix);
-        array[4..4 + bytes.len()].copy_from_slice(bytes);
-        array[4 + bytes.len()..4 + bytes.len() + 4].copy_from_slice(&checksum[..]);
-        (4 + bytes.len() + 4, array)
-    };
-
-    let len = bs58::encode(&input[..len])
-        .into(&mut out[..])
-        .expect("encoded in base58 is not of the right length");
-    Ok(len)
-}
-
 #[cfg(test)]
 impl<'b> Reveal<'b> {
     pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
@@ -247,8 +231,8 @@ impl<'b> Reveal<'b> {
         self.storage_limit.is(&json["storage_limit"]);
 
         //verify public key
-        let mut pk_base58 = [0; MAX_PK_BASE58_LEN];
-        let pk_base58_len = pk_to_base58(self.public_key, &mut pk_base58)
+        let mut pk_base58 = [0; PUBLIC_KEY_BASE58_LEN];
+        let pk_base58_len = public_key_base58(self.public_key, &mut pk_base58)
             .expect("couldn't compute public key base58");
 
         let expected_pk_base58 = json["public_key"]
@@ -263,12 +247,9 @@ impl<'b> Reveal<'b> {
 mod tests {
     use arrayref::array_ref;
 
-    use crate::{
-        crypto::Curve,
-        parser::{operations::reveal::MAX_PK_BASE58_LEN, Zarith},
-    };
+    use crate::{crypto::Curve, parser::Zarith};
 
-    use super::{pk_to_base58, Reveal};
+    use super::Reveal;
 
     #[test]
     fn reveal() {
@@ -308,31 +289,31 @@ mod tests {
     }
 
     #[test]
-    fn public_key_base58() {
-        let mut base58 = [0; MAX_PK_BASE58_LEN];
-
-        let len = pk_to_base58((Curve::Bip32Ed25519, &[0x00; 32]), &mut base58)
-            .expect("couldn't encode Secp256K1 to base58");
-        assert_eq!(len, 54);
-
-        let len = pk_to_base58((Curve::Bip32Ed25519, &[0xff; 32]), &mut base58)
-            .expect("couldn't encode Secp256K1 to base58");
-        assert_eq!(len, 54);
-
-        let len = pk_to_base58((Curve::Secp256K1, &[0; 33]), &mut base58)
-            .expect("couldn't encode Secp256K1 to base58");
-        assert_eq!(len, 55);
-
-        let len = pk_to_base58((Curve::Secp256K1, &[0xff; 33]), &mut base58)
-            .expect("couldn't encode Secp256K1 to base58");
-        assert_eq!(len, 55);
-
-        let len = pk_to_base58((Curve::Secp256R1, &[0; 33]), &mut base58)
-            .expect("couldn't encode Secp256K1 to base58");
-        assert_eq!(len, 55);
+    fn reveal_all_curves() {
+        const PARTIAL_INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                         904e\
+                                         01\
+                                         0a\
+                                         0a";
+
+        for (tag, curve, len) in [
+            (0x00, Curve::Bip32Ed25519, 32),
+            (0x01, Curve::Secp256K1, 33),
+            (0x02, Curve::Secp256R1, 33),
+        ] {
+            let mut input = hex::decode(PARTIAL_INPUT_HEX).expect("invalid input hex");
+            input.push(tag);
+            input.resize(input.len() + len, 0x42);
+
+            let (rem, parsed) = Reveal::from_bytes(&input).expect("failed to parse reveal");
+            assert_eq!(rem.len(), 0);
+            assert_eq!(parsed.public_key, (curve, &input[26 + 1..]));
+        }
 
-        let len = pk_to_base58((Curve::Secp256R1, &[0xff; 33]), &mut base58)
-            .expect("couldn't encode Secp256K1 to base58");
-        assert_eq!(len, 55);
+        //unknown curve tag
+        let mut input = hex::decode(PARTIAL_INPUT_HEX).expect("invalid input hex");
+        input.push(0x03);
+        input.extend_from_slice(&[0x42; 33]);
+        assert!(Reveal::from_bytes(&input).is_err());
     }
 }
@@ -14,8 +14,15 @@
 *  limitations under the License.
 ********************************************************************************/
 use nom::{bytes::complete::take, number::complete::be_i32, IResult};
+use zemu_sys::ViewError;
 
-use crate::handlers::parser_common::ParserError;
+use crate::{
+    handlers::{
+        handle_ui_message, intstr_to_fpstr_inplace, parser_common::ParserError,
+        MAX_THOUSANDS_SEPARATORS,
+    },
+    parser::DisplayableItem,
+};
 
 const SEED_NONCE_BYTES_LEN: usize = 32;
 
@@ -37,6 +44,63 @@ impl<'b> SeedNonceRevelation<'b> {
     }
 }
 
+impl<'b> DisplayableItem for SeedNonceRevelation<'b> {
+    fn num_items(&self) -> usize {
+        1 + 2
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+        use lexical_core::{write as itoa, Number};
+
+        match item_n {
+            //home
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"Seed Nonce Revelation")[..], message, page)
+            }
+            //level
+            1 => {
+                let title_content = pic_str!(b"Level");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                //+1 for the null terminator
+                let mut zarith_buf =
+                    [0u8; i32::FORMATTED_SIZE_DECIMAL + 1 + MAX_THOUSANDS_SEPARATORS];
+                itoa(self.level, &mut zarith_buf);
+
+                handle_ui_message(
+                    intstr_to_fpstr_inplace(&mut zarith_buf, 0, true)
+                        .map_err(|_| ViewError::Unknown)?,
+                    message,
+                    page,
+                )
+            }
+            //nonce
+            2 => {
+                let title_content = pic_str!(b"Nonce");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut hex_buf = [0; SEED_NONCE_BYTES_LEN * 2];
+                //this is impossible that will error since the sizes are all checked
+                hex::encode_to_slice(&self.nonce[..], &mut hex_buf).unwrap();
+
+                handle_ui_message(&hex_buf[..], message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+}
+
 #[cfg(test)]
 impl<'b> SeedNonceRevelation<'b> {
     pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
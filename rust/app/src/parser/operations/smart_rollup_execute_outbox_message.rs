@@ -0,0 +1,384 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use nom::{bytes::complete::take, call, do_parse, number::complete::be_u32, IResult};
+use zemu_sys::ViewError;
+
+use crate::{
+    constants::tzprefix::{SR1, SRC1},
+    crypto::Curve,
+    handlers::{
+        handle_ui_message, parser_common::ParserError, public_key::Addr, sha256x2,
+        MAX_THOUSANDS_SEPARATORS,
+    },
+    parser::{public_key_hash, DisplayableItem, Zarith},
+};
+
+pub(super) const ROLLUP_BYTES_LEN: usize = 20;
+pub(super) const COMMITMENT_BYTES_LEN: usize = 32;
+
+pub(super) const ROLLUP_BASE58_LEN: usize = 36;
+/// Also the length of the state hashes
+pub(super) const COMMITMENT_BASE58_LEN: usize = 54;
+
+#[derive(Clone, Copy, PartialEq, property::Property)]
+#[cfg_attr(test, derive(Debug))]
+#[property(mut(disable), get(public), set(disable))]
+pub struct SmartRollupExecuteOutboxMessage<'b> {
+    source: (Curve, &'b [u8; 20]),
+    fee: Zarith<'b>,
+    counter: Zarith<'b>,
+    gas_limit: Zarith<'b>,
+    storage_limit: Zarith<'b>,
+    rollup: &'b [u8; ROLLUP_BYTES_LEN],
+    cemented_commitment: &'b [u8; COMMITMENT_BYTES_LEN],
+    output_proof: &'b [u8],
+}
+
+impl<'b> SmartRollupExecuteOutboxMessage<'b> {
+    #[inline(never)]
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        crate::sys::zemu_log_stack("SmartRollupExecuteOutboxMessage::from_bytes\x00");
+
+        let (
+            rem,
+            (
+                source,
+                fee,
+                counter,
+                gas_limit,
+                storage_limit,
+                rollup,
+                cemented_commitment,
+                output_proof,
+            ),
+        ) = do_parse! {input,
+            source: public_key_hash >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            rollup: call!(take(ROLLUP_BYTES_LEN)) >>
+            cemented_commitment: call!(take(COMMITMENT_BYTES_LEN)) >>
+            proof_len: be_u32 >>
+            output_proof: call!(take(proof_len)) >>
+            (source, fee, counter, gas_limit, storage_limit, rollup, cemented_commitment, output_proof)
+        }?;
+
+        Ok((
+            rem,
+            Self {
+                source,
+                fee,
+                counter,
+                gas_limit,
+                storage_limit,
+                rollup: arrayref::array_ref!(rollup, 0, ROLLUP_BYTES_LEN),
+                cemented_commitment: arrayref::array_ref!(
+                    cemented_commitment,
+                    0,
+                    COMMITMENT_BYTES_LEN
+                ),
+                output_proof,
+            },
+        ))
+    }
+
+    pub fn rollup_base58(&self) -> Result<(usize, [u8; ROLLUP_BASE58_LEN]), bolos::Error> {
+        rollup_hash_base58(SR1, &self.rollup[..])
+    }
+
+    pub fn commitment_base58(&self) -> Result<(usize, [u8; COMMITMENT_BASE58_LEN]), bolos::Error> {
+        rollup_hash_base58(SRC1, &self.cemented_commitment[..])
+    }
+}
+
+/// Encodes a smart rollup address or hash in base58, with the given prefix
+///
+/// The prefix is at most 4 bytes and the hash at most 32 bytes
+#[inline(never)]
+pub(super) fn rollup_hash_base58<const N: usize>(
+    prefix: &[u8],
+    hash: &[u8],
+) -> Result<(usize, [u8; N]), bolos::Error> {
+    let mut checksum = [0; 4];
+
+    sha256x2(&[prefix, hash], &mut checksum)?;
+
+    //longest prefix, longest hash and checksum
+    let mut input = [0; 4 + COMMITMENT_BYTES_LEN + 4];
+    let len = prefix.len() + hash.len() + 4;
+
+    input[..prefix.len()].copy_from_slice(prefix);
+    input[prefix.len()..prefix.len() + hash.len()].copy_from_slice(hash);
+    input[prefix.len() + hash.len()..len].copy_from_slice(&checksum[..]);
+
+    let mut out = [0; N];
+    let len = bs58::encode(&input[..len])
+        .into(&mut out[..])
+        .expect("encoded in base58 is not of the right length");
+
+    Ok((len, out))
+}
+
+impl SmartRollupExecuteOutboxMessage<'_> {
+    const GAS_LIMIT_ITEM: u8 = 6;
+    const STORAGE_LIMIT_ITEM: u8 = 7;
+    const COUNTER_ITEM: u8 = 8;
+}
+
+impl<'a> DisplayableItem for SmartRollupExecuteOutboxMessage<'a> {
+    fn num_items(&self) -> usize {
+        1 + 8
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+        use lexical_core::{write as itoa, Number};
+
+        //+2 for fixed point formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
+
+        match item_n {
+            //home
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"SR: Execute Outbox Message")[..], message, page)
+            }
+            //source
+            1 => {
+                let title_content = pic_str!(b"Source");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (crv, hash) = self.source();
+
+                let addr = Addr::from_hash(hash, *crv).map_err(|_| ViewError::Unknown)?;
+
+                let (len, mex) = addr.base58();
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //rollup
+            2 => {
+                let title_content = pic_str!(b"Rollup");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (len, mex) = self.rollup_base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //cemented_commitment
+            3 => {
+                let title_content = pic_str!(b"Commitment");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (len, mex) = self.commitment_base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //output_proof
+            4 => {
+                let title_content = pic_str!(b"Output Proof Size");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let suffix = pic_str!(b" bytes"!);
+                let len = itoa(self.output_proof.len(), &mut zarith_buf).len();
+                zarith_buf[len..len + suffix.len()].copy_from_slice(&suffix[..]);
+
+                handle_ui_message(&zarith_buf[..len + suffix.len()], message, page)
+            }
+            //fee
+            5 => {
+                let title_content = pic_str!(b"Fee");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(fee, message, page)
+            }
+            //gas_limit
+            Self::GAS_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Gas Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, gas_limit) = self
+                    .gas_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
+            }
+            //storage_limit
+            Self::STORAGE_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Storage Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, storage_limit) = self
+                    .storage_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
+            }
+            //counter
+            Self::COUNTER_ITEM => {
+                let title_content = pic_str!(b"Counter");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, counter) = self
+                    .counter()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(counter, &mut zarith_buf), message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<'b> SmartRollupExecuteOutboxMessage<'b> {
+    pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
+        //verify source address of the operation
+        let (crv, hash) = self.source();
+        let (len, source_base58) = Addr::from_hash(hash, *crv)
+            .expect("couldn't compute source base58")
+            .base58();
+        let expected_source_base58 = json["source"]
+            .as_str()
+            .expect("given json .source is not a string");
+        assert_eq!(&source_base58[..len], expected_source_base58.as_bytes());
+
+        self.counter().is(&json["counter"]);
+        self.fee().is(&json["fee"]);
+        self.gas_limit().is(&json["gas_limit"]);
+        self.storage_limit().is(&json["storage_limit"]);
+
+        let (len, rollup) = self
+            .rollup_base58()
+            .expect("couldn't compute rollup base58");
+        let expected_rollup = json["rollup"]
+            .as_str()
+            .expect("given json .rollup is not a string");
+        assert_eq!(&rollup[..len], expected_rollup.as_bytes());
+
+        let (len, commitment) = self
+            .commitment_base58()
+            .expect("couldn't compute commitment base58");
+        let expected_commitment = json["cemented_commitment"]
+            .as_str()
+            .expect("given json .cemented_commitment is not a string");
+        assert_eq!(&commitment[..len], expected_commitment.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmartRollupExecuteOutboxMessage;
+    use crate::{
+        crypto::Curve,
+        parser::{DisplayableItem, Zarith},
+    };
+
+    const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                             904e\
+                             01\
+                             0a\
+                             0a\
+                             0000000000000000000000000000000000000000\
+                             0000000000000000000000000000000000000000000000000000000000000000\
+                             00000003abcdef";
+
+    #[test]
+    fn smart_rollup_execute_outbox_message() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, parsed) = SmartRollupExecuteOutboxMessage::from_bytes(&input)
+            .expect("couldn't parse smart rollup execute outbox message");
+        assert_eq!(rem.len(), 0);
+
+        let expected = SmartRollupExecuteOutboxMessage {
+            //0 is the 00 to identify implicit contract
+            source: (Curve::Bip32Ed25519, arrayref::array_ref!(input, 1, 20)),
+            fee: Zarith {
+                is_negative: None,
+                bytes: &input[21..23],
+            },
+            counter: Zarith {
+                is_negative: None,
+                bytes: &input[23..24],
+            },
+            gas_limit: Zarith {
+                is_negative: None,
+                bytes: &input[24..25],
+            },
+            storage_limit: Zarith {
+                is_negative: None,
+                bytes: &input[25..26],
+            },
+            rollup: arrayref::array_ref!(input, 26, 20),
+            cemented_commitment: arrayref::array_ref!(input, 46, 32),
+            //78..82 is the length of the proof
+            output_proof: &input[82..],
+        };
+
+        assert_eq!(parsed, expected);
+
+        //truncated proof
+        assert!(SmartRollupExecuteOutboxMessage::from_bytes(&input[..input.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn smart_rollup_execute_outbox_message_ui() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = SmartRollupExecuteOutboxMessage::from_bytes(&input)
+            .expect("couldn't parse smart rollup execute outbox message");
+
+        let mut title = [0; 18];
+        let mut message = [0; 64];
+
+        parsed.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Rollup\x00"));
+        assert!(message.starts_with(b"sr163Lv22CdE8QagCwf48PWDTquk6isQwv57\x00"));
+
+        parsed.render_item(3, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Commitment\x00"));
+        assert!(message.starts_with(b"src12UJzB8mg7yU6nWPzicH7ofJbFjyJEbHvwtZdfRXi8DQHNp1LY8\x00"));
+
+        parsed.render_item(4, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Output Proof Size\x00"));
+        assert!(message.starts_with(b"3 bytes\x00"));
+
+        assert!(parsed.render_item(9, &mut title, &mut message, 0).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use core::convert::TryFrom;
+
+use nom::{
+    bytes::complete::take,
+    call, do_parse,
+    number::complete::{be_u32, le_u8},
+    IResult,
+};
+use zemu_sys::ViewError;
+
+use crate::{
+    crypto::Curve,
+    handlers::{
+        handle_ui_message, parser_common::ParserError, public_key::Addr, MAX_THOUSANDS_SEPARATORS,
+    },
+    parser::{public_key_hash, DisplayableItem, Zarith},
+};
+
+const KERNEL_DIGEST_LEN: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+#[repr(u8)]
+pub enum PvmKind {
+    Arith,
+    Wasm200,
+}
+
+impl TryFrom<u8> for PvmKind {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Arith,
+            1 => Self::Wasm200,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl PvmKind {
+    /// Name of the PVM, as used by the node RPCs
+    pub fn name(&self) -> &'static [u8] {
+        use bolos::{pic_str, PIC};
+
+        match self {
+            Self::Arith => pic_str!(b"arith"!),
+            Self::Wasm200 => pic_str!(b"wasm_2_0_0"!),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, property::Property)]
+#[cfg_attr(test, derive(Debug))]
+#[property(mut(disable), get(public), set(disable))]
+pub struct SmartRollupOriginate<'b> {
+    source: (Curve, &'b [u8; 20]),
+    fee: Zarith<'b>,
+    counter: Zarith<'b>,
+    gas_limit: Zarith<'b>,
+    storage_limit: Zarith<'b>,
+    pvm_kind: PvmKind,
+    kernel: &'b [u8],
+    /// Michelson type of the rollup inbox messages, binary encoded
+    parameters_ty: &'b [u8],
+}
+
+impl<'b> SmartRollupOriginate<'b> {
+    #[inline(never)]
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        crate::sys::zemu_log_stack("SmartRollupOriginate::from_bytes\x00");
+
+        let (
+            rem,
+            (source, fee, counter, gas_limit, storage_limit, pvm_kind, kernel, parameters_ty),
+        ) = do_parse! {input,
+            source: public_key_hash >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            pvm_kind: le_u8 >>
+            kernel_len: be_u32 >>
+            kernel: call!(take(kernel_len)) >>
+            parameters_len: be_u32 >>
+            parameters_ty: call!(take(parameters_len)) >>
+            (source, fee, counter, gas_limit, storage_limit, pvm_kind, kernel, parameters_ty)
+        }?;
+
+        let pvm_kind =
+            PvmKind::try_from(pvm_kind).map_err(|_| ParserError::parser_unexpected_value)?;
+
+        Ok((
+            rem,
+            Self {
+                source,
+                fee,
+                counter,
+                gas_limit,
+                storage_limit,
+                pvm_kind,
+                kernel,
+                parameters_ty,
+            },
+        ))
+    }
+
+    /// Computes the blake2b digest of the kernel
+    ///
+    /// This lets the operator compare the kernel being deployed
+    /// with the one they built
+    pub fn kernel_digest(&self) -> Result<[u8; KERNEL_DIGEST_LEN], bolos::Error> {
+        use crate::sys::hash::{Blake2b, Hasher};
+
+        Blake2b::digest(self.kernel)
+    }
+}
+
+impl SmartRollupOriginate<'_> {
+    const GAS_LIMIT_ITEM: u8 = 7;
+    const STORAGE_LIMIT_ITEM: u8 = 8;
+    const COUNTER_ITEM: u8 = 9;
+}
+
+impl<'a> DisplayableItem for SmartRollupOriginate<'a> {
+    fn num_items(&self) -> usize {
+        1 + 9
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+        use lexical_core::{write as itoa, Number};
+
+        //+2 for fixed point formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
+
+        match item_n {
+            //home
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"SR: Originate")[..], message, page)
+            }
+            //source
+            1 => {
+                let title_content = pic_str!(b"Source");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (crv, hash) = self.source();
+
+                let addr = Addr::from_hash(hash, *crv).map_err(|_| ViewError::Unknown)?;
+
+                let (len, mex) = addr.base58();
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //pvm_kind
+            2 => {
+                let title_content = pic_str!(b"Kind");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(self.pvm_kind.name(), message, page)
+            }
+            //kernel size
+            3 => {
+                let title_content = pic_str!(b"Kernel Size");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let suffix = pic_str!(b" bytes"!);
+                let len = itoa(self.kernel.len(), &mut zarith_buf).len();
+                zarith_buf[len..len + suffix.len()].copy_from_slice(&suffix[..]);
+
+                handle_ui_message(&zarith_buf[..len + suffix.len()], message, page)
+            }
+            //kernel digest
+            4 => {
+                let title_content = pic_str!(b"Kernel Hash");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let digest = self.kernel_digest().map_err(|_| ViewError::Unknown)?;
+
+                let mut hex_buf = [0; KERNEL_DIGEST_LEN * 2];
+                hex::encode_to_slice(&digest[..], &mut hex_buf).map_err(|_| ViewError::Unknown)?;
+
+                handle_ui_message(&hex_buf[..], message, page)
+            }
+            //parameters_ty
+            5 => {
+                let title_content = pic_str!(b"Parameters Size");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let suffix = pic_str!(b" bytes"!);
+                let len = itoa(self.parameters_ty.len(), &mut zarith_buf).len();
+                zarith_buf[len..len + suffix.len()].copy_from_slice(&suffix[..]);
+
+                handle_ui_message(&zarith_buf[..len + suffix.len()], message, page)
+            }
+            //fee
+            6 => {
+                let title_content = pic_str!(b"Fee");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(fee, message, page)
+            }
+            //gas_limit
+            Self::GAS_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Gas Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, gas_limit) = self
+                    .gas_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
+            }
+            //storage_limit
+            Self::STORAGE_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Storage Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, storage_limit) = self
+                    .storage_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
+            }
+            //counter
+            Self::COUNTER_ITEM => {
+                let title_content = pic_str!(b"Counter");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, counter) = self
+                    .counter()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(counter, &mut zarith_buf), message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<'b> SmartRollupOriginate<'b> {
+    pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
+        //verify source address of the operation
+        let (crv, hash) = self.source();
+        let (len, source_base58) = Addr::from_hash(hash, *crv)
+            .expect("couldn't compute source base58")
+            .base58();
+        let expected_source_base58 = json["source"]
+            .as_str()
+            .expect("given json .source is not a string");
+        assert_eq!(&source_base58[..len], expected_source_base58.as_bytes());
+
+        self.counter().is(&json["counter"]);
+        self.fee().is(&json["fee"]);
+        self.gas_limit().is(&json["gas_limit"]);
+        self.storage_limit().is(&json["storage_limit"]);
+
+        let expected_kind = json["pvm_kind"]
+            .as_str()
+            .expect("given json .pvm_kind is not a string");
+        assert_eq!(self.pvm_kind.name(), expected_kind.as_bytes());
+
+        let expected_kernel = json["kernel"]
+            .as_str()
+            .expect("given json .kernel is not a string");
+        let expected_kernel =
+            hex::decode(expected_kernel).expect("given json .kernel is not a hex string");
+        assert_eq!(self.kernel, &expected_kernel[..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PvmKind, SmartRollupOriginate};
+    use crate::{
+        crypto::Curve,
+        handlers::parser_common::ParserError,
+        parser::{DisplayableItem, Zarith},
+    };
+
+    const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                             904e\
+                             01\
+                             0a\
+                             0a\
+                             01\
+                             00000004deadbeef\
+                             00000002036c";
+
+    #[test]
+    fn smart_rollup_originate() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, parsed) = SmartRollupOriginate::from_bytes(&input)
+            .expect("couldn't parse smart rollup originate");
+        assert_eq!(rem.len(), 0);
+
+        let expected = SmartRollupOriginate {
+            //0 is the 00 to identify implicit contract
+            source: (Curve::Bip32Ed25519, arrayref::array_ref!(input, 1, 20)),
+            fee: Zarith {
+                is_negative: None,
+                bytes: &input[21..23],
+            },
+            counter: Zarith {
+                is_negative: None,
+                bytes: &input[23..24],
+            },
+            gas_limit: Zarith {
+                is_negative: None,
+                bytes: &input[24..25],
+            },
+            storage_limit: Zarith {
+                is_negative: None,
+                bytes: &input[25..26],
+            },
+            pvm_kind: PvmKind::Wasm200,
+            //27..31 is the length of the kernel
+            kernel: &input[31..35],
+            //35..39 is the length of the parameters type
+            parameters_ty: &input[39..],
+        };
+
+        assert_eq!(parsed, expected);
+
+        assert_eq!(
+            hex::encode(parsed.kernel_digest().expect("couldn't hash kernel")),
+            "f3e925002fed7cc0ded46842569eb5c90c910c091d8d04a1bdf96e0db719fd91"
+        );
+
+        //unknown pvm
+        let mut input = input;
+        input[26] = 2;
+        assert_eq!(
+            SmartRollupOriginate::from_bytes(&input).unwrap_err(),
+            nom::Err::Error(ParserError::parser_unexpected_value)
+        );
+
+        //truncated kernel
+        assert!(SmartRollupOriginate::from_bytes(&input[..33]).is_err());
+    }
+
+    #[test]
+    fn smart_rollup_originate_ui() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = SmartRollupOriginate::from_bytes(&input)
+            .expect("couldn't parse smart rollup originate");
+
+        let mut title = [0; 18];
+        let mut message = [0; 32];
+
+        parsed.render_item(0, &mut title, &mut message, 0).unwrap();
+        assert!(message.starts_with(b"SR: Originate\x00"));
+
+        parsed.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Kind\x00"));
+        assert!(message.starts_with(b"wasm_2_0_0\x00"));
+
+        parsed.render_item(3, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Kernel Size\x00"));
+        assert!(message.starts_with(b"4 bytes\x00"));
+
+        //digest is split across multiple pages
+        let pages = parsed.render_item(4, &mut title, &mut message, 0).unwrap();
+        assert!(pages > 1);
+        assert!(title.starts_with(b"Kernel Hash\x00"));
+        assert!(message.starts_with(b"f3e925002fed7cc0"));
+
+        parsed.render_item(6, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Fee\x00"));
+        assert!(message.starts_with(b"0.01 XTZ\x00"));
+
+        assert!(parsed.render_item(10, &mut title, &mut message, 0).is_err());
+    }
+}
@@ -0,0 +1,404 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use nom::{
+    bytes::complete::take,
+    call, do_parse,
+    number::complete::{be_i32, be_i64},
+    IResult,
+};
+use zemu_sys::ViewError;
+
+use crate::{
+    constants::tzprefix::{SR1, SRC1, SRS1},
+    crypto::Curve,
+    handlers::{
+        handle_ui_message, parser_common::ParserError, public_key::Addr, MAX_THOUSANDS_SEPARATORS,
+    },
+    parser::{public_key_hash, DisplayableItem, Zarith},
+};
+
+use super::smart_rollup_execute_outbox_message::{
+    rollup_hash_base58, COMMITMENT_BASE58_LEN, COMMITMENT_BYTES_LEN, ROLLUP_BASE58_LEN,
+    ROLLUP_BYTES_LEN,
+};
+
+#[derive(Clone, Copy, PartialEq, property::Property)]
+#[cfg_attr(test, derive(Debug))]
+#[property(mut(disable), get(public), set(disable))]
+pub struct SmartRollupPublish<'b> {
+    source: (Curve, &'b [u8; 20]),
+    fee: Zarith<'b>,
+    counter: Zarith<'b>,
+    gas_limit: Zarith<'b>,
+    storage_limit: Zarith<'b>,
+    rollup: &'b [u8; ROLLUP_BYTES_LEN],
+    /// State of the rollup after the commitment
+    compressed_state: &'b [u8; COMMITMENT_BYTES_LEN],
+    inbox_level: i32,
+    /// Commitment this one builds upon
+    predecessor: &'b [u8; COMMITMENT_BYTES_LEN],
+    number_of_ticks: i64,
+}
+
+impl<'b> SmartRollupPublish<'b> {
+    #[inline(never)]
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        crate::sys::zemu_log_stack("SmartRollupPublish::from_bytes\x00");
+
+        let (
+            rem,
+            (
+                source,
+                fee,
+                counter,
+                gas_limit,
+                storage_limit,
+                rollup,
+                compressed_state,
+                inbox_level,
+                predecessor,
+                number_of_ticks,
+            ),
+        ) = do_parse! {input,
+            source: public_key_hash >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            rollup: call!(take(ROLLUP_BYTES_LEN)) >>
+            compressed_state: call!(take(COMMITMENT_BYTES_LEN)) >>
+            inbox_level: be_i32 >>
+            predecessor: call!(take(COMMITMENT_BYTES_LEN)) >>
+            number_of_ticks: be_i64 >>
+            (source, fee, counter, gas_limit, storage_limit, rollup, compressed_state, inbox_level, predecessor, number_of_ticks)
+        }?;
+
+        Ok((
+            rem,
+            Self {
+                source,
+                fee,
+                counter,
+                gas_limit,
+                storage_limit,
+                rollup: arrayref::array_ref!(rollup, 0, ROLLUP_BYTES_LEN),
+                compressed_state: arrayref::array_ref!(compressed_state, 0, COMMITMENT_BYTES_LEN),
+                inbox_level,
+                predecessor: arrayref::array_ref!(predecessor, 0, COMMITMENT_BYTES_LEN),
+                number_of_ticks,
+            },
+        ))
+    }
+
+    pub fn rollup_base58(&self) -> Result<(usize, [u8; ROLLUP_BASE58_LEN]), bolos::Error> {
+        rollup_hash_base58(SR1, &self.rollup[..])
+    }
+
+    pub fn state_base58(&self) -> Result<(usize, [u8; COMMITMENT_BASE58_LEN]), bolos::Error> {
+        rollup_hash_base58(SRS1, &self.compressed_state[..])
+    }
+
+    pub fn predecessor_base58(&self) -> Result<(usize, [u8; COMMITMENT_BASE58_LEN]), bolos::Error> {
+        rollup_hash_base58(SRC1, &self.predecessor[..])
+    }
+}
+
+impl SmartRollupPublish<'_> {
+    const GAS_LIMIT_ITEM: u8 = 8;
+    const STORAGE_LIMIT_ITEM: u8 = 9;
+    const COUNTER_ITEM: u8 = 10;
+}
+
+impl<'a> DisplayableItem for SmartRollupPublish<'a> {
+    fn num_items(&self) -> usize {
+        1 + 10
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+        use lexical_core::{write as itoa, Number};
+
+        //+2 for fixed point formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
+
+        match item_n {
+            //home
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"SR: Publish")[..], message, page)
+            }
+            //source
+            1 => {
+                let title_content = pic_str!(b"Source");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (crv, hash) = self.source();
+
+                let addr = Addr::from_hash(hash, *crv).map_err(|_| ViewError::Unknown)?;
+
+                let (len, mex) = addr.base58();
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //rollup
+            2 => {
+                let title_content = pic_str!(b"Rollup");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (len, mex) = self.rollup_base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //state
+            3 => {
+                let title_content = pic_str!(b"State");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (len, mex) = self.state_base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //inbox_level
+            4 => {
+                let title_content = pic_str!(b"Inbox Level");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut itoa_buf = [0; i64::FORMATTED_SIZE_DECIMAL];
+                handle_ui_message(itoa(self.inbox_level, &mut itoa_buf), message, page)
+            }
+            //predecessor
+            5 => {
+                let title_content = pic_str!(b"Predecessor");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (len, mex) = self.predecessor_base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //number_of_ticks
+            6 => {
+                let title_content = pic_str!(b"Ticks");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut itoa_buf = [0; i64::FORMATTED_SIZE_DECIMAL];
+                handle_ui_message(itoa(self.number_of_ticks, &mut itoa_buf), message, page)
+            }
+            //fee
+            7 => {
+                let title_content = pic_str!(b"Fee");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(fee, message, page)
+            }
+            //gas_limit
+            Self::GAS_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Gas Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, gas_limit) = self
+                    .gas_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
+            }
+            //storage_limit
+            Self::STORAGE_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Storage Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, storage_limit) = self
+                    .storage_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
+            }
+            //counter
+            Self::COUNTER_ITEM => {
+                let title_content = pic_str!(b"Counter");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, counter) = self
+                    .counter()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(counter, &mut zarith_buf), message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<'b> SmartRollupPublish<'b> {
+    pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
+        //verify source address of the operation
+        let (crv, hash) = self.source();
+        let (len, source_base58) = Addr::from_hash(hash, *crv)
+            .expect("couldn't compute source base58")
+            .base58();
+        let expected_source_base58 = json["source"]
+            .as_str()
+            .expect("given json .source is not a string");
+        assert_eq!(&source_base58[..len], expected_source_base58.as_bytes());
+
+        self.counter().is(&json["counter"]);
+        self.fee().is(&json["fee"]);
+        self.gas_limit().is(&json["gas_limit"]);
+        self.storage_limit().is(&json["storage_limit"]);
+
+        let (len, rollup) = self
+            .rollup_base58()
+            .expect("couldn't compute rollup base58");
+        let expected_rollup = json["rollup"]
+            .as_str()
+            .expect("given json .rollup is not a string");
+        assert_eq!(&rollup[..len], expected_rollup.as_bytes());
+
+        let commitment = json["commitment"]
+            .as_object()
+            .expect("given json .commitment is not an object");
+
+        let (len, state) = self.state_base58().expect("couldn't compute state base58");
+        let expected_state = commitment["compressed_state"]
+            .as_str()
+            .expect("given json .commitment.compressed_state is not a string");
+        assert_eq!(&state[..len], expected_state.as_bytes());
+
+        let (len, predecessor) = self
+            .predecessor_base58()
+            .expect("couldn't compute predecessor base58");
+        let expected_predecessor = commitment["predecessor"]
+            .as_str()
+            .expect("given json .commitment.predecessor is not a string");
+        assert_eq!(&predecessor[..len], expected_predecessor.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmartRollupPublish;
+    use crate::{
+        crypto::Curve,
+        parser::{DisplayableItem, Zarith},
+    };
+
+    const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                             904e\
+                             01\
+                             0a\
+                             0a\
+                             0000000000000000000000000000000000000000\
+                             1111111111111111111111111111111111111111111111111111111111111111\
+                             00000064\
+                             2222222222222222222222222222222222222222222222222222222222222222\
+                             00000000000003e8";
+
+    #[test]
+    fn smart_rollup_publish() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, parsed) =
+            SmartRollupPublish::from_bytes(&input).expect("couldn't parse smart rollup publish");
+        assert_eq!(rem.len(), 0);
+
+        let expected = SmartRollupPublish {
+            //0 is the 00 to identify implicit contract
+            source: (Curve::Bip32Ed25519, arrayref::array_ref!(input, 1, 20)),
+            fee: Zarith {
+                is_negative: None,
+                bytes: &input[21..23],
+            },
+            counter: Zarith {
+                is_negative: None,
+                bytes: &input[23..24],
+            },
+            gas_limit: Zarith {
+                is_negative: None,
+                bytes: &input[24..25],
+            },
+            storage_limit: Zarith {
+                is_negative: None,
+                bytes: &input[25..26],
+            },
+            rollup: arrayref::array_ref!(input, 26, 20),
+            compressed_state: arrayref::array_ref!(input, 46, 32),
+            inbox_level: 100,
+            predecessor: arrayref::array_ref!(input, 82, 32),
+            number_of_ticks: 1000,
+        };
+
+        assert_eq!(parsed, expected);
+
+        //truncated ticks
+        assert!(SmartRollupPublish::from_bytes(&input[..input.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn smart_rollup_publish_ui() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) =
+            SmartRollupPublish::from_bytes(&input).expect("couldn't parse smart rollup publish");
+
+        let mut title = [0; 18];
+        let mut message = [0; 64];
+
+        parsed.render_item(0, &mut title, &mut message, 0).unwrap();
+        assert!(message.starts_with(b"SR: Publish\x00"));
+
+        parsed.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Rollup\x00"));
+        assert!(message.starts_with(b"sr163Lv22CdE8QagCwf48PWDTquk6isQwv57\x00"));
+
+        parsed.render_item(3, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"State\x00"));
+        assert!(message.starts_with(b"srs11ZWE34ur1d8j81Eqt68v2P5gFkP3hHms6kQ9Qo26j7ktDeu85y\x00"));
+
+        parsed.render_item(4, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Inbox Level\x00"));
+        assert!(message.starts_with(b"100\x00"));
+
+        parsed.render_item(5, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Predecessor\x00"));
+        assert!(message.starts_with(b"src12jLsojB83XmKRnVT4ZLyiQcBQMfejEk32qF8ciT3Qt7wzH67bB\x00"));
+
+        parsed.render_item(6, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Ticks\x00"));
+        assert!(message.starts_with(b"1000\x00"));
+
+        assert!(parsed.render_item(11, &mut title, &mut message, 0).is_err());
+    }
+}
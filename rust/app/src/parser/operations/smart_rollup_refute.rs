@@ -0,0 +1,555 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use nom::{
+    bytes::complete::take,
+    call, cond, do_parse,
+    number::complete::{be_i16, be_i32, be_u16, be_u32, le_u8},
+    IResult,
+};
+use zemu_sys::ViewError;
+
+use crate::{
+    constants::tzprefix::{SR1, SRC1},
+    crypto::Curve,
+    handlers::{
+        handle_ui_message, parser_common::ParserError, public_key::Addr, MAX_THOUSANDS_SEPARATORS,
+    },
+    parser::{boolean, public_key_hash, DisplayableItem, Zarith},
+};
+
+use super::smart_rollup_execute_outbox_message::{
+    rollup_hash_base58, COMMITMENT_BASE58_LEN, COMMITMENT_BYTES_LEN, ROLLUP_BASE58_LEN,
+    ROLLUP_BYTES_LEN,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Refutation<'b> {
+    /// Starts a refutation game
+    Start {
+        player_commitment: &'b [u8; COMMITMENT_BYTES_LEN],
+        opponent_commitment: &'b [u8; COMMITMENT_BYTES_LEN],
+    },
+    /// Plays a move in an ongoing game
+    Move {
+        /// Tick chosen in the previous dissection
+        choice: Zarith<'b>,
+        /// Encoded dissection or proof, only reviewed by its size
+        step: &'b [u8],
+    },
+}
+
+impl<'b> Refutation<'b> {
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        let (rem, kind) = le_u8(input)?;
+
+        match kind {
+            0x00 => {
+                let (rem, (player, opponent)) = do_parse! {rem,
+                    player: call!(take(COMMITMENT_BYTES_LEN)) >>
+                    opponent: call!(take(COMMITMENT_BYTES_LEN)) >>
+                    (player, opponent)
+                }?;
+
+                let data = Self::Start {
+                    player_commitment: arrayref::array_ref!(player, 0, COMMITMENT_BYTES_LEN),
+                    opponent_commitment: arrayref::array_ref!(opponent, 0, COMMITMENT_BYTES_LEN),
+                };
+
+                Ok((rem, data))
+            }
+            0x01 => {
+                let (step, choice) = Zarith::from_bytes(rem, false, Zarith::COUNTER_MAX_LEN)?;
+                let (rem, _) = Self::skip_step(step)?;
+
+                let data = Self::Move {
+                    choice,
+                    step: &step[..step.len() - rem.len()],
+                };
+
+                Ok((rem, data))
+            }
+            _ => Err(ParserError::parser_unexpected_value.into()),
+        }
+    }
+
+    /// Skips a move's step, either a dissection or a proof
+    fn skip_step(input: &'b [u8]) -> IResult<&[u8], (), ParserError> {
+        let (rem, kind) = le_u8(input)?;
+
+        match kind {
+            //dissection
+            0x00 => {
+                let (rem, len) = be_u32(rem)?;
+                let (rem, _) = take(len)(rem)?;
+
+                Ok((rem, ()))
+            }
+            //proof
+            0x01 => {
+                let (rem, input_proof_kind) = do_parse! {rem,
+                    pvm_step_len: be_u32 >>
+                    _pvm_step: call!(take(pvm_step_len)) >>
+                    has_input_proof: boolean >>
+                    input_proof_kind: cond!(has_input_proof, le_u8) >>
+                    (input_proof_kind)
+                }?;
+
+                match input_proof_kind {
+                    None => Ok((rem, ())),
+                    //inbox proof
+                    Some(0x00) => {
+                        let (rem, _) = do_parse! {rem,
+                            _level: be_i32 >>
+                            _message_counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+                            len: be_u32 >>
+                            _serialized_proof: call!(take(len)) >>
+                            ()
+                        }?;
+
+                        Ok((rem, ()))
+                    }
+                    //reveal proof
+                    Some(0x01) => Self::skip_reveal_proof(rem),
+                    //first input
+                    Some(0x02) => Ok((rem, ())),
+                    _ => Err(ParserError::parser_unexpected_value.into()),
+                }
+            }
+            _ => Err(ParserError::parser_unexpected_value.into()),
+        }
+    }
+
+    fn skip_reveal_proof(input: &'b [u8]) -> IResult<&[u8], (), ParserError> {
+        let (rem, kind) = le_u8(input)?;
+
+        match kind {
+            //raw data
+            0x00 => {
+                let (rem, len) = be_u16(rem)?;
+                let (rem, _) = take(len)(rem)?;
+
+                Ok((rem, ()))
+            }
+            //metadata
+            0x01 => Ok((rem, ())),
+            //dal page
+            0x02 => {
+                let (rem, _) = do_parse! {rem,
+                    _published_level: be_i32 >>
+                    _slot_index: le_u8 >>
+                    _page_index: be_i16 >>
+                    len: be_u32 >>
+                    _dal_proof: call!(take(len)) >>
+                    ()
+                }?;
+
+                Ok((rem, ()))
+            }
+            _ => Err(ParserError::parser_unexpected_value.into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, property::Property)]
+#[cfg_attr(test, derive(Debug))]
+#[property(mut(disable), get(public), set(disable))]
+pub struct SmartRollupRefute<'b> {
+    source: (Curve, &'b [u8; 20]),
+    fee: Zarith<'b>,
+    counter: Zarith<'b>,
+    gas_limit: Zarith<'b>,
+    storage_limit: Zarith<'b>,
+    rollup: &'b [u8; ROLLUP_BYTES_LEN],
+    opponent: (Curve, &'b [u8; 20]),
+    refutation: Refutation<'b>,
+}
+
+impl<'b> SmartRollupRefute<'b> {
+    #[inline(never)]
+    pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
+        crate::sys::zemu_log_stack("SmartRollupRefute::from_bytes\x00");
+
+        let (rem, (source, fee, counter, gas_limit, storage_limit, rollup, opponent, refutation)) =
+            do_parse! {input,
+                source: public_key_hash >>
+                fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+                counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+                gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+                storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+                rollup: call!(take(ROLLUP_BYTES_LEN)) >>
+                opponent: public_key_hash >>
+                refutation: call!(Refutation::from_bytes) >>
+                (source, fee, counter, gas_limit, storage_limit, rollup, opponent, refutation)
+            }?;
+
+        Ok((
+            rem,
+            Self {
+                source,
+                fee,
+                counter,
+                gas_limit,
+                storage_limit,
+                rollup: arrayref::array_ref!(rollup, 0, ROLLUP_BYTES_LEN),
+                opponent,
+                refutation,
+            },
+        ))
+    }
+
+    pub fn rollup_base58(&self) -> Result<(usize, [u8; ROLLUP_BASE58_LEN]), bolos::Error> {
+        rollup_hash_base58(SR1, &self.rollup[..])
+    }
+
+    /// Returns the player's and the opponent's commitments,
+    /// when starting a game
+    #[allow(clippy::type_complexity)]
+    pub fn start_base58(
+        &self,
+    ) -> Option<
+        Result<
+            (
+                (usize, [u8; COMMITMENT_BASE58_LEN]),
+                (usize, [u8; COMMITMENT_BASE58_LEN]),
+            ),
+            bolos::Error,
+        >,
+    > {
+        match self.refutation {
+            Refutation::Start {
+                player_commitment,
+                opponent_commitment,
+            } => Some(
+                rollup_hash_base58(SRC1, &player_commitment[..]).and_then(|player| {
+                    rollup_hash_base58(SRC1, &opponent_commitment[..]).map(|opp| (player, opp))
+                }),
+            ),
+            Refutation::Move { .. } => None,
+        }
+    }
+}
+
+impl SmartRollupRefute<'_> {
+    const GAS_LIMIT_ITEM: u8 = 6;
+    const STORAGE_LIMIT_ITEM: u8 = 7;
+    const COUNTER_ITEM: u8 = 8;
+}
+
+impl<'a> DisplayableItem for SmartRollupRefute<'a> {
+    fn num_items(&self) -> usize {
+        1 + 8
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            item_n,
+            Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+        )
+    }
+
+    #[inline(never)]
+    fn render_item(
+        &self,
+        item_n: u8,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+        use lexical_core::{write as itoa, Number};
+
+        //+2 for fixed point formatting, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
+
+        match item_n {
+            //home
+            0 => {
+                let title_content = pic_str!(b"Type");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                handle_ui_message(&pic_str!(b"SR: Refute")[..], message, page)
+            }
+            //source
+            1 => {
+                let title_content = pic_str!(b"Source");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (crv, hash) = self.source();
+
+                let addr = Addr::from_hash(hash, *crv).map_err(|_| ViewError::Unknown)?;
+
+                let (len, mex) = addr.base58();
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //rollup
+            2 => {
+                let title_content = pic_str!(b"Rollup");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (len, mex) = self.rollup_base58().map_err(|_| ViewError::Unknown)?;
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //opponent
+            3 => {
+                let title_content = pic_str!(b"Opponent");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (crv, hash) = self.opponent();
+
+                let addr = Addr::from_hash(hash, *crv).map_err(|_| ViewError::Unknown)?;
+
+                let (len, mex) = addr.base58();
+                handle_ui_message(&mex[..len], message, page)
+            }
+            //refutation
+            4 => {
+                let title_content = pic_str!(b"Refutation");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                match self.refutation {
+                    Refutation::Start { .. } => {
+                        handle_ui_message(&pic_str!(b"Start")[..], message, page)
+                    }
+                    Refutation::Move { choice, .. } => {
+                        let (_, tick) = choice.read_as::<usize>().ok_or(ViewError::Unknown)?;
+
+                        let prefix = pic_str!(b"Move at tick "!);
+                        let mut mex = [0; 13 + usize::FORMATTED_SIZE_DECIMAL];
+                        mex[..prefix.len()].copy_from_slice(&prefix[..]);
+                        let len = itoa(tick, &mut mex[prefix.len()..]).len();
+
+                        handle_ui_message(&mex[..prefix.len() + len], message, page)
+                    }
+                }
+            }
+            //fee
+            5 => {
+                let title_content = pic_str!(b"Fee");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(fee, message, page)
+            }
+            //gas_limit
+            Self::GAS_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Gas Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, gas_limit) = self
+                    .gas_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
+            }
+            //storage_limit
+            Self::STORAGE_LIMIT_ITEM => {
+                let title_content = pic_str!(b"Storage Limit");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, storage_limit) = self
+                    .storage_limit()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
+            }
+            //counter
+            Self::COUNTER_ITEM => {
+                let title_content = pic_str!(b"Counter");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let (_, counter) = self
+                    .counter()
+                    .read_as::<usize>()
+                    .ok_or(ViewError::Unknown)?;
+
+                handle_ui_message(itoa(counter, &mut zarith_buf), message, page)
+            }
+            _ => Err(ViewError::NoData),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<'b> SmartRollupRefute<'b> {
+    pub fn is(&self, json: &serde_json::Map<std::string::String, serde_json::Value>) {
+        //verify source address of the operation
+        let (crv, hash) = self.source();
+        let (len, source_base58) = Addr::from_hash(hash, *crv)
+            .expect("couldn't compute source base58")
+            .base58();
+        let expected_source_base58 = json["source"]
+            .as_str()
+            .expect("given json .source is not a string");
+        assert_eq!(&source_base58[..len], expected_source_base58.as_bytes());
+
+        self.counter().is(&json["counter"]);
+        self.fee().is(&json["fee"]);
+        self.gas_limit().is(&json["gas_limit"]);
+        self.storage_limit().is(&json["storage_limit"]);
+
+        let (len, rollup) = self
+            .rollup_base58()
+            .expect("couldn't compute rollup base58");
+        let expected_rollup = json["rollup"]
+            .as_str()
+            .expect("given json .rollup is not a string");
+        assert_eq!(&rollup[..len], expected_rollup.as_bytes());
+
+        let (crv, hash) = self.opponent();
+        let (len, opponent_base58) = Addr::from_hash(hash, *crv)
+            .expect("couldn't compute opponent base58")
+            .base58();
+        let expected_opponent_base58 = json["opponent"]
+            .as_str()
+            .expect("given json .opponent is not a string");
+        assert_eq!(&opponent_base58[..len], expected_opponent_base58.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Refutation, SmartRollupRefute};
+    use crate::{
+        crypto::Curve,
+        handlers::parser_common::ParserError,
+        parser::{DisplayableItem, Zarith},
+    };
+
+    const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                             904e\
+                             01\
+                             0a\
+                             0a\
+                             0000000000000000000000000000000000000000\
+                             0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                             00\
+                             2222222222222222222222222222222222222222222222222222222222222222\
+                             1111111111111111111111111111111111111111111111111111111111111111";
+
+    #[test]
+    fn smart_rollup_refute() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+
+        let (rem, parsed) =
+            SmartRollupRefute::from_bytes(&input).expect("couldn't parse smart rollup refute");
+        assert_eq!(rem.len(), 0);
+
+        let expected = SmartRollupRefute {
+            //0 is the 00 to identify implicit contract
+            source: (Curve::Bip32Ed25519, arrayref::array_ref!(input, 1, 20)),
+            fee: Zarith {
+                is_negative: None,
+                bytes: &input[21..23],
+            },
+            counter: Zarith {
+                is_negative: None,
+                bytes: &input[23..24],
+            },
+            gas_limit: Zarith {
+                is_negative: None,
+                bytes: &input[24..25],
+            },
+            storage_limit: Zarith {
+                is_negative: None,
+                bytes: &input[25..26],
+            },
+            rollup: arrayref::array_ref!(input, 26, 20),
+            opponent: (Curve::Bip32Ed25519, arrayref::array_ref!(input, 47, 20)),
+            refutation: Refutation::Start {
+                player_commitment: arrayref::array_ref!(input, 68, 32),
+                opponent_commitment: arrayref::array_ref!(input, 100, 32),
+            },
+        };
+
+        assert_eq!(parsed, expected);
+
+        let (player, _) = parsed
+            .start_base58()
+            .expect("not a start")
+            .expect("couldn't compute commitments base58");
+        assert_eq!(
+            &player.1[..player.0],
+            b"src12jLsojB83XmKRnVT4ZLyiQcBQMfejEk32qF8ciT3Qt7wzH67bB"
+        );
+
+        //move at tick 10 with a dissection
+        let mut input = input[..67].to_vec();
+        input.extend_from_slice(&hex::decode("010a0000000003abcdef").unwrap());
+        let (rem, parsed) =
+            SmartRollupRefute::from_bytes(&input).expect("couldn't parse smart rollup refute");
+        assert_eq!(rem.len(), 0);
+        assert_eq!(
+            parsed.refutation,
+            Refutation::Move {
+                choice: Zarith {
+                    is_negative: None,
+                    bytes: &input[68..69],
+                },
+                step: &input[69..],
+            }
+        );
+
+        //move with a proof, with an inbox input proof
+        let mut input = input[..69].to_vec();
+        input.extend_from_slice(&hex::decode("0100000001aaff000000002a0100000002bbcc").unwrap());
+        let (rem, parsed) =
+            SmartRollupRefute::from_bytes(&input).expect("couldn't parse smart rollup refute");
+        assert_eq!(rem.len(), 0);
+        assert!(matches!(parsed.refutation, Refutation::Move { step, .. } if step.len() == 19));
+
+        //unknown refutation kind
+        input[67] = 2;
+        assert_eq!(
+            SmartRollupRefute::from_bytes(&input).unwrap_err(),
+            nom::Err::Error(ParserError::parser_unexpected_value)
+        );
+    }
+
+    #[test]
+    fn smart_rollup_refute_ui() {
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) =
+            SmartRollupRefute::from_bytes(&input).expect("couldn't parse smart rollup refute");
+
+        let mut title = [0; 18];
+        let mut message = [0; 64];
+
+        parsed.render_item(0, &mut title, &mut message, 0).unwrap();
+        assert!(message.starts_with(b"SR: Refute\x00"));
+
+        parsed.render_item(2, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Rollup\x00"));
+        assert!(message.starts_with(b"sr163Lv22CdE8QagCwf48PWDTquk6isQwv57\x00"));
+
+        parsed.render_item(3, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Opponent\x00"));
+        assert!(message.starts_with(b"tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn\x00"));
+
+        parsed.render_item(4, &mut title, &mut message, 0).unwrap();
+        assert!(title.starts_with(b"Refutation\x00"));
+        assert!(message.starts_with(b"Start\x00"));
+
+        assert!(parsed.render_item(9, &mut title, &mut message, 0).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Operation tags and which of them can be reviewed by each build
+
+/// Declares the tag of each operation, together with the features
+/// of the builds that are able to review and sign it
+///
+/// Generates the `tag` module with a constant for each operation
+/// and the `SUPPORTED_OPERATIONS` table for the current build
+macro_rules! operation_tags {
+    ($($name:ident = $tag:literal => [$($feature:literal),*];)*) => {
+        /// Tags of the operations known to the parser
+        pub mod tag {
+            $(pub const $name: u8 = $tag;)*
+        }
+
+        /// Tags of the operations this build can review and sign
+        pub const SUPPORTED_OPERATIONS: &[u8] = &[
+            $(#[cfg(any($(feature = $feature),*))] tag::$name,)*
+        ];
+    };
+}
+
+operation_tags! {
+    ENDORSEMENT = 0x00 => ["baking"];
+    SEED_NONCE_REVELATION = 0x01 => ["baking"];
+    DOUBLE_ENDORSEMENT_EVIDENCE = 0x02 => [];
+    DOUBLE_BAKING_EVIDENCE = 0x03 => [];
+    ACTIVATE_ACCOUNT = 0x04 => ["wallet"];
+    PROPOSALS = 0x05 => ["wallet"];
+    BALLOT = 0x06 => ["wallet"];
+    DRAIN_DELEGATE = 0x09 => ["wallet", "baking"];
+    ENDORSEMENT_WITH_SLOT = 0x0A => ["baking"];
+    FAILING_NOOP = 0x11 => ["wallet"];
+    REVEAL = 0x6B => ["wallet", "baking"];
+    TRANSACTION = 0x6C => ["wallet"];
+    ORIGINATION = 0x6D => ["wallet"];
+    DELEGATION = 0x6E => ["wallet", "baking"];
+    REGISTER_GLOBAL_CONSTANT = 0x6F => ["wallet"];
+    SET_DEPOSITS_LIMIT = 0x70 => ["wallet", "baking"];
+    INCREASE_PAID_STORAGE = 0x71 => ["wallet"];
+    UPDATE_CONSENSUS_KEY = 0x72 => ["wallet", "baking"];
+    SMART_ROLLUP_ORIGINATE = 0xC8 => ["wallet"];
+    SMART_ROLLUP_ADD_MESSAGES = 0xC9 => ["wallet"];
+    SMART_ROLLUP_CEMENT = 0xCA => ["wallet"];
+    SMART_ROLLUP_PUBLISH = 0xCB => ["wallet"];
+    SMART_ROLLUP_REFUTE = 0xCC => ["wallet"];
+    SMART_ROLLUP_TIMEOUT = 0xCD => ["wallet"];
+    SMART_ROLLUP_EXECUTE_OUTBOX_MESSAGE = 0xCE => ["wallet"];
+    SMART_ROLLUP_RECOVER_BOND = 0xCF => ["wallet"];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tag, SUPPORTED_OPERATIONS};
+
+    #[test]
+    #[cfg(feature = "wallet")]
+    fn wallet_operations() {
+        assert!(SUPPORTED_OPERATIONS.contains(&tag::TRANSACTION));
+        assert!(SUPPORTED_OPERATIONS.contains(&tag::REVEAL));
+        assert!(!SUPPORTED_OPERATIONS.contains(&tag::SEED_NONCE_REVELATION));
+        //consensus operations are only signed by bakers
+        assert!(!SUPPORTED_OPERATIONS.contains(&tag::ENDORSEMENT));
+        assert!(!SUPPORTED_OPERATIONS.contains(&tag::ENDORSEMENT_WITH_SLOT));
+    }
+
+    #[test]
+    #[cfg(feature = "baking")]
+    fn baking_operations() {
+        assert_eq!(
+            SUPPORTED_OPERATIONS,
+            &[
+                tag::ENDORSEMENT,
+                tag::SEED_NONCE_REVELATION,
+                tag::DRAIN_DELEGATE,
+                tag::ENDORSEMENT_WITH_SLOT,
+                tag::REVEAL,
+                tag::DELEGATION,
+                tag::SET_DEPOSITS_LIMIT,
+                tag::UPDATE_CONSENSUS_KEY
+            ]
+        );
+    }
+}
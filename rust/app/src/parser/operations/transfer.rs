@@ -15,18 +15,25 @@
 ********************************************************************************/
 use core::{mem::MaybeUninit, ptr::addr_of_mut};
 use nom::{
+    bytes::complete::take,
     call, cond, do_parse,
     number::complete::{be_u32, le_u8},
-    take, IResult,
+    IResult,
 };
 use zemu_sys::ViewError;
 
 use crate::{
+    constants::{DANGEROUS_ENTRYPOINTS, KNOWN_TOKENS},
     crypto::Curve,
     handlers::{
         handle_ui_message, intstr_to_fpstr_inplace, parser_common::ParserError, public_key::Addr,
+        MAX_THOUSANDS_SEPARATORS,
+    },
+    parser::{
+        boolean,
+        michelson::{Fa12Transfer, ManagerAction},
+        public_key_hash, DisplayableItem, Zarith,
     },
-    parser::{boolean, public_key_hash, DisplayableItem, Zarith},
 };
 
 use super::ContractID;
@@ -43,6 +50,9 @@ pub enum Entrypoint<'b> {
 }
 
 impl<'b> Entrypoint<'b> {
+    /// Maximum length of the name of a custom entrypoint
+    pub const MAX_NAME_LEN: usize = 31;
+
     pub fn from_bytes(input: &'b [u8]) -> IResult<&[u8], Self, ParserError> {
         let (mut rem, tag) = le_u8(input)?;
 
@@ -54,9 +64,15 @@ impl<'b> Entrypoint<'b> {
             0x04 => Self::RemoveDelegate,
             0xFF => {
                 let (rem2, length) = le_u8(rem)?;
-                let (rem2, name) = take!(rem2, length)?;
+                let (rem2, name) = take(length)(rem2)?;
                 rem = rem2;
 
+                //names are shown as they are, so only allow the characters
+                // Michelson allows in entrypoint names
+                if name.len() > Self::MAX_NAME_LEN || !name.iter().all(Self::is_name_char) {
+                    return Err(ParserError::parser_invalid_contract_name.into());
+                }
+
                 Self::Custom(name)
             }
             _ => return Err(ParserError::parser_invalid_contract_name.into()),
@@ -64,6 +80,39 @@ impl<'b> Entrypoint<'b> {
 
         Ok((rem, data))
     }
+
+    /// Characters allowed in custom entrypoint names: `[A-Za-z0-9_.%@]`
+    fn is_name_char(c: &u8) -> bool {
+        c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.' | b'%' | b'@')
+    }
+
+    /// Returns true if the entrypoint is in the list of
+    /// entrypoints requiring expert mode and an additional confirmation
+    pub fn is_dangerous(&self) -> bool {
+        use bolos::PIC;
+
+        match self {
+            Self::Custom(name) => PIC::new(DANGEROUS_ENTRYPOINTS)
+                .into_inner()
+                .iter()
+                .any(|&dangerous| PIC::new(dangerous).into_inner() == *name),
+            _ => false,
+        }
+    }
+
+    /// Name of the entrypoint, as written in Michelson
+    pub fn name(&self) -> &'b [u8] {
+        use bolos::{pic_str, PIC};
+
+        match self {
+            Self::Default => pic_str!(b"default"!),
+            Self::Root => pic_str!(b"root"!),
+            Self::Do => pic_str!(b"do"!),
+            Self::SetDelegate => pic_str!(b"set_delegate"!),
+            Self::RemoveDelegate => pic_str!(b"remove_delegate"!),
+            Self::Custom(name) => name,
+        }
+    }
 }
 
 impl<'b> core::fmt::Display for Entrypoint<'b> {
@@ -75,7 +124,8 @@ impl<'b> core::fmt::Display for Entrypoint<'b> {
             Entrypoint::SetDelegate => write!(f, "set_delegate"),
             Entrypoint::RemoveDelegate => write!(f, "remove_delegate"),
             Entrypoint::Custom(custom) => {
-                let custom = core::str::from_utf8(custom).expect("custom entrypoint was not utf8");
+                //always ASCII when parsed with `from_bytes`
+                let custom = core::str::from_utf8(custom).map_err(|_| core::fmt::Error)?;
                 f.write_str(custom)
             }
         }
@@ -96,7 +146,7 @@ impl<'b> Parameters<'b> {
             input,
             entrypoint: call!(Entrypoint::from_bytes)
                 >> length: be_u32
-                >> out: take!(length)
+                >> out: call!(take(length))
                 >> (entrypoint, out)
         )?;
 
@@ -108,8 +158,40 @@ impl<'b> Parameters<'b> {
             },
         ))
     }
+
+    pub fn requires_expert(&self) -> bool {
+        self.entrypoint.is_dangerous() || self.is_unlimited_approve()
+    }
+
+    /// Returns true if the parameters are a FA1.2 `approve` of an allowance
+    /// that doesn't fit in 128 bits, or of the largest one that does,
+    /// which is how unlimited allowances are usually given
+    pub fn is_unlimited_approve(&self) -> bool {
+        use bolos::PIC;
+
+        match self.entrypoint {
+            Entrypoint::Custom(name) if name == PIC::new(b"approve").into_inner() => {
+                crate::parser::michelson::fa12_amount(name, self.michelson)
+                    .map(|allowance| allowance.to_u128().map_or(true, |a| a == u128::MAX))
+                    .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Computes the hash of the parameters' value, as used in `expr` hashes
+    ///
+    /// This is the blake2b digest of the packed value
+    pub fn expr_hash(&self) -> Result<[u8; 32], bolos::Error> {
+        crate::parser::michelson::expr_hash(self.michelson)
+    }
 }
 
+/// Length of the amount of a known token, with room for the decimals,
+/// the separators and the ticker
+const TOKEN_AMOUNT_FORMATTED_SIZE: usize =
+    Zarith::FORMATTED_SIZE + 2 + 18 + Zarith::FORMATTED_SIZE / 3 + 16;
+
 #[derive(Clone, Copy, PartialEq, property::Property)]
 #[cfg_attr(test, derive(Debug))]
 #[property(mut(disable), get(public), set(disable))]
@@ -135,11 +217,11 @@ impl<'b> Transfer<'b> {
             (source, fee, counter, gas_limit, storage_limit, amount, destination, parameters),
         ) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
-            amount: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            amount: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
             destination: call!(ContractID::from_bytes) >>
             has_params: boolean >>
             params: cond!(has_params, Parameters::from_bytes) >>
@@ -173,11 +255,11 @@ impl<'b> Transfer<'b> {
             (source, fee, counter, gas_limit, storage_limit, amount, destination, parameters),
         ) = do_parse! {input,
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
-            amount: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            amount: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
             destination: call!(ContractID::from_bytes) >>
             has_params: boolean >>
             params: cond!(has_params, Parameters::from_bytes) >>
@@ -212,11 +294,11 @@ impl<'b> Transfer<'b> {
         ) = dbg_basic! {input,
             do_parse!(
             source: public_key_hash >>
-            fee: call!(Zarith::from_bytes, false) >>
-            counter: call!(Zarith::from_bytes, false) >>
-            gas_limit: call!(Zarith::from_bytes, false) >>
-            storage_limit: call!(Zarith::from_bytes, false) >>
-            amount: call!(Zarith::from_bytes, false) >>
+            fee: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
+            counter: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            gas_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            storage_limit: call!(Zarith::from_bytes, false, Zarith::COUNTER_MAX_LEN) >>
+            amount: call!(Zarith::from_bytes, false, Zarith::MUTEZ_MAX_LEN) >>
             destination: call!(ContractID::from_bytes) >>
             has_params: boolean >>
             params: cond!(has_params, Parameters::from_bytes) >>
@@ -238,17 +320,321 @@ impl<'b> Transfer<'b> {
         ))
     }
 
+    /// Returns true if the transfer can only be signed in expert mode
+    pub fn requires_expert(&self) -> bool {
+        self.parameters
+            .map(|params| params.requires_expert())
+            .unwrap_or(false)
+    }
+
+    /// Returns the ticker, the decimals and the amount of a `transfer`
+    /// or an `approve` on one of the [`KNOWN_TOKENS`]
+    pub fn token_amount(&self) -> Option<(&'static [u8], usize, Zarith<'b>)> {
+        use bolos::PIC;
+
+        let params = self.parameters?;
+        let entrypoint = match params.entrypoint {
+            Entrypoint::Custom(name) => name,
+            _ => return None,
+        };
+
+        if self.destination.is_implicit() {
+            return None;
+        }
+
+        let token = PIC::new(KNOWN_TOKENS)
+            .into_inner()
+            .iter()
+            .find(|token| PIC::new(token.contract).into_inner() == self.destination.hash())?;
+
+        let amount = crate::parser::michelson::fa12_amount(entrypoint, params.michelson)?;
+
+        Some((
+            PIC::new(token.ticker).into_inner(),
+            token.decimals as usize,
+            amount,
+        ))
+    }
+
+    /// Returns true if the amount of a known token gets its own item,
+    /// which is the case unless it's shown with the token transfer
+    fn shows_token_amount(&self) -> bool {
+        self.token_amount().is_some() && self.token_transfer().is_none()
+    }
+
+    /// Writes the amount of a known token, scaled by its decimals
+    /// and followed by its ticker
+    fn format_token_amount<'m>(
+        (ticker, decimals, amount): (&[u8], usize, Zarith),
+        mex: &'m mut [u8; TOKEN_AMOUNT_FORMATTED_SIZE],
+    ) -> Option<&'m [u8]> {
+        let mut digits = [0; Zarith::FORMATTED_SIZE];
+        let digits = amount.format_into(&mut digits)?;
+
+        let sep = 1 + ticker.len();
+        let room = mex.len() - sep;
+        mex[..digits.len()].copy_from_slice(digits);
+        let len = intstr_to_fpstr_inplace(&mut mex[..room], decimals, true)
+            .ok()?
+            .len();
+
+        mex[len] = b' ';
+        mex[len + 1..len + sep].copy_from_slice(ticker);
+
+        Some(&mex[..len + sep])
+    }
+
+    /// Returns the decoded parameters of a FA1.2 `transfer` to a contract
+    pub fn token_transfer(&self) -> Option<Fa12Transfer<'b>> {
+        use bolos::PIC;
+
+        let params = self.parameters?;
+        match params.entrypoint {
+            Entrypoint::Custom(name) if name == PIC::new(b"transfer").into_inner() => {}
+            _ => return None,
+        }
+
+        if self.destination.is_implicit() {
+            return None;
+        }
+
+        Fa12Transfer::from_bytes(params.michelson)
+    }
+
+    /// Returns the action of the lambda given to the `do` entrypoint
+    /// of a legacy manager contract
+    pub fn manager_action(&self) -> Option<ManagerAction<'b>> {
+        let params = self.parameters?;
+        if params.entrypoint != Entrypoint::Do || self.destination.is_implicit() {
+            return None;
+        }
+
+        ManagerAction::from_bytes(params.michelson).filter(|action| match action {
+            ManagerAction::Transfer { amount, .. } => amount.to_u64().is_ok(),
+            _ => true,
+        })
+    }
+
     fn source_base58(&self) -> Result<(usize, [u8; Addr::BASE58_LEN]), bolos::Error> {
         let source = self.source();
         let addr = Addr::from_hash(source.1, source.0)?;
 
         Ok(addr.base58())
     }
+
+    /// Renders the called contract followed by the entrypoint,
+    /// in a single item spanning as many pages as needed
+    #[inline(never)]
+    fn render_contract_call(
+        &self,
+        entrypoint: &Entrypoint,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+
+        let title_content = pic_str!(b"Contract");
+        title[..title_content.len()].copy_from_slice(title_content);
+
+        let (len, cid) = self
+            .destination()
+            .base58()
+            .map_err(|_| ViewError::Unknown)?;
+
+        let separator = pic_str!(b" / Entrypoint: "!);
+        let name = entrypoint.name();
+
+        //entrypoint names are at most `Entrypoint::MAX_NAME_LEN` bytes long,
+        // custom ones are refused by the parser otherwise
+        let mut mex = [0; ContractID::BASE58_LEN + 15 + Entrypoint::MAX_NAME_LEN];
+        mex[..len].copy_from_slice(&cid[..len]);
+        mex[len..len + separator.len()].copy_from_slice(separator);
+        let len = len + separator.len();
+        mex[len..len + name.len()].copy_from_slice(name);
+
+        handle_ui_message(&mex[..len + name.len()], message, page)
+    }
+
+    /// Renders the action of a manager contract, with the contract
+    /// being the source of the transferred tez
+    #[inline(never)]
+    fn render_manager_action(
+        &self,
+        action: &ManagerAction,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+
+        let title_content = pic_str!(b"Manager Action");
+        title[..title_content.len()].copy_from_slice(title_content);
+
+        let mut amount_buf = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+        let mut from = [0; ContractID::BASE58_LEN];
+        let mut to = [0; ContractID::BASE58_LEN];
+
+        //"Transfer <amount> XTZ from <contract> to <address>"
+        let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE + 2 * ContractID::BASE58_LEN + 20];
+        let mut len = 0;
+        let mut write = |parts: &[&[u8]]| {
+            for part in parts {
+                mex[len..len + part.len()].copy_from_slice(part);
+                len += part.len();
+            }
+        };
+
+        match action {
+            ManagerAction::Transfer { to: addr, amount } => {
+                let amount = amount
+                    .format_mutez_into(&mut amount_buf)
+                    .ok_or(ViewError::Unknown)?;
+
+                let (from_len, cid) = self
+                    .destination()
+                    .base58()
+                    .map_err(|_| ViewError::Unknown)?;
+                from.copy_from_slice(&cid[..]);
+                let to_len = addr.base58_into(&mut to).map_err(|_| ViewError::Unknown)?;
+
+                write(&[
+                    pic_str!(b"Transfer "!),
+                    amount,
+                    pic_str!(b" from "!),
+                    &from[..from_len],
+                    pic_str!(b" to "!),
+                    &to[..to_len],
+                ]);
+            }
+            ManagerAction::SetDelegate(delegate) => {
+                let to_len = delegate
+                    .base58_into(&mut to)
+                    .map_err(|_| ViewError::Unknown)?;
+
+                write(&[pic_str!(b"Set delegate to "!), &to[..to_len]]);
+            }
+            ManagerAction::RemoveDelegate => write(&[pic_str!(b"Remove delegate"!)]),
+        }
+
+        handle_ui_message(&mex[..len], message, page)
+    }
+
+    /// Renders the sender, the recipient and the amount of a token transfer,
+    /// in a single item spanning as many pages as needed
+    ///
+    /// The amount is scaled if the token is known, otherwise it's shown raw
+    #[inline(never)]
+    fn render_token_transfer(
+        &self,
+        transfer: &Fa12Transfer,
+        title: &mut [u8],
+        message: &mut [u8],
+        page: u8,
+    ) -> Result<u8, ViewError> {
+        use bolos::{pic_str, PIC};
+
+        let title_content = pic_str!(b"Token Transfer");
+        title[..title_content.len()].copy_from_slice(title_content);
+
+        let mut mex = [0; 6 + 7 + 11 + 2 * ContractID::BASE58_LEN + TOKEN_AMOUNT_FORMATTED_SIZE];
+        let mut len = 0;
+        let mut push = |bytes: &[u8]| {
+            mex[len..len + bytes.len()].copy_from_slice(bytes);
+            len += bytes.len();
+        };
+
+        let mut addr = [0; ContractID::BASE58_LEN];
+        let mut amount = [0; TOKEN_AMOUNT_FORMATTED_SIZE];
+
+        push(pic_str!(b"From: "!));
+        let n = transfer
+            .from
+            .base58_into(&mut addr)
+            .map_err(|_| ViewError::Unknown)?;
+        push(&addr[..n]);
+
+        push(pic_str!(b" / To: "!));
+        let n = transfer
+            .to
+            .base58_into(&mut addr)
+            .map_err(|_| ViewError::Unknown)?;
+        push(&addr[..n]);
+
+        push(pic_str!(b" / Amount: "!));
+        let amount = match self.token_amount() {
+            Some(token) => Self::format_token_amount(token, &mut amount),
+            None => transfer.amount.format_into(arrayref::array_mut_ref!(
+                amount,
+                0,
+                Zarith::FORMATTED_SIZE
+            )),
+        };
+        push(amount.ok_or(ViewError::Unknown)?);
+
+        handle_ui_message(&mex[..len], message, page)
+    }
+}
+
+/// Item of a transfer, with the optional ones told apart
+/// from those always shown
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TransferItem {
+    Warning,
+    TokenAmount,
+    ParametersSize,
+    /// Index of the item among those always shown
+    Fixed(u8),
+}
+
+impl Transfer<'_> {
+    const GAS_LIMIT_ITEM: u8 = 6;
+    const STORAGE_LIMIT_ITEM: u8 = 7;
+    const COUNTER_ITEM: u8 = 8;
+
+    /// Retrieves which item is shown as `item_n`
+    fn item(&self, item_n: u8) -> TransferItem {
+        //the warning is right after the type
+        let item_n = match item_n {
+            1 if self.requires_expert() => return TransferItem::Warning,
+            n if n > 1 && self.requires_expert() => n - 1,
+            n => n,
+        };
+
+        //the token amount is right after the parameters size
+        let item_n = match (item_n, self.shows_token_amount()) {
+            (7, true) => return TransferItem::TokenAmount,
+            (n, true) if n > 7 => n - 1,
+            (n, _) => n,
+        };
+
+        //the parameters size is right after the parameters
+        match (item_n, self.parameters.is_some()) {
+            (6, true) => TransferItem::ParametersSize,
+            (n, true) if n > 6 => TransferItem::Fixed(n - 1),
+            (n, _) => TransferItem::Fixed(n),
+        }
+    }
 }
 
 impl<'a> DisplayableItem for Transfer<'a> {
     fn num_items(&self) -> usize {
+        //+1 for the dangerous entrypoint warning
+        //+1 for the parameters size
+        //+1 for the token amount
         1 + 8
+            + self.requires_expert() as usize
+            + self.parameters.is_some() as usize
+            + self.shows_token_amount() as usize
+    }
+
+    fn is_expert_item(&self, item_n: u8) -> bool {
+        matches!(
+            self.item(item_n),
+            TransferItem::Fixed(
+                Self::GAS_LIMIT_ITEM | Self::STORAGE_LIMIT_ITEM | Self::COUNTER_ITEM
+            )
+        )
     }
 
     #[inline(never)]
@@ -262,7 +648,53 @@ impl<'a> DisplayableItem for Transfer<'a> {
         use bolos::{pic_str, PIC};
         use lexical_core::{write as itoa, Number};
 
-        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2]; //+ 2 for point and extra 0 in front
+        //+2 for point and extra 0 in front, and room for the separators
+        let mut zarith_buf = [0; usize::FORMATTED_SIZE_DECIMAL + 2 + MAX_THOUSANDS_SEPARATORS];
+
+        let item_n = match self.item(item_n) {
+            TransferItem::Warning => {
+                let title_content = pic_str!(b"Warning");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let unlimited = self
+                    .parameters
+                    .map(|params| params.is_unlimited_approve())
+                    .unwrap_or(false);
+                let mex = if unlimited {
+                    pic_str!("Unlimited allowance")
+                } else {
+                    pic_str!("Dangerous entrypoint")
+                };
+
+                return handle_ui_message(mex.as_bytes(), message, page);
+            }
+            TransferItem::TokenAmount => {
+                let token = self.token_amount().ok_or(ViewError::Unknown)?;
+
+                let title_content = pic_str!(b"Token Amount");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; TOKEN_AMOUNT_FORMATTED_SIZE];
+                let amount =
+                    Self::format_token_amount(token, &mut mex).ok_or(ViewError::Unknown)?;
+
+                return handle_ui_message(amount, message, page);
+            }
+            TransferItem::ParametersSize => {
+                let params = self.parameters.ok_or(ViewError::Unknown)?;
+
+                let title_content = pic_str!(b"Parameters Size");
+                title[..title_content.len()].copy_from_slice(title_content);
+
+                let mut mex = [0; usize::FORMATTED_SIZE_DECIMAL + 6];
+                let len = itoa(params.michelson.len(), &mut mex[..]).len();
+                let unit = pic_str!(b" bytes");
+                mex[len..len + unit.len()].copy_from_slice(unit);
+
+                return handle_ui_message(&mex[..len + unit.len()], message, page);
+            }
+            TransferItem::Fixed(n) => n,
+        };
 
         match item_n {
             //home
@@ -288,12 +720,14 @@ impl<'a> DisplayableItem for Transfer<'a> {
             }
             //destination
             2 => {
-                let title_content = if self.destination.is_implicit() {
-                    pic_str!("Destination")
-                } else {
-                    pic_str!("Contract Addr")
-                };
-                title[..title_content.len()].copy_from_slice(title_content.as_bytes());
+                //contract calls show the called entrypoint with the contract,
+                // so both are reviewed together
+                if let (Some(params), false) = (self.parameters, self.destination.is_implicit()) {
+                    return self.render_contract_call(params.entrypoint(), title, message, page);
+                }
+
+                let title_content = pic_str!(b"Destination");
+                title[..title_content.len()].copy_from_slice(title_content);
 
                 let (len, cid) = self
                     .destination()
@@ -307,38 +741,56 @@ impl<'a> DisplayableItem for Transfer<'a> {
                 let title_content = pic_str!(b"Amount");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let (_, amount) = self.amount().read_as::<usize>().ok_or(ViewError::Unknown)?;
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let amount = self
+                    .amount()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
 
-                itoa(amount, &mut zarith_buf);
-                handle_ui_message(
-                    intstr_to_fpstr_inplace(&mut zarith_buf, 6).map_err(|_| ViewError::Unknown)?,
-                    message,
-                    page,
-                )
+                handle_ui_message(amount, message, page)
             }
             //fee
             4 => {
                 let title_content = pic_str!(b"Fee");
                 title[..title_content.len()].copy_from_slice(title_content);
 
-                let (_, fee) = self.fee().read_as::<usize>().ok_or(ViewError::Unknown)?;
+                let mut mex = [0; Zarith::MUTEZ_FORMATTED_SIZE];
+                let fee = self
+                    .fee()
+                    .format_mutez_into(&mut mex)
+                    .ok_or(ViewError::Unknown)?;
 
-                itoa(fee, &mut zarith_buf);
-                handle_ui_message(
-                    intstr_to_fpstr_inplace(&mut zarith_buf, 6).map_err(|_| ViewError::Unknown)?,
-                    message,
-                    page,
-                )
+                handle_ui_message(fee, message, page)
             }
             //has_parameters
             5 => {
+                if let Some(transfer) = self.token_transfer() {
+                    return self.render_token_transfer(&transfer, title, message, page);
+                }
+
+                if let Some(action) = self.manager_action() {
+                    return self.render_manager_action(&action, title, message, page);
+                }
+
                 let title_content = pic_str!(b"Parameters");
                 title[..title_content.len()].copy_from_slice(title_content);
 
                 match self.parameters {
                     Some(params) => {
+                        use crate::parser::michelson::{
+                            render, render_constructors, MAX_RENDERED_LEN,
+                        };
                         use bolos::hash::{Hasher, Sha256};
 
+                        //show simple options and ors directly,
+                        // otherwise the whole value if it's short enough
+                        let mut rendered = [0; MAX_RENDERED_LEN];
+                        if let Some(len) = render_constructors(params.michelson, &mut rendered)
+                            .or_else(|| render(params.michelson, &mut rendered))
+                        {
+                            return handle_ui_message(&rendered[..len], message, page);
+                        }
+
                         //Display sha256 of michelson code
                         let sha =
                             Sha256::digest(params.michelson).map_err(|_| ViewError::Unknown)?;
@@ -352,7 +804,7 @@ impl<'a> DisplayableItem for Transfer<'a> {
                 }
             }
             //gas_limit
-            6 => {
+            Self::GAS_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Gas Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -364,7 +816,7 @@ impl<'a> DisplayableItem for Transfer<'a> {
                 handle_ui_message(itoa(gas_limit, &mut zarith_buf), message, page)
             }
             //storage_limit
-            7 => {
+            Self::STORAGE_LIMIT_ITEM => {
                 let title_content = pic_str!(b"Storage Limit");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -376,7 +828,7 @@ impl<'a> DisplayableItem for Transfer<'a> {
                 handle_ui_message(itoa(storage_limit, &mut zarith_buf), message, page)
             }
             //counter
-            8 => {
+            Self::COUNTER_ITEM => {
                 let title_content = pic_str!(b"Counter");
                 title[..title_content.len()].copy_from_slice(title_content);
 
@@ -476,7 +928,7 @@ impl<'b> Transfer<'b> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{crypto::Curve, parser::Zarith};
+    use crate::{crypto::Curve, handlers::parser_common::ParserError, parser::Zarith};
 
     use super::{ContractID, Entrypoint, Parameters, Transfer};
 
@@ -508,13 +960,327 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Incomplete(Size(8))")]
+    fn entrypoint_names() {
+        use std::string::ToString;
+
+        let custom = |name: &[u8]| {
+            let mut input = std::vec![0xFF, name.len() as u8];
+            input.extend_from_slice(name);
+            Entrypoint::from_bytes(&input).map(|(_, entrypoint)| entrypoint.to_string())
+        };
+
+        assert_eq!(custom(b"%mint@1.b_C").as_deref(), Ok("%mint@1.b_C"));
+        assert_eq!(custom(&[b'a'; 31]).map(|name| name.len()), Ok(31));
+        assert!(custom(&[b'a'; 32]).is_err());
+
+        assert!(custom("entrypoint©Ͻ".as_bytes()).is_err());
+        assert!(custom(b"entry point").is_err());
+        assert!(custom(b"entry\npoint").is_err());
+
+        //every name of a single byte is either rejected or shown as it is
+        for c in 0..=u8::MAX {
+            match custom(&[c]) {
+                Ok(name) => assert_eq!(name.as_bytes(), [c]),
+                Err(_) => assert!(!c.is_ascii_alphanumeric() && !b"_.%@".contains(&c)),
+            }
+        }
+
+        //invalid character anywhere in the name
+        for pos in 0..4 {
+            let mut name = *b"abcd";
+            name[pos] = b'-';
+            assert!(custom(&name).is_err());
+        }
+    }
+
+    #[test]
+    fn parameters_expr_hash() {
+        //default entrypoint, `Unit`
+        let input = hex::decode("0000000002030b").expect("invalid input hex");
+        let (_, params) = Parameters::from_bytes(&input).expect("failed to parse parameters");
+
+        let expected =
+            hex::decode("8b456a4530fb6d0fea9a0dcd0e9d6ff6b30d13b05d05cb4a375dd91bf2a7eadc")
+                .unwrap();
+        assert_eq!(&params.expr_hash().unwrap()[..], &expected[..]);
+    }
+
+    #[test]
+    fn dangerous_entrypoint() {
+        assert!(!Entrypoint::Default.is_dangerous());
+        assert!(!Entrypoint::SetDelegate.is_dangerous());
+        assert!(!Entrypoint::Custom(b"transfer").is_dangerous());
+
+        assert!(!Entrypoint::Custom(b"approve").is_dangerous());
+        assert!(Entrypoint::Custom(b"set_administrator").is_dangerous());
+    }
+
+    #[test]
+    fn unlimited_approve() {
+        let approve = |entrypoint: &'static [u8], allowance_hex: &str| {
+            //Pair 0x0102 <allowance>
+            let michelson = hex::decode(std::format!("07070a00000002010200{}", allowance_hex))
+                .expect("invalid input hex");
+            Parameters {
+                entrypoint: Entrypoint::Custom(entrypoint),
+                michelson: std::boxed::Box::leak(michelson.into_boxed_slice()),
+            }
+        };
+
+        //150000000
+        assert!(!approve(b"approve", "80c6868f01").requires_expert());
+        //2^128 - 2
+        assert!(!approve(b"approve", "beffffffffffffffffffffffffffffffffff07").requires_expert());
+
+        //2^128 - 1
+        assert!(approve(b"approve", "bfffffffffffffffffffffffffffffffffff07").requires_expert());
+        //2^128
+        assert!(approve(b"approve", "80808080808080808080808080808080808008").requires_expert());
+
+        //same parameters, for another entrypoint
+        assert!(!approve(b"transfer", "80808080808080808080808080808080808008").requires_expert());
+    }
+
+    #[test]
+    fn parameters_size() {
+        use crate::parser::DisplayableItem;
+
+        //transfer of 0.00001 to KT1 with parameters `Unit`
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e01\
+                                 0a0a0a\
+                                 0174b19c9bf9ee3b6ec88d6c2ea4e45c4d7a08bf0100\
+                                 ff\
+                                 00\
+                                 00000002030b";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
+        assert_eq!(parsed.num_items(), 10);
+
+        let mut title = [0; 32];
+        let mut message = [0; 32];
+        parsed
+            .render_item(6, &mut title, &mut message, 0)
+            .expect("couldn't render parameters size");
+        assert!(title.starts_with(b"Parameters Size"));
+        assert!(message.starts_with(b"2 bytes\x00"));
+    }
+
+    #[test]
+    fn token_transfer() {
+        use crate::parser::DisplayableItem;
+
+        //FA1.2 `transfer` of 150000000 from tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn
+        // to KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA, on an unknown token
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e01\
+                                 0a0a00\
+                                 016a7d4a43f51be0934a441fba4f13f9beaa47575100\
+                                 ff\
+                                 ff087472616e73666572\
+                                 0000004e\
+                                 07070a00000016000035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 070701000000244b54314a487148516448536757424b6f364834556647386477334a6e5a53796a476b4841\
+                                 0080c6868f01";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
+        assert!(parsed.token_transfer().is_some());
+        assert!(parsed.token_amount().is_none());
+
+        let mut title = [0; 32];
+        let mut message = [0; 128];
+        parsed
+            .render_item(5, &mut title, &mut message, 0)
+            .expect("couldn't render token transfer");
+        assert!(title.starts_with(b"Token Transfer\x00"));
+        assert!(message.starts_with(
+            b"From: tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn / \
+              To: KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA / Amount: 150000000\x00"
+        ));
+    }
+
+    #[test]
+    fn known_token_transfer() {
+        use crate::parser::DisplayableItem;
+
+        //same transfer as above, on tzBTC
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e01\
+                                 0a0a00\
+                                 01a3d0f58d8964bd1b37fb0a0c197b38cf46608d4900\
+                                 ff\
+                                 ff087472616e73666572\
+                                 0000004e\
+                                 07070a00000016000035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 070701000000244b54314a487148516448536757424b6f364834556647386477334a6e5a53796a476b4841\
+                                 0080c6868f01";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
+        assert!(parsed.token_transfer().is_some());
+        assert!(parsed.token_amount().is_some());
+
+        //the amount is only shown with the transfer
+        assert_eq!(parsed.num_items(), 10);
+
+        let mut title = [0; 32];
+        let mut message = [0; 128];
+        parsed
+            .render_item(5, &mut title, &mut message, 0)
+            .expect("couldn't render token transfer");
+        assert!(title.starts_with(b"Token Transfer\x00"));
+        assert!(message.starts_with(
+            b"From: tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn / \
+              To: KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA / Amount: 1.50000000 tzBTC\x00"
+        ));
+
+        for item_n in 0..parsed.num_items() as u8 {
+            parsed
+                .render_item(item_n, &mut title, &mut message, 0)
+                .expect("couldn't render item");
+            assert!(!title.starts_with(b"Token Amount"));
+        }
+    }
+
+    #[test]
+    fn manager_action() {
+        use crate::parser::DisplayableItem;
+
+        //`do` on KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA,
+        // transferring 1 tez to tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e01\
+                                 0a0a00\
+                                 016a7d4a43f51be0934a441fba4f13f9beaa47575100\
+                                 ff\
+                                 02\
+                                 00000039\
+                                 02000000340320053d036d0743035d0a000000150035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 031e0743036a0080897a034f034d031b";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
+        assert!(parsed.manager_action().is_some());
+
+        let mut title = [0; 32];
+        let mut message = [0; 128];
+        parsed
+            .render_item(5, &mut title, &mut message, 0)
+            .expect("couldn't render manager action");
+        assert!(title.starts_with(b"Manager Action\x00"));
+        assert!(message.starts_with(
+            b"Transfer 1 XTZ from KT1JHqHQdHSgWBKo6H4UfG8dw3JnZSyjGkHA \
+              to tz1QZ6KY7d3BuZDT1d19dUxoQrtFPN2QJ3hn\x00"
+        ));
+    }
+
+    #[test]
+    fn token_amount() {
+        use crate::parser::DisplayableItem;
+
+        //transfer of 1.5 tzBTC, `Pair 0x0102 (Pair 0x0304 150000000)`
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e01\
+                                 0a0a00\
+                                 01a3d0f58d8964bd1b37fb0a0c197b38cf46608d4900\
+                                 ff\
+                                 ff087472616e73666572\
+                                 00000018\
+                                 07070a00000002010207070a00000002030400\
+                                 80c6868f01";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
+        assert_eq!(parsed.num_items(), 11);
+
+        let mut title = [0; 32];
+        let mut message = [0; 32];
+        parsed
+            .render_item(7, &mut title, &mut message, 0)
+            .expect("couldn't render token amount");
+        assert!(title.starts_with(b"Token Amount\x00"));
+        assert!(message.starts_with(b"1.50000000 tzBTC\x00"));
+
+        //following items are shifted
+        parsed
+            .render_item(8, &mut title, &mut message, 0)
+            .expect("couldn't render gas limit");
+        assert!(title.starts_with(b"Gas Limit\x00"));
+
+        //the expert items are told apart by their kind, wherever they are
+        for item_n in 0..parsed.num_items() as u8 {
+            parsed
+                .render_item(item_n, &mut title, &mut message, 0)
+                .expect("couldn't render item");
+            let secondary = [&b"Gas Limit\x00"[..], b"Storage Limit\x00", b"Counter\x00"]
+                .iter()
+                .any(|kind| title.starts_with(kind));
+            assert_eq!(parsed.is_expert_item(item_n), secondary);
+        }
+
+        //unknown contract
+        let mut input = input;
+        input[32] ^= 0xFF;
+        let (_, parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
+        assert!(parsed.token_amount().is_none());
+        assert_eq!(parsed.num_items(), 10);
+    }
+
+    #[test]
+    fn contract_entrypoint_item() {
+        use crate::parser::DisplayableItem;
+
+        //call of `transfer` on tzBTC
+        const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd\
+                                 904e01\
+                                 0a0a00\
+                                 01a3d0f58d8964bd1b37fb0a0c197b38cf46608d4900\
+                                 ff\
+                                 ff087472616e73666572\
+                                 00000018\
+                                 07070a00000002010207070a00000002030400\
+                                 80c6868f01";
+
+        let input = hex::decode(INPUT_HEX).expect("invalid input hex");
+        let (_, parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
+
+        let (len, cid) = parsed.destination().base58().unwrap();
+        let mut expected = cid[..len].to_vec();
+        expected.extend_from_slice(b" / Entrypoint: transfer");
+
+        //the combined item doesn't fit in a single page
+        let mut title = [0; 32];
+        let mut rendered = std::vec::Vec::new();
+        for page in 0.. {
+            let mut message = [0; 32];
+            let n_pages = parsed
+                .render_item(2, &mut title, &mut message, page)
+                .expect("couldn't render contract");
+            assert!(title.starts_with(b"Contract\x00"));
+
+            let len = message.iter().position(|&b| b == 0).unwrap();
+            rendered.extend_from_slice(&message[..len]);
+
+            if page + 1 == n_pages {
+                assert_eq!(n_pages, 2);
+                break;
+            }
+        }
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
     fn entrypoint_eof() {
-        Entrypoint::from_bytes(&[0xFF, 10, 0x61, 0x62]).expect("failed to parse custom entrypoint");
+        assert_eq!(
+            Entrypoint::from_bytes(&[0xFF, 10, 0x61, 0x62]).unwrap_err(),
+            nom::Err::Error(ParserError::UnexpectedEof)
+        );
     }
 
     mod parameters {
-        use super::{super::Parameters, Entrypoint};
+        use super::{super::Parameters, Entrypoint, ParserError};
 
         #[test]
         fn manual() {
@@ -557,7 +1323,6 @@ mod tests {
         }
 
         #[test]
-        #[should_panic(expected = "Incomplete(Size(8))")]
         fn manual_eof() {
             const MICHELSON_CODE: &[u8] = &[0xab, 0xcd];
 
@@ -565,7 +1330,10 @@ mod tests {
             input.extend_from_slice(&(10u32).to_be_bytes()[..]);
             input.extend_from_slice(MICHELSON_CODE);
 
-            Parameters::from_bytes(&input).expect("failed to parse parameters");
+            assert_eq!(
+                Parameters::from_bytes(&input).unwrap_err(),
+                nom::Err::Error(ParserError::UnexpectedEof)
+            );
         }
     }
 
@@ -619,15 +1387,16 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Incomplete")]
     fn transfer_eof() {
         const INPUT_HEX: &str = "0035e993d8c7aaa42b5e3ccd86a33390ececc73abd904e01";
 
         let mut input = hex::decode(INPUT_HEX).expect("invalid input hex");
         input.extend_from_slice(&[0xDE, 0xEA, 0xBE, 0xEF]);
 
-        let (rem, _parsed) = Transfer::from_bytes(&input).expect("couldn't parse transfer");
-        assert_eq!(rem.len(), 4);
+        assert_eq!(
+            Transfer::from_bytes(&input).unwrap_err(),
+            nom::Err::Error(ParserError::UnexpectedEof)
+        );
     }
 
     #[test]
@@ -0,0 +1,186 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Conditions required by each instruction
+//!
+//! The dispatcher checks them before invoking the handler, so handlers
+//! only need to care about the conditions depending on the payload
+//! (ie: an operation requiring expert mode)
+
+use crate::{apdu_spec::*, constants::ApduError as Error, sys};
+
+/// Conditions an instruction can require
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Requirements {
+    /// The device has been unlocked with the PIN
+    pub unlocked: bool,
+    /// The request asks for the user's confirmation (P1 is set)
+    pub confirmed: bool,
+    /// A baking key is currently authorized
+    #[cfg(feature = "baking")]
+    pub baking_authorized: bool,
+}
+
+impl Requirements {
+    const NONE: Self = Self {
+        unlocked: false,
+        confirmed: false,
+        #[cfg(feature = "baking")]
+        baking_authorized: false,
+    };
+
+    const UNLOCKED: Self = Self {
+        unlocked: true,
+        ..Self::NONE
+    };
+
+    #[cfg(feature = "baking")]
+    const CONFIRMED: Self = Self {
+        confirmed: true,
+        ..Self::UNLOCKED
+    };
+
+    #[cfg(feature = "baking")]
+    const BAKING_AUTHORIZED: Self = Self {
+        baking_authorized: true,
+        ..Self::UNLOCKED
+    };
+
+    /// Retrieves the conditions required by `ins`
+    ///
+    /// Instructions not listed only query public information about the app
+    pub fn of(ins: u8) -> Self {
+        match ins {
+            //keys are used or shown
+            INS_LEGACY_GET_PUBLIC_KEY
+            | INS_LEGACY_PROMPT_PUBLIC_KEY
+            | INS_GET_ADDRESS
+            | INS_GET_ALL_ADDRESSES
+            | INS_LEGACY_SIGN
+            | INS_LEGACY_SIGN_WITH_HASH
+            | INS_SIGN => Self::UNLOCKED,
+
+            //settings are changed
+            INS_SET_FEE_THRESHOLD => Self::UNLOCKED,
+
+            #[cfg(feature = "wallet")]
+            INS_LEGACY_SIGN_UNSAFE => Self::UNLOCKED,
+
+            #[cfg(feature = "baking")]
+            INS_LEGACY_AUTHORIZE_BAKING | INS_LEGACY_SETUP | INS_LEGACY_HMAC | INS_LEGACY_RESET => {
+                Self::UNLOCKED
+            }
+
+            //the baking key and its watermarks are replaced
+            #[cfg(feature = "baking")]
+            INS_AUTHORIZE_BAKING | INS_IMPORT_BAKING_STATE => Self::CONFIRMED,
+
+            //the legacy instruction takes no P1, but always asks for confirmation
+            #[cfg(feature = "baking")]
+            INS_DEAUTHORIZE_BAKING => Self {
+                confirmed: true,
+                ..Self::BAKING_AUTHORIZED
+            },
+
+            #[cfg(feature = "baking")]
+            INS_BAKER_SIGN
+            | INS_LEGACY_DEAUTHORIZE
+            | INS_QUERY_AUTH_KEY
+            | INS_QUERY_AUTH_KEY_WITH_CURVE
+            | INS_LEGACY_QUERY_AUTH_KEY
+            | INS_LEGACY_QUERY_AUTH_KEY_WITH_CURVE
+            | INS_EXPORT_BAKING_STATE => Self::BAKING_AUTHORIZED,
+
+            #[cfg(feature = "dev")]
+            INS_BLIND_SIGN => Self::UNLOCKED,
+
+            _ => Self::NONE,
+        }
+    }
+
+    /// Verifies that all the conditions are currently met
+    /// by a request with the given `p1`
+    pub fn check(&self, p1: u8) -> Result<(), Error> {
+        if self.unlocked && !sys::pin_validated() {
+            return Err(Error::ApduCodeConditionsNotSatisfied);
+        }
+
+        if self.confirmed && p1 < 1 {
+            return Err(Error::ApduCodeConditionsNotSatisfied);
+        }
+
+        #[cfg(feature = "baking")]
+        if self.baking_authorized && crate::handlers::baking::Baking::read_baking_key()?.is_none() {
+            return Err(Error::ApduCodeConditionsNotSatisfied);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirements_met() {
+        //public information
+        assert_eq!(Requirements::of(INS_GET_VERSION), Requirements::NONE);
+        assert!(Requirements::NONE.check(0).is_ok());
+
+        //the mocked device is always unlocked
+        assert!(Requirements::of(INS_SIGN).unlocked);
+        assert!(Requirements::of(INS_SIGN).check(0).is_ok());
+
+        let confirmed = Requirements {
+            confirmed: true,
+            ..Requirements::UNLOCKED
+        };
+        assert_eq!(
+            confirmed.check(0),
+            Err(Error::ApduCodeConditionsNotSatisfied)
+        );
+        assert!(confirmed.check(1).is_ok());
+    }
+
+    #[cfg(feature = "baking")]
+    #[test]
+    #[serial_test::serial(hwm)]
+    fn baking_authorized() {
+        use crate::{crypto::Curve, handlers::baking::Baking, utils::ApduFixture};
+        use bolos::crypto::bip32::BIP32Path;
+
+        Baking::remove_baking_key().expect("couldn't remove baking key");
+
+        let requirements = Requirements::of(INS_BAKER_SIGN);
+        assert!(requirements.baking_authorized);
+        assert_eq!(
+            requirements.check(0),
+            Err(Error::ApduCodeConditionsNotSatisfied)
+        );
+
+        //rejected before reaching the handler
+        ApduFixture::new()
+            .send(INS_QUERY_AUTH_KEY, 0, 0, &[])
+            .assert_code(Error::ApduCodeConditionsNotSatisfied);
+
+        let path = BIP32Path::<10>::new([44, 1729, 0, 0].iter().map(|n| 0x8000_0000 + n)).unwrap();
+        Baking::store_baking_key(Curve::Ed25519, path).expect("couldn't store baking key");
+        assert!(requirements.check(0).is_ok());
+
+        Baking::remove_baking_key().expect("couldn't remove baking key");
+    }
+}
@@ -36,6 +36,14 @@ pub use apdu_wrapper::*;
 mod buffer_upload;
 pub use buffer_upload::*;
 
+mod endian;
+pub use endian::*;
+
+#[cfg(test)]
+mod apdu_fixture;
+#[cfg(test)]
+pub use apdu_fixture::*;
+
 /// This function returns the index of the first null byte in the slice
 #[cfg(test)]
 pub fn strlen(s: &[u8]) -> usize {
@@ -0,0 +1,136 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use std::convert::TryFrom;
+
+use crate::{
+    constants::{ApduError, APDU_MIN_LENGTH},
+    dispatcher::{handle_apdu, CLA},
+    handlers::ZPacketType,
+};
+
+/// Biggest payload that fits in a single command
+pub const MAX_PAYLOAD: usize = 0xFF;
+
+/// Builds commands and hands them to the dispatcher,
+/// keeping the `flags` and buffer around like the device would
+pub struct ApduFixture {
+    flags: u32,
+    tx: u32,
+    buffer: [u8; 260],
+}
+
+impl Default for ApduFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApduFixture {
+    pub fn new() -> Self {
+        Self {
+            flags: 0,
+            tx: 0,
+            buffer: [0; 260],
+        }
+    }
+
+    /// Sends the given bytes as is, `rx` being their length
+    pub fn send_raw(&mut self, raw: &[u8]) -> Response<'_> {
+        self.buffer[..raw.len()].copy_from_slice(raw);
+        self.tx = 0;
+
+        handle_apdu(
+            &mut self.flags,
+            &mut self.tx,
+            raw.len() as u32,
+            &mut self.buffer,
+        );
+
+        Response::new(&self.buffer[..self.tx as usize])
+    }
+
+    /// Sends a single command with the app's CLA
+    pub fn send(&mut self, ins: u8, p1: u8, p2: u8, payload: &[u8]) -> Response<'_> {
+        assert!(
+            payload.len() <= MAX_PAYLOAD,
+            "payload too big for a single command"
+        );
+
+        let header = APDU_MIN_LENGTH as usize;
+        let mut raw = [0; 260];
+        raw[..header].copy_from_slice(&[CLA, ins, p1, p2, payload.len() as u8]);
+        raw[header..header + payload.len()].copy_from_slice(payload);
+
+        self.send_raw(&raw[..header + payload.len()])
+    }
+
+    /// Sends `init` as the `Init` packet and `data` in as many `Add` packets as needed,
+    /// the last chunk being sent as `Last` (empty if `data` is)
+    ///
+    /// Returns the first non successful response, or the response to `Last`
+    pub fn send_chunks(&mut self, ins: u8, p2: u8, init: &[u8], data: &[u8]) -> Response<'_> {
+        if self.send(ins, ZPacketType::Init.into(), p2, init).code() != ApduError::Success {
+            return Response::new(&self.buffer[..self.tx as usize]);
+        }
+
+        let mut chunks = data.chunks(MAX_PAYLOAD).peekable();
+        while let Some(chunk) = chunks.next() {
+            if chunks.peek().is_none() {
+                return self.send(ins, ZPacketType::Last.into(), p2, chunk);
+            }
+
+            if self.send(ins, ZPacketType::Add.into(), p2, chunk).code() != ApduError::Success {
+                return Response::new(&self.buffer[..self.tx as usize]);
+            }
+        }
+
+        self.send(ins, ZPacketType::Last.into(), p2, &[])
+    }
+}
+
+/// Response to a command, split in payload and status word
+#[derive(Debug, Clone, Copy)]
+pub struct Response<'f> {
+    raw: &'f [u8],
+}
+
+impl<'f> Response<'f> {
+    fn new(raw: &'f [u8]) -> Self {
+        assert!(raw.len() >= 2, "response is missing the status word");
+        Self { raw }
+    }
+
+    /// The status word of the response
+    pub fn code(&self) -> ApduError {
+        ApduError::try_from(&self.raw[self.raw.len() - 2..]).expect("unknown status word")
+    }
+
+    /// The response without the status word
+    pub fn data(&self) -> &'f [u8] {
+        &self.raw[..self.raw.len() - 2]
+    }
+
+    /// Asserts the response has the given status word
+    pub fn assert_code(&self, expected: ApduError) -> &'f [u8] {
+        assert_eq!(self.code(), expected, "response was {:02x?}", self.raw);
+        self.data()
+    }
+
+    /// Asserts the response is successful, returning its payload
+    pub fn assert_success(&self) -> &'f [u8] {
+        self.assert_code(ApduError::Success)
+    }
+}
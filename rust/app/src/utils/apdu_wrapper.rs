@@ -1,13 +1,17 @@
 use crate::constants::{
     APDU_INDEX_CLA, APDU_INDEX_INS, APDU_INDEX_LEN, APDU_INDEX_P1, APDU_INDEX_P2, APDU_MIN_LENGTH,
 };
+use crate::utils::ApduPanic;
+
+use zeroize::Zeroize;
 
 /// Wraps an apdu_buffer and provides utility methods
 pub struct ApduBufferRead<'apdu> {
     inner: &'apdu mut [u8],
+    rx: usize,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, PartialEq)]
 pub enum ApduBufferReadError {
     /// The provided buffer was not long enough
     ///
@@ -65,7 +69,10 @@ impl<'apdu> ApduBufferRead<'apdu> {
         //check buf is at least rx
         Self::check_min_len(buf.len(), rx as usize, None)?;
 
-        Ok(Self { inner: buf })
+        Ok(Self {
+            inner: buf,
+            rx: rx as usize,
+        })
     }
 
     /// Alias to idx APDU_INDEX_CLA
@@ -92,10 +99,13 @@ impl<'apdu> ApduBufferRead<'apdu> {
     ///
     /// It's expected the buffer to have the prepended len at idx APDU_INDEX_LEN,
     /// thus the data would start at idx 5 until len - 5
+    ///
+    /// The payload is checked against the number of bytes received (rx),
+    /// so stale data in the buffer is never returned
     pub fn payload(&self) -> Result<&[u8], ApduBufferReadError> {
         let plen = self.inner[APDU_INDEX_LEN] as usize;
-        //check that the buffer is long enough for the payload
-        Self::check_min_len(self.inner.len(), plen as usize, APDU_MIN_LENGTH as usize)
+        //check that we received enough data for the payload
+        Self::check_min_len(self.rx, plen, APDU_MIN_LENGTH as usize)
             .map_err(|err| err.length_to_payload())?;
 
         Ok(&self.inner[APDU_MIN_LENGTH as usize..APDU_MIN_LENGTH as usize + plen])
@@ -106,4 +116,86 @@ impl<'apdu> ApduBufferRead<'apdu> {
     pub fn write(self) -> &'apdu mut [u8] {
         self.inner
     }
+
+    /// Borrow the same buffer for a shorter time,
+    /// to hand it to a handler that might not process it
+    pub fn reborrow(&mut self) -> ApduBufferRead<'_> {
+        ApduBufferRead {
+            inner: &mut *self.inner,
+            rx: self.rx,
+        }
+    }
+}
+
+/// Wraps an apdu_buffer once the command has been handled,
+/// to finish writing the response
+pub struct ApduBufferWrite<'apdu> {
+    inner: &'apdu mut [u8],
+}
+
+impl<'apdu> ApduBufferWrite<'apdu> {
+    pub fn new(buf: &'apdu mut [u8]) -> Self {
+        Self { inner: buf }
+    }
+
+    /// Appends the status word to the `tx` bytes of response already written
+    /// and zeroizes the rest of the buffer
+    ///
+    /// Handlers only write what they reply, so the remainder could still hold
+    /// the command or intermediate data (like key material) that shouldn't reach the host
+    ///
+    /// Returns the length of the response, status word included
+    #[inline(never)]
+    pub fn close(self, tx: usize, status_word: u16) -> usize {
+        let len = tx + 2;
+
+        self.inner
+            .get_mut(tx..len)
+            .apdu_unwrap()
+            .copy_from_slice(status_word.to_be_bytes().as_ref());
+
+        self.inner[len..].zeroize();
+
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApduBufferRead, ApduBufferReadError, ApduBufferWrite};
+
+    #[test]
+    fn payload() {
+        let mut buffer = [0xE0, 0x01, 0x02, 0x03, 2, 0xAA, 0xBB, 0xCC];
+
+        let reader = ApduBufferRead::new(&mut buffer, 7).expect("valid apdu");
+        assert_eq!(reader.p1(), 0x02);
+        assert_eq!(reader.p2(), 0x03);
+        assert!(matches!(reader.payload(), Ok(&[0xAA, 0xBB])));
+    }
+
+    #[test]
+    fn payload_longer_than_rx() {
+        //the buffer is big enough but only part of the payload was received
+        let mut buffer = [0xE0, 0x01, 0x00, 0x00, 3, 0xAA, 0xBB, 0xCC];
+
+        let reader = ApduBufferRead::new(&mut buffer, 7).expect("valid apdu");
+        assert!(matches!(
+            reader.payload(),
+            Err(ApduBufferReadError::NotEnoughPayload {
+                expected: 3,
+                got: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn close() {
+        let mut buffer = [0xAA; 16];
+
+        let len = ApduBufferWrite::new(&mut buffer).close(3, 0x9000);
+        assert_eq!(len, 5);
+        assert_eq!(&buffer[..5], &[0xAA, 0xAA, 0xAA, 0x90, 0x00]);
+        assert!(buffer[5..].iter().all(|&b| b == 0));
+    }
 }
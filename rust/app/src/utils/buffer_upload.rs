@@ -20,7 +20,7 @@ use crate::{
     handlers::{
         lock::LockError,
         resources::{BUFFERAccessors, BUFFER},
-        PacketType, PacketTypes,
+        PacketType, PacketTypes, ZPacketType,
     },
 };
 
@@ -29,6 +29,26 @@ use super::ApduBufferRead;
 #[bolos::lazy_static]
 static mut INIT_LEN: usize = 0;
 
+//running CRC32 of the uploaded data, see `ZPacketType::LastChecked`
+#[bolos::lazy_static]
+static mut CRC: u32 = 0;
+
+/// Length of the checksum following the data of a `LastChecked` packet
+pub const CHECKSUM_LEN: usize = 4;
+
+/// Updates the (not finalized) CRC32 state with `data`
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+
+    crc
+}
+
 pub struct Uploader {
     accessor: BUFFERAccessors,
 }
@@ -45,6 +65,12 @@ pub enum UploaderError {
 
     /// Error writing to `BUFFER`
     Nvm(NVMError),
+
+    /// The last packet was too short to contain the checksum
+    ChecksumMissing,
+
+    /// The checksum doesn't match the uploaded data
+    ChecksumMismatch,
 }
 
 impl From<LockError> for UploaderError {
@@ -67,6 +93,8 @@ impl From<UploaderError> for ApduError {
             }
             UploaderError::Nvm(_) => ApduError::DataInvalid,
             UploaderError::Lock(e) => e.into(),
+            UploaderError::ChecksumMissing => ApduError::WrongLength,
+            UploaderError::ChecksumMismatch => ApduError::ChecksumMismatch,
         }
     }
 }
@@ -108,45 +136,88 @@ impl Uploader {
         let packet_type = PacketTypes::try_either(buffer.p1())
             .map_err(|_| UploaderError::PacketTypeParseError)?;
 
+        //the checksum of the uploaded data is not part of it
+        let checked = matches!(packet_type, PacketTypes::Z(ZPacketType::LastChecked));
+        let (payload, checksum) = match buffer.payload() {
+            Ok(payload) if checked => {
+                let split = payload
+                    .len()
+                    .checked_sub(CHECKSUM_LEN)
+                    .ok_or(UploaderError::ChecksumMissing)?;
+                let (payload, checksum) = payload.split_at(split);
+
+                (payload, Some(checksum))
+            }
+            Ok(payload) => (payload, None),
+            Err(_) if checked => return Err(UploaderError::ChecksumMissing),
+            Err(_) => (&[] as _, None),
+        };
+
         if packet_type.is_init() {
             let zbuffer = unsafe { BUFFER.lock(self.accessor)? };
             zbuffer.reset();
 
             zbuffer.write(&[buffer.p2()])?;
-            if let Ok(payload) = buffer.payload() {
-                unsafe {
-                    *INIT_LEN = payload.len();
-                }
-                zbuffer.write(payload)?;
+            zbuffer.write(payload)?;
+            unsafe {
+                *INIT_LEN = payload.len();
+                *CRC = crc32_update(u32::MAX, payload);
             }
-
-            Ok(None)
-        } else if packet_type.is_next() {
+        } else if packet_type.is_next() || packet_type.is_last() {
             let zbuffer = unsafe { BUFFER.acquire(self.accessor)? };
 
-            if let Ok(payload) = buffer.payload() {
-                zbuffer.write(payload)?;
+            zbuffer.write(payload)?;
+            unsafe {
+                *CRC = crc32_update(*CRC, payload);
             }
+        } else {
+            return Err(UploaderError::PacketTypeInvalid);
+        }
 
-            Ok(None)
-        } else if packet_type.is_last() {
-            let zbuffer = unsafe { BUFFER.acquire(self.accessor)? };
+        //the host echoes the CRC32 of everything it sent,
+        // catching chunks lost or reordered while reassembling
+        if let Some(checksum) = checksum {
+            let crc = unsafe { !*CRC };
 
-            if let Ok(payload) = buffer.payload() {
-                zbuffer.write(payload)?;
-            }
+            if crc.to_be_bytes()[..] != checksum[..] {
+                let zbuffer = unsafe { BUFFER.acquire(self.accessor)? };
+                zbuffer.reset();
+                unsafe { BUFFER.release(self.accessor)? };
 
-            let data = zbuffer.read_exact();
-            let (head, tail) = data[1..].split_at(unsafe { *INIT_LEN });
+                return Err(UploaderError::ChecksumMismatch);
+            }
+        }
 
-            Ok(Some(UploaderOutput {
-                p2: data[0],
-                first: head,
-                data: tail,
-                accessor: self.accessor,
-            }))
-        } else {
-            Err(UploaderError::PacketTypeInvalid)
+        //legacy P1 allows a packet to be both the first and the last one
+        // so we check for the last packet separately
+        if !packet_type.is_last() {
+            return Ok(None);
         }
+
+        let zbuffer = unsafe { BUFFER.acquire(self.accessor)? };
+        let data = zbuffer.read_exact();
+        let (head, tail) = data[1..].split_at(unsafe { *INIT_LEN });
+
+        Ok(Some(UploaderOutput {
+            p2: data[0],
+            first: head,
+            data: tail,
+            accessor: self.accessor,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crc32_update;
+
+    #[test]
+    fn crc32() {
+        assert_eq!(!crc32_update(u32::MAX, b""), 0);
+        assert_eq!(!crc32_update(u32::MAX, b"123456789"), 0xCBF4_3926);
+
+        //the state can be updated in chunks
+        let crc = crc32_update(u32::MAX, b"1234");
+        assert_eq!(!crc32_update(crc, b"56789"), 0xCBF4_3926);
     }
 }
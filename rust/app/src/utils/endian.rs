@@ -0,0 +1,66 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+
+/// The input didn't have enough bytes for the requested field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShortInput;
+
+fn read_array<const N: usize>(input: &[u8], offset: usize) -> Result<[u8; N], ShortInput> {
+    let end = offset.checked_add(N).ok_or(ShortInput)?;
+    let bytes = input.get(offset..end).ok_or(ShortInput)?;
+
+    let mut array = [0; N];
+    array.copy_from_slice(bytes);
+    Ok(array)
+}
+
+/// Reads a big endian u16 starting at `offset`
+pub fn read_u16_be(input: &[u8], offset: usize) -> Result<u16, ShortInput> {
+    read_array(input, offset).map(u16::from_be_bytes)
+}
+
+/// Reads a big endian u32 starting at `offset`
+pub fn read_u32_be(input: &[u8], offset: usize) -> Result<u32, ShortInput> {
+    read_array(input, offset).map(u32::from_be_bytes)
+}
+
+/// Reads a big endian i32 starting at `offset`
+pub fn read_i32_be(input: &[u8], offset: usize) -> Result<i32, ShortInput> {
+    read_array(input, offset).map(i32::from_be_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_be() {
+        let input = [0x01, 0x02, 0xFF, 0xFF, 0xFF, 0xFE];
+
+        assert_eq!(read_u16_be(&input, 0), Ok(0x0102));
+        assert_eq!(read_u32_be(&input, 0), Ok(0x0102FFFF));
+        assert_eq!(read_i32_be(&input, 2), Ok(-2));
+    }
+
+    #[test]
+    fn read_be_short() {
+        let input = [0x01, 0x02, 0x03];
+
+        assert_eq!(read_u16_be(&input, 2), Err(ShortInput));
+        assert_eq!(read_u32_be(&input, 0), Err(ShortInput));
+        assert_eq!(read_i32_be(&input, usize::MAX), Err(ShortInput));
+    }
+}
@@ -0,0 +1,84 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Checks the keys derived by the mock against the vectors
+//! generated by `examples/test_vectors.rs`
+mod prelude;
+use prelude::*;
+
+use bolos::crypto::bip32::BIP32Path;
+use serde_json::Value;
+
+const VECTORS: &str = include_str!("vectors.json");
+
+fn path_components(path: &str) -> Vec<u32> {
+    path.trim_start_matches("m/")
+        .split('/')
+        .map(|n| n.trim_end_matches('\'').parse().expect("invalid path"))
+        .collect()
+}
+
+#[test]
+fn mock_vectors() {
+    let vectors: Vec<Value> = serde_json::from_str(VECTORS).expect("invalid vectors");
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let path = path_components(vector["path"].as_str().unwrap());
+        let curve: Curve = (vector["curve"].as_u64().unwrap() as u8)
+            .try_into()
+            .expect("invalid curve");
+
+        //retrieve the key and address like a host would
+        let mut flags = 0;
+        let mut tx = 0;
+        let mut buffer = [0; 260];
+
+        let data = prepare_path::<{ constants::BIP32_MAX_LENGTH }>(&path);
+        buffer[..5].copy_from_slice(&[CLA, INS_GET_ADDRESS, 0, curve.into(), data.len() as u8]);
+        buffer[5..5 + data.len()].copy_from_slice(&data);
+        handle_apdu(&mut flags, &mut tx, 5 + data.len() as u32, &mut buffer);
+
+        let out = &buffer[..tx as usize];
+        assert_eq!(&out[out.len() - 2..], &[0x90, 0x00]);
+
+        let pk_len = out[0] as usize;
+        let pk = &out[1..1 + pk_len];
+        assert_eq!(hex::encode(pk), vector["public_key"].as_str().unwrap());
+
+        let address = vector["address"].as_str().unwrap();
+        assert_eq!(
+            &out[1 + pk_len..1 + pk_len + address.len()],
+            address.as_bytes()
+        );
+
+        //signatures are deterministic too
+        let bip32 =
+            BIP32Path::<{ constants::BIP32_MAX_LENGTH }>::new(path.iter().map(|n| 0x8000_0000 + n))
+                .unwrap();
+        for sig in vector["signatures"].as_array().unwrap() {
+            let msg = hex::decode(sig["message"].as_str().unwrap()).unwrap();
+
+            let mut out = [0; 100];
+            let sk = curve.to_secret(&bip32).expect("couldn't derive key");
+            let len = match sk.sign(&msg, &mut out) {
+                Ok(len) => len,
+                Err(_) => panic!("couldn't sign with {}", vector["path"]),
+            };
+
+            assert_eq!(hex::encode(&out[..len]), sig["signature"].as_str().unwrap());
+        }
+    }
+}
@@ -0,0 +1,123 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Status words shared by the app, the UI and the mock harness
+
+use core::convert::TryFrom;
+
+/// Status word returned at the end of every APDU response
+///
+/// Based on ISO7816, with the legacy Tezos app's status words
+#[repr(u16)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(any(feature = "derive-debug", test), derive(Debug))]
+pub enum ApduError {
+    ExecutionError = 0x6400,
+    WrongLength = 0x6700,
+    ApduCodeEmptyBuffer = 0x6982,
+    OutputBufferTooSmall = 0x6983,
+    DataInvalid = 0x6984,
+    ApduCodeConditionsNotSatisfied = 0x6985,
+    CommandNotAllowed = 0x6986,
+    /// Values out of the allowed range (ie: level below the high watermark)
+    WrongValues = 0x6A80,
+    ConsensusNotAllowed = 0x6A81,
+    /// The checksum sent with the last chunk doesn't match the uploaded data
+    ChecksumMismatch = 0x6A82,
+    InvalidP1P2 = 0x6B00,
+    InsNotSupported = 0x6D00,
+    ClaNotSupported = 0x6E00,
+    Unknown = 0x6F00,
+    SignVerifyError = 0x6F01,
+    Success = 0x9000,
+    Busy = 0x9001,
+    /// Payload couldn't be parsed
+    ParseError = 0x9405,
+}
+
+#[cfg_attr(any(feature = "derive-debug", test), derive(Debug))]
+pub enum ConvertApduError {
+    Length { expected: usize, found: usize },
+    Unknown(u16),
+}
+
+impl TryFrom<&[u8]> for ApduError {
+    type Error = ConvertApduError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match *value {
+            [hi, lo] => Self::try_from(u16::from_be_bytes([hi, lo])),
+            _ => Err(Self::Error::Length {
+                expected: 2,
+                found: value.len(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<u16> for ApduError {
+    type Error = ConvertApduError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0x6400 => Ok(Self::ExecutionError),
+            0x6700 => Ok(Self::WrongLength),
+            0x6982 => Ok(Self::ApduCodeEmptyBuffer),
+            0x6983 => Ok(Self::OutputBufferTooSmall),
+            0x6984 => Ok(Self::DataInvalid),
+            0x6985 => Ok(Self::ApduCodeConditionsNotSatisfied),
+            0x6986 => Ok(Self::CommandNotAllowed),
+            0x6A80 => Ok(Self::WrongValues),
+            0x6A81 => Ok(Self::ConsensusNotAllowed),
+            0x6A82 => Ok(Self::ChecksumMismatch),
+            0x6B00 => Ok(Self::InvalidP1P2),
+            0x6D00 => Ok(Self::InsNotSupported),
+            0x6E00 => Ok(Self::ClaNotSupported),
+            0x6F00 => Ok(Self::Unknown),
+            0x6F01 => Ok(Self::SignVerifyError),
+            0x9000 => Ok(Self::Success),
+            0x9001 => Ok(Self::Busy),
+            0x9405 => Ok(Self::ParseError),
+            err => Err(Self::Error::Unknown(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_word_roundtrip() {
+        let sw = ApduError::DataInvalid as u16;
+        assert_eq!(ApduError::try_from(sw).unwrap(), ApduError::DataInvalid);
+        assert_eq!(
+            ApduError::try_from(&sw.to_be_bytes()[..]).unwrap(),
+            ApduError::DataInvalid
+        );
+
+        assert!(matches!(
+            ApduError::try_from(0x1234),
+            Err(ConvertApduError::Unknown(0x1234))
+        ));
+        assert!(matches!(
+            ApduError::try_from(&[0x90][..]),
+            Err(ConvertApduError::Length {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+}
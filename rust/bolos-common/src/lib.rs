@@ -18,6 +18,7 @@
 
 extern crate no_std_compat as std;
 
+pub mod apdu;
 pub mod bip32;
 
 pub mod hash {
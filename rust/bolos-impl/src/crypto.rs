@@ -277,8 +277,6 @@ mod bindings {
                 };
 
                 catch(might_throw)?;
-            } else {
-                unimplemented!("os derive called in non-bolos")
             }
         }
 
@@ -54,6 +54,25 @@ impl PublicKey {
     pub fn len(&self) -> usize {
         self.0.W_len as usize
     }
+
+    /// Returns the compressed SEC1 encoding of a secp256k1/secp256r1 public key
+    ///
+    /// Returns None if the key is of another curve or isn't an uncompressed point
+    pub fn compressed_secp(&self) -> Option<[u8; 33]> {
+        let point = self.as_ref();
+        match self.curve() {
+            Curve::Secp256K1 | Curve::Secp256R1 if point.len() == 65 => {
+                let mut out = [0; 33];
+
+                //prefix is 0x02 if y is even, 0x03 if odd
+                out[0] = 0x02 | (point[64] & 0x01);
+                out[1..].copy_from_slice(&point[1..33]);
+
+                Some(out)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl AsRef<[u8]> for PublicKey {
@@ -142,6 +161,37 @@ impl<const B: usize> SecretKey<B> {
         Ok(())
     }
 
+    /// Derives the private key, which can then be used for
+    /// multiple signatures without deriving it again
+    #[inline(never)]
+    pub fn derive(&self) -> Result<DerivedSecretKey, Error> {
+        Ok(DerivedSecretKey {
+            curve: self.curve,
+            raw: self.generate()?,
+        })
+    }
+
+    #[inline(never)]
+    pub fn sign<H>(&self, data: &[u8], out: &mut [u8]) -> Result<usize, Error>
+    where
+        H: HasherId,
+        H::Id: Into<u8>,
+    {
+        self.derive()?.sign::<H>(data, out)
+    }
+}
+
+/// Private key derived from the device seed, zeroized on drop
+pub struct DerivedSecretKey {
+    curve: Curve,
+    raw: Zeroizing<cx_ecfp_private_key_t>,
+}
+
+impl DerivedSecretKey {
+    pub const fn curve(&self) -> Curve {
+        self.curve
+    }
+
     #[inline(never)]
     pub fn sign<H>(&self, data: &[u8], out: &mut [u8]) -> Result<usize, Error>
     where
@@ -150,18 +200,17 @@ impl<const B: usize> SecretKey<B> {
     {
         let crv = self.curve;
         if crv.is_weirstrass() {
-            let (parity, size) = bindings::cx_ecdsa_sign::<H, B>(self, data, out)?;
+            let (parity, size) = bindings::cx_ecdsa_sign::<H>(crv, &self.raw, data, out)?;
             if parity {
                 out[0] |= 0x01;
             }
 
             Ok(size)
         } else if crv.is_twisted_edward() {
-            bindings::cx_eddsa_sign(self, data, out)
-        } else if crv.is_montgomery() {
-            todo!("montgomery sign")
+            bindings::cx_eddsa_sign(crv, &self.raw, data, out)
         } else {
-            todo!("unknown signature type")
+            //montgomery curves can only be used for key exchange
+            Err(Error::NotSupported)
         }
     }
 }
@@ -190,8 +239,6 @@ mod bindings {
                     0 => Ok(33),
                     err => Err(err.into())
                 }
-            } else {
-                unimplemented!("edwards_compress_point called in non-bolos");
             }
         }
     }
@@ -234,8 +281,6 @@ mod bindings {
                     0 => {},
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("init ecfp_private_key called in non-bolos");
             }
         }
 
@@ -277,8 +322,6 @@ mod bindings {
                     0 => (),
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("generate_ecfp_keypair called in non-bolos");
             }
         }
 
@@ -287,8 +330,9 @@ mod bindings {
 
     //first item says if Y is odd when computing k.G
     // second item in the tuple is the number of bytes written to `sig_out`
-    pub fn cx_ecdsa_sign<H, const B: usize>(
-        sk: &SecretKey<B>,
+    pub fn cx_ecdsa_sign<H>(
+        crv: Curve,
+        raw_sk: &cx_ecfp_private_key_t,
         data: &[u8],
         sig_out: &mut [u8],
     ) -> Result<(bool, usize), Error>
@@ -300,11 +344,7 @@ mod bindings {
 
         let id: u8 = H::id().into();
 
-        let crv = sk.curve;
-
-        let mut raw_sk = sk.generate()?;
-        let raw_sk: *mut cx_ecfp_private_key_t = &mut *raw_sk;
-        let raw_sk = raw_sk as *const _;
+        let raw_sk = raw_sk as *const cx_ecfp_private_key_t;
 
         let (data, data_len) = (data.as_ptr(), data.len() as u32);
         let sig = sig_out.as_mut_ptr();
@@ -331,26 +371,21 @@ mod bindings {
                     0 => {},
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("cx_ecdsa_sign called in not bolos")
             }
         }
 
         Ok((info == crate::raw::CX_ECCINFO_PARITY_ODD, sig_len as usize))
     }
 
-    pub fn cx_eddsa_sign<const B: usize>(
-        sk: &SecretKey<B>,
+    pub fn cx_eddsa_sign(
+        crv: Curve,
+        raw_sk: &cx_ecfp_private_key_t,
         data: &[u8],
         sig_out: &mut [u8],
     ) -> Result<usize, Error> {
         let id: u8 = crate::hash::Sha512::id().into();
 
-        let crv = sk.curve;
-
-        let mut raw_sk = sk.generate()?;
-        let raw_sk: *mut cx_ecfp_private_key_t = &mut *raw_sk;
-        let raw_sk = raw_sk as *const _;
+        let raw_sk = raw_sk as *const cx_ecfp_private_key_t;
 
         let (data, data_len) = (data.as_ptr(), data.len() as u32);
         let sig = sig_out.as_mut_ptr();
@@ -382,8 +417,6 @@ mod bindings {
                     },
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("cx_eddsa_sign called in not bolos")
             }
         }
 
@@ -89,6 +89,8 @@ impl Into<cx_err_t> for CXError {
 pub enum Error {
     Syscall(SyscallError),
     Cx(CXError),
+    /// The operation isn't available on this device or SDK
+    NotSupported,
 }
 
 impl From<SyscallError> for Error {
@@ -128,6 +130,7 @@ impl Into<u32> for Error {
                 let u: u16 = sys.into();
                 u as u32
             },
+            Self::NotSupported => crate::raw::CX_INVALID_PARAMETER,
         }
     }
 }
@@ -26,10 +26,17 @@ pub use sha256::Sha256;
 
 pub mod sha512;
 pub use sha512::Sha512;
+/// Amount of input hashed between each heartbeat
+#[cfg(bolos_sdk)]
+const HEARTBEAT_CHUNK: usize = 1024;
+
 ///Perform a hash computation
 ///
 /// if write_out is true then `out` must be of the necessary size
 ///
+/// Big inputs are hashed in chunks, sending a heartbeat after each one
+/// so the MCU doesn't reset the device
+///
 /// Abstracts away nanos or nanox implementations
 #[inline(never)]
 pub(self) fn cx_hash(
@@ -45,23 +52,43 @@ pub(self) fn cx_hash(
 
     cfg_if! {
         if #[cfg(bolos_sdk)] {
-            match unsafe { crate::raw::cx_hash_no_throw(
-                hash as *mut _,
-                write_out as u8 as _,
-                input.as_ptr() as *const _,
-                input.len() as u32 as _,
-                out as *mut _,
-                out_len as _,
-            )} {
-                0 => Ok(()),
-                err => Err(err.into())
+            let mut input = input;
+            while input.len() > HEARTBEAT_CHUNK {
+                let (chunk, rest) = input.split_at(HEARTBEAT_CHUNK);
+                cx_hash_raw(hash, chunk, false, std::ptr::null_mut(), 0)?;
+                crate::heartbeat();
+
+                input = rest;
             }
-        } else {
-            unimplemented!("cx_hash called in not bolos")
+
+            cx_hash_raw(hash, input, write_out, out, out_len)
         }
     }
 }
 
+#[cfg(bolos_sdk)]
+fn cx_hash_raw(
+    hash: &mut cx_hash_t,
+    input: &[u8],
+    write_out: bool,
+    out: *mut u8,
+    out_len: u32,
+) -> Result<(), Error> {
+    match unsafe {
+        crate::raw::cx_hash_no_throw(
+            hash as *mut _,
+            write_out as u8 as _,
+            input.as_ptr() as *const _,
+            input.len() as u32 as _,
+            out as *mut _,
+            out_len as _,
+        )
+    } {
+        0 => Ok(()),
+        err => Err(err.into()),
+    }
+}
+
 mod sealed {
     //This is intentionally private since we want only _our_ hashes to be able to implement it
     pub trait CxHash<const S: usize>: Sized {
@@ -60,8 +60,6 @@ impl<const S: usize> Blake2b<S> {
                     0 => {},
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("blake2b init called in non bolos")
             }
         }
 
@@ -52,8 +52,6 @@ impl Sha256 {
                     0 => {},
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("sha256 init called in non-bolos")
             }
         }
 
@@ -52,8 +52,6 @@ impl Sha512 {
                     0 => {},
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("sha512init called in non-bolos")
             }
         }
 
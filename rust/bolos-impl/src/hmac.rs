@@ -58,8 +58,6 @@ pub(self) fn cx_hmac(
                 0 => Ok(()),
                 err => Err(err.into())
             }
-        } else {
-            unimplemented!("cx_hmac called in not bolos")
         }
     }
 }
@@ -55,8 +55,6 @@ impl Sha256HMAC {
                     0 => {}
                     err => return Err(err.into()),
                 }
-            } else {
-                unimplemented!("hmac sha256 init called in non-bolos")
             }
         }
 
@@ -32,6 +32,8 @@ cfg_if! {
 pub(self) use bolos_sys::raw;
 
 pub use bolos_sys::exit_app;
+pub use bolos_sys::heartbeat;
+pub use bolos_sys::pin_validated;
 
 pub use bolos_sys::pic;
 pub use bolos_sys::pic::PIC;
@@ -45,6 +47,8 @@ pub use nvm::NVM;
 pub mod errors;
 pub use errors::Error;
 
+pub use bolos_common::apdu;
+
 pub mod crypto;
 pub mod hash;
 pub mod hmac;
@@ -72,5 +76,7 @@ mod panic {
 /// Provides miscellaneous utilities for types in this crate
 mod misc;
 //------------------------
+    } else if #[cfg(all(target_arch = "arm", target_os = "none"))] {
+        compile_error!("bolos-impl requires BOLOS_SDK and TARGET_NAME to build for a device");
     }
 }
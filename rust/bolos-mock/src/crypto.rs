@@ -100,3 +100,4 @@ impl Default for Mode {
 }
 
 pub mod ecfp256;
+mod hdw;
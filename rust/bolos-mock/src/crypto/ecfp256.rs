@@ -28,24 +28,10 @@ pub struct PublicKey {
 }
 
 impl PublicKey {
+    /// Ed25519 keys are already compressed, and secp256k1/secp256r1 keys
+    /// are left uncompressed like on the device
     pub fn compress(&mut self) -> Result<(), Error> {
-        match self.curve {
-            Curve::Secp256K1 => {
-                let point = k256::EncodedPoint::from_bytes(&self.data[..self.len]).unwrap();
-                let compressed = point.compress();
-
-                self.data[..33].copy_from_slice(compressed.as_ref());
-                Ok(())
-            }
-            Curve::Secp256R1 => {
-                let point = p256::EncodedPoint::from_bytes(&self.data[..self.len]).unwrap();
-                let compressed = point.compress();
-
-                self.data[..33].copy_from_slice(compressed.as_ref());
-                Ok(())
-            }
-            _ => Ok(()),
-        }
+        Ok(())
     }
 
     pub fn curve(&self) -> Curve {
@@ -56,6 +42,25 @@ impl PublicKey {
         self.len
     }
 
+    /// Returns the compressed SEC1 encoding of a secp256k1/secp256r1 public key
+    ///
+    /// Returns None if the key is of another curve or isn't an uncompressed point
+    pub fn compressed_secp(&self) -> Option<[u8; 33]> {
+        let point = self.as_ref();
+        match self.curve() {
+            Curve::Secp256K1 | Curve::Secp256R1 if point.len() == 65 => {
+                let mut out = [0; 33];
+
+                //prefix is 0x02 if y is even, 0x03 if odd
+                out[0] = 0x02 | (point[64] & 0x01);
+                out[1..].copy_from_slice(&point[1..33]);
+
+                Some(out)
+            }
+            _ => None,
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -72,25 +77,22 @@ pub struct SecretKey<const B: usize> {
     bytes: [u8; 32],
 }
 
-impl<const B: usize> SecretKey<B> {
-    pub fn new(_: Mode, curve: Curve, _: BIP32Path<B>) -> Self {
-        let bytes = match curve {
-            Curve::Secp256K1 => {
-                let secret = k256::ecdsa::SigningKey::random(&mut rand8::thread_rng());
+/// Mnemonic of the emulator used by the zemu tests,
+/// so the mock derives the same keys as the device there
+pub const MOCK_MNEMONIC: &str =
+    "equip will roof matter pink blind book anxiety banner elbow sun young";
 
-                *secret.to_bytes().as_ref()
-            }
-            Curve::Secp256R1 => {
-                let secret = p256::ecdsa::SigningKey::random(&mut rand8::thread_rng());
+/// BIP39 seed of [`MOCK_MNEMONIC`], without passphrase
+pub(crate) const MOCK_SEED: [u8; 64] = [
+    0xED, 0x2F, 0x66, 0x4E, 0x65, 0xB5, 0xEF, 0x0D, 0xD9, 0x07, 0xAE, 0x15, 0xA2, 0x78, 0x8C, 0xFC,
+    0x98, 0xE4, 0x19, 0x70, 0xBC, 0x9F, 0xCB, 0x46, 0xF5, 0x90, 0x0F, 0x69, 0x19, 0x86, 0x20, 0x75,
+    0xE7, 0x21, 0xF3, 0x72, 0x12, 0x30, 0x4A, 0x56, 0x50, 0x5D, 0xAB, 0x99, 0xB0, 0x01, 0xCC, 0x89,
+    0x07, 0xEF, 0x09, 0x3B, 0x7C, 0x50, 0x16, 0xA4, 0x6B, 0x50, 0xC0, 0x1C, 0xC3, 0xEC, 0x1C, 0xAC,
+];
 
-                *secret.to_bytes().as_ref()
-            }
-            Curve::Ed25519 => {
-                let secret = ed25519_dalek::SecretKey::generate(&mut rand7::thread_rng());
-
-                secret.to_bytes()
-            }
-        };
+impl<const B: usize> SecretKey<B> {
+    pub fn new(mode: Mode, curve: Curve, path: BIP32Path<B>) -> Self {
+        let bytes = super::hdw::derive(mode, curve, &MOCK_SEED, path.components());
 
         Self { curve, bytes }
     }
@@ -135,10 +137,12 @@ impl<const B: usize> SecretKey<B> {
                 let secret = ed25519_dalek::SecretKey::from_bytes(&self.bytes[..]).unwrap();
 
                 let public = ed25519_dalek::PublicKey::from(&secret);
+                //already in the compressed form of the device, tagged with 0x02
                 let mut bytes = [0; 65];
-                bytes[..32].copy_from_slice(&public.as_bytes()[..]);
+                bytes[0] = 0x02;
+                bytes[1..33].copy_from_slice(&public.as_bytes()[..]);
 
-                (bytes, 32)
+                (bytes, 33)
             }
         };
 
@@ -157,6 +161,32 @@ impl<const B: usize> SecretKey<B> {
         Ok(())
     }
 
+    pub fn derive(&self) -> Result<DerivedSecretKey, Error> {
+        Ok(DerivedSecretKey {
+            curve: self.curve,
+            bytes: self.bytes,
+        })
+    }
+
+    pub fn sign<H>(&self, data: &[u8], out: &mut [u8]) -> Result<usize, Error>
+    where
+        H: HasherId,
+        H::Id: Into<u8>,
+    {
+        self.derive()?.sign::<H>(data, out)
+    }
+}
+
+pub struct DerivedSecretKey {
+    curve: Curve,
+    bytes: [u8; 32],
+}
+
+impl DerivedSecretKey {
+    pub const fn curve(&self) -> Curve {
+        self.curve
+    }
+
     pub fn sign<H>(&self, data: &[u8], out: &mut [u8]) -> Result<usize, Error>
     where
         H: HasherId,
@@ -0,0 +1,332 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Derivation of the mock keys, following what a device does with its seed
+//!
+//! * [`Mode::Ed25519Slip10`] and secp256k1/secp256r1 keys use SLIP-10,
+//!   which is BIP32 for secp256k1
+//! * ed25519 keys in [`Mode::BIP32`] use BIP32-Ed25519 (Khovratovich and Law),
+//!   with the master key generation of Ledger devices
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Sha256, Sha512};
+
+use super::{Curve, Mode};
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// Order of secp256k1, big endian
+const SECP256K1_ORDER: [u8; 32] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE,
+    0xBA, 0xAE, 0xDC, 0xE6, 0xAF, 0x48, 0xA0, 0x3B, 0xBF, 0xD2, 0x5E, 0x8C, 0xD0, 0x36, 0x41, 0x41,
+];
+
+/// Order of secp256r1, big endian
+const SECP256R1_ORDER: [u8; 32] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xBC, 0xE6, 0xFA, 0xAD, 0xA7, 0x17, 0x9E, 0x84, 0xF3, 0xB9, 0xCA, 0xC2, 0xFC, 0x63, 0x25, 0x51,
+];
+
+fn hmac_sha512(key: &[u8], pieces: &[&[u8]]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for piece in pieces {
+        mac.update(piece);
+    }
+
+    let mut out = [0; 64];
+    out.copy_from_slice(&mac.finalize().into_bytes()[..]);
+    out
+}
+
+fn split(i: [u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut left = [0; 32];
+    let mut right = [0; 32];
+    left.copy_from_slice(&i[..32]);
+    right.copy_from_slice(&i[32..]);
+
+    (left, right)
+}
+
+/// BIP39 seed of `mnemonic`, without passphrase
+#[cfg(test)]
+fn bip39_seed(mnemonic: &str) -> [u8; 64] {
+    //PBKDF2-HMAC-SHA512 with 2048 iterations,
+    // the output is a single block
+    let mut u = hmac_sha512(mnemonic.as_bytes(), &[b"mnemonic", &1u32.to_be_bytes()[..]]);
+    let mut seed = u;
+    for _ in 1..2048 {
+        u = hmac_sha512(mnemonic.as_bytes(), &[&u[..]]);
+        seed.iter_mut().zip(u.iter()).for_each(|(s, u)| *s ^= u);
+    }
+
+    seed
+}
+
+/// Derives the private key at `path` from `seed`
+pub fn derive(mode: Mode, curve: Curve, seed: &[u8], path: &[u32]) -> [u8; 32] {
+    match (mode, curve) {
+        (Mode::BIP32, Curve::Ed25519) => bip32_ed25519(seed, path),
+        _ => slip10(curve, seed, path),
+    }
+}
+
+/// Returns `a + b mod order` if the sum is a valid key,
+/// all of them big endian
+fn add_mod(a: &[u8; 32], b: &[u8; 32], order: &[u8; 32]) -> Option<[u8; 32]> {
+    let mut sum = [0; 32];
+    let mut carry = 0;
+    for i in (0..32).rev() {
+        let s = a[i] as u16 + b[i] as u16 + carry;
+        sum[i] = s as u8;
+        carry = s >> 8;
+    }
+
+    //both are below the order, so subtracting it once is enough
+    if carry != 0 || sum >= *order {
+        let mut borrow = 0;
+        for i in (0..32).rev() {
+            let d = sum[i] as i16 - order[i] as i16 - borrow;
+            sum[i] = d as u8;
+            borrow = (d < 0) as i16;
+        }
+    }
+
+    if sum == [0; 32] {
+        None
+    } else {
+        Some(sum)
+    }
+}
+
+fn slip10(curve: Curve, seed: &[u8], path: &[u32]) -> [u8; 32] {
+    let (key, order): (&[u8], _) = match curve {
+        Curve::Secp256K1 => (b"Bitcoin seed", Some(&SECP256K1_ORDER)),
+        Curve::Secp256R1 => (b"Nist256p1 seed", Some(&SECP256R1_ORDER)),
+        Curve::Ed25519 => (b"ed25519 seed", None),
+    };
+    let is_valid = |k: &[u8; 32]| match order {
+        Some(n) => *k != [0; 32] && k < n,
+        None => true,
+    };
+
+    let mut i = hmac_sha512(key, &[seed]);
+    while !is_valid(&split(i).0) {
+        i = hmac_sha512(key, &[&i[..]]);
+    }
+    let (mut k, mut c) = split(i);
+
+    for &index in path {
+        //ed25519 only has hardened derivation
+        let index = match order {
+            None => index | HARDENED,
+            Some(_) => index,
+        };
+
+        let mut data = [0; 33];
+        if index & HARDENED != 0 {
+            data[1..].copy_from_slice(&k);
+        } else {
+            data.copy_from_slice(&compressed_public(curve, &k));
+        }
+
+        let mut i = hmac_sha512(&c, &[&data[..], &index.to_be_bytes()[..]]);
+        loop {
+            let (il, ir) = split(i);
+            let child = match order {
+                None => Some(il),
+                Some(n) if il < *n => add_mod(&il, &k, n),
+                Some(_) => None,
+            };
+
+            match child {
+                Some(child) => {
+                    k = child;
+                    c = ir;
+                    break;
+                }
+                None => i = hmac_sha512(&c, &[&[1], &ir[..], &index.to_be_bytes()[..]]),
+            }
+        }
+    }
+
+    k
+}
+
+/// Compressed SEC1 encoding of the public key of `k`
+fn compressed_public(curve: Curve, k: &[u8; 32]) -> [u8; 33] {
+    let mut out = [0; 33];
+    match curve {
+        Curve::Secp256K1 => {
+            let key = k256::ecdsa::SigningKey::from_bytes(&k[..]).expect("invalid key");
+            //already compressed
+            out.copy_from_slice(&key.verifying_key().to_bytes()[..]);
+        }
+        Curve::Secp256R1 => {
+            let key = p256::ecdsa::SigningKey::from_bytes(&k[..]).expect("invalid key");
+            out.copy_from_slice(key.verifying_key().to_encoded_point(true).as_ref());
+        }
+        Curve::Ed25519 => unreachable!("ed25519 keys are only derived hardened"),
+    }
+
+    out
+}
+
+/// Adds `b` to `a`, both little endian, dropping the last carry
+fn add_le(a: &mut [u8; 32], b: &[u8]) {
+    let mut carry = 0;
+    for (i, a) in a.iter_mut().enumerate() {
+        let s = *a as u16 + *b.get(i).unwrap_or(&0) as u16 + carry;
+        *a = s as u8;
+        carry = s >> 8;
+    }
+}
+
+/// Returns the 32 bytes private key, being the left half of the extended key,
+/// as that's what the device uses as ed25519 secret
+fn bip32_ed25519(seed: &[u8], path: &[u32]) -> [u8; 32] {
+    const KEY: &[u8] = b"ed25519 seed";
+
+    let mut c = {
+        let mut mac = Hmac::<Sha256>::new_from_slice(KEY).expect("HMAC accepts keys of any length");
+        mac.update(&[1]);
+        mac.update(seed);
+
+        let mut c = [0; 32];
+        c.copy_from_slice(&mac.finalize().into_bytes()[..]);
+        c
+    };
+
+    //the third highest bit of kL must be clear
+    let mut i = hmac_sha512(KEY, &[seed]);
+    while i[31] & 0x20 != 0 {
+        i = hmac_sha512(KEY, &[&i[..]]);
+    }
+    let (mut kl, mut kr) = split(i);
+    kl[0] &= 0xF8;
+    kl[31] = (kl[31] & 0x7F) | 0x40;
+
+    for &index in path {
+        let mut data = [0; 1 + 64];
+        let data = if index & HARDENED != 0 {
+            data[1..33].copy_from_slice(&kl);
+            data[33..].copy_from_slice(&kr);
+            &mut data[..]
+        } else {
+            let mut extended = [0; 64];
+            extended[..32].copy_from_slice(&kl);
+            extended[32..].copy_from_slice(&kr);
+            let extended =
+                ed25519_dalek::ExpandedSecretKey::from_bytes(&extended).expect("invalid key");
+
+            data[0] = 0x02;
+            data[1..33].copy_from_slice(ed25519_dalek::PublicKey::from(&extended).as_bytes());
+            &mut data[..33]
+        };
+
+        let z = hmac_sha512(&c, &[data, &index.to_le_bytes()[..]]);
+        data[0] |= 1;
+        c = split(hmac_sha512(&c, &[data, &index.to_le_bytes()[..]])).1;
+
+        //kL = 8 * zL[..28] + kL, kR = zR + kR
+        let (zl, zr) = split(z);
+        let mut zl8 = [0; 32];
+        let mut carry = 0;
+        for (i, &z) in zl[..28].iter().enumerate() {
+            let v = ((z as u16) << 3) | carry;
+            zl8[i] = v as u8;
+            carry = v >> 8;
+        }
+        zl8[28] = carry as u8;
+
+        add_le(&mut kl, &zl8);
+        add_le(&mut kr, &zr);
+    }
+
+    kl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let mut out = [0; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    const SEED: &[u8] = &[
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+
+    #[test]
+    fn bip39() {
+        let seed = bip39_seed(
+            "abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon abandon about",
+        );
+
+        assert_eq!(
+            seed[..32],
+            hex32("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1")
+        );
+        assert_eq!(
+            seed[32..],
+            hex32("9a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4")
+        );
+
+        use super::super::ecfp256::{MOCK_MNEMONIC, MOCK_SEED};
+        assert_eq!(bip39_seed(MOCK_MNEMONIC)[..], MOCK_SEED[..]);
+    }
+
+    //test vector 1 of SLIP-10 (and BIP32 for secp256k1)
+    #[test]
+    fn slip10_vectors() {
+        let derive = |curve, path: &[u32]| slip10(curve, SEED, path);
+
+        assert_eq!(
+            derive(Curve::Secp256K1, &[]),
+            hex32("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35")
+        );
+        assert_eq!(
+            derive(Curve::Secp256K1, &[HARDENED]),
+            hex32("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea")
+        );
+        assert_eq!(
+            derive(Curve::Secp256K1, &[HARDENED, 1]),
+            hex32("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368")
+        );
+
+        assert_eq!(
+            derive(Curve::Secp256R1, &[]),
+            hex32("612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2")
+        );
+        assert_eq!(
+            derive(Curve::Secp256R1, &[HARDENED]),
+            hex32("6939694369114c67917a182c59ddb8cafc3004e63ca5d3b84403ba8613debc0c")
+        );
+
+        assert_eq!(
+            derive(Curve::Ed25519, &[]),
+            hex32("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7")
+        );
+        assert_eq!(
+            derive(Curve::Ed25519, &[HARDENED]),
+            hex32("68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3")
+        );
+    }
+}
@@ -27,6 +27,16 @@ pub fn exit_app(status: u8) -> ! {
     panic!("exiting app: {}", status);
 }
 
+/// Wrapper for 'io_seproxyhal_io_heartbeat'
+/// Nothing to keep alive when mocked
+pub fn heartbeat() {}
+
+/// Wrapper for 'os_global_pin_is_validated'
+/// The mocked device is always unlocked
+pub fn pin_validated() -> bool {
+    true
+}
+
 pub const TARGET_ID: u32 = 0;
 
 pub mod pic;
@@ -39,6 +49,8 @@ pub use nvm::NVM;
 pub mod errors;
 pub use errors::Error;
 
+pub use bolos_common::apdu;
+
 pub mod crypto;
 pub mod hash;
 pub mod hmac;
@@ -1,21 +1,71 @@
 use std::env;
 
+/// Everything needed to build and generate the bindings of a given device
+#[cfg_attr(not(feature = "generate"), allow(dead_code))]
+struct Target {
+    /// Value of `TARGET_NAME` for this device
+    name: &'static str,
+    /// rustc cfg to set
+    cfg: &'static str,
+    /// Wrapper header in `bindgen/` with the defines of the device
+    wrapper: &'static str,
+    /// clang target and cpu the SDK is compiled for
+    clang_target: &'static str,
+    cpu: &'static str,
+    /// `BOLOS_VERSION` of the SDK the bindings are generated from
+    sdk_version: &'static str,
+    /// Checked in bindings in `src/bindings`
+    bindings: &'static str,
+}
+
+/// Supported devices, a new device (or SDK) only needs a new entry here
+/// and its wrapper header
+const TARGETS: &[Target] = &[
+    Target {
+        name: "TARGET_NANOX",
+        cfg: "nanox",
+        wrapper: "wrapperX.h",
+        clang_target: "thumbv6-none-eabi",
+        cpu: "cortex-m0",
+        sdk_version: "2.0.2",
+        bindings: "bindingsX.rs",
+    },
+    Target {
+        name: "TARGET_NANOS",
+        cfg: "nanos",
+        wrapper: "wrapperS.h",
+        clang_target: "thumbv6-none-eabi",
+        cpu: "cortex-m0",
+        sdk_version: "2.1.0",
+        bindings: "bindingsS.rs",
+    },
+    Target {
+        name: "TARGET_NANOS2",
+        cfg: "nanosplus",
+        wrapper: "wrapperSP.h",
+        clang_target: "thumbv8m.main-none-eabi",
+        cpu: "cortex-m35p",
+        sdk_version: "1.0.3",
+        bindings: "bindingsSP.rs",
+    },
+];
+
 fn main() {
     println!("cargo:rerun-if-env-changed=TARGET_NAME");
 
     if let Some(v) = env::var_os("BOLOS_SDK") {
         if !v.is_empty() {
-            match env::var("TARGET_NAME")
-                .expect("unable to get TARGET_NAME")
-                .as_str()
-            {
-                "TARGET_NANOX" => println!("cargo:rustc-cfg=nanox"),
-                "TARGET_NANOS" => println!("cargo:rustc-cfg=nanos"),
-                "TARGET_NANOS2" => println!("cargo:rustc-cfg=nanosplus"),
-                _ => panic!("TARGET_NAME is not valid"),
-            }
+            let name = env::var("TARGET_NAME").expect("unable to get TARGET_NAME");
+            let target = TARGETS
+                .iter()
+                .find(|t| t.name == name)
+                .expect("TARGET_NAME is not valid");
 
+            println!("cargo:rustc-cfg={}", target.cfg);
             println!("cargo:rustc-cfg=bolos_sdk");
+
+            #[cfg(feature = "generate")]
+            generate(target, &v);
         } else {
             panic!("BOLOS_SDK is not valid");
         }
@@ -23,3 +73,75 @@ fn main() {
         println!("cargo:warning=BOLOS_SDK not set, not exporting anything")
     }
 }
+
+/// Generates the bindings for `target` from the headers of the given SDK,
+/// in place of the ones checked in `src/bindings`
+///
+/// The newlib headers are looked up in `BOLOS_ARM_INCLUDE`, see `make prep_generate`
+///
+/// With `BOLOS_SYS_UPDATE_BINDINGS` set the checked in bindings are overwritten too,
+/// which is what `make generate` does
+#[cfg(feature = "generate")]
+fn generate(target: &Target, sdk: &std::ffi::OsStr) {
+    use std::path::{Path, PathBuf};
+
+    println!("cargo:rerun-if-env-changed=BOLOS_SDK");
+    println!("cargo:rerun-if-env-changed=BOLOS_ARM_INCLUDE");
+    println!("cargo:rerun-if-env-changed=BOLOS_SYS_UPDATE_BINDINGS");
+    println!("cargo:rerun-if-changed=bindgen");
+
+    let sdk = Path::new(sdk);
+    check_sdk_version(target, sdk);
+    let arm_include =
+        env::var("BOLOS_ARM_INCLUDE").unwrap_or_else(|_| "/tmp/bolos/arm-none-eabi/include".into());
+
+    let bindings = bindgen::Builder::default()
+        .header(format!("bindgen/{}", target.wrapper))
+        .use_core()
+        .derive_default(true)
+        .derive_debug(false)
+        .ctypes_prefix("cty")
+        .clang_arg(format!("-I{}", sdk.join("include").display()))
+        .clang_arg(format!("-I{}", sdk.join("lib_ux/include").display()))
+        .clang_arg(format!("-I{}", sdk.join("lib_cxng/include").display()))
+        .clang_arg(format!("-I{}", arm_include))
+        .clang_arg("-Ibindgen/include")
+        .clang_arg(format!("--target={}", target.clang_target))
+        .clang_arg(format!("-mcpu={}", target.cpu))
+        .clang_arg("-mthumb")
+        .generate()
+        .expect("unable to generate bindings");
+
+    let out = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR not set")).join("bindings.rs");
+    bindings
+        .write_to_file(out)
+        .expect("unable to write bindings");
+
+    if env::var_os("BOLOS_SYS_UPDATE_BINDINGS").is_some() {
+        bindings
+            .write_to_file(Path::new("src/bindings").join(target.bindings))
+            .expect("unable to update the checked in bindings");
+    }
+}
+
+/// Makes sure the SDK is the one the bindings are expected for,
+/// as the headers (and thus the bindings) change between SDK versions
+#[cfg(feature = "generate")]
+fn check_sdk_version(target: &Target, sdk: &std::path::Path) {
+    let header = sdk.join("include/bolos_version.h");
+    println!("cargo:rerun-if-changed={}", header.display());
+
+    let header = std::fs::read_to_string(&header).expect("unable to read bolos_version.h");
+    let version = header
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("#define BOLOS_VERSION"))
+        .map(|version| version.trim().trim_matches('"'))
+        .expect("BOLOS_VERSION not found in bolos_version.h");
+
+    if version != target.sdk_version {
+        panic!(
+            "{} expects SDK version {}, BOLOS_SDK is version {}",
+            target.name, target.sdk_version, version
+        );
+    }
+}
@@ -34,7 +34,9 @@ pub mod raw {
     #![allow(clippy::upper_case_acronyms)]
 
     cfg_if! {
-        if #[cfg(nanos)] {
+        if #[cfg(feature = "generate")] {
+            include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+        } else if #[cfg(nanos)] {
             include!("./bindings/bindingsS.rs");
         } else if #[cfg(nanox)] {
             include!("./bindings/bindingsX.rs");
@@ -55,6 +57,20 @@ pub fn exit_app(_status: u8) -> ! {
     unsafe { core::hint::unreachable_unchecked() }
 }
 
+/// Wrapper for 'io_seproxyhal_io_heartbeat'
+/// Keeps the MCU from resetting the device during long computations
+#[cfg(bolos_sdk)]
+pub fn heartbeat() {
+    unsafe { raw::io_seproxyhal_io_heartbeat() }
+}
+
+/// Wrapper for 'os_global_pin_is_validated'
+/// Returns true if the device has been unlocked with the PIN
+#[cfg(bolos_sdk)]
+pub fn pin_validated() -> bool {
+    unsafe { raw::os_global_pin_is_validated() as u8 as u32 == raw::BOLOS_TRUE }
+}
+
 /// Contains some impls for items coming from the bindings
 #[cfg(bolos_sdk)]
 mod extra_traits;
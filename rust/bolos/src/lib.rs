@@ -39,7 +39,10 @@ pub use swapping_buffer::*;
 
 cfg_if! {
     if #[cfg(all(__impl, __mock))] {
-        compiler_error!("Can't have both `__impl` and `__mock` enabled");
+        compile_error!("Can't have both `__impl` and `__mock` enabled");
+    } else if #[cfg(all(__mock, target_arch = "arm", target_os = "none"))] {
+        //bolos-mock is only available on the host
+        compile_error!("BOLOS_SDK needs to be set to build for a device");
     } else if #[cfg(__impl)] {
         pub use bolos_impl::*;
     } else if #[cfg(__mock)] {
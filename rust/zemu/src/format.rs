@@ -0,0 +1,148 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+//! Helpers to fit values in the space available on screen
+
+use bolos_sys::pic::PIC;
+
+/// Written in place of the characters left out by [`truncate_middle`]
+///
+/// The fonts only cover ASCII, so no unicode ellipsis here
+pub const ELLIPSIS: &[u8] = b"...";
+
+/// Writes `input` to `out`, dropping characters from the middle if it doesn't fit
+///
+/// The start and the end of the value are kept, with [`ELLIPSIS`] in between,
+/// so "tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb" in 16 bytes becomes "tz1VSU...h8Cjcjb",
+/// with the end being favored since that's what users usually compare
+///
+/// Multi-byte UTF-8 characters are never split, so fewer bytes than `out.len()`
+/// might be written
+///
+/// Returns the number of bytes written, no null terminator is added
+pub fn truncate_middle(input: &[u8], out: &mut [u8]) -> usize {
+    if input.len() <= out.len() {
+        out[..input.len()].copy_from_slice(input);
+        return input.len();
+    }
+
+    let ellipsis = PIC::new(ELLIPSIS).into_inner();
+    if out.len() <= ellipsis.len() {
+        //not even room for the ellipsis, keep what we can of the start
+        let len = char_start(input, out.len());
+        out[..len].copy_from_slice(&input[..len]);
+        return len;
+    }
+
+    let available = out.len() - ellipsis.len();
+    let head = char_start(input, available / 2);
+    let tail = input.len() - char_end(input, input.len() - (available - available / 2));
+
+    out[..head].copy_from_slice(&input[..head]);
+    out[head..head + ellipsis.len()].copy_from_slice(ellipsis);
+    out[head + ellipsis.len()..head + ellipsis.len() + tail]
+        .copy_from_slice(&input[input.len() - tail..]);
+
+    head + ellipsis.len() + tail
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+/// Moves `idx` back to the start of the character it's in
+fn char_start(input: &[u8], mut idx: usize) -> usize {
+    while idx > 0 && idx < input.len() && is_continuation(input[idx]) {
+        idx -= 1;
+    }
+
+    idx
+}
+
+/// Moves `idx` forward to the start of the next character, if it's in the middle of one
+fn char_end(input: &[u8], mut idx: usize) -> usize {
+    while idx < input.len() && is_continuation(input[idx]) {
+        idx += 1;
+    }
+
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn truncate<const N: usize>(input: &str) -> ([u8; N], usize) {
+        let mut out = [0; N];
+        let len = truncate_middle(input.as_bytes(), &mut out);
+
+        (out, len)
+    }
+
+    #[test]
+    fn fits() {
+        let (out, len) = truncate::<16>("tz1VSUr8wwNhLAze");
+        assert_eq!(&out[..len], b"tz1VSUr8wwNhLAze");
+
+        let (out, len) = truncate::<16>("tz1");
+        assert_eq!(&out[..len], b"tz1");
+    }
+
+    #[test]
+    fn middle() {
+        let (out, len) = truncate::<16>("tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb");
+        assert_eq!(&out[..len], b"tz1VSU...h8Cjcjb");
+
+        //one byte too many
+        let (out, len) = truncate::<16>("tz1VSUr8wwNhLAzem");
+        assert_eq!(&out[..len], b"tz1VSU...NhLAzem");
+    }
+
+    #[test]
+    fn tiny() {
+        let (out, len) = truncate::<4>("tz1VSU");
+        //the end is favored
+        assert_eq!(&out[..len], b"...U");
+
+        let (out, len) = truncate::<3>("tz1VSU");
+        assert_eq!(&out[..len], b"tz1");
+
+        let (out, len) = truncate::<1>("tz1VSU");
+        assert_eq!(&out[..len], b"t");
+
+        let (_, len) = truncate::<0>("tz1VSU");
+        assert_eq!(len, 0);
+    }
+
+    #[test]
+    fn multi_byte() {
+        //é is 2 bytes, the 8 byte input doesn't fit in 7
+        let (out, len) = truncate::<7>("éééé");
+        assert_eq!(core::str::from_utf8(&out[..len]), Ok("é...é"));
+
+        let (out, len) = truncate::<8>("aéééé");
+        assert_eq!(core::str::from_utf8(&out[..len]), Ok("a...é"));
+
+        //the head and the tail are shortened instead of splitting a character
+        let (out, len) = truncate::<10>("abcééééxyz");
+        assert_eq!(core::str::from_utf8(&out[..len]), Ok("abc...xyz"));
+
+        let (_, len) = truncate::<1>("éa");
+        assert_eq!(len, 0);
+
+        let (out, len) = truncate::<3>("éaa");
+        assert_eq!(core::str::from_utf8(&out[..len]), Ok("éa"));
+    }
+}
@@ -57,3 +57,8 @@ mod ui;
 pub use ui::*;
 
 mod ui_toolkit;
+
+mod settings;
+pub use settings::Settings;
+
+pub mod format;
@@ -0,0 +1,150 @@
+/*******************************************************************************
+*   (c) 2021 Zondax GmbH
+*
+*  Licensed under the Apache License, Version 2.0 (the "License");
+*  you may not use this file except in compliance with the License.
+*  You may obtain a copy of the License at
+*
+*      http://www.apache.org/licenses/LICENSE-2.0
+*
+*  Unless required by applicable law or agreed to in writing, software
+*  distributed under the License is distributed on an "AS IS" BASIS,
+*  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+*  See the License for the specific language governing permissions and
+*  limitations under the License.
+********************************************************************************/
+use bolos_sys::pic::PIC;
+
+/// User settings, persisted in NVM
+///
+/// This is the single source of truth for both the UI and the handlers,
+/// all accesses go thru the associated functions
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Settings {
+    expert: bool,
+    blind_sign: bool,
+    //seconds, 0 means disabled
+    timeout: u16,
+    //mutez, 0 means disabled
+    fee_threshold: u64,
+    //mutez per byte, 0 means disabled
+    fee_per_byte_threshold: u64,
+}
+
+#[cfg_attr(zemu_sdk, link_section = ".rodata.N_")]
+static mut N_SETTINGS: PIC<Settings> = PIC::new(Settings::DEFAULT);
+
+impl Settings {
+    pub const DEFAULT: Self = Self {
+        expert: false,
+        blind_sign: false,
+        timeout: 0,
+        fee_threshold: 0,
+        fee_per_byte_threshold: 0,
+    };
+
+    fn load() -> Self {
+        unsafe { *N_SETTINGS.get_ref() }
+    }
+
+    fn store(self) {
+        unsafe {
+            let dst = N_SETTINGS.get_mut();
+
+            cfg_if::cfg_if! {
+                if #[cfg(zemu_sdk)] {
+                    let mut src = self;
+                    bolos_sys::raw::nvm_write(
+                        dst as *mut Self as *mut _,
+                        &mut src as *mut Self as *mut _,
+                        core::mem::size_of::<Self>() as u32,
+                    );
+                } else {
+                    *dst = self;
+                }
+            }
+        }
+    }
+
+    /// Returns whether the user has enabled expert mode
+    pub fn expert() -> bool {
+        Self::load().expert
+    }
+
+    pub fn set_expert(expert: bool) {
+        Self {
+            expert,
+            ..Self::load()
+        }
+        .store()
+    }
+
+    /// Toggles expert mode, returning the new value
+    pub fn toggle_expert() -> bool {
+        let expert = !Self::expert();
+        Self::set_expert(expert);
+
+        expert
+    }
+
+    /// Returns whether the user allows signing of data that can't be parsed
+    pub fn blind_sign() -> bool {
+        Self::load().blind_sign
+    }
+
+    pub fn set_blind_sign(blind_sign: bool) {
+        Self {
+            blind_sign,
+            ..Self::load()
+        }
+        .store()
+    }
+
+    /// Returns the review inactivity timeout, in seconds
+    ///
+    /// 0 means the review never times out
+    pub fn timeout() -> u16 {
+        Self::load().timeout
+    }
+
+    pub fn set_timeout(timeout: u16) {
+        Self {
+            timeout,
+            ..Self::load()
+        }
+        .store()
+    }
+
+    /// Returns the fees and storage burn (in mutez)
+    /// above which the user should be warned
+    ///
+    /// 0 means there's no threshold
+    pub fn fee_threshold() -> u64 {
+        Self::load().fee_threshold
+    }
+
+    pub fn set_fee_threshold(fee_threshold: u64) {
+        Self {
+            fee_threshold,
+            ..Self::load()
+        }
+        .store()
+    }
+
+    /// Returns the fee for each byte of the operation (in mutez)
+    /// above which the user should be warned
+    ///
+    /// 0 means there's no threshold
+    pub fn fee_per_byte_threshold() -> u64 {
+        Self::load().fee_per_byte_threshold
+    }
+
+    pub fn set_fee_per_byte_threshold(fee_per_byte_threshold: u64) {
+        Self {
+            fee_per_byte_threshold,
+            ..Self::load()
+        }
+        .store()
+    }
+}
@@ -90,6 +90,28 @@ impl<T: Viewable + Sized + 'static> Show for T {
     }
 }
 
+/// Rejects the review currently being shown, if any
+///
+/// This is meant to be called when a new APDU is received while a review is pending,
+/// since the host won't be waiting for the response of the previous command anymore
+///
+/// Returns true if a review was pending
+pub fn cancel_pending_review(rx: u32) -> bool {
+    use crate::ui_toolkit::RUST_ZUI;
+
+    unsafe { RUST_ZUI.cancel(rx as usize) }
+}
+
+/// Sets the status shown on the idle screen, in place of the default one
+///
+/// The status is truncated to the space available on screen,
+/// see [`crate::format::truncate_middle`]
+pub fn set_idle_status(status: &[u8]) {
+    use crate::ui_toolkit::RUST_ZUI;
+
+    unsafe { RUST_ZUI.set_idle_status(status) }
+}
+
 fn cleanup_ui() {
     unsafe {
         bindings::view_review_init(None, None, None);
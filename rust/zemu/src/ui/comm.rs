@@ -39,6 +39,14 @@ pub trait Viewable {
         page: u8,
     ) -> Result<u8, ViewError>;
 
+    /// Return the first item of the section following `item_n`, if any
+    ///
+    /// Sections let the user skip the remaining items of the current one,
+    /// by default there's a single section
+    fn next_section(&mut self, _item_n: u8) -> Option<u8> {
+        None
+    }
+
     /// Called when the last item shown has been "accepted"
     ///
     /// `out` is the apdu_buffer
@@ -51,6 +59,16 @@ pub trait Viewable {
     ///
     /// Return is number of bytes written to out and the return code
     fn reject(&mut self, out: &mut [u8]) -> (usize, u16);
+
+    /// Called in place of `reject` when the review is cancelled
+    /// but the item itself can't be accessed anymore
+    ///
+    /// Should release the same global state `reject` does
+    fn cleanup()
+    where
+        Self: Sized,
+    {
+    }
 }
 
 pub struct ShowTooBig;
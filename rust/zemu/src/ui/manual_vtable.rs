@@ -25,15 +25,19 @@ mod private {
 
 type NumItemsFn = unsafe fn(*mut This) -> Result<u8, ViewError>;
 type RenderItemFn = unsafe fn(*mut This, u8, &mut [u8], &mut [u8], u8) -> Result<u8, ViewError>;
+type NextSectionFn = unsafe fn(*mut This, u8) -> Option<u8>;
 type AcceptFn = unsafe fn(*mut This, &mut [u8]) -> (usize, u16);
 type RejectFn = unsafe fn(*mut This, &mut [u8]) -> (usize, u16);
+type CleanupFn = fn();
 type DropFn = unsafe fn(*mut This);
 
 struct ViewableVTable {
     num_items: NumItemsFn,
     render_item: RenderItemFn,
+    next_section: NextSectionFn,
     accept: AcceptFn,
     reject: RejectFn,
+    cleanup: CleanupFn,
     drop: DropFn,
 }
 
@@ -58,6 +62,13 @@ trait ViewableWithVTable: Viewable + Sized {
                 this.render_item(item_n, title, message, page)
             }
         },
+        next_section: |this: *mut This, item_n: u8| -> Option<u8> {
+            unsafe {
+                let this = this.cast::<Self>().as_mut().expect("Got NULL");
+
+                this.next_section(item_n)
+            }
+        },
         accept: |this: *mut This, out: &mut [u8]| -> (usize, u16) {
             unsafe {
                 let this = this.cast::<Self>().as_mut().expect("Got NULL");
@@ -72,6 +83,7 @@ trait ViewableWithVTable: Viewable + Sized {
                 this.reject(out)
             }
         },
+        cleanup: <Self as Viewable>::cleanup,
         drop: |this: *mut This| unsafe {
             let this = this.cast::<Self>();
 
@@ -110,6 +122,14 @@ impl RefMutDynViewable {
         unsafe { (ptr)(self.ptr.as_ptr(), item_n, title, message, page) }
     }
 
+    pub fn next_section(&mut self, item_n: u8) -> Option<u8> {
+        let to_pic = self.vtable.next_section as usize;
+        let picced = unsafe { PIC::manual(to_pic) };
+        let ptr: NextSectionFn = unsafe { core::mem::transmute(picced) };
+
+        unsafe { (ptr)(self.ptr.as_ptr(), item_n) }
+    }
+
     pub fn accept(&mut self, out: &mut [u8]) -> (usize, u16) {
         let to_pic = self.vtable.accept as usize;
         let picced = unsafe { PIC::manual(to_pic) };
@@ -126,6 +146,14 @@ impl RefMutDynViewable {
         unsafe { (ptr)(self.ptr.as_ptr(), out) }
     }
 
+    pub fn cleanup(&self) {
+        let to_pic = self.vtable.cleanup as usize;
+        let picced = unsafe { PIC::manual(to_pic) };
+        let ptr: CleanupFn = unsafe { core::mem::transmute(picced) };
+
+        (ptr)()
+    }
+
     pub fn drop_item(&mut self) {
         let to_pic = self.vtable.drop as usize;
         let picced = unsafe { PIC::manual(to_pic) };
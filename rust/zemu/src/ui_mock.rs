@@ -61,6 +61,18 @@ pub fn get_out() -> Option<(usize, [u8; UI_OUT_SIZE])> {
     unsafe { OUT.flush() }
 }
 
+/// Rejects the review currently being shown, if any
+///
+/// Reviews are accepted immediately in the mock, so there's never one pending
+pub fn cancel_pending_review(_rx: u32) -> bool {
+    false
+}
+
+/// Sets the status shown on the idle screen, in place of the default one
+///
+/// There's no idle screen in the mock, so this does nothing
+pub fn set_idle_status(_status: &[u8]) {}
+
 impl<T: Viewable + Sized> Show for T {
     unsafe fn show(mut self, _: &mut u32) -> Result<(), ShowTooBig> {
         let out = OUT.as_mut();
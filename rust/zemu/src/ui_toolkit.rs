@@ -15,10 +15,11 @@
 ********************************************************************************/
 use crate::{
     ui::{manual_vtable::RefMutDynViewable, Viewable},
-    ShowTooBig, ViewError,
+    Settings, ShowTooBig, ViewError,
 };
 use arrayvec::ArrayString;
 
+use bolos_common::apdu::ApduError;
 use bolos_derive::pic_str;
 use bolos_sys::pic::PIC;
 
@@ -26,6 +27,9 @@ mod backends;
 use backends::UIBackend;
 pub use backends::RUST_ZUI;
 
+/// Maximum length of the status shown on the idle screen, null terminator included
+pub const IDLE_STATUS_SIZE: usize = 16 + 1;
+
 #[allow(clippy::upper_case_acronyms)]
 #[repr(C)]
 pub struct ZUI<B: UIBackend<KS> + 'static, const KS: usize> {
@@ -38,6 +42,9 @@ pub struct ZUI<B: UIBackend<KS> + 'static, const KS: usize> {
     backend: &'static mut B,
 
     current_viewable: Option<RefMutDynViewable>,
+
+    //null terminated, empty to use the default status
+    idle_status: [u8; IDLE_STATUS_SIZE],
 }
 
 impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
@@ -49,6 +56,7 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
             page_count: 0,
             backend: B::static_mut(),
             current_viewable: None,
+            idle_status: [0; IDLE_STATUS_SIZE],
         }
     }
 
@@ -56,15 +64,13 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
         self.show_idle(0, None);
         self.backend.wait_ui();
 
-        if let Some(viewable) = self.current_viewable.as_mut() {
+        //remove current viewable, so the idle screen can be refreshed while accepting
+        if let Some(mut viewable) = self.current_viewable.take() {
             let out = self.backend.accept_reject_out();
 
             let (len, code) = viewable.accept(out);
             out[len..len + 2].copy_from_slice(&code.to_be_bytes()[..]);
 
-            //remove current viewable
-            self.current_viewable.take();
-
             self.backend.accept_reject_end(len + 2);
         }
     }
@@ -73,15 +79,13 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
         self.show_idle(0, None);
         self.backend.wait_ui();
 
-        if let Some(viewable) = self.current_viewable.as_mut() {
+        //remove current viewable, so the idle screen can be refreshed while rejecting
+        if let Some(mut viewable) = self.current_viewable.take() {
             let out = self.backend.accept_reject_out();
 
             let (len, code) = viewable.reject(out);
             out[len..len + 2].copy_from_slice(&code.to_be_bytes()[..]);
 
-            //remove current viewable
-            self.current_viewable.take();
-
             self.backend.accept_reject_end(len + 2);
         }
     }
@@ -93,8 +97,8 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
         if self.current_viewable.as_mut().is_some() {
             let out = self.backend.accept_reject_out();
 
-            const APDU_CODE_DATA_INVALID: u16 = 0x6984;
-            out[..2].copy_from_slice(&APDU_CODE_DATA_INVALID.to_be_bytes()[..]);
+            let code = ApduError::DataInvalid as u16;
+            out[..2].copy_from_slice(&code.to_be_bytes()[..]);
 
             //remove current viewable
             self.current_viewable.take();
@@ -103,6 +107,33 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
         }
     }
 
+    /// Rejects the pending review (if any) without sending a response
+    ///
+    /// `rx` is the length of the APDU that was received in the meantime,
+    /// used to determine if the stored viewable was overwritten
+    ///
+    /// Returns true if a review was pending
+    pub(crate) fn cancel(&mut self, rx: usize) -> bool {
+        match self.current_viewable.take() {
+            None => false,
+            Some(mut viewable) => {
+                //the viewable is stored at the end of the apdu buffer,
+                // if the new apdu reached it we can't touch it anymore
+                if rx > self.backend.accept_reject_out().len() {
+                    //still release the state of the pending request
+                    viewable.cleanup();
+                    core::mem::forget(viewable);
+                } else {
+                    //let the viewable clean its state, the response is discarded
+                    viewable.reject(&mut []);
+                }
+
+                self.show_idle(0, None);
+                true
+            }
+        }
+    }
+
     pub(crate) fn paging_init(&mut self) {
         self.item_idx = 0;
         self.page_idx = 0;
@@ -161,6 +192,31 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
         B::update_review(self)
     }
 
+    /// Moves to the first item of the next section,
+    /// or to the accept item if there are no sections left
+    pub(crate) fn skip_section(&mut self) {
+        let item_idx = self.item_idx as u8;
+        let next = self
+            .current_viewable
+            .as_mut()
+            .and_then(|viewable| viewable.next_section(item_idx));
+
+        match next {
+            //never go past the accept item
+            Some(item) if (item as usize) + 1 < self.item_count => {
+                self.item_idx = item as usize;
+                self.page_idx = 0;
+            }
+            _ if self.item_count > 0 => self.set_accept_item(),
+            _ => {}
+        }
+    }
+
+    pub fn skip_button(&mut self) {
+        self.skip_section();
+        B::update_review(self)
+    }
+
     fn is_accept_item(&self) -> bool {
         self.item_idx == self.item_count - 1
     }
@@ -182,7 +238,7 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
             self.reject();
         }
 
-        if self.backend.expert() {
+        if Settings::expert() {
             self.set_accept_item();
 
             B::update_review(self)
@@ -379,9 +435,30 @@ impl<B: UIBackend<KS>, const KS: usize> ZUI<B, KS> {
 
     //view_idle_show
     fn show_idle(&mut self, item_idx: usize, status: Option<&[u8]>) {
+        let idle_status = &self.idle_status;
+        let status = status.or_else(|| match strlen(idle_status) {
+            Ok(0) | Err(_) => None,
+            //keep the null terminator
+            Ok(len) => Some(&idle_status[..=len]),
+        });
+
         self.backend.show_idle(item_idx, status)
     }
 
+    /// Sets the status shown on the idle screen when none is given,
+    /// refreshing the screen if no review is pending
+    ///
+    /// The status is truncated in the middle to fit [`IDLE_STATUS_SIZE`],
+    /// an empty status restores the default one
+    pub(crate) fn set_idle_status(&mut self, status: &[u8]) {
+        self.idle_status = [0; IDLE_STATUS_SIZE];
+        crate::format::truncate_middle(status, &mut self.idle_status[..IDLE_STATUS_SIZE - 1]);
+
+        if self.current_viewable.is_none() {
+            self.show_idle(0, None);
+        }
+    }
+
     //view_review_show
     pub fn show(&mut self, viewable: impl Viewable + Sized + 'static) -> Result<(), ShowTooBig> {
         let viewable = self.backend.store_viewable(viewable).ok_or(ShowTooBig)?;
@@ -424,3 +501,109 @@ pub(self) fn c_strlen(s: *const u8, max: usize) -> Result<usize, StrNotNullTermi
         count += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    const KEY_SIZE: usize = 64;
+
+    /// 5 items, sections start at items 1 and 3
+    struct Sections;
+
+    impl Viewable for Sections {
+        fn num_items(&mut self) -> Result<u8, ViewError> {
+            Ok(5)
+        }
+
+        fn render_item(&mut self, _: u8, _: &mut [u8], _: &mut [u8], _: u8) -> Result<u8, ViewError> {
+            Ok(1)
+        }
+
+        fn next_section(&mut self, item_n: u8) -> Option<u8> {
+            [1, 3].iter().copied().find(|&start| start > item_n)
+        }
+
+        fn accept(&mut self, _: &mut [u8]) -> (usize, u16) {
+            (0, 0x9000)
+        }
+
+        fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
+            (0, 0x6986)
+        }
+    }
+
+    /// Claims a section past the last item
+    struct OutOfBounds;
+
+    impl Viewable for OutOfBounds {
+        fn num_items(&mut self) -> Result<u8, ViewError> {
+            Ok(2)
+        }
+
+        fn render_item(&mut self, _: u8, _: &mut [u8], _: &mut [u8], _: u8) -> Result<u8, ViewError> {
+            Ok(1)
+        }
+
+        fn next_section(&mut self, _: u8) -> Option<u8> {
+            Some(200)
+        }
+
+        fn accept(&mut self, _: &mut [u8]) -> (usize, u16) {
+            (0, 0x9000)
+        }
+
+        fn reject(&mut self, _: &mut [u8]) -> (usize, u16) {
+            (0, 0x6986)
+        }
+    }
+
+    fn zui<V: Viewable + 'static>(viewable: V) -> ZUI<backends::ConsoleBackend, KEY_SIZE> {
+        //leak to obtain 'static, it's fine in tests
+        let viewable = std::boxed::Box::leak(std::boxed::Box::new(viewable));
+
+        let mut ui = ZUI::new();
+        ui.item_count = viewable.num_items().unwrap_or(0) as usize + 1;
+        ui.current_viewable = Some(viewable.into());
+
+        ui
+    }
+
+    #[test]
+    fn skip_section() {
+        let mut ui = zui(Sections);
+
+        ui.skip_section();
+        assert_eq!(ui.item_idx, 1);
+
+        //skipping starts from the first page of the next section
+        ui.page_idx = 2;
+        ui.skip_section();
+        assert_eq!((ui.item_idx, ui.page_idx), (3, 0));
+
+        ui.item_idx = 2;
+        ui.skip_section();
+        assert_eq!(ui.item_idx, 3);
+
+        //no sections left
+        ui.skip_section();
+        assert!(ui.is_accept_item());
+        ui.skip_section();
+        assert!(ui.is_accept_item());
+    }
+
+    #[test]
+    fn skip_section_bounds() {
+        let mut ui = zui(OutOfBounds);
+
+        ui.skip_section();
+        assert!(ui.is_accept_item());
+
+        //without a viewable there's nothing to skip
+        let mut ui = ZUI::<backends::ConsoleBackend, KEY_SIZE>::new();
+        ui.skip_section();
+        assert_eq!(ui.item_idx, 0);
+    }
+}
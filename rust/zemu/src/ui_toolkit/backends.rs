@@ -51,8 +51,6 @@ pub trait UIBackend<const KEY_SIZE: usize>: Sized {
     //UX_WAIT macro equivalent
     fn wait_ui(&mut self);
 
-    fn expert(&self) -> bool;
-
     fn toggle_expert(&mut self);
 
     fn update_expert(&mut self);
@@ -17,6 +17,7 @@ use super::UIBackend;
 use crate::{
     ui::{manual_vtable::RefMutDynViewable, Viewable},
     ui_toolkit::ZUI,
+    Settings,
 };
 
 use bolos_sys::pic::PIC;
@@ -29,10 +30,14 @@ const MESSAGE_SIZE: usize = 1024;
 #[bolos_derive::lazy_static]
 pub static mut RUST_ZUI: ZUI<ConsoleBackend, KEY_SIZE> = ZUI::new();
 
+static mut BACKEND: ConsoleBackend = ConsoleBackend {
+    key: [0; KEY_SIZE],
+    message: ArrayString::new_const(),
+};
+
 pub struct ConsoleBackend {
     key: [u8; KEY_SIZE],
     message: ArrayString<MESSAGE_SIZE>,
-    expert: bool,
 }
 
 impl UIBackend<KEY_SIZE> for ConsoleBackend {
@@ -42,7 +47,7 @@ impl UIBackend<KEY_SIZE> for ConsoleBackend {
     const INCLUDE_ACTIONS_COUNT: usize = 0;
 
     fn static_mut() -> &'static mut Self {
-        todo!("static_mut")
+        unsafe { &mut BACKEND }
     }
 
     fn key_buf(&mut self) -> &mut [u8; KEY_SIZE] {
@@ -86,12 +91,8 @@ impl UIBackend<KEY_SIZE> for ConsoleBackend {
         todo!("wait_ui")
     }
 
-    fn expert(&self) -> bool {
-        self.expert
-    }
-
     fn toggle_expert(&mut self) {
-        self.expert = !self.expert;
+        Settings::toggle_expert();
     }
 
     fn update_expert(&mut self) {
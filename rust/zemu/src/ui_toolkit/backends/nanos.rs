@@ -17,6 +17,7 @@ use super::UIBackend;
 use crate::{
     ui::{manual_vtable::RefMutDynViewable, Viewable},
     ui_toolkit::{strlen, ZUI},
+    Settings,
 };
 use bolos_derive::pic_str;
 use bolos_sys::pic::PIC;
@@ -44,7 +45,6 @@ pub struct NanoSBackend {
     value2: [u8; MESSAGE_LINE_SIZE],
 
     viewable_size: usize,
-    expert: bool,
 }
 
 impl Default for NanoSBackend {
@@ -54,7 +54,6 @@ impl Default for NanoSBackend {
             value: [0; MESSAGE_LINE_SIZE],
             value2: [0; MESSAGE_LINE_SIZE],
             viewable_size: 0,
-            expert: false,
         }
     }
 }
@@ -165,18 +164,14 @@ impl UIBackend<KEY_SIZE> for NanoSBackend {
         }
     }
 
-    fn expert(&self) -> bool {
-        self.expert
-    }
-
     fn toggle_expert(&mut self) {
-        self.expert = !self.expert;
+        Settings::toggle_expert();
 
         self.show_idle(1, None);
     }
 
     fn update_expert(&mut self) {
-        let msg = if self.expert {
+        let msg = if Settings::expert() {
             &pic_str!(b"enabled")[..]
         } else {
             &pic_str!(b"disabled")[..]
@@ -286,6 +281,11 @@ mod cabi {
     pub unsafe extern "C" fn rs_h_review_button_right() {
         RUST_ZUI.right_button();
     }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rs_h_review_button_skip() {
+        RUST_ZUI.skip_button();
+    }
 }
 
 mod bindings {
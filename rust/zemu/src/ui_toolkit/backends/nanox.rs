@@ -17,6 +17,7 @@ use super::UIBackend;
 use crate::{
     ui::{manual_vtable::RefMutDynViewable, ViewError, Viewable},
     ui_toolkit::ZUI,
+    Settings,
 };
 use bolos_derive::pic_str;
 use bolos_sys::pic::PIC;
@@ -39,7 +40,6 @@ pub struct NanoXBackend {
     message: [u8; MESSAGE_SIZE],
 
     viewable_size: usize,
-    expert: bool,
 
     flow_inside_loop: bool,
 }
@@ -50,7 +50,6 @@ impl Default for NanoXBackend {
             key: [0; KEY_SIZE],
             message: [0; MESSAGE_SIZE],
             viewable_size: 0,
-            expert: false,
             flow_inside_loop: false,
         }
     }
@@ -110,6 +109,25 @@ impl NanoXBackend {
             bindings::crapoline_ux_flow_relayout();
         }
     }
+
+    pub fn review_skip(&mut self, ui: &mut ZUI<Self, KEY_SIZE>) {
+        ui.skip_section();
+
+        match ui.review_update_data() {
+            Ok(_) => unsafe {
+                bindings::crapoline_ux_layout_bnnn_paging_reset();
+                bindings::crapoline_ux_flow_refresh();
+            },
+            Err(ViewError::NoData) => {
+                //no sections left, exit the loop to approve
+                self.flow_inside_loop = false;
+                unsafe {
+                    bindings::crapoline_ux_show_review_approve();
+                }
+            }
+            Err(_) => ui.show_error(),
+        }
+    }
 }
 
 impl UIBackend<KEY_SIZE> for NanoXBackend {
@@ -122,7 +140,7 @@ impl UIBackend<KEY_SIZE> for NanoXBackend {
     }
 
     fn update_expert(&mut self) {
-        let msg = if self.expert {
+        let msg = if Settings::expert() {
             &pic_str!(b"enabled")[..]
         } else {
             &pic_str!(b"disabled")[..]
@@ -146,7 +164,9 @@ impl UIBackend<KEY_SIZE> for NanoXBackend {
     fn show_idle(&mut self, _item_idx: usize, status: Option<&[u8]>) {
         let status = status.unwrap_or(&pic_str!(b"DO NOT USE")[..]);
 
-        self.key[..status.len()].copy_from_slice(status);
+        //the status can be set by the app, so make sure it fits
+        let len = core::cmp::min(self.key.len(), status.len());
+        self.key[..len].copy_from_slice(&status[..len]);
 
         unsafe {
             bindings::crapoline_ux_show_idle();
@@ -194,12 +214,8 @@ impl UIBackend<KEY_SIZE> for NanoXBackend {
         }
     }
 
-    fn expert(&self) -> bool {
-        self.expert
-    }
-
     fn toggle_expert(&mut self) {
-        self.expert = !self.expert;
+        Settings::toggle_expert();
 
         unsafe {
             bindings::crapoline_ux_flow_init_idle_flow_toggle_expert();
@@ -307,6 +323,11 @@ mod cabi {
     pub unsafe extern "C" fn rs_h_review_loop_end() {
         BACKEND.review_loop_end(&mut RUST_ZUI)
     }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rs_h_review_button_skip() {
+        BACKEND.review_skip(&mut RUST_ZUI)
+    }
 }
 
 mod bindings {
@@ -320,5 +341,7 @@ mod bindings {
         pub fn crapoline_ux_flow_next();
         pub fn crapoline_ux_layout_bnnn_paging_reset();
         pub fn crapoline_ux_flow_relayout();
+        pub fn crapoline_ux_flow_refresh();
+        pub fn crapoline_ux_show_review_approve();
     }
 }
@@ -6,12 +6,15 @@ import TezosApp, { Curve } from '@zondax/ledger-tezos'
 import Zemu, { DeviceModel } from '@zondax/zemu'
 
 const Resolve = require('path').resolve
-const createHash = require('crypto').createHash
+const blake2b = require('blakejs').blake2b
 
 import { APP_DERIVATION, defaultOptions } from '../tests/common'
 
 import { ledger_fmt, ledger_fmt_currency, MUTEZ_MULT, RPC_ADDR } from './common'
 
+//prefix of packed michelson values, used for `expr` hashes
+const PACKED = Buffer.from([0x05])
+
 async function getAddress(app: TezosApp, curve: Curve): Promise<string> {
   const response = await app.getAddressAndPubKey(APP_DERIVATION, curve)
 
@@ -138,9 +141,9 @@ async function generate_vector(n: number): Promise<TestVector> {
     }
 
     const forgedCode = Buffer.from('020000000c0500036c0501036c05020302', 'hex')
-    const forgedCodeHash = createHash('sha256').update(forgedCode).digest()
+    const forgedCodeHash = blake2b(Buffer.concat([PACKED, forgedCode]), undefined, 32)
     const forgedStorage = Buffer.from('030b', 'hex')
-    const forgedStorageHash = createHash('sha256').update(forgedStorage).digest()
+    const forgedStorageHash = blake2b(Buffer.concat([PACKED, forgedStorage]), undefined, 32)
 
     console.log(`Operation ready, forging... ${JSON.stringify(op)}`)
     //forge the prepared operation
@@ -160,11 +163,12 @@ async function generate_vector(n: number): Promise<TestVector> {
         { idx: 3, key: 'Balance', val: ledger_fmt_currency(n.toString()) },
         { idx: 4, key: 'Delegate', val: ledger_fmt(delegation_str) },
         { idx: 5, key: 'Fee', val: ledger_fmt_currency('10000') },
-        { idx: 6, key: 'Code', val: ledger_fmt(forgedCodeHash.toString('hex')) },
-        { idx: 7, key: 'Storage', val: ledger_fmt(forgedStorageHash.toString('hex')) },
-        { idx: 8, key: 'Gas Limit', val: ledger_fmt('10') },
-        { idx: 9, key: 'Storage Limit', val: ledger_fmt('10') },
-        { idx: 10, key: 'Counter', val: ledger_fmt(counterNum.toString()) },
+        { idx: 6, key: 'Code Hash', val: ledger_fmt(Buffer.from(forgedCodeHash).toString('hex')) },
+        { idx: 7, key: 'Storage Hash', val: ledger_fmt(Buffer.from(forgedStorageHash).toString('hex')) },
+        { idx: 8, key: 'Script Size', val: ledger_fmt(`${forgedCode.length + forgedStorage.length} bytes`) },
+        { idx: 9, key: 'Gas Limit', val: ledger_fmt('10') },
+        { idx: 10, key: 'Storage Limit', val: ledger_fmt('10') },
+        { idx: 11, key: 'Counter', val: ledger_fmt(counterNum.toString()) },
       ],
     }
 
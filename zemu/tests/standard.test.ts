@@ -19,7 +19,7 @@ import { APP_DERIVATION, cartesianProduct, curves, defaultOptions, models } from
 import TezosApp, { Curve } from '@zondax/ledger-tezos'
 import * as secp256k1 from 'noble-secp256k1'
 
-import { SAMPLE_TRANSACTION, KNOWN_DELEGATE } from './tezos'
+import { SAMPLE_TRANSACTION, KNOWN_DELEGATE, KEY_VECTORS } from './tezos'
 
 const ed25519 = require('ed25519-supercop')
 
@@ -128,6 +128,23 @@ describe.each(models)('Standard [%s] - pubkey', function (m) {
   )
 })
 
+describe.each(models)('Standard [%s] - key vectors', function (m) {
+  test.each(KEY_VECTORS)('pubkey and addr match the vectors $path, $curve', async function (vector) {
+    const sim = new Zemu(m.path)
+    try {
+      await sim.start({ ...defaultOptions, model: m.name })
+      const app = new TezosApp(sim.getTransport())
+      const resp = await app.getAddressAndPubKey(vector.path, vector.curve as Curve)
+
+      expect(resp.returnCode).toEqual(0x9000)
+      expect(resp.publicKey.toString('hex')).toEqual(vector.public_key)
+      expect(resp.address).toEqual(vector.address)
+    } finally {
+      await sim.close()
+    }
+  })
+})
+
 describe.each(models)('Standard [%s]; legacy - pubkey', function (m) {
   test.each(cartesianProduct(curves, ["m/44'/1729'"]))(
     'get pubkey and compute addr %s, %s',
@@ -156,12 +173,12 @@ describe.each(models)('Standard [%s]; legacy - pubkey', function (m) {
 const SIGN_TEST_DATA = cartesianProduct(curves,
                                         [{
                                           name: 'transfer',
-                                          nav: { s: [13, 0], x: [11, 0], sp: [11, 0] },
+                                          nav: { s: [10, 0], x: [8, 0], sp: [8, 0] },
                                           op: SAMPLE_TRANSACTION
                                         },
                                          {
                                           name: 'known baker',
-                                          nav: { s: [10, 0], x: [9, 0], sp : [9, 0] },
+                                          nav: { s: [7, 0], x: [6, 0], sp : [6, 0] },
                                           op: KNOWN_DELEGATE
                                         }])
 const MICHELSON_SIGN_TEST_DATA = cartesianProduct(curves,
@@ -21,6 +21,11 @@ const dataPath = resolve(__dirname, 'data')
 export const SAMPLE_OPERATIONS: { op: any; blob: string }[] = JSON.parse(readFileSync(dataPath + '/samples.json', 'utf-8'))
 export const SAMPLE_CONTRACTS: { op: any; blob: string }[] = JSON.parse(readFileSync(dataPath + '/michelson.json', 'utf-8'))
 
+// keys derived by the mock, generated with `make mock_vectors`
+export const KEY_VECTORS: { path: string; curve: number; public_key: string; address: string }[] = JSON.parse(
+  readFileSync(resolve(__dirname, '../../rust/app/tests/vectors.json'), 'utf-8'),
+)
+
 export const SAMPLE_TRANSACTION = SAMPLE_OPERATIONS[6]
 export const SAMPLE_DELEGATION = SAMPLE_OPERATIONS[0]
 export const SAMPLE_ENDORSEMENT = SAMPLE_OPERATIONS[3]